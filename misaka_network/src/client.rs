@@ -1,7 +1,11 @@
-use anyhow::Result;
+use anyhow::{Result, anyhow};
+use async_nats::HeaderMap;
+use async_nats::header::NATS_MESSAGE_ID;
 use async_nats::jetstream;
-use futures::StreamExt;
+use async_nats::jetstream::context::GetStreamErrorKind;
+use futures::{Stream, StreamExt};
 use prost::Message;
+use std::str::FromStr;
 use std::time::Duration;
 
 use crate::proto::MisakaSignal;
@@ -9,6 +13,16 @@ use crate::proto::MisakaSignal;
 pub struct MisakaNetwork {
     client: async_nats::Client,
     jetstream: jetstream::Context,
+    /// `emit_signal` 使用的默认确认策略
+    ack_policy: AckPolicy,
+    /// 等待 JetStream ack 的超时时间
+    ack_timeout: Duration,
+    /// ack 超时或发布失败后的重试次数
+    max_retries: u32,
+    /// 第一次重试前的等待时间，之后每次翻倍
+    retry_delay: Duration,
+    /// 重试等待时间的上限
+    max_retry_delay: Duration,
 }
 
 pub struct TelepathConfig {
@@ -27,54 +41,255 @@ impl Default for TelepathConfig {
     }
 }
 
+/// 确认策略
+///
+/// 发布端（`emit_signal_with`）的投递保证：
+/// - `Explicit` / `All`：等待 JetStream 写入 stream 后的 ack，超时或失败时按
+///   `max_retries` 重发，至少一次（at-least-once）。重发带相同的 `Nats-Msg-Id`
+///   （signal uuid），在 stream 去重窗口内不会产生重复消息
+/// - `None`：直接用 core NATS 发布，不等待 ack 也不重试，至多一次（fire-and-forget），
+///   吞吐最高但服务端未写入时不会察觉
+///
+/// 订阅端：`subscribe` 按客户端配置的策略创建 consumer 并确认；
+/// `subscribe_telepath` 总是逐条显式 ack
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum AckPolicy {
     Explicit,
     None,
     All,
 }
 
+impl AckPolicy {
+    fn waits_for_ack(self) -> bool {
+        !matches!(self, AckPolicy::None)
+    }
+
+    fn consumer_ack_policy(self) -> jetstream::consumer::AckPolicy {
+        match self {
+            AckPolicy::Explicit => jetstream::consumer::AckPolicy::Explicit,
+            AckPolicy::None => jetstream::consumer::AckPolicy::None,
+            AckPolicy::All => jetstream::consumer::AckPolicy::All,
+        }
+    }
+}
+
+impl FromStr for AckPolicy {
+    type Err = anyhow::Error;
+
+    /// 解析配置中的策略名（不区分大小写）
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "explicit" => Ok(AckPolicy::Explicit),
+            "none" => Ok(AckPolicy::None),
+            "all" => Ok(AckPolicy::All),
+            other => Err(anyhow!(
+                "unknown ack policy '{}' (expected explicit, none or all)",
+                other
+            )),
+        }
+    }
+}
+
+/// 第 attempt 次（从 0 开始）重试前的等待时间：从 `base` 开始翻倍，不超过 `max`
+pub fn retry_delay(base: Duration, max: Duration, attempt: u32) -> Duration {
+    base.saturating_mul(1u32 << attempt.min(16)).min(max)
+}
+
 impl MisakaNetwork {
     /// 连接到 NATS
     pub async fn new(url: &str) -> Result<Self> {
         let client = async_nats::connect(url).await?;
         let jetstream = jetstream::new(client.clone());
-        Ok(Self { client, jetstream })
+        Ok(Self {
+            client,
+            jetstream,
+            ack_policy: AckPolicy::Explicit,
+            ack_timeout: Duration::from_secs(5),
+            max_retries: 3,
+            retry_delay: Duration::from_millis(100),
+            max_retry_delay: Duration::from_secs(5),
+        })
+    }
+
+    /// 设置 `emit_signal` 的默认确认策略
+    pub fn with_ack_policy(mut self, ack_policy: AckPolicy) -> Self {
+        self.ack_policy = ack_policy;
+        self
+    }
+
+    /// 设置等待 ack 的超时时间
+    pub fn with_ack_timeout(mut self, ack_timeout: Duration) -> Self {
+        self.ack_timeout = ack_timeout;
+        self
+    }
+
+    /// 设置 ack 超时后的重试次数（0 表示不重试）
+    pub fn with_max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// 设置重试的退避时间：从 `retry_delay` 开始翻倍，不超过 `max_retry_delay`
+    pub fn with_retry_delay(mut self, retry_delay: Duration, max_retry_delay: Duration) -> Self {
+        self.retry_delay = retry_delay;
+        self.max_retry_delay = max_retry_delay;
+        self
+    }
+
+    pub fn ack_policy(&self) -> AckPolicy {
+        self.ack_policy
     }
 
     /// 创建 Telepath (Stream)
     pub async fn create_telepath(&self, name: &str, config: TelepathConfig) -> Result<()> {
-        let stream_name = format!("telepath_{}", name);
-        
         self.jetstream
-            .create_stream(jetstream::stream::Config {
-                name: stream_name.clone(),
-                subjects: vec![format!("{}.>", stream_name)],
-                max_age: config.ttl,
-                max_messages: config.max_msgs,
-                max_bytes: config.max_bytes,
-                storage: jetstream::stream::StorageType::Memory,
-                retention: jetstream::stream::RetentionPolicy::Limits,
-                discard: jetstream::stream::DiscardPolicy::Old,
-                num_replicas: 1,
-                ..Default::default()
-            })
+            .create_stream(Self::stream_config(name, &config))
             .await?;
         
         Ok(())
     }
 
-    /// 发布 Signal
+    /// Telepath 是否已存在（按 JetStream 错误码判断，不依赖错误消息文本）
+    pub async fn telepath_exists(&self, name: &str) -> Result<bool> {
+        let stream_name = format!("telepath_{}", name);
+        match self.jetstream.get_stream(&stream_name).await {
+            Ok(_) => Ok(true),
+            Err(e) => match e.kind() {
+                GetStreamErrorKind::JetStream(error)
+                    if error.error_code() == jetstream::ErrorCode::STREAM_NOT_FOUND =>
+                {
+                    Ok(false)
+                }
+                _ => Err(e.into()),
+            },
+        }
+    }
+
+    /// Telepath 不存在时创建，已存在时保持原配置不变
+    pub async fn ensure_telepath(&self, name: &str, config: TelepathConfig) -> Result<()> {
+        self.jetstream
+            .get_or_create_stream(Self::stream_config(name, &config))
+            .await?;
+
+        Ok(())
+    }
+
+    fn stream_config(name: &str, config: &TelepathConfig) -> jetstream::stream::Config {
+        let stream_name = format!("telepath_{}", name);
+        jetstream::stream::Config {
+            name: stream_name.clone(),
+            subjects: vec![format!("{}.>", stream_name)],
+            max_age: config.ttl,
+            max_messages: config.max_msgs,
+            max_bytes: config.max_bytes,
+            storage: jetstream::stream::StorageType::Memory,
+            retention: jetstream::stream::RetentionPolicy::Limits,
+            discard: jetstream::stream::DiscardPolicy::Old,
+            num_replicas: 1,
+            ..Default::default()
+        }
+    }
+
+    /// 按默认确认策略发布 Signal
     pub async fn emit_signal(&self, telepath: &str, signal: MisakaSignal) -> Result<String> {
+        self.emit_signal_with(telepath, signal, self.ack_policy).await
+    }
+
+    /// 按指定确认策略发布 Signal
+    ///
+    /// 等待 ack 时返回 stream sequence；`AckPolicy::None` 不等待 ack，返回空字符串
+    pub async fn emit_signal_with(
+        &self,
+        telepath: &str,
+        signal: MisakaSignal,
+        ack_policy: AckPolicy,
+    ) -> Result<String> {
         let stream_name = format!("telepath_{}", telepath);
         let subject = format!("{}.lv{}", stream_name, signal.authority);
-        
-        let data = signal.encode_to_vec();
-        let ack = self.jetstream.publish(subject, data.into()).await?;
-        let ack = ack.await?;
-        
+        let data: bytes::Bytes = signal.encode_to_vec().into();
+
+        if !ack_policy.waits_for_ack() {
+            self.client.publish(subject, data).await?;
+            return Ok(String::new());
+        }
+
+        // 用 uuid 作为消息 ID，重发时由 JetStream 去重
+        let mut headers = HeaderMap::new();
+        if !signal.uuid.is_empty() {
+            headers.insert(NATS_MESSAGE_ID, signal.uuid.as_str());
+        }
+
+        let mut attempt = 0;
+        loop {
+            let error = match self
+                .publish_and_wait(subject.clone(), headers.clone(), data.clone())
+                .await
+            {
+                Ok(sequence) => return Ok(sequence),
+                Err(e) => e,
+            };
+            if attempt >= self.max_retries {
+                return Err(error.context(format!(
+                    "failed to emit signal to '{}' after {} retries",
+                    telepath, attempt
+                )));
+            }
+            tokio::time::sleep(retry_delay(self.retry_delay, self.max_retry_delay, attempt)).await;
+            attempt += 1;
+        }
+    }
+
+    /// 发布一次并在 `ack_timeout` 内等待 ack
+    async fn publish_and_wait(
+        &self,
+        subject: String,
+        headers: HeaderMap,
+        data: bytes::Bytes,
+    ) -> Result<String> {
+        let ack = self
+            .jetstream
+            .publish_with_headers(subject, headers, data)
+            .await?;
+        let ack = tokio::time::timeout(self.ack_timeout, ack)
+            .await
+            .map_err(|_| anyhow!("ack not received within {:?}", self.ack_timeout))??;
+
         Ok(ack.sequence.to_string())
     }
 
+    /// 订阅 Telepath，返回之后发布到该 telepath 的 Signal 流
+    ///
+    /// 使用临时 consumer，只接收订阅之后的消息；按客户端配置的 `AckPolicy` 确认：
+    /// `Explicit` / `All` 在解码前逐条 ack（无法解码的消息不会被重复投递），`None` 不 ack
+    pub async fn subscribe(
+        &self,
+        telepath: &str,
+    ) -> Result<impl Stream<Item = Result<MisakaSignal>> + Send + use<>> {
+        let stream_name = format!("telepath_{}", telepath);
+        let ack_policy = self.ack_policy;
+
+        let consumer = self
+            .jetstream
+            .create_consumer_on_stream(
+                jetstream::consumer::pull::Config {
+                    deliver_policy: jetstream::consumer::DeliverPolicy::New,
+                    ack_policy: ack_policy.consumer_ack_policy(),
+                    ..Default::default()
+                },
+                &stream_name,
+            )
+            .await?;
+
+        let messages = consumer.messages().await?;
+        Ok(messages.then(move |msg| async move {
+            let msg = msg?;
+            if ack_policy.waits_for_ack() {
+                msg.ack().await.map_err(|e| anyhow!("ack error: {}", e))?;
+            }
+            Ok(MisakaSignal::decode(&msg.payload[..])?)
+        }))
+    }
+
     /// 订阅 Telepath
     pub async fn subscribe_telepath<F>(
         &self,
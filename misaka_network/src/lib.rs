@@ -5,5 +5,5 @@ pub mod proto {
 
 pub mod client;
 
-pub use client::{AckPolicy, MisakaNetwork, TelepathConfig};
+pub use client::{AckPolicy, MisakaNetwork, TelepathConfig, retry_delay};
 pub use proto::*;
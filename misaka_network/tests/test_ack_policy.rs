@@ -0,0 +1,24 @@
+use misaka_network::{AckPolicy, retry_delay};
+use std::time::Duration;
+
+#[test]
+fn test_parse_ack_policy() {
+    assert_eq!("explicit".parse::<AckPolicy>().unwrap(), AckPolicy::Explicit);
+    assert_eq!("None".parse::<AckPolicy>().unwrap(), AckPolicy::None);
+    assert_eq!("ALL".parse::<AckPolicy>().unwrap(), AckPolicy::All);
+
+    let error = "at_least_once".parse::<AckPolicy>().unwrap_err();
+    assert!(error.to_string().contains("unknown ack policy"));
+}
+
+#[test]
+fn test_retry_delay_doubles_up_to_max() {
+    let base = Duration::from_millis(100);
+    let max = Duration::from_secs(5);
+    assert_eq!(retry_delay(base, max, 0), Duration::from_millis(100));
+    assert_eq!(retry_delay(base, max, 3), Duration::from_millis(800));
+    assert_eq!(retry_delay(base, max, 40), max);
+
+    // 基础等待时间很大时不溢出
+    assert_eq!(retry_delay(Duration::MAX / 2, max, 16), max);
+}
@@ -2,6 +2,7 @@ use misaka_network::{MisakaNetwork, TelepathConfig, MisakaSignal, AckPolicy};
 use misaka_network::misaka_signal::AuthorityLevel;
 use std::time::Duration;
 use std::sync::{Arc, atomic::{AtomicUsize, Ordering}};
+use futures::StreamExt;
 use tokio::time::sleep;
 use uuid::Uuid;
 use prost_types::Timestamp;
@@ -94,3 +95,46 @@ async fn test_create_and_pub_sub() -> anyhow::Result<()> {
     println!("✅ Test completed successfully!");
     Ok(())
 }
+
+#[tokio::test]
+async fn test_emit_and_subscribe_round_trip() -> anyhow::Result<()> {
+    let client = MisakaNetwork::new("nats://localhost:4222").await?;
+    let telepath_name = "test_round_trip";
+    client.ensure_telepath(telepath_name, TelepathConfig::default()).await?;
+    assert!(client.telepath_exists(telepath_name).await?);
+    // 已存在时再次调用不报错
+    client.ensure_telepath(telepath_name, TelepathConfig::default()).await?;
+
+    // 先订阅，只会收到之后发布的消息
+    let mut signals = Box::pin(client.subscribe(telepath_name).await?);
+
+    let signal = MisakaSignal {
+        timestamp: Some(Timestamp {
+            seconds: 1_700_000_000,
+            nanos: 42,
+        }),
+        uuid: Uuid::new_v4().to_string(),
+        parent_uuid: Uuid::new_v4().to_string(),
+        sender_agent: "test_agent".to_string(),
+        authority: AuthorityLevel::Lv3 as i32,
+        content_type: "test.round_trip".to_string(),
+        payload: vec![0, 1, 2, 255],
+    };
+    client.emit_signal(telepath_name, signal.clone()).await?;
+
+    let received = tokio::time::timeout(Duration::from_secs(5), signals.next())
+        .await?
+        .expect("signal stream ended")?;
+    assert_eq!(received, signal);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_missing_telepath_does_not_exist() -> anyhow::Result<()> {
+    let client = MisakaNetwork::new("nats://localhost:4222").await?;
+    let name = format!("missing_{}", Uuid::new_v4().simple());
+
+    assert!(!client.telepath_exists(&name).await?);
+    Ok(())
+}
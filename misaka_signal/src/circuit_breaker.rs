@@ -0,0 +1,75 @@
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// 发送失败率熔断器
+///
+/// 按固定时间窗口统计发送结果，某个窗口结束时失败率高于阈值即触发。
+/// 单次或短时间的失败不会触发，只有失败率在整个窗口内持续偏高才会；
+/// 窗口内失败次数少于 `min_failures` 时不判断失败率（低流量时一次失败就是 100%）
+pub struct CircuitBreaker {
+    error_rate_threshold: f64,
+    min_failures: u64,
+    window: Duration,
+    state: Mutex<WindowState>,
+}
+
+struct WindowState {
+    started_at: Instant,
+    successes: u64,
+    failures: u64,
+}
+
+impl CircuitBreaker {
+    pub fn new(error_rate_threshold: f64, min_failures: u64, window: Duration) -> Self {
+        Self::starting_at(error_rate_threshold, min_failures, window, Instant::now())
+    }
+
+    /// 指定第一个窗口的起始时间（测试用）
+    pub fn starting_at(
+        error_rate_threshold: f64,
+        min_failures: u64,
+        window: Duration,
+        now: Instant,
+    ) -> Self {
+        Self {
+            error_rate_threshold,
+            min_failures,
+            window,
+            state: Mutex::new(WindowState {
+                started_at: now,
+                successes: 0,
+                failures: 0,
+            }),
+        }
+    }
+
+    /// 记录一次发送结果，返回熔断器是否触发
+    pub fn record(&self, success: bool) -> bool {
+        self.record_at(success, Instant::now())
+    }
+
+    pub fn record_at(&self, success: bool, now: Instant) -> bool {
+        let mut state = self.state.lock().unwrap();
+
+        // 上一个窗口已结束：按整个窗口的失败率判断，然后开始新窗口
+        if now.duration_since(state.started_at) >= self.window {
+            let total = state.successes + state.failures;
+            let tripped = total > 0
+                && state.failures >= self.min_failures
+                && state.failures as f64 / total as f64 > self.error_rate_threshold;
+            if tripped {
+                return true;
+            }
+            state.started_at = now;
+            state.successes = 0;
+            state.failures = 0;
+        }
+
+        if success {
+            state.successes += 1;
+        } else {
+            state.failures += 1;
+        }
+        false
+    }
+}
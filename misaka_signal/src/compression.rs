@@ -0,0 +1,51 @@
+use serde::Deserialize;
+use std::io;
+
+/// signal 负载的压缩方式，写入 signal_type 供接收端解压
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PayloadCodec {
+    #[default]
+    None,
+    Zstd,
+    Lz4,
+}
+
+/// zstd 压缩级别（偏向速度）
+const ZSTD_LEVEL: i32 = 3;
+
+impl PayloadCodec {
+    /// 发送时使用的 signal_type，未压缩时保持原来的 "bytes"
+    pub fn signal_type(&self) -> &'static str {
+        match self {
+            PayloadCodec::None => "bytes",
+            PayloadCodec::Zstd => "bytes+zstd",
+            PayloadCodec::Lz4 => "bytes+lz4",
+        }
+    }
+
+    /// 由接收到的 signal_type 推断压缩方式
+    pub fn from_signal_type(signal_type: &str) -> Option<Self> {
+        [PayloadCodec::None, PayloadCodec::Zstd, PayloadCodec::Lz4]
+            .into_iter()
+            .find(|codec| codec.signal_type() == signal_type)
+    }
+
+    pub fn compress(&self, data: &[u8]) -> io::Result<Vec<u8>> {
+        match self {
+            PayloadCodec::None => Ok(data.to_vec()),
+            PayloadCodec::Zstd => zstd::encode_all(data, ZSTD_LEVEL),
+            // 头部带原始长度，解压时无需额外元数据
+            PayloadCodec::Lz4 => Ok(lz4_flex::compress_prepend_size(data)),
+        }
+    }
+
+    pub fn decompress(&self, data: &[u8]) -> io::Result<Vec<u8>> {
+        match self {
+            PayloadCodec::None => Ok(data.to_vec()),
+            PayloadCodec::Zstd => zstd::decode_all(data),
+            PayloadCodec::Lz4 => lz4_flex::decompress_size_prepended(data)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e)),
+        }
+    }
+}
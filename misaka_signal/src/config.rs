@@ -1,5 +1,10 @@
+use crate::compression::PayloadCodec;
+use crate::event_bundle::EventBundle;
 use serde::Deserialize;
 use std::fs;
+use std::time::Duration;
+use utils::env_expand::parse_toml;
+use utils::url::{NATS_SCHEMES, normalize_url, normalize_url_list};
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct Config {
@@ -9,12 +14,141 @@ pub struct Config {
     pub telepath_name: String,
     pub sender_agent: String,
     pub authority_level: String,
+    /// 心跳间隔（秒），None 表示关闭
+    pub heartbeat_interval_secs: Option<u64>,
+    /// msgpack 负载的压缩方式：none / zstd / lz4，signal_type 会标记压缩方式
+    #[serde(default)]
+    pub compression: PayloadCodec,
+    /// 只转发这些事件表（名称同 EventBundle 字段，如 "pumpfun_create_event"），为空则全部转发
+    #[serde(default)]
+    pub allowed_event_types: Vec<String>,
+    /// NATS 订阅断开后连续重新订阅的次数上限，0 表示无限重试
+    #[serde(default)]
+    pub max_reconnect_attempts: u32,
+    /// Prometheus 指标端点地址（需 metrics feature），None 表示不启动
+    pub metrics_addr: Option<String>,
+    /// gRPC 发送失败后的重试次数（指数退避）
+    #[serde(default = "default_max_send_retries")]
+    pub max_send_retries: u32,
+    /// 第一次重试前的等待时间（毫秒），之后每次翻倍
+    #[serde(default = "default_send_retry_delay_ms")]
+    pub send_retry_delay_ms: u64,
+    /// 重试等待时间的上限（毫秒）
+    #[serde(default = "default_send_retry_max_delay_ms")]
+    pub send_retry_max_delay_ms: u64,
+    /// 熔断：一个窗口内发送失败率超过该值时退出进程
+    #[serde(default = "default_circuit_breaker_error_rate")]
+    pub circuit_breaker_error_rate: f64,
+    /// 熔断：窗口内失败次数达到该值才按失败率判断，避免低流量时一次失败就触发
+    #[serde(default = "default_circuit_breaker_min_failures")]
+    pub circuit_breaker_min_failures: u64,
+    /// 熔断统计窗口（秒）
+    #[serde(default = "default_circuit_breaker_window_secs")]
+    pub circuit_breaker_window_secs: u64,
+    /// 同时发送中的 signal 上限，达到上限时暂停消费 NATS（背压）
+    #[serde(default = "default_max_in_flight")]
+    pub max_in_flight: usize,
+    /// 无法解码的 NATS 消息写入该目录，None 表示只记录日志后丢弃
+    pub dead_letter_dir: Option<String>,
+    /// 一个窗口内解码失败超过该数量时停止订阅并正常退出，0 表示不限
+    #[serde(default = "default_max_decode_errors")]
+    pub max_decode_errors: u64,
+    /// 解码失败的统计窗口（秒）
+    #[serde(default = "default_decode_error_window_secs")]
+    pub decode_error_window_secs: u64,
+}
+
+fn default_max_send_retries() -> u32 {
+    3
+}
+
+fn default_send_retry_delay_ms() -> u64 {
+    100
+}
+
+fn default_send_retry_max_delay_ms() -> u64 {
+    5_000
+}
+
+fn default_circuit_breaker_error_rate() -> f64 {
+    0.5
+}
+
+fn default_circuit_breaker_min_failures() -> u64 {
+    5
+}
+
+fn default_circuit_breaker_window_secs() -> u64 {
+    60
+}
+
+fn default_max_in_flight() -> usize {
+    256
+}
+
+fn default_max_decode_errors() -> u64 {
+    100
+}
+
+fn default_decode_error_window_secs() -> u64 {
+    60
 }
 
 impl Config {
     pub fn from_toml_file(path: &str) -> Result<Self, Box<dyn std::error::Error>> {
         let content = fs::read_to_string(path)?;
-        let config: Config = toml::from_str(&content)?;
+        let mut config: Config = parse_toml(&content)?;
+        config.validate()?;
         Ok(config)
     }
+
+    /// 第 attempt 次（从 0 开始）重试前的等待时间：从 send_retry_delay_ms 开始翻倍，
+    /// 不超过 send_retry_max_delay_ms
+    pub fn send_retry_delay(&self, attempt: u32) -> Duration {
+        let delay_ms = self
+            .send_retry_delay_ms
+            .saturating_mul(1u64 << attempt.min(16))
+            .min(self.send_retry_max_delay_ms);
+        Duration::from_millis(delay_ms)
+    }
+
+    /// 校验配置，并把 nats_url / grpc_server_url 补全为带 scheme 和端口的形式
+    ///
+    /// 在连接之前调用，URL 写错时给出具体是哪个字段、哪里不对
+    pub fn validate(&mut self) -> Result<(), String> {
+        // NATS 支持逗号分隔的多个服务器地址
+        self.nats_url = normalize_url_list("nats_url", &self.nats_url, &NATS_SCHEMES)?;
+        self.grpc_server_url = normalize_url("grpc_server_url", &self.grpc_server_url, &["http", "https"])?;
+
+        if self.max_in_flight == 0 {
+            return Err("max_in_flight must be at least 1".to_string());
+        }
+        if self.decode_error_window_secs == 0 {
+            return Err("decode_error_window_secs must be greater than 0".to_string());
+        }
+
+        // custom 行（外部 decoder 产出）不参与序列化，配置了也不会转发
+        if self.allowed_event_types.iter().any(|name| name == "custom") {
+            return Err(
+                "allowed_event_types cannot contain \"custom\": rows from external decoders are never forwarded"
+                    .to_string(),
+            );
+        }
+
+        // allowed_event_types 中的名称必须是会被转发的事件表
+        let unknown: Vec<&str> = self
+            .allowed_event_types
+            .iter()
+            .map(String::as_str)
+            .filter(|name| !EventBundle::SERIALIZED_TABLE_NAMES.contains(name))
+            .collect();
+        if !unknown.is_empty() {
+            return Err(format!(
+                "Unknown event types in allowed_event_types: {:?}, expected any of {:?}",
+                unknown,
+                EventBundle::SERIALIZED_TABLE_NAMES
+            ));
+        }
+        Ok(())
+    }
 }
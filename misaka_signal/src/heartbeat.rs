@@ -0,0 +1,4 @@
+pub use utils::heartbeat::Heartbeat;
+
+/// 心跳信号的 signal_type，真实数据为 "bytes"
+pub const HEARTBEAT_SIGNAL_TYPE: &str = "heartbeat";
@@ -1,4 +1,8 @@
+pub mod circuit_breaker;
+pub mod compression;
 pub mod config;
 pub mod event_bundle;
 pub mod grpc_client;
+pub mod heartbeat;
+pub mod metrics;
 pub mod signal_service;
@@ -13,6 +13,9 @@ struct Args {
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // 错误日志输出到 stderr，带上所在消息 span 的 trace_id
+    tracing_subscriber::fmt().with_writer(std::io::stderr).init();
+
     let args = Args::parse();
 
     println!("🔧 Loading config from: {}", args.config);
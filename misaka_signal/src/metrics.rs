@@ -0,0 +1,194 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// SignalService 运行期间的累计计数器（启动以来的总量，不清零）
+#[derive(Debug, Default)]
+pub struct SignalCounters {
+    pub nats_messages_received: AtomicU64,
+    pub signals_sent: AtomicU64,
+    /// 重试耗尽后仍发送失败的 signal 数
+    pub send_errors: AtomicU64,
+    /// 无法解码的 NATS 消息数
+    pub decode_errors: AtomicU64,
+    // 性能指标（累积值，单位：微秒）
+    pub conversion_time_us: AtomicU64,
+    pub serialization_time_us: AtomicU64,
+    pub grpc_time_us: AtomicU64,
+    /// 实际发送（压缩后）的负载字节数
+    pub bytes_sent: AtomicU64,
+    /// 压缩前的 msgpack 字节数
+    pub uncompressed_bytes: AtomicU64,
+    /// 当前发送中的 signal 数（瞬时值，不是累计值）
+    pub in_flight: AtomicU64,
+}
+
+impl SignalCounters {
+    pub fn snapshot(&self) -> SignalMetrics {
+        SignalMetrics {
+            nats_messages_received: self.nats_messages_received.load(Ordering::Relaxed),
+            signals_sent: self.signals_sent.load(Ordering::Relaxed),
+            send_errors: self.send_errors.load(Ordering::Relaxed),
+            decode_errors: self.decode_errors.load(Ordering::Relaxed),
+            conversion_time_us: self.conversion_time_us.load(Ordering::Relaxed),
+            serialization_time_us: self.serialization_time_us.load(Ordering::Relaxed),
+            grpc_time_us: self.grpc_time_us.load(Ordering::Relaxed),
+            bytes_sent: self.bytes_sent.load(Ordering::Relaxed),
+            uncompressed_bytes: self.uncompressed_bytes.load(Ordering::Relaxed),
+            in_flight: self.in_flight.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// 计数器快照，`SignalService::metrics` 返回启动以来的总量
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SignalMetrics {
+    pub nats_messages_received: u64,
+    pub signals_sent: u64,
+    pub send_errors: u64,
+    pub decode_errors: u64,
+    pub conversion_time_us: u64,
+    pub serialization_time_us: u64,
+    pub grpc_time_us: u64,
+    pub bytes_sent: u64,
+    pub uncompressed_bytes: u64,
+    pub in_flight: u64,
+}
+
+impl SignalMetrics {
+    /// 两次快照之间的增量（周期统计），in_flight 取较新的快照
+    pub fn since(&self, earlier: &SignalMetrics) -> SignalMetrics {
+        SignalMetrics {
+            nats_messages_received: self
+                .nats_messages_received
+                .saturating_sub(earlier.nats_messages_received),
+            signals_sent: self.signals_sent.saturating_sub(earlier.signals_sent),
+            send_errors: self.send_errors.saturating_sub(earlier.send_errors),
+            decode_errors: self.decode_errors.saturating_sub(earlier.decode_errors),
+            conversion_time_us: self.conversion_time_us.saturating_sub(earlier.conversion_time_us),
+            serialization_time_us: self
+                .serialization_time_us
+                .saturating_sub(earlier.serialization_time_us),
+            grpc_time_us: self.grpc_time_us.saturating_sub(earlier.grpc_time_us),
+            bytes_sent: self.bytes_sent.saturating_sub(earlier.bytes_sent),
+            uncompressed_bytes: self.uncompressed_bytes.saturating_sub(earlier.uncompressed_bytes),
+            in_flight: self.in_flight,
+        }
+    }
+
+    /// 每条 NATS 消息的平均转换耗时
+    pub fn avg_conversion_us(&self) -> u64 {
+        average(self.conversion_time_us, self.nats_messages_received)
+    }
+
+    /// 每个 signal 的平均序列化耗时
+    pub fn avg_serialization_us(&self) -> u64 {
+        average(self.serialization_time_us, self.signals_sent)
+    }
+
+    /// 每个 signal 的平均 gRPC 耗时
+    pub fn avg_grpc_us(&self) -> u64 {
+        average(self.grpc_time_us, self.signals_sent)
+    }
+
+    /// 每个 signal 的平均大小
+    pub fn avg_bytes(&self) -> u64 {
+        average(self.bytes_sent, self.signals_sent)
+    }
+}
+
+fn average(total: u64, count: u64) -> u64 {
+    if count > 0 { total / count } else { 0 }
+}
+
+/// 把计数器注册到 prometheus 默认 registry（抓取时读取当前值）
+#[cfg(feature = "metrics")]
+pub fn register_prometheus(
+    counters: std::sync::Arc<SignalCounters>,
+) -> Result<(), prometheus::Error> {
+    prometheus::register(Box::new(prometheus_collector::SignalCollector::new(counters)?))
+}
+
+#[cfg(feature = "metrics")]
+mod prometheus_collector {
+    use super::{SignalCounters, SignalMetrics};
+    use prometheus::core::{Collector, Desc};
+    use prometheus::proto::MetricFamily;
+    use prometheus::{IntCounter, IntGauge, Opts};
+    use std::sync::Arc;
+
+    pub struct SignalCollector {
+        counters: Arc<SignalCounters>,
+        // 与 SignalMetrics 字段一一对应
+        metrics: Vec<(IntCounter, fn(&SignalMetrics) -> u64)>,
+        in_flight: IntGauge,
+    }
+
+    impl SignalCollector {
+        pub fn new(counters: Arc<SignalCounters>) -> Result<Self, prometheus::Error> {
+            let counter = |name: &str, help: &str| {
+                IntCounter::with_opts(Opts::new(name, help).namespace("misaka_signal"))
+            };
+            let metrics: Vec<(IntCounter, fn(&SignalMetrics) -> u64)> = vec![
+                (
+                    counter("nats_messages_received_total", "NATS messages received")?,
+                    |m| m.nats_messages_received,
+                ),
+                (counter("signals_sent_total", "Signals sent over gRPC")?, |m| m.signals_sent),
+                (
+                    counter("send_errors_total", "Signals that failed after all retries")?,
+                    |m| m.send_errors,
+                ),
+                (
+                    counter("decode_errors_total", "NATS messages that failed to decode")?,
+                    |m| m.decode_errors,
+                ),
+                (
+                    counter("conversion_time_us_total", "Time spent converting transactions")?,
+                    |m| m.conversion_time_us,
+                ),
+                (
+                    counter("serialization_time_us_total", "Time spent serializing bundles")?,
+                    |m| m.serialization_time_us,
+                ),
+                (counter("grpc_time_us_total", "Time spent in gRPC calls")?, |m| m.grpc_time_us),
+                (counter("bytes_sent_total", "Signal payload bytes sent")?, |m| m.bytes_sent),
+                (
+                    counter("uncompressed_bytes_total", "Signal payload bytes before compression")?,
+                    |m| m.uncompressed_bytes,
+                ),
+            ];
+            let in_flight = IntGauge::with_opts(
+                Opts::new("in_flight", "Signals currently being sent").namespace("misaka_signal"),
+            )?;
+            Ok(Self {
+                counters,
+                metrics,
+                in_flight,
+            })
+        }
+    }
+
+    impl Collector for SignalCollector {
+        fn desc(&self) -> Vec<&Desc> {
+            self.metrics
+                .iter()
+                .flat_map(|(counter, _)| counter.desc())
+                .chain(self.in_flight.desc())
+                .collect()
+        }
+
+        fn collect(&self) -> Vec<MetricFamily> {
+            let snapshot = self.counters.snapshot();
+            self.in_flight.set(snapshot.in_flight as i64);
+            self.metrics
+                .iter()
+                .flat_map(|(counter, value)| {
+                    // 以 SignalCounters 为准，抓取时同步到 prometheus 计数器
+                    counter.reset();
+                    counter.inc_by(value(&snapshot));
+                    counter.collect()
+                })
+                .chain(self.in_flight.collect())
+                .collect()
+        }
+    }
+}
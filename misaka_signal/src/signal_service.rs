@@ -1,32 +1,44 @@
+use crate::circuit_breaker::CircuitBreaker;
+use crate::compression::PayloadCodec;
 use crate::config::Config;
 use crate::event_bundle::EventBundle;
 use crate::grpc_client::{misaka_network::*, GrpcClient};
+use crate::heartbeat::{Heartbeat, HEARTBEAT_SIGNAL_TYPE};
+use crate::metrics::{SignalCounters, SignalMetrics};
+use utils::reconnect::ReconnectBackoff;
 use common::nats_client::NatsClient;
 use prost::Message;
 use proto_lib::transaction::solana::Transaction;
-use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::atomic::Ordering;
 use std::sync::Arc;
 use std::time::Duration;
+use tokio::sync::Semaphore;
 use tokio::time::interval;
 use tokio_stream::StreamExt;
-use utils::convert_transaction::TransactionConverter;
+use tracing::Instrument;
+use utils::dead_letter::DeadLetter;
+use utils::convert_transaction::{ConversionMetrics, ConverterOptions, TransactionConverter};
+use utils::trace::{TRACE_ID_HEADER, message_span, resolve_trace_id};
 
 pub struct SignalService {
     nats_client: NatsClient,
     grpc_client: Arc<GrpcClient>,
     config: Arc<Config>,
-    // 统计计数器
-    nats_messages_received: Arc<AtomicU64>,
-    signals_sent: Arc<AtomicU64>,
-    // 性能指标（累积值，单位：微秒）
-    total_conversion_time_us: Arc<AtomicU64>,
-    total_serialization_time_us: Arc<AtomicU64>,
-    total_grpc_time_us: Arc<AtomicU64>,
-    total_bytes_sent: Arc<AtomicU64>,
+    // 统计计数器（周期汇总、metrics() 和 Prometheus 共用）
+    counters: Arc<SignalCounters>,
+    heartbeat: Option<Heartbeat>,
+    circuit_breaker: Arc<CircuitBreaker>,
+    // 发送中 signal 的并发上限（max_in_flight）
+    in_flight: Arc<Semaphore>,
+    // 无法解码的消息
+    dead_letter: DeadLetter,
 }
 
 impl SignalService {
-    pub async fn new(config: Config) -> Result<Self, Box<dyn std::error::Error>> {
+    pub async fn new(mut config: Config) -> Result<Self, Box<dyn std::error::Error>> {
+        // 连接前校验，URL 写错时直接报出是哪个字段
+        config.validate()?;
+
         // 连接 NATS
         let nats_client = NatsClient::new(&config.nats_url).await?;
         println!("✅ Connected to NATS: {}", config.nats_url);
@@ -35,78 +47,117 @@ impl SignalService {
         let grpc_client = GrpcClient::new(&config.grpc_server_url).await?;
         println!("✅ Connected to gRPC: {}", config.grpc_server_url);
 
+        let heartbeat = config
+            .heartbeat_interval_secs
+            .map(|secs| Heartbeat::new(Duration::from_secs(secs)));
+
+        let counters = Arc::new(SignalCounters::default());
+        if let Some(addr) = &config.metrics_addr {
+            Self::start_metrics_endpoint(addr, &counters).await?;
+        }
+
+        let max_in_flight = config.max_in_flight;
+        let dead_letter = DeadLetter::new(
+            config.dead_letter_dir.as_ref().map(std::path::PathBuf::from),
+            config.max_decode_errors,
+            Duration::from_secs(config.decode_error_window_secs),
+        );
+        let circuit_breaker = CircuitBreaker::new(
+            config.circuit_breaker_error_rate,
+            config.circuit_breaker_min_failures,
+            Duration::from_secs(config.circuit_breaker_window_secs),
+        );
+
         Ok(Self {
             nats_client,
             grpc_client: Arc::new(grpc_client),
             config: Arc::new(config),
-            nats_messages_received: Arc::new(AtomicU64::new(0)),
-            signals_sent: Arc::new(AtomicU64::new(0)),
-            total_conversion_time_us: Arc::new(AtomicU64::new(0)),
-            total_serialization_time_us: Arc::new(AtomicU64::new(0)),
-            total_grpc_time_us: Arc::new(AtomicU64::new(0)),
-            total_bytes_sent: Arc::new(AtomicU64::new(0)),
+            counters,
+            heartbeat,
+            circuit_breaker: Arc::new(circuit_breaker),
+            in_flight: Arc::new(Semaphore::new(max_in_flight)),
+            dead_letter,
         })
     }
 
+    /// 启动以来的累计指标
+    pub fn metrics(&self) -> SignalMetrics {
+        self.counters.snapshot()
+    }
+
+    /// 启动 Prometheus 指标端点；未启用 `metrics` feature 时只输出警告
+    async fn start_metrics_endpoint(
+        addr: &str,
+        counters: &Arc<SignalCounters>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        #[cfg(feature = "metrics")]
+        {
+            crate::metrics::register_prometheus(Arc::clone(counters))?;
+            utils::metrics_server::serve_metrics(addr).await?;
+        }
+        #[cfg(not(feature = "metrics"))]
+        {
+            let _ = counters;
+            tracing::warn!(
+                metrics_addr = addr,
+                "metrics_addr is set but misaka_signal was built without the `metrics` feature"
+            );
+        }
+        Ok(())
+    }
+
+    /// 启动心跳任务（仅在配置了 heartbeat_interval_secs 时）
+    fn start_heartbeat_task(&self) {
+        let Some(heartbeat) = &self.heartbeat else {
+            return;
+        };
+
+        let grpc_client = Arc::clone(&self.grpc_client);
+        let config = Arc::clone(&self.config);
+        heartbeat.spawn(move || {
+            let grpc_client = Arc::clone(&grpc_client);
+            let config = Arc::clone(&config);
+            async move {
+                let signal = Self::create_signal(&config, HEARTBEAT_SIGNAL_TYPE, Vec::new());
+                grpc_client
+                    .emit_signal(&config.telepath_name, signal)
+                    .await
+                    .map(|_| ())
+                    .map_err(|e| e.to_string())
+            }
+        });
+    }
+
     async fn start_statistics_task(&self) {
         let mut timer = interval(Duration::from_secs(60));
-        let nats_counter = Arc::clone(&self.nats_messages_received);
-        let signals_counter = Arc::clone(&self.signals_sent);
-        let conversion_time_counter = Arc::clone(&self.total_conversion_time_us);
-        let serialization_time_counter = Arc::clone(&self.total_serialization_time_us);
-        let grpc_time_counter = Arc::clone(&self.total_grpc_time_us);
-        let bytes_counter = Arc::clone(&self.total_bytes_sent);
+        let counters = Arc::clone(&self.counters);
 
         tokio::spawn(async move {
+            let mut last = counters.snapshot();
             loop {
                 timer.tick().await;
 
-                let nats_count = nats_counter.swap(0, Ordering::Relaxed);
-                let signals_count = signals_counter.swap(0, Ordering::Relaxed);
-                let total_conversion_us = conversion_time_counter.swap(0, Ordering::Relaxed);
-                let total_serialization_us = serialization_time_counter.swap(0, Ordering::Relaxed);
-                let total_grpc_us = grpc_time_counter.swap(0, Ordering::Relaxed);
-                let total_bytes = bytes_counter.swap(0, Ordering::Relaxed);
-
-                // 计算平均值
-                let avg_conversion_us = if nats_count > 0 {
-                    total_conversion_us / nats_count
-                } else {
-                    0
-                };
-
-                let avg_serialization_us = if signals_count > 0 {
-                    total_serialization_us / signals_count
-                } else {
-                    0
-                };
-
-                let avg_grpc_us = if signals_count > 0 {
-                    total_grpc_us / signals_count
-                } else {
-                    0
-                };
-
-                let avg_bytes = if signals_count > 0 {
-                    total_bytes / signals_count
-                } else {
-                    0
-                };
+                // 本周期的增量
+                let current = counters.snapshot();
+                let period = current.since(&last);
+                last = current;
 
                 // 格式化时间
                 let now = chrono::Local::now();
                 let timestamp = now.format("%H:%M:00").to_string();
 
                 println!(
-                    "[Summary] {} NATS: {} | Signals: {} | Avg conv: {} us | Avg serial: {} us | Avg gRPC: {} us | Avg size: {} bytes | Total data: {:.2} MB",
+                    "[Summary] {} NATS: {} | Signals: {} | In flight: {} | Avg conv: {} us | Avg serial: {} us | Avg gRPC: {} us | Avg size: {} bytes | Total data: {:.2} MB (raw {:.2} MB)",
                     timestamp,
-                    nats_count,
-                    signals_count,
-                    avg_conversion_us,
-                    avg_serialization_us,
-                    avg_grpc_us,
-                    avg_bytes,
-                    total_bytes as f64 / (1024.0 * 1024.0)
+                    period.nats_messages_received,
+                    period.signals_sent,
+                    period.in_flight,
+                    period.avg_conversion_us(),
+                    period.avg_serialization_us(),
+                    period.avg_grpc_us(),
+                    period.avg_bytes(),
+                    period.bytes_sent as f64 / (1024.0 * 1024.0),
+                    period.uncompressed_bytes as f64 / (1024.0 * 1024.0)
                 );
             }
         });
@@ -119,120 +170,225 @@ impl SignalService {
 
         // 启动统计任务
         self.start_statistics_task().await;
+        self.start_heartbeat_task();
 
+        let backoff = ReconnectBackoff::new(self.config.max_reconnect_attempts);
         let mut subscriber = self.nats_client.subscribe(&self.config.topic).await?;
+        let mut reconnect_attempts = 0u32;
+
+        loop {
+            // 流结束（NATS 断开）时退避后重新订阅，订阅失败同样计入连续重连次数
+            let Some(message) = subscriber.next().await else {
+                println!("NATS stream ended");
+                subscriber = backoff
+                    .resubscribe(&mut reconnect_attempts, || {
+                        self.nats_client.subscribe(&self.config.topic)
+                    })
+                    .await?;
+                println!("✅ Resubscribed to NATS topic: {}", self.config.topic);
+                continue;
+            };
+            reconnect_attempts = 0;
 
-        while let Some(message) = subscriber.next().await {
             // 增加 NATS 消息接收计数
-            self.nats_messages_received.fetch_add(1, Ordering::Relaxed);
-
-            // 1. 反序列化 Transaction
-            let tx = Transaction::decode(message.payload.as_ref()).unwrap_or_else(|e| {
-                eprintln!("❌ FATAL: Failed to decode transaction: {:?}", e);
-                std::process::exit(1);
-            });
+            self.counters.nats_messages_received.fetch_add(1, Ordering::Relaxed);
+
+            // 链路 id：优先使用发布端的 header，没有则生成
+            let trace_id = resolve_trace_id(
+                message
+                    .headers
+                    .as_ref()
+                    .and_then(|headers| headers.get(TRACE_ID_HEADER))
+                    .map(|value| value.as_str()),
+            );
+            let span = message_span(&trace_id);
+
+            // 1. 反序列化 Transaction，失败的消息进入死信目录后继续
+            let tx = match Transaction::decode(message.payload.as_ref()) {
+                Ok(tx) => tx,
+                Err(e) => {
+                    self.counters.decode_errors.fetch_add(1, Ordering::Relaxed);
+                    let too_many =
+                        span.in_scope(|| self.dead_letter.record(&message.payload, &e.to_string()));
+                    if too_many {
+                        tracing::error!(
+                            decode_errors = self.dead_letter.total(),
+                            "❌ Too many undecodable messages, stopping subscription"
+                        );
+                        break;
+                    }
+                    continue;
+                }
+            };
 
             // 2. 转换为 Events (主线程快速处理，记录时间)
             let start = std::time::Instant::now();
-            let event_bundle = self.convert_transaction(&tx);
+            let mut event_bundle = span.in_scope(|| self.convert_transaction(&tx));
             let conversion_time_us = start.elapsed().as_micros() as u64;
-            self.total_conversion_time_us.fetch_add(conversion_time_us, Ordering::Relaxed);
+            self.counters
+                .conversion_time_us
+                .fetch_add(conversion_time_us, Ordering::Relaxed);
 
-            // 3. 跳过空事件
+            // 3. 按配置过滤事件类型，跳过空事件
+            if !self.config.allowed_event_types.is_empty() {
+                event_bundle.retain_tables(&self.config.allowed_event_types);
+            }
             if event_bundle.is_empty() {
                 continue;
             }
 
-            // 4. Spawn 异步任务发送 (不阻塞主循环)
+            // 4. Spawn 异步任务发送 (不阻塞主循环)；发送中的 signal 达到 max_in_flight 时
+            //    在这里等待，暂停消费 NATS
+            let permit = Arc::clone(&self.in_flight)
+                .acquire_owned()
+                .await
+                .expect("in-flight semaphore is never closed");
+            self.counters.in_flight.fetch_add(1, Ordering::Relaxed);
             let grpc_client = Arc::clone(&self.grpc_client);
             let config = Arc::clone(&self.config);
-            let signals_counter = Arc::clone(&self.signals_sent);
-            let serialization_time_counter = Arc::clone(&self.total_serialization_time_us);
-            let grpc_time_counter = Arc::clone(&self.total_grpc_time_us);
-            let bytes_counter = Arc::clone(&self.total_bytes_sent);
-
-            tokio::spawn(async move {
-                if let Err(e) = Self::send_signal(
-                    grpc_client,
-                    config,
-                    event_bundle,
-                    signals_counter,
-                    serialization_time_counter,
-                    grpc_time_counter,
-                    bytes_counter,
-                ).await {
-                    eprintln!("❌ FATAL: Failed to send signal: {:?}", e);
-                    std::process::exit(1);
+            let counters = Arc::clone(&self.counters);
+            let heartbeat = self.heartbeat.clone();
+            let circuit_breaker = Arc::clone(&self.circuit_breaker);
+
+            tokio::spawn(
+                async move {
+                    // 单个 signal 失败只记录，失败率持续超过阈值才退出
+                    let result =
+                        Self::send_signal(grpc_client, config, event_bundle, &counters, heartbeat)
+                            .await;
+                    if let Err(e) = &result {
+                        counters.send_errors.fetch_add(1, Ordering::Relaxed);
+                        tracing::error!("❌ Failed to send signal after retries: {}", e);
+                    }
+                    counters.in_flight.fetch_sub(1, Ordering::Relaxed);
+                    drop(permit);
+                    if circuit_breaker.record(result.is_ok()) {
+                        tracing::error!(
+                            "❌ FATAL: gRPC send error rate stayed above threshold, circuit breaker open"
+                        );
+                        std::process::exit(1);
+                    }
                 }
-            });
+                .instrument(span),
+            );
         }
 
-        println!("NATS stream ended");
-        Ok(())
+        // 解码失败过多：等待发送中的 signal 完成后返回错误
+        drop(subscriber);
+        let _all_permits = self
+            .in_flight
+            .acquire_many(self.config.max_in_flight as u32)
+            .await
+            .expect("in-flight semaphore is never closed");
+        Err(format!("Stopped after {} undecodable messages", self.dead_letter.total()).into())
     }
 
     /// 转换单个 Transaction 为 EventBundle
     fn convert_transaction(&self, tx: &Transaction) -> EventBundle {
         let mut bundle = EventBundle::default();
+        let mut metrics = ConversionMetrics::default();
 
-        TransactionConverter::convert(
+        TransactionConverter::convert_into_with_options(
             tx,
-            &mut bundle.pumpfun_trade_event,
-            &mut bundle.pumpfun_create_event,
-            &mut bundle.pumpfun_migrate_event,
-            &mut bundle.pumpfun_amm_buy_event,
-            &mut bundle.pumpfun_amm_sell_event,
-            &mut bundle.pumpfun_amm_create_pool_event,
-            &mut bundle.pumpfun_amm_deposit_event,
-            &mut bundle.pumpfun_amm_withdraw_event,
+            &ConverterOptions::default(),
+            &mut metrics,
+            &mut bundle,
         );
 
+        if metrics.dropped_instructions > 0 {
+            tracing::warn!(
+                slot = tx.slot,
+                dropped_instructions = metrics.dropped_instructions,
+                "⚠️  Instruction stack overflow during conversion, some events may be missing"
+            );
+        }
+
         bundle
     }
 
-    /// 发送 Signal 到 gRPC 服务
+    /// 发送 Signal 到 gRPC 服务，返回重试耗尽后的最后一个错误
     async fn send_signal(
         grpc_client: Arc<GrpcClient>,
         config: Arc<Config>,
         event_bundle: EventBundle,
-        signals_counter: Arc<AtomicU64>,
-        serialization_time_counter: Arc<AtomicU64>,
-        grpc_time_counter: Arc<AtomicU64>,
-        bytes_counter: Arc<AtomicU64>,
-    ) -> Result<(), Box<dyn std::error::Error>> {
+        counters: &SignalCounters,
+        heartbeat: Option<Heartbeat>,
+    ) -> Result<(), String> {
         // 1. 序列化为 MessagePack（记录时间）
         // 使用 to_vec_named 以生成 map 格式（字段名作为 key），而非 compact 数组格式
         let start = std::time::Instant::now();
         let msgpack_bytes = rmp_serde::to_vec_named(&event_bundle).unwrap_or_else(|e| {
-            eprintln!("❌ FATAL: Failed to serialize EventBundle: {:?}", e);
+            tracing::error!("❌ FATAL: Failed to serialize EventBundle: {:?}", e);
             std::process::exit(1);
         });
+        let uncompressed_len = msgpack_bytes.len() as u64;
+
+        // 按配置压缩负载，signal_type 标记压缩方式；压缩失败时退回未压缩发送
+        let codec = config.compression;
+        let (signal_type, payload) = match codec {
+            PayloadCodec::None => (codec.signal_type(), msgpack_bytes),
+            _ => match codec.compress(&msgpack_bytes) {
+                Ok(compressed) => (codec.signal_type(), compressed),
+                Err(e) => {
+                    tracing::warn!(
+                        "⚠️  Failed to compress payload with {:?}, sending uncompressed: {}",
+                        codec,
+                        e
+                    );
+                    (PayloadCodec::None.signal_type(), msgpack_bytes)
+                }
+            },
+        };
         let serialization_time_us = start.elapsed().as_micros() as u64;
-        serialization_time_counter.fetch_add(serialization_time_us, Ordering::Relaxed);
-
-        // 记录字节数
-        let bytes_len = msgpack_bytes.len() as u64;
-        bytes_counter.fetch_add(bytes_len, Ordering::Relaxed);
-
-        // 2. 创建 MisakaSignal
-        let signal = Self::create_signal(&config, msgpack_bytes);
-
-        // 3. 发送 gRPC（记录时间）
+        counters
+            .serialization_time_us
+            .fetch_add(serialization_time_us, Ordering::Relaxed);
+
+        // 记录压缩前后的字节数
+        counters
+            .uncompressed_bytes
+            .fetch_add(uncompressed_len, Ordering::Relaxed);
+        counters
+            .bytes_sent
+            .fetch_add(payload.len() as u64, Ordering::Relaxed);
+
+        // 2. 创建 MisakaSignal（uuid / timestamp 不受压缩影响）
+        let signal = Self::create_signal(&config, signal_type, payload);
+
+        // 3. 发送 gRPC（记录时间），失败时按指数退避重试
         let start = std::time::Instant::now();
-        grpc_client
-            .emit_signal(&config.telepath_name, signal)
-            .await?;
+        let mut attempt = 0;
+        loop {
+            let error = match grpc_client
+                .emit_signal(&config.telepath_name, signal.clone())
+                .await
+            {
+                Ok(_) => break,
+                Err(e) => e.to_string(),
+            };
+            if attempt >= config.max_send_retries {
+                return Err(format!("{} (after {} retries)", error, attempt));
+            }
+
+            let delay = config.send_retry_delay(attempt);
+            tracing::warn!(attempt = attempt + 1, ?delay, "⚠️  Failed to send signal, retrying: {}", error);
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        }
         let grpc_time_us = start.elapsed().as_micros() as u64;
-        grpc_time_counter.fetch_add(grpc_time_us, Ordering::Relaxed);
+        counters.grpc_time_us.fetch_add(grpc_time_us, Ordering::Relaxed);
 
         // 增加发送成功计数
-        signals_counter.fetch_add(1, Ordering::Relaxed);
+        counters.signals_sent.fetch_add(1, Ordering::Relaxed);
+        if let Some(heartbeat) = heartbeat {
+            heartbeat.mark_activity();
+        }
 
         Ok(())
     }
 
     /// 创建 MisakaSignal
-    fn create_signal(config: &Config, binary_data: Vec<u8>) -> MisakaSignal {
+    fn create_signal(config: &Config, signal_type: &str, binary_data: Vec<u8>) -> MisakaSignal {
         use prost_types::Timestamp;
 
         let now = std::time::SystemTime::now()
@@ -242,7 +398,7 @@ impl SignalService {
         let authority = Self::parse_authority_level(&config.authority_level);
 
         MisakaSignal {
-            signal_type: "bytes".to_string(),
+            signal_type: signal_type.to_string(),
             timestamp: Some(Timestamp {
                 seconds: now.as_secs() as i64,
                 nanos: now.subsec_nanos() as i32,
@@ -0,0 +1,66 @@
+use misaka_signal::circuit_breaker::CircuitBreaker;
+use std::time::{Duration, Instant};
+
+const WINDOW: Duration = Duration::from_secs(60);
+
+#[test]
+fn test_failures_within_window_do_not_trip() {
+    let start = Instant::now();
+    let breaker = CircuitBreaker::starting_at(0.5, 1, WINDOW, start);
+
+    for i in 0..10 {
+        assert!(!breaker.record_at(false, start + Duration::from_secs(i)));
+    }
+}
+
+#[test]
+fn test_sustained_error_rate_trips_after_window() {
+    let start = Instant::now();
+    let breaker = CircuitBreaker::starting_at(0.5, 1, WINDOW, start);
+
+    assert!(!breaker.record_at(true, start));
+    assert!(!breaker.record_at(false, start + Duration::from_secs(10)));
+    assert!(!breaker.record_at(false, start + Duration::from_secs(20)));
+
+    // 窗口结束时失败率 2/3 > 0.5
+    assert!(breaker.record_at(true, start + WINDOW));
+}
+
+#[test]
+fn test_low_error_rate_starts_new_window() {
+    let start = Instant::now();
+    let breaker = CircuitBreaker::starting_at(0.5, 1, WINDOW, start);
+
+    assert!(!breaker.record_at(false, start));
+    assert!(!breaker.record_at(true, start + Duration::from_secs(1)));
+    assert!(!breaker.record_at(true, start + Duration::from_secs(2)));
+
+    // 1/3 未超过阈值，计数清零
+    assert!(!breaker.record_at(false, start + WINDOW));
+    assert!(!breaker.record_at(true, start + WINDOW + Duration::from_secs(1)));
+    assert!(!breaker.record_at(true, start + WINDOW * 2));
+}
+
+#[test]
+fn test_single_failure_does_not_trip() {
+    let start = Instant::now();
+    let breaker = CircuitBreaker::starting_at(0.5, 3, WINDOW, start);
+
+    // 低流量窗口里唯一的一次发送失败，失败率 100% 但次数不够
+    assert!(!breaker.record_at(false, start));
+    assert!(!breaker.record_at(true, start + WINDOW));
+}
+
+#[test]
+fn test_min_failures_reached_trips() {
+    let start = Instant::now();
+    let breaker = CircuitBreaker::starting_at(0.5, 3, WINDOW, start);
+
+    assert!(!breaker.record_at(true, start));
+    for i in 1..=3 {
+        assert!(!breaker.record_at(false, start + Duration::from_secs(i)));
+    }
+
+    // 3 次失败达到下限，失败率 3/4 > 0.5
+    assert!(breaker.record_at(true, start + WINDOW));
+}
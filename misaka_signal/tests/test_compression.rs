@@ -0,0 +1,28 @@
+use misaka_signal::compression::PayloadCodec;
+
+fn payload() -> Vec<u8> {
+    // 重复内容，保证压缩后更小
+    b"pumpfun_trade_event mint=So11111111111111111111111111111111111111112 ".repeat(64)
+}
+
+#[test]
+fn test_codec_round_trip() {
+    let data = payload();
+
+    for codec in [PayloadCodec::None, PayloadCodec::Zstd, PayloadCodec::Lz4] {
+        let compressed = codec.compress(&data).unwrap();
+        assert_eq!(codec.decompress(&compressed).unwrap(), data, "{:?}", codec);
+        if codec != PayloadCodec::None {
+            assert!(compressed.len() < data.len(), "{:?}", codec);
+        }
+    }
+}
+
+#[test]
+fn test_signal_type_identifies_codec() {
+    assert_eq!(PayloadCodec::None.signal_type(), "bytes");
+    for codec in [PayloadCodec::None, PayloadCodec::Zstd, PayloadCodec::Lz4] {
+        assert_eq!(PayloadCodec::from_signal_type(codec.signal_type()), Some(codec));
+    }
+    assert_eq!(PayloadCodec::from_signal_type("heartbeat"), None);
+}
@@ -0,0 +1,98 @@
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// 单条交易信号的 content_type
+pub const TRANSACTION_CONTENT_TYPE: &str = "parsed_transaction";
+
+/// 批量信号的 content_type，payload 为 `encode_batch` 的格式
+pub const BATCH_CONTENT_TYPE: &str = "parsed_transaction_batch";
+
+/// 把多条交易 payload 拼接为一个批量 payload
+///
+/// 每条 payload 前写入 4 字节小端长度：`[len u32 LE][bytes][len u32 LE][bytes]...`
+pub fn encode_batch(payloads: &[Vec<u8>]) -> Vec<u8> {
+    let total: usize = payloads.iter().map(|p| 4 + p.len()).sum();
+    let mut buffer = Vec::with_capacity(total);
+    for payload in payloads {
+        buffer.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+        buffer.extend_from_slice(payload);
+    }
+    buffer
+}
+
+/// 拆分 `encode_batch` 生成的批量 payload（接收端使用）
+pub fn decode_batch(mut data: &[u8]) -> Result<Vec<Vec<u8>>, String> {
+    let mut payloads = Vec::new();
+    while !data.is_empty() {
+        let Some((len_bytes, rest)) = data.split_first_chunk::<4>() else {
+            return Err(format!("truncated length prefix ({} bytes left)", data.len()));
+        };
+        let len = u32::from_le_bytes(*len_bytes) as usize;
+        if rest.len() < len {
+            return Err(format!(
+                "truncated payload: expected {} bytes, got {}",
+                len,
+                rest.len()
+            ));
+        }
+        let (payload, rest) = rest.split_at(len);
+        payloads.push(payload.to_vec());
+        data = rest;
+    }
+    Ok(payloads)
+}
+
+/// 待发送的交易批次
+///
+/// 攒满 `max_size` 条或第一条进入后超过 `timeout` 即应发送
+pub struct TransactionBatch {
+    max_size: usize,
+    timeout: Duration,
+    payloads: Vec<Vec<u8>>,
+    started_at: Option<Instant>,
+}
+
+impl TransactionBatch {
+    pub fn new(max_size: usize, timeout: Duration) -> Self {
+        Self {
+            max_size,
+            timeout,
+            payloads: Vec::with_capacity(max_size),
+            started_at: None,
+        }
+    }
+
+    pub fn push(&mut self, payload: Vec<u8>) {
+        if self.payloads.is_empty() {
+            self.started_at = Some(Instant::now());
+        }
+        self.payloads.push(payload);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.payloads.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.payloads.len()
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.payloads.len() >= self.max_size
+    }
+
+    /// 当前批次的发送截止时间，空批次返回 None
+    pub fn deadline(&self) -> Option<Instant> {
+        self.started_at.map(|started| started + self.timeout)
+    }
+
+    /// 取出当前批次并编码，空批次返回 None
+    pub fn take(&mut self) -> Option<Vec<u8>> {
+        if self.payloads.is_empty() {
+            return None;
+        }
+        self.started_at = None;
+        let payloads = std::mem::replace(&mut self.payloads, Vec::with_capacity(self.max_size));
+        Some(encode_batch(&payloads))
+    }
+}
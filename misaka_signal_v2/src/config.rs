@@ -1,5 +1,8 @@
+use misaka_network::AckPolicy;
 use serde::Deserialize;
 use std::fs;
+use utils::env_expand::parse_toml;
+use utils::url::{NATS_SCHEMES, normalize_url_list};
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct Config {
@@ -8,12 +11,83 @@ pub struct Config {
     pub telepath_name: String,
     pub sender_agent: String,
     pub authority_level: String,
+    /// 无真实信号超过该秒数时发送心跳，不配置则关闭心跳
+    pub heartbeat_interval_secs: Option<u64>,
+    /// NATS 订阅断开后连续重新订阅的次数上限，0 表示无限重试
+    #[serde(default)]
+    pub max_reconnect_attempts: u32,
+    /// 每个信号最多合并的交易数，1 表示逐条发送
+    #[serde(default = "default_batch_size")]
+    pub batch_size: usize,
+    /// 批次中第一条交易最多等待的毫秒数，超时即发送
+    #[serde(default = "default_batch_timeout_ms")]
+    pub batch_timeout_ms: u64,
+    /// 发布确认策略：explicit（等待 ack，至少一次）/ none（不等待，至多一次）/ all
+    #[serde(default = "default_ack_policy")]
+    pub ack_policy: String,
+    /// 等待 JetStream ack 的超时毫秒数
+    #[serde(default = "default_ack_timeout_ms")]
+    pub ack_timeout_ms: u64,
+    /// ack 超时后的重发次数
+    #[serde(default = "default_max_emit_retries")]
+    pub max_emit_retries: u32,
+    /// 第一次重发前的等待时间（毫秒），之后每次翻倍
+    #[serde(default = "default_send_retry_delay_ms")]
+    pub send_retry_delay_ms: u64,
+    /// 重发等待时间的上限（毫秒）
+    #[serde(default = "default_send_retry_max_delay_ms")]
+    pub send_retry_max_delay_ms: u64,
+}
+
+fn default_batch_size() -> usize {
+    1
+}
+
+fn default_batch_timeout_ms() -> u64 {
+    50
+}
+
+fn default_ack_policy() -> String {
+    "explicit".to_string()
+}
+
+fn default_ack_timeout_ms() -> u64 {
+    5000
+}
+
+fn default_max_emit_retries() -> u32 {
+    3
+}
+
+fn default_send_retry_delay_ms() -> u64 {
+    100
+}
+
+fn default_send_retry_max_delay_ms() -> u64 {
+    5_000
 }
 
 impl Config {
     pub fn from_toml_file(path: &str) -> Result<Self, Box<dyn std::error::Error>> {
         let content = fs::read_to_string(path)?;
-        let config: Config = toml::from_str(&content)?;
+        let mut config: Config = parse_toml(&content)?;
+        config.validate()?;
         Ok(config)
     }
+
+    /// 校验配置，并把 nats_url 补全为 `nats://host:port` 形式
+    ///
+    /// 在连接之前调用，URL 写错时给出具体哪里不对
+    pub fn validate(&mut self) -> Result<(), String> {
+        // NATS 支持逗号分隔的多个服务器地址
+        self.nats_url = normalize_url_list("nats_url", &self.nats_url, &NATS_SCHEMES)?;
+
+        if self.batch_size == 0 {
+            return Err("batch_size must be at least 1".to_string());
+        }
+        self.ack_policy
+            .parse::<AckPolicy>()
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    }
 }
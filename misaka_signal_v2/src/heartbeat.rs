@@ -0,0 +1,4 @@
+pub use utils::heartbeat::Heartbeat;
+
+/// 心跳信号的 content_type，消费端据此区分心跳和真实数据
+pub const HEARTBEAT_CONTENT_TYPE: &str = "heartbeat";
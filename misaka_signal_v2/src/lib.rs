@@ -1,5 +1,8 @@
+pub mod batch;
 pub mod config;
+pub mod heartbeat;
 pub mod signal_service;
 
 pub use config::Config;
-pub use signal_service::SignalService;
+pub use heartbeat::Heartbeat;
+pub use signal_service::{SignalRunStats, SignalService};
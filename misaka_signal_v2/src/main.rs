@@ -12,6 +12,9 @@ struct Args {
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // 重连等日志输出到 stderr
+    tracing_subscriber::fmt().with_writer(std::io::stderr).init();
+
     let args = Args::parse();
 
     // 加载配置
@@ -1,74 +1,149 @@
+use crate::batch::{BATCH_CONTENT_TYPE, TRANSACTION_CONTENT_TYPE, TransactionBatch};
 use crate::config::Config;
+use crate::heartbeat::{Heartbeat, HEARTBEAT_CONTENT_TYPE};
+use utils::reconnect::ReconnectBackoff;
 use common::nats_client::NatsClient;
-use misaka_network::MisakaNetwork;
+use misaka_network::{AckPolicy, MisakaNetwork};
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
-use tokio::time::interval;
+use tokio::task::JoinHandle;
+use tokio::time::{Instant, interval, sleep_until};
 use tokio_stream::StreamExt;
+use tokio_util::sync::CancellationToken;
+use tokio_util::task::TaskTracker;
+
+/// `run_with_shutdown` 返回的统计（启动以来的总量）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SignalRunStats {
+    pub messages_received: u64,
+    pub signals_sent: u64,
+    pub bytes_sent: u64,
+    /// 重试后仍发送失败的信号数
+    pub send_errors: u64,
+}
+
+/// SignalService 运行期间的累计计数器（启动以来的总量，周期统计按差值计算）
+#[derive(Debug, Default)]
+struct SignalCounters {
+    nats_messages_received: AtomicU64,
+    signals_sent: AtomicU64,
+    /// 重试后仍发送失败的信号数
+    send_errors: AtomicU64,
+    // 性能指标（累积值，单位：微秒）
+    total_emit_time_us: AtomicU64,
+    total_bytes_sent: AtomicU64,
+}
 
 pub struct SignalService {
     nats_client: NatsClient,
     network: Arc<MisakaNetwork>,
     config: Arc<Config>,
-    // 统计计数器
-    nats_messages_received: Arc<AtomicU64>,
-    signals_sent: Arc<AtomicU64>,
-    // 性能指标（累积值，单位：微秒）
-    total_emit_time_us: Arc<AtomicU64>,
-    total_bytes_sent: Arc<AtomicU64>,
+    counters: Arc<SignalCounters>,
+    // 心跳（未配置 heartbeat_interval_secs 时为 None）
+    heartbeat: Option<Heartbeat>,
+    // 发送中的信号，退出前等待全部完成
+    in_flight: TaskTracker,
 }
 
 impl SignalService {
-    pub async fn new(config: Config) -> Result<Self, Box<dyn std::error::Error>> {
+    pub async fn new(mut config: Config) -> Result<Self, Box<dyn std::error::Error>> {
+        // 连接前校验，URL 写错时直接报出哪里不对
+        config.validate()?;
+
         // 连接 NATS
         let nats_client = NatsClient::new(&config.nats_url).await?;
         println!("✅ Connected to NATS: {}", config.nats_url);
 
         // 创建 MisakaNetwork 客户端（new 已经包含连接）
-        let network = MisakaNetwork::new(&config.nats_url).await?;
-        println!("✅ MisakaNetwork connected");
+        let ack_policy: AckPolicy = config.ack_policy.parse()?;
+        let network = MisakaNetwork::new(&config.nats_url)
+            .await?
+            .with_ack_policy(ack_policy)
+            .with_ack_timeout(Duration::from_millis(config.ack_timeout_ms))
+            .with_max_retries(config.max_emit_retries)
+            .with_retry_delay(
+                Duration::from_millis(config.send_retry_delay_ms),
+                Duration::from_millis(config.send_retry_max_delay_ms),
+            );
+        println!("✅ MisakaNetwork connected (ack policy: {:?})", ack_policy);
 
         // 创建 Telepath（如果不存在）
         let telepath_config = misaka_network::TelepathConfig::default();
-        match network.create_telepath(&config.telepath_name, telepath_config).await {
-            Ok(_) => println!("✅ Telepath '{}' created", config.telepath_name),
-            Err(e) => {
-                // 如果已存在，忽略错误
-                if e.to_string().contains("already exists") || e.to_string().contains("name already in use") {
-                    println!("ℹ️  Telepath '{}' already exists", config.telepath_name);
-                } else {
-                    return Err(e.into());
-                }
-            }
+        let existed = network.telepath_exists(&config.telepath_name).await?;
+        network
+            .ensure_telepath(&config.telepath_name, telepath_config)
+            .await?;
+        if existed {
+            println!("ℹ️  Telepath '{}' already exists", config.telepath_name);
+        } else {
+            println!("✅ Telepath '{}' created", config.telepath_name);
         }
 
+        let heartbeat = config
+            .heartbeat_interval_secs
+            .map(|secs| Heartbeat::new(Duration::from_secs(secs)));
+
         Ok(Self {
             nats_client,
             network: Arc::new(network),
             config: Arc::new(config),
-            nats_messages_received: Arc::new(AtomicU64::new(0)),
-            signals_sent: Arc::new(AtomicU64::new(0)),
-            total_emit_time_us: Arc::new(AtomicU64::new(0)),
-            total_bytes_sent: Arc::new(AtomicU64::new(0)),
+            counters: Arc::new(SignalCounters::default()),
+            heartbeat,
+            in_flight: TaskTracker::new(),
         })
     }
 
-    async fn start_statistics_task(&self) {
+    /// 启动以来的累计统计
+    pub fn stats(&self) -> SignalRunStats {
+        let counters = &self.counters;
+        SignalRunStats {
+            messages_received: counters.nats_messages_received.load(Ordering::Relaxed),
+            signals_sent: counters.signals_sent.load(Ordering::Relaxed),
+            bytes_sent: counters.total_bytes_sent.load(Ordering::Relaxed),
+            send_errors: counters.send_errors.load(Ordering::Relaxed),
+        }
+    }
+
+    /// 启动心跳任务：空闲超过间隔时发送空 payload 的心跳信号
+    fn start_heartbeat_task(&self) -> Option<JoinHandle<()>> {
+        let heartbeat = self.heartbeat.as_ref()?;
+
+        let network = Arc::clone(&self.network);
+        let config = Arc::clone(&self.config);
+        Some(heartbeat.spawn(move || {
+            let network = Arc::clone(&network);
+            let config = Arc::clone(&config);
+            async move {
+                let signal = Self::create_signal(&config, HEARTBEAT_CONTENT_TYPE, Vec::new());
+                network
+                    .emit_signal(&config.telepath_name, signal)
+                    .await
+                    .map(|_| ())
+                    .map_err(|e| e.to_string())
+            }
+        }))
+    }
+
+    fn start_statistics_task(&self) -> JoinHandle<()> {
         let mut timer = interval(Duration::from_secs(60));
-        let nats_counter = Arc::clone(&self.nats_messages_received);
-        let signals_counter = Arc::clone(&self.signals_sent);
-        let emit_time_counter = Arc::clone(&self.total_emit_time_us);
-        let bytes_counter = Arc::clone(&self.total_bytes_sent);
+        let counters = Arc::clone(&self.counters);
 
         tokio::spawn(async move {
+            // 计数器是累计值，周期统计取与上次的差值
+            let mut last = [0u64; 4];
             loop {
                 timer.tick().await;
 
-                let nats_count = nats_counter.swap(0, Ordering::Relaxed);
-                let signals_count = signals_counter.swap(0, Ordering::Relaxed);
-                let total_emit_us = emit_time_counter.swap(0, Ordering::Relaxed);
-                let total_bytes = bytes_counter.swap(0, Ordering::Relaxed);
+                let current = [
+                    counters.nats_messages_received.load(Ordering::Relaxed),
+                    counters.signals_sent.load(Ordering::Relaxed),
+                    counters.total_emit_time_us.load(Ordering::Relaxed),
+                    counters.total_bytes_sent.load(Ordering::Relaxed),
+                ];
+                let [nats_count, signals_count, total_emit_us, total_bytes] =
+                    std::array::from_fn(|i| current[i].saturating_sub(last[i]));
+                last = current;
 
                 // 计算平均值
                 let avg_emit_us = if signals_count > 0 {
@@ -97,68 +172,183 @@ impl SignalService {
                     total_bytes as f64 / (1024.0 * 1024.0)
                 );
             }
-        });
+        })
     }
 
+    /// 运行直到收到 Ctrl-C
     pub async fn run(self) -> Result<(), Box<dyn std::error::Error>> {
+        let token = CancellationToken::new();
+        let ctrl_c_token = token.clone();
+        tokio::spawn(async move {
+            if tokio::signal::ctrl_c().await.is_ok() {
+                ctrl_c_token.cancel();
+            }
+        });
+
+        self.run_with_shutdown(token).await?;
+        Ok(())
+    }
+
+    /// 运行直到 `token` 被取消：发出剩余批次、等待发送中的信号完成后返回累计统计
+    pub async fn run_with_shutdown(
+        self,
+        token: CancellationToken,
+    ) -> Result<SignalRunStats, Box<dyn std::error::Error>> {
         println!("🚀 SignalService V2 starting...");
         println!("📡 NATS topic: {}", self.config.topic);
         println!("🎯 Telepath: {}", self.config.telepath_name);
 
         // 启动统计任务
-        self.start_statistics_task().await;
+        let statistics_task = self.start_statistics_task();
+        let heartbeat_task = self.start_heartbeat_task();
+        // 转为 String，等待发送任务期间不持有非 Send 的错误
+        let result = self.forward_until(&token).await.map_err(|e| e.to_string());
+
+        // 退出前等待发送中的信号（包括最后一个批次）完成，统计才是准确的
+        self.in_flight.close();
+        self.in_flight.wait().await;
+        statistics_task.abort();
+        if let Some(heartbeat_task) = heartbeat_task {
+            heartbeat_task.abort();
+        }
 
+        result?;
+        let stats = self.stats();
+        println!(
+            "🏁 SignalService V2 stopped | NATS: {} | Signals: {} | Errors: {} | Total data: {:.2} MB",
+            stats.messages_received,
+            stats.signals_sent,
+            stats.send_errors,
+            stats.bytes_sent as f64 / (1024.0 * 1024.0)
+        );
+        Ok(stats)
+    }
+
+    /// 订阅 NATS 并转发，直到 `token` 被取消或重连次数用尽
+    async fn forward_until(&self, token: &CancellationToken) -> Result<(), Box<dyn std::error::Error>> {
+
+        let backoff = ReconnectBackoff::new(self.config.max_reconnect_attempts);
         let mut subscriber = self.nats_client.subscribe(&self.config.topic).await?;
+        let mut reconnect_attempts = 0u32;
+
+        // batch_size 为 1 时保持逐条发送
+        let batching = self.config.batch_size > 1;
+        let mut batch = TransactionBatch::new(
+            self.config.batch_size,
+            Duration::from_millis(self.config.batch_timeout_ms),
+        );
+        if batching {
+            println!(
+                "📦 Batching up to {} transactions / {} ms per signal",
+                self.config.batch_size, self.config.batch_timeout_ms
+            );
+        }
+
+        loop {
+            let deadline = batch.deadline();
+            let next = tokio::select! {
+                next = subscriber.next() => next,
+                _ = sleep_until(deadline.unwrap_or_else(Instant::now)), if deadline.is_some() => {
+                    self.flush_batch(&mut batch);
+                    continue;
+                }
+                _ = token.cancelled() => {
+                    println!("🛑 Shutdown signal received");
+                    break;
+                }
+            };
+
+            // 流结束（NATS 断开）时退避后重新订阅，订阅失败同样计入连续重连次数
+            let Some(message) = next else {
+                println!("NATS stream ended");
+                // 已攒的交易先发出去，不等重连
+                self.flush_batch(&mut batch);
+                let resubscribe = backoff.resubscribe(&mut reconnect_attempts, || {
+                    self.nats_client.subscribe(&self.config.topic)
+                });
+                // 退避等待期间也响应停止信号
+                subscriber = tokio::select! {
+                    subscriber = resubscribe => subscriber?,
+                    _ = token.cancelled() => {
+                        println!("🛑 Shutdown signal received");
+                        break;
+                    }
+                };
+                println!("✅ Resubscribed to NATS topic: {}", self.config.topic);
+                continue;
+            };
+            reconnect_attempts = 0;
 
-        while let Some(message) = subscriber.next().await {
             // 增加 NATS 消息接收计数
-            self.nats_messages_received.fetch_add(1, Ordering::Relaxed);
+            self.counters
+                .nats_messages_received
+                .fetch_add(1, Ordering::Relaxed);
 
             // 直接获取 bytes，不需要反序列化
             let tx_bytes = message.payload.to_vec();
 
-            // Spawn 异步任务发送
-            let network = Arc::clone(&self.network);
-            let config = Arc::clone(&self.config);
-            let signals_counter = Arc::clone(&self.signals_sent);
-            let emit_time_counter = Arc::clone(&self.total_emit_time_us);
-            let bytes_counter = Arc::clone(&self.total_bytes_sent);
-
-            tokio::spawn(async move {
-                if let Err(e) = Self::send_signal(
-                    network,
-                    config,
-                    tx_bytes,
-                    signals_counter,
-                    emit_time_counter,
-                    bytes_counter,
-                )
-                .await
-                {
-                    eprintln!("❌ Failed to send signal: {:?}", e);
-                }
-            });
+            if !batching {
+                self.spawn_send(TRANSACTION_CONTENT_TYPE, tx_bytes);
+                continue;
+            }
+
+            batch.push(tx_bytes);
+            if batch.is_full() {
+                self.flush_batch(&mut batch);
+            }
+        }
+
+        // 退出前发送剩余批次（由 run_with_shutdown 等待完成）
+        let pending = batch.len();
+        if let Some(payload) = batch.take() {
+            println!("📦 Flushing {} pending transactions before exit", pending);
+            self.spawn_send(BATCH_CONTENT_TYPE, payload);
         }
 
-        println!("NATS stream ended");
         Ok(())
     }
 
+    /// 取出当前批次并异步发送
+    fn flush_batch(&self, batch: &mut TransactionBatch) {
+        if let Some(payload) = batch.take() {
+            self.spawn_send(BATCH_CONTENT_TYPE, payload);
+        }
+    }
+
+    /// Spawn 异步任务发送一个信号
+    fn spawn_send(&self, content_type: &'static str, payload: Vec<u8>) {
+        let network = Arc::clone(&self.network);
+        let config = Arc::clone(&self.config);
+        let counters = Arc::clone(&self.counters);
+        let heartbeat = self.heartbeat.clone();
+
+        self.in_flight.spawn(async move {
+            if let Err(e) =
+                Self::send_signal(network, config, content_type, payload, &counters, heartbeat).await
+            {
+                counters.send_errors.fetch_add(1, Ordering::Relaxed);
+                eprintln!("❌ Failed to send signal: {:?}", e);
+            }
+        });
+    }
+
     /// 发送 Signal 到 MisakaNetwork
     async fn send_signal(
         network: Arc<MisakaNetwork>,
         config: Arc<Config>,
+        content_type: &str,
         tx_bytes: Vec<u8>,
-        signals_counter: Arc<AtomicU64>,
-        emit_time_counter: Arc<AtomicU64>,
-        bytes_counter: Arc<AtomicU64>,
+        counters: &SignalCounters,
+        heartbeat: Option<Heartbeat>,
     ) -> Result<(), Box<dyn std::error::Error>> {
         // 记录字节数
         let bytes_len = tx_bytes.len() as u64;
-        bytes_counter.fetch_add(bytes_len, Ordering::Relaxed);
+        counters
+            .total_bytes_sent
+            .fetch_add(bytes_len, Ordering::Relaxed);
 
         // 创建 MisakaSignal
-        let signal = Self::create_signal(&config, tx_bytes);
+        let signal = Self::create_signal(&config, content_type, tx_bytes);
 
         // 发送（记录时间）
         let start = std::time::Instant::now();
@@ -166,16 +356,25 @@ impl SignalService {
             .emit_signal(&config.telepath_name, signal)
             .await?;
         let emit_time_us = start.elapsed().as_micros() as u64;
-        emit_time_counter.fetch_add(emit_time_us, Ordering::Relaxed);
+        counters
+            .total_emit_time_us
+            .fetch_add(emit_time_us, Ordering::Relaxed);
 
         // 增加发送成功计数
-        signals_counter.fetch_add(1, Ordering::Relaxed);
+        counters.signals_sent.fetch_add(1, Ordering::Relaxed);
+        if let Some(heartbeat) = heartbeat {
+            heartbeat.mark_activity();
+        }
 
         Ok(())
     }
 
     /// 创建 MisakaSignal
-    fn create_signal(config: &Config, binary_data: Vec<u8>) -> misaka_network::MisakaSignal {
+    fn create_signal(
+        config: &Config,
+        content_type: &str,
+        binary_data: Vec<u8>,
+    ) -> misaka_network::MisakaSignal {
         use prost_types::Timestamp;
 
         let now = std::time::SystemTime::now()
@@ -193,7 +392,7 @@ impl SignalService {
             parent_uuid: String::new(),
             sender_agent: config.sender_agent.clone(),
             authority: authority as i32,
-            content_type: "parsed_transaction".to_string(),
+            content_type: content_type.to_string(),
             payload: binary_data,
         }
     }
@@ -0,0 +1,38 @@
+use misaka_signal_v2::batch::{TransactionBatch, decode_batch, encode_batch};
+use std::time::Duration;
+
+#[test]
+fn test_encode_decode_round_trip() {
+    let payloads = vec![b"tx-1".to_vec(), Vec::new(), vec![0u8; 300]];
+
+    let encoded = encode_batch(&payloads);
+    assert_eq!(encoded.len(), 3 * 4 + 4 + 300);
+    assert_eq!(&encoded[..4], &4u32.to_le_bytes());
+    assert_eq!(decode_batch(&encoded).unwrap(), payloads);
+}
+
+#[test]
+fn test_decode_rejects_truncated_batch() {
+    let encoded = encode_batch(&[b"transaction".to_vec()]);
+
+    assert!(decode_batch(&encoded[..2]).is_err());
+    assert!(decode_batch(&encoded[..encoded.len() - 1]).is_err());
+}
+
+#[tokio::test]
+async fn test_batch_fills_and_resets() {
+    let mut batch = TransactionBatch::new(2, Duration::from_millis(50));
+    assert!(batch.deadline().is_none());
+    assert!(batch.take().is_none());
+
+    batch.push(b"a".to_vec());
+    assert!(batch.deadline().is_some());
+    assert!(!batch.is_full());
+    batch.push(b"b".to_vec());
+    assert!(batch.is_full());
+
+    let payload = batch.take().unwrap();
+    assert_eq!(decode_batch(&payload).unwrap(), vec![b"a".to_vec(), b"b".to_vec()]);
+    assert!(batch.is_empty());
+    assert!(batch.deadline().is_none());
+}
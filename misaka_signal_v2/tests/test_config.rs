@@ -0,0 +1,45 @@
+use misaka_signal_v2::Config;
+
+fn parse(extra: &str) -> Config {
+    toml::from_str(&format!(
+        r#"
+        topic = "geyser.shyft_processed"
+        telepath_name = "parsed_transaction"
+        sender_agent = "env.parsed_transaction_v2"
+        authority_level = "LV5"
+        {}
+        "#,
+        extra
+    ))
+    .unwrap()
+}
+
+#[test]
+fn test_validate_normalizes_nats_url() {
+    let mut config = parse(r#"nats_url = "localhost""#);
+    config.validate().unwrap();
+    assert_eq!(config.nats_url, "nats://localhost:4222");
+
+    let mut config = parse(r#"nats_url = "nats://a:4222,b""#);
+    config.validate().unwrap();
+    assert_eq!(config.nats_url, "nats://a:4222,nats://b:4222");
+}
+
+#[test]
+fn test_validate_rejects_bad_config() {
+    let error = parse(r#"nats_url = "http://localhost:4222""#).validate().unwrap_err();
+    assert!(error.contains("nats_url") && error.contains("scheme"), "{}", error);
+
+    let error = parse(r#"nats_url = "localhost:port""#).validate().unwrap_err();
+    assert!(error.contains("invalid port"), "{}", error);
+
+    let error = parse("nats_url = \"localhost\"\nbatch_size = 0").validate().unwrap_err();
+    assert!(error.contains("batch_size"), "{}", error);
+}
+
+#[test]
+fn test_send_retry_delay_defaults() {
+    let config = parse(r#"nats_url = "localhost""#);
+    assert_eq!(config.send_retry_delay_ms, 100);
+    assert_eq!(config.send_retry_max_delay_ms, 5_000);
+}
@@ -0,0 +1,46 @@
+use misaka_signal_v2::{Config, SignalService};
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+
+const NATS_URL: &str = "nats://localhost:4222";
+
+fn test_config(topic: &str) -> Config {
+    toml::from_str(&format!(
+        r#"
+        nats_url = "{}"
+        topic = "{}"
+        telepath_name = "test_signal_v2_run"
+        sender_agent = "test.signal_v2"
+        authority_level = "LV0"
+        "#,
+        NATS_URL, topic
+    ))
+    .unwrap()
+}
+
+#[tokio::test]
+#[ignore = "integration test, requires NATS with JetStream"]
+async fn test_run_with_shutdown_returns_stats() {
+    let topic = format!("test.signal_v2.{}", uuid::Uuid::new_v4());
+    let service = SignalService::new(test_config(&topic)).await.unwrap();
+
+    let token = CancellationToken::new();
+    let run = tokio::spawn(service.run_with_shutdown(token.clone()));
+
+    // 等待订阅建立后发布几条消息
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    let publisher = async_nats::connect(NATS_URL).await.unwrap();
+    for i in 0..3u8 {
+        publisher.publish(topic.clone(), vec![i; 16].into()).await.unwrap();
+    }
+    publisher.flush().await.unwrap();
+
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    token.cancel();
+
+    let stats = run.await.unwrap().unwrap();
+    assert_eq!(stats.messages_received, 3);
+    assert_eq!(stats.signals_sent, 3);
+    assert_eq!(stats.bytes_sent, 48);
+    assert_eq!(stats.send_errors, 0);
+}
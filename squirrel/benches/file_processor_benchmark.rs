@@ -0,0 +1,71 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use squirrel::block_parser::file_processor::FileProcessor;
+use squirrel::block_parser::progress::NoopReporter;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tempfile::TempDir;
+use utils::slot_meta::SlotMeta;
+
+const SLOTS: u64 = 200;
+const SLOT_BYTES: usize = 1024 * 1024;
+
+/// 生成 SLOTS 个 zstd 压缩的 slot
+///
+/// 内容不是合法 block，解析阶段会被跳过，不会产生插入，测的是读取 + 解压流水线的吞吐
+fn write_file_pair(dir: &Path) -> (PathBuf, PathBuf) {
+    let meta_path = dir.join("bench.meta");
+    let bin_path = dir.join("bench.bin");
+
+    let mut bin = Vec::new();
+    let mut slots = Vec::new();
+    for slot in 0..SLOTS {
+        // 伪随机但可压缩的数据，接近真实 slot 的压缩比
+        let raw: Vec<u8> = (0..SLOT_BYTES)
+            .map(|i| ((i as u64 * 31 + slot * 7) % 251) as u8 ^ (i / 64) as u8)
+            .collect();
+        let compressed = zstd::encode_all(&raw[..], 3).unwrap();
+        slots.push(SlotMeta {
+            slot,
+            offset: Some(bin.len() as u64),
+            size: compressed.len() as u64,
+        });
+        bin.extend_from_slice(&compressed);
+    }
+    std::fs::write(&bin_path, &bin).unwrap();
+    std::fs::write(&meta_path, rmp_serde::to_vec(&slots).unwrap()).unwrap();
+
+    (meta_path, bin_path)
+}
+
+fn bench_decode_workers(c: &mut Criterion) {
+    let temp_dir = TempDir::new().unwrap();
+    let (meta_path, bin_path) = write_file_pair(temp_dir.path());
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let cpus = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(4);
+
+    let mut group = c.benchmark_group("process_file_pair");
+    group.sample_size(10);
+
+    let mut worker_counts = vec![1, 2, 4, cpus];
+    worker_counts.sort();
+    worker_counts.dedup();
+    for workers in worker_counts {
+        group.bench_function(format!("decode_workers_{}", workers), |b| {
+            b.iter(|| {
+                runtime.block_on(async {
+                    let mut processor = FileProcessor::new(1)
+                        .with_reporter(Arc::new(NoopReporter))
+                        .with_decode_workers(workers);
+                    let report = processor.process_file_pair(&meta_path, &bin_path).await.unwrap();
+                    assert_eq!(report.slots_skipped_parse, SLOTS as usize);
+                    processor.finish().await;
+                })
+            })
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_decode_workers);
+criterion_main!(benches);
@@ -1,9 +1,15 @@
 use super::file_scanner::{FileScanner, FilePair};
 use super::file_processor::FileProcessor;
 use super::processed_tracker::ProcessedTracker;
-use std::path::PathBuf;
+use super::checkpoint::CheckpointStore;
+use super::compression::Compression;
+use std::collections::{BTreeMap, HashMap};
+use utils::convert_transaction::ConvertedEvents;
+use std::path::{Path, PathBuf};
+use tokio::task::{Id, JoinSet};
 use tokio::time::{sleep, Duration};
 use toml;
+use utils::env_expand::read_toml_value;
 
 pub struct BlockParserService {
     scanner: FileScanner,
@@ -11,6 +17,19 @@ pub struct BlockParserService {
     processor: FileProcessor,
     scan_interval_seconds: u64,
     enable_watch: bool,
+    max_concurrent_files: usize,
+    on_error: ErrorPolicy,
+    data_dir: PathBuf,
+    failed: Vec<FailedFile>, // 本次运行中处理失败的文件对
+    files_this_run: usize, // 本次运行成功处理的文件对数
+    rows_per_table: BTreeMap<String, u64>, // 本次运行提交插入的行数（按表）
+}
+
+/// 处理失败的文件对
+#[derive(Debug, Clone)]
+pub struct FailedFile {
+    pub prefix: String,
+    pub error: String,
 }
 
 #[derive(Debug, Clone)]
@@ -20,13 +39,51 @@ pub struct Config {
     pub scan_interval_seconds: u64,
     pub enable_watch: bool,
     pub max_concurrent_clickhouse_tasks: usize,
+    pub spill_dir: Option<String>, // 插入失败时批次落盘目录，不配置则失败直接退出
+    pub max_concurrent_files: usize, // 同时处理的文件对数量，默认 1（逐个处理）
+    pub on_error: ErrorPolicy, // 单个文件对处理失败时的处理方式，默认 Abort
+    pub strict: bool, // 遇到损坏的 slot 时该文件处理失败，默认 false（跳过并计数）
+    pub dedup_tables: Vec<String>, // 插入前批量内去重的表（如 "pumpfun_trade_event"），默认不去重
+    pub compression: Option<Compression>, // slot 压缩格式（zstd / gzip / none），不配置则按魔数自动判断
+    pub decode_workers: Option<usize>, // 每个文件并行解压、解析 slot 的线程数，不配置则为 CPU 核数
+    pub slot_range: Option<(u64, u64)>, // 只处理与 [起始slot, 结束slot] 有交集的文件对，不配置则处理全部
+}
+
+/// 单个文件对处理失败时的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorPolicy {
+    /// 立即返回错误（调试用，默认）
+    Abort,
+    /// 记录失败并继续处理其他文件，本次运行不再重试该文件，重启后会重新处理
+    SkipAndContinue,
+    /// 记录失败并把文件对移到 `{data_dir}/failed/`，不再被扫描到
+    Quarantine,
+}
+
+impl std::str::FromStr for ErrorPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "abort" => Ok(ErrorPolicy::Abort),
+            "skip" | "skip_and_continue" => Ok(ErrorPolicy::SkipAndContinue),
+            "quarantine" => Ok(ErrorPolicy::Quarantine),
+            other => Err(format!(
+                "Invalid 'on_error' value '{}', expected abort, skip_and_continue or quarantine",
+                other
+            )),
+        }
+    }
 }
 
 impl Config {
     pub fn from_toml_file(config_path: &str) -> Result<Self, Box<dyn std::error::Error>> {
-        let config_content = std::fs::read_to_string(config_path)?;
-        let toml_value: toml::Value = toml::from_str(&config_content)?;
+        let toml_value = read_toml_value(config_path)?;
         
+        Self::from_toml_value(&toml_value)
+    }
+    
+    pub fn from_toml_value(toml_value: &toml::Value) -> Result<Self, Box<dyn std::error::Error>> {
         let config = Config {
             data_dir: toml_value.get("data_dir")
                 .and_then(|v| v.as_str())
@@ -45,41 +102,94 @@ impl Config {
             max_concurrent_clickhouse_tasks: toml_value.get("max_concurrent_clickhouse_tasks")
                 .and_then(|v| v.as_integer())
                 .unwrap_or(3) as usize,
-        };
-        
-        Ok(config)
-    }
-    
-    pub fn from_toml_value(toml_value: &toml::Value) -> Result<Self, Box<dyn std::error::Error>> {
-        let config = Config {
-            data_dir: toml_value.get("data_dir")
+            spill_dir: toml_value.get("spill_dir")
                 .and_then(|v| v.as_str())
-                .ok_or("Missing 'data_dir' in config")?
-                .to_string(),
-            processed_dir: toml_value.get("processed_dir")
-                .and_then(|v| v.as_str())
-                .ok_or("Missing 'processed_dir' in config")?
-                .to_string(),
-            scan_interval_seconds: toml_value.get("scan_interval_seconds")
+                .map(|s| s.to_string()),
+            max_concurrent_files: toml_value.get("max_concurrent_files")
                 .and_then(|v| v.as_integer())
-                .unwrap_or(600) as u64,
-            enable_watch: toml_value.get("enable_watch")
+                .unwrap_or(1) as usize,
+            on_error: toml_value.get("on_error")
+                .and_then(|v| v.as_str())
+                .map(str::parse)
+                .transpose()?
+                .unwrap_or(ErrorPolicy::Abort),
+            strict: toml_value.get("strict")
                 .and_then(|v| v.as_bool())
-                .unwrap_or(true),
-            max_concurrent_clickhouse_tasks: toml_value.get("max_concurrent_clickhouse_tasks")
+                .unwrap_or(false),
+            dedup_tables: toml_value.get("dedup_tables")
+                .and_then(|v| v.as_array())
+                .map(|tables| {
+                    tables.iter()
+                        .map(|t| t.as_str().map(str::to_string).ok_or("'dedup_tables' must be strings"))
+                        .collect::<Result<Vec<_>, _>>()
+                })
+                .transpose()?
+                .unwrap_or_default(),
+            compression: toml_value.get("compression")
+                .and_then(|v| v.as_str())
+                .map(str::parse)
+                .transpose()?,
+            decode_workers: toml_value.get("decode_workers")
                 .and_then(|v| v.as_integer())
-                .unwrap_or(3) as usize,
+                .map(|n| n as usize),
+            slot_range: toml_value.get("slot_range")
+                .map(|v| {
+                    v.as_array()
+                        .filter(|range| range.len() == 2)
+                        .and_then(|range| Some((range[0].as_integer()? as u64, range[1].as_integer()? as u64)))
+                        .ok_or("'slot_range' must be [start_slot, end_slot]")
+                })
+                .transpose()?,
         };
+
+        if let Some((start, end)) = config.slot_range.filter(|(start, end)| start > end) {
+            return Err(format!("Invalid 'slot_range': start {} is after end {}", start, end).into());
+        }
+
+        // custom 行没有统一的去重键，不允许配置
+        if let Some(table) = config.dedup_tables.iter().find(|table| {
+            table.as_str() == "custom" || !ConvertedEvents::TABLE_NAMES.contains(&table.as_str())
+        }) {
+            return Err(format!("Invalid table '{}' in 'dedup_tables'", table).into());
+        }
         
         Ok(config)
     }
+
+    /// 用命令行的 `--since` / `--until` 覆盖 slot 区间，只给出一端时另一端沿用配置（或不限）
+    pub fn override_slot_range(&mut self, since: Option<u64>, until: Option<u64>) -> Result<(), String> {
+        if since.is_none() && until.is_none() {
+            return Ok(());
+        }
+
+        let (start, end) = self.slot_range.unwrap_or((0, u64::MAX));
+        let (start, end) = (since.unwrap_or(start), until.unwrap_or(end));
+        if start > end {
+            return Err(format!("Invalid slot range: --since {} is after --until {}", start, end));
+        }
+        self.slot_range = Some((start, end));
+        Ok(())
+    }
 }
 
 impl BlockParserService {
     pub fn new(config: Config) -> Result<Self, Box<dyn std::error::Error>> {
-        let scanner = FileScanner::new(PathBuf::from(&config.data_dir));
+        let scanner = FileScanner::new(PathBuf::from(&config.data_dir))
+            .with_slot_range(config.slot_range);
         let mut tracker = ProcessedTracker::new(PathBuf::from(&config.processed_dir));
-        let processor = FileProcessor::new(config.max_concurrent_clickhouse_tasks);
+        let mut processor = FileProcessor::with_spill_dir(
+            config.max_concurrent_clickhouse_tasks,
+            config.spill_dir.as_ref().map(PathBuf::from),
+        )
+        .with_strict(config.strict)
+        .with_dedup_tables(config.dedup_tables.clone())
+        .with_compression(config.compression)
+        .with_checkpoints(CheckpointStore::new(
+            PathBuf::from(&config.processed_dir).join("checkpoints"),
+        ));
+        if let Some(decode_workers) = config.decode_workers {
+            processor = processor.with_decode_workers(decode_workers);
+        }
         
         // 加载已处理文件列表
         tracker.load_processed_list()?;
@@ -90,6 +200,12 @@ impl BlockParserService {
             processor,
             scan_interval_seconds: config.scan_interval_seconds,
             enable_watch: config.enable_watch,
+            max_concurrent_files: config.max_concurrent_files.max(1),
+            on_error: config.on_error,
+            data_dir: PathBuf::from(&config.data_dir),
+            failed: Vec::new(),
+            files_this_run: 0,
+            rows_per_table: BTreeMap::new(),
         })
     }
 
@@ -108,8 +224,8 @@ impl BlockParserService {
                 }
                 Err(e) => {
                     eprintln!("Error processing files: {}", e);
-                    // 错误时直接panic，便于调试
-                    panic!("Processing failed: {}", e);
+                    // 交给调用方决定是否退出
+                    return Err(e);
                 }
             }
             
@@ -144,6 +260,8 @@ impl BlockParserService {
         let pending_pairs: Vec<FilePair> = file_pairs
             .into_iter()
             .filter(|pair| !self.tracker.is_processed(&pair.prefix))
+            // SkipAndContinue 时本次运行不再重试已失败的文件
+            .filter(|pair| !self.failed.iter().any(|failed| failed.prefix == pair.prefix))
             .collect();
             
         if pending_pairs.is_empty() {
@@ -153,34 +271,121 @@ impl BlockParserService {
         
         println!("Processing {} pending file pairs", pending_pairs.len());
         
-        // 处理每个文件对
+        // 并行处理文件对，最多同时处理 max_concurrent_files 个；
+        // 每个文件使用独立的处理器（独立批量和插入错误），共享 ClickHouse 插入协程池
+        let mut remaining = pending_pairs.into_iter();
+        let mut tasks = JoinSet::new();
+        let mut running: HashMap<Id, FilePair> = HashMap::new();
         let mut processed_count = 0;
-        for pair in pending_pairs {
-            println!("Processing file pair: {}", pair.prefix);
-            
-            match self.processor.process_file_pair(&pair.meta_path, &pair.bin_path).await {
-                Ok(_) => {
-                    // 标记为已处理
-                    self.tracker.mark_as_processed(&pair.prefix)?;
+        let mut first_error: Option<String> = None;
+
+        loop {
+            // 出现需要中止的错误后不再启动新文件，但等已开始的文件处理完并正常标记
+            while first_error.is_none() && tasks.len() < self.max_concurrent_files {
+                let Some(pair) = remaining.next() else {
+                    break;
+                };
+                println!("Processing file pair: {}", pair.prefix);
+
+                let mut processor = self.processor.scoped();
+                let (meta_path, bin_path) = (pair.meta_path.clone(), pair.bin_path.clone());
+                let handle = tasks.spawn(async move {
+                    processor
+                        .process_file_pair(&meta_path, &bin_path)
+                        .await
+                        .map_err(|e| e.to_string())
+                });
+                running.insert(handle.id(), pair);
+            }
+
+            let Some(joined) = tasks.join_next_with_id().await else {
+                break;
+            };
+            // 任务 panic 也按该文件处理失败对待
+            let (id, result) = match joined {
+                Ok((id, result)) => (id, result),
+                Err(e) => (e.id(), Err(format!("processing task failed: {}", e))),
+            };
+            let pair = running
+                .remove(&id)
+                .expect("every spawned task has a file pair");
+
+            match result {
+                Ok(report) => {
+                    // 文件的插入全部完成后才标记为已处理
+                    if let Err(e) = self.tracker.mark_as_processed(&pair.prefix) {
+                        eprintln!("Failed to mark {} as processed: {}", pair.prefix, e);
+                        first_error.get_or_insert(format!(
+                            "Failed to mark {} as processed: {}",
+                            pair.prefix, e
+                        ));
+                        continue;
+                    }
                     processed_count += 1;
-                    println!("Successfully processed: {}", pair.prefix);
+                    self.files_this_run += 1;
+                    for (table, rows) in &report.rows_per_table {
+                        *self.rows_per_table.entry(table.clone()).or_default() += *rows as u64;
+                    }
+                    println!(
+                        "Successfully processed: {} ({}/{} slots, {} rows, {} corrupt slots skipped, {} duplicate rows removed)",
+                        pair.prefix,
+                        report.slots_processed,
+                        report.slots_total,
+                        report.rows_inserted(),
+                        report.slots_skipped(),
+                        report.rows_deduped
+                    );
                 }
                 Err(e) => {
                     eprintln!("Failed to process {}: {}", pair.prefix, e);
-                    // 根据需求，处理失败直接panic
-                    panic!("Processing failed for {}: {}", pair.prefix, e);
+                    // 未标记为已处理，Abort 时下次运行会重新处理该文件对
+                    if let Err(abort) = self.handle_failed_file(&pair, &e) {
+                        first_error.get_or_insert(abort);
+                    }
                 }
             }
         }
-        
+
+        if let Some(error) = first_error {
+            return Err(error.into());
+        }
+
         Ok(processed_count)
     }
     
+    /// 按 on_error 处理失败的文件对，返回 Err 表示需要中止本次扫描
+    fn handle_failed_file(&mut self, pair: &FilePair, error: &str) -> Result<(), String> {
+        if self.on_error == ErrorPolicy::Abort {
+            return Err(format!("Processing failed for {}: {}", pair.prefix, error));
+        }
+
+        if let Err(e) = self.tracker.mark_as_failed(&pair.prefix, error) {
+            eprintln!("Failed to record failure of {}: {}", pair.prefix, e);
+        }
+        self.failed.push(FailedFile {
+            prefix: pair.prefix.clone(),
+            error: error.to_string(),
+        });
+
+        if self.on_error == ErrorPolicy::Quarantine {
+            let failed_dir = self.data_dir.join("failed");
+            match quarantine_file_pair(pair, &failed_dir) {
+                Ok(()) => println!("Moved {} to {}", pair.prefix, failed_dir.display()),
+                Err(e) => eprintln!("Failed to quarantine {}: {}", pair.prefix, e),
+            }
+        }
+
+        Ok(())
+    }
+
     /// 获取已处理文件的统计信息
     pub fn get_stats(&self) -> ServiceStats {
         ServiceStats {
             processed_count: self.tracker.processed_count(),
             processed_prefixes: self.tracker.get_processed_prefixes(),
+            failed_files: self.failed.clone(),
+            files_this_run: self.files_this_run,
+            rows_per_table: self.rows_per_table.clone(),
         }
     }
     
@@ -190,10 +395,24 @@ impl BlockParserService {
     }
 }
 
+/// 把文件对移到 failed 目录（与 data_dir 同一文件系统，直接 rename）
+fn quarantine_file_pair(pair: &FilePair, failed_dir: &Path) -> std::io::Result<()> {
+    std::fs::create_dir_all(failed_dir)?;
+    for path in [&pair.meta_path, &pair.bin_path] {
+        if let Some(file_name) = path.file_name() {
+            std::fs::rename(path, failed_dir.join(file_name))?;
+        }
+    }
+    Ok(())
+}
+
 #[derive(Debug)]
 pub struct ServiceStats {
     pub processed_count: usize,
     pub processed_prefixes: Vec<String>,
+    pub failed_files: Vec<FailedFile>,
+    pub files_this_run: usize, // 本次运行成功处理的文件对数
+    pub rows_per_table: BTreeMap<String, u64>, // 本次运行提交插入的行数（按表）
 }
 
 impl ServiceStats {
@@ -212,6 +431,24 @@ impl ServiceStats {
                 println!("  ... and {} more", self.processed_prefixes.len() - 10);
             }
         }
+
+        println!("Files processed this run: {}", self.files_this_run);
+        if !self.rows_per_table.is_empty() {
+            println!("Rows inserted this run:");
+            for (table, rows) in &self.rows_per_table {
+                println!("  - {}: {}", table, rows);
+            }
+        } else if self.files_this_run > 0 {
+            // 文件处理成功却没有产出任何行，多半是解码出了问题
+            println!("⚠️  {} files processed this run but no rows were inserted", self.files_this_run);
+        }
+
+        if !self.failed_files.is_empty() {
+            println!("Failed files: {}", self.failed_files.len());
+            for failed in &self.failed_files {
+                println!("  - {}: {}", failed.prefix, failed.error);
+            }
+        }
         println!("====================================");
     }
 }
\ No newline at end of file
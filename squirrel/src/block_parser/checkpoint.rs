@@ -0,0 +1,74 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+/// 文件内处理进度的检查点
+///
+/// 每个文件一个检查点文件 `{dir}/{prefix}.checkpoint`，内容为一行
+/// `bin_size,bin_mtime_nanos,next_slot_index`。只有 `.bin` 的大小和修改时间
+/// 与记录一致时才信任检查点，文件被替换或重写后从头处理
+#[derive(Debug, Clone)]
+pub struct CheckpointStore {
+    dir: PathBuf,
+}
+
+impl CheckpointStore {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    /// 读取检查点，返回下一个待处理的 slot 下标；不存在、格式错误或文件已变化时返回 None
+    pub fn load(&self, bin_path: &Path) -> Option<usize> {
+        let content = fs::read_to_string(self.checkpoint_path(bin_path)?).ok()?;
+        let parts: Vec<&str> = content.trim().split(',').collect();
+        if parts.len() != 3 {
+            return None;
+        }
+
+        let recorded_size: u64 = parts[0].parse().ok()?;
+        let recorded_mtime: u128 = parts[1].parse().ok()?;
+        let next_slot: usize = parts[2].parse().ok()?;
+
+        let (size, mtime) = Self::fingerprint(bin_path).ok()?;
+        if size != recorded_size || mtime != recorded_mtime {
+            return None;
+        }
+        Some(next_slot)
+    }
+
+    /// 记录 `next_slot` 之前的 slot 已全部写入 ClickHouse（先写临时文件再 rename，避免写一半）
+    pub fn save(&self, bin_path: &Path, next_slot: usize) -> Result<(), Box<dyn std::error::Error>> {
+        let path = self
+            .checkpoint_path(bin_path)
+            .ok_or_else(|| format!("Invalid bin path: {}", bin_path.display()))?;
+        let (size, mtime) = Self::fingerprint(bin_path)?;
+
+        fs::create_dir_all(&self.dir)?;
+        let temp_path = path.with_extension("checkpoint.tmp");
+        fs::write(&temp_path, format!("{},{},{}\n", size, mtime, next_slot))?;
+        fs::rename(temp_path, path)?;
+        Ok(())
+    }
+
+    /// 文件处理完成后删除检查点
+    pub fn clear(&self, bin_path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(path) = self.checkpoint_path(bin_path) {
+            if path.exists() {
+                fs::remove_file(path)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn checkpoint_path(&self, bin_path: &Path) -> Option<PathBuf> {
+        let prefix = bin_path.file_stem()?.to_str()?;
+        Some(self.dir.join(format!("{}.checkpoint", prefix)))
+    }
+
+    /// `.bin` 的大小和修改时间（纳秒）
+    fn fingerprint(bin_path: &Path) -> Result<(u64, u128), Box<dyn std::error::Error>> {
+        let metadata = fs::metadata(bin_path)?;
+        let mtime = metadata.modified()?.duration_since(UNIX_EPOCH)?.as_nanos();
+        Ok((metadata.len(), mtime))
+    }
+}
@@ -0,0 +1,58 @@
+use flate2::read::GzDecoder;
+use std::io::{self, Read};
+use std::str::FromStr;
+use zstd::stream::read::Decoder as ZstdDecoder;
+
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xB5, 0x2F, 0xFD];
+const GZIP_MAGIC: [u8; 2] = [0x1F, 0x8B];
+
+/// slot 数据的压缩格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    Zstd,
+    Gzip,
+    /// 未压缩
+    None,
+}
+
+impl Compression {
+    /// 按魔数判断压缩格式，都不匹配时视为未压缩
+    pub fn detect(data: &[u8]) -> Self {
+        if data.starts_with(&ZSTD_MAGIC) {
+            Compression::Zstd
+        } else if data.starts_with(&GZIP_MAGIC) {
+            Compression::Gzip
+        } else {
+            Compression::None
+        }
+    }
+
+    /// 解压到 out（先清空）
+    pub fn decompress_into(self, data: &[u8], out: &mut Vec<u8>) -> io::Result<()> {
+        out.clear();
+        match self {
+            Compression::Zstd => ZstdDecoder::new(data)?.read_to_end(out).map(|_| ()),
+            Compression::Gzip => GzDecoder::new(data).read_to_end(out).map(|_| ()),
+            Compression::None => {
+                out.extend_from_slice(data);
+                Ok(())
+            }
+        }
+    }
+}
+
+impl FromStr for Compression {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "zstd" => Ok(Compression::Zstd),
+            "gzip" => Ok(Compression::Gzip),
+            "none" => Ok(Compression::None),
+            other => Err(format!(
+                "Invalid 'compression' value '{}', expected zstd, gzip or none",
+                other
+            )),
+        }
+    }
+}
@@ -1,117 +1,417 @@
 use utils::slot_meta::SlotMeta;
-use utils::convert_transaction;
-use utils::clickhouse_events;
-use common::async_pool::AsyncPool;
+use utils::convert_transaction::{ConvertedEvents, TransactionConverter};
+use utils::task_pool::{TaskPanic, TaskPool};
 use utils::clickhouse_client::ClickHouseClient;
-use indicatif::{ProgressBar, ProgressStyle};
+use crate::spill::{self, SpillWriter};
+use clickhouse::Client;
+use super::checkpoint::CheckpointStore;
+use super::compression::Compression;
+use super::progress::{self, ProgressReporter};
 use rmp_serde::from_slice;
+use std::collections::BTreeMap;
+use std::fmt;
 use std::fs::File;
 use std::io::{Read, Seek, SeekFrom};
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, mpsc};
+use std::time::Instant;
 use tweezers::combinator::solana_combinator::SolanaCombinator;
 use tweezers::normalizer::Normalizer;
-use zstd::stream::read::Decoder;
+
+/// ClickHouse 批量插入失败（未配置 spill_dir，或落盘也失败）
+#[derive(Debug, Clone)]
+pub struct InsertError {
+    pub table: String,
+    pub rows: usize,
+    pub message: String,
+}
+
+impl fmt::Display for InsertError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Failed to insert {} rows into {}: {}", self.rows, self.table, self.message)
+    }
+}
+
+impl std::error::Error for InsertError {}
+
+/// 插入任务 panic 时不知道是哪张表的数据
+impl From<TaskPanic> for InsertError {
+    fn from(panic: TaskPanic) -> Self {
+        Self {
+            table: "unknown".to_string(),
+            rows: 0,
+            message: panic.to_string(),
+        }
+    }
+}
+
+/// 每处理这么多 slot 等待插入完成并保存一次检查点
+pub const CHECKPOINT_INTERVAL_SLOTS: usize = 10_000;
+
+/// 读取线程最多领先解码线程的 slot 数（也是解码结果通道的容量）
+const PREFETCH_SLOTS: usize = 64;
+
+/// 单个文件的处理结果，记录因数据损坏被跳过的 slot，便于发现数据丢失
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct FileReport {
+    pub slots_total: usize, // meta 中的 slot 数（含没有数据的 slot）
+    pub slots_processed: usize,
+    pub slots_skipped_seek: usize, // offset 超出文件或数据不完整
+    pub slots_skipped_decompress: usize,
+    pub slots_skipped_parse: usize,
+    pub slots_resumed: usize, // 按检查点跳过的已处理 slot 数
+    pub rows_deduped: usize, // 批量内去重删除的行数
+    pub rows_per_table: BTreeMap<String, usize>, // 提交插入的行数（按 ClickHouse 表名）
+}
+
+impl FileReport {
+    /// 提交插入的总行数
+    pub fn rows_inserted(&self) -> usize {
+        self.rows_per_table.values().sum()
+    }
+}
+
+impl FileReport {
+    /// 因损坏跳过的 slot 总数
+    pub fn slots_skipped(&self) -> usize {
+        self.slots_skipped_seek + self.slots_skipped_decompress + self.slots_skipped_parse
+    }
+
+    fn record_skip(&mut self, skip: SlotSkip) {
+        match skip {
+            SlotSkip::Seek => self.slots_skipped_seek += 1,
+            SlotSkip::Decompress => self.slots_skipped_decompress += 1,
+            SlotSkip::Parse => self.slots_skipped_parse += 1,
+        }
+    }
+}
+
+/// slot 数据损坏的位置
+#[derive(Debug, Clone, Copy)]
+enum SlotSkip {
+    Seek,
+    Decompress,
+    Parse,
+}
+
+impl fmt::Display for SlotSkip {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SlotSkip::Seek => write!(f, "slot data out of range"),
+            SlotSkip::Decompress => write!(f, "failed to decompress slot data"),
+            SlotSkip::Parse => write!(f, "failed to parse block"),
+        }
+    }
+}
 
 pub struct FileProcessor {
-    async_pool: AsyncPool,
-    // 批量积累的数据
-    pumpfun_trade_event_batch: Vec<clickhouse_events::PumpfunTradeEventV2>,
-    pumpfun_create_event_batch: Vec<clickhouse_events::PumpfunCreateEventV2>,
-    pumpfun_migrate_event_batch: Vec<clickhouse_events::PumpfunMigrateEventV2>,
-    pumpfun_amm_buy_event_batch: Vec<clickhouse_events::PumpfunAmmBuyEventV2>,
-    pumpfun_amm_sell_event_batch: Vec<clickhouse_events::PumpfunAmmSellEventV2>,
-    pumpfun_amm_create_pool_event_batch:
-        Vec<clickhouse_events::PumpfunAmmCreatePoolEventV2>,
-    pumpfun_amm_deposit_event_batch:
-        Vec<clickhouse_events::PumpfunAmmDepositEventV2>,
-    pumpfun_amm_withdraw_event_batch:
-        Vec<clickhouse_events::PumpfunAmmWithdrawEventV2>,
+    async_pool: TaskPool<InsertError>, // 插入任务失败时记录错误，由 process_file_pair 返回
+    batch: ConvertedEvents, // 批量积累的数据
     batch_size: usize, // 批量大小
+    spill: Option<SpillWriter>, // 插入失败时的落盘目录
+    strict: bool, // 遇到损坏的 slot 时直接报错，而不是跳过
+    checkpoints: Option<CheckpointStore>, // 文件内进度检查点，不配置则每次从头处理
+    dedup_tables: Vec<String>, // 提交前按 (signature, instruction_index) 去重的表，为空则不去重
+    rows_deduped: usize, // 当前文件累计去重删除的行数
+    rows_per_table: BTreeMap<String, usize>, // 当前文件累计提交插入的行数
+    reporter: Arc<dyn ProgressReporter>, // 进度回调，默认终端中显示进度条
+    compression: Option<Compression>, // slot 数据的压缩格式，None 表示按魔数自动判断
+    current_file: PathBuf, // 正在处理的 .bin，供 flush 时回调使用
+    decode_workers: usize, // 并行解压、解析 slot 的线程数
+    client: Option<Client>, // 插入用的 ClickHouse 客户端，None 时使用 ClickHouseClient::instance()
 }
 
 impl FileProcessor {
     pub fn new(max_concurrent_clickhouse_tasks: usize) -> Self {
+        Self::with_spill_dir(max_concurrent_clickhouse_tasks, None)
+    }
+
+    /// 创建处理器，`spill_dir` 为 Some 时插入失败的批次写入该目录，否则插入失败作为错误返回
+    pub fn with_spill_dir(
+        max_concurrent_clickhouse_tasks: usize,
+        spill_dir: Option<PathBuf>,
+    ) -> Self {
         Self {
-            async_pool: AsyncPool::new(max_concurrent_clickhouse_tasks),
-            pumpfun_trade_event_batch: Vec::new(),
-            pumpfun_create_event_batch: Vec::new(),
-            pumpfun_migrate_event_batch: Vec::new(),
-            pumpfun_amm_buy_event_batch: Vec::new(),
-            pumpfun_amm_sell_event_batch: Vec::new(),
-            pumpfun_amm_create_pool_event_batch: Vec::new(),
-            pumpfun_amm_deposit_event_batch: Vec::new(),
-            pumpfun_amm_withdraw_event_batch: Vec::new(),
+            async_pool: TaskPool::new(max_concurrent_clickhouse_tasks),
+            batch: ConvertedEvents::default(),
             batch_size: 1000, // 每1000条记录提交一次
+            spill: spill_dir.map(SpillWriter::new),
+            strict: false,
+            checkpoints: None,
+            dedup_tables: Vec::new(),
+            rows_deduped: 0,
+            rows_per_table: BTreeMap::new(),
+            reporter: progress::default_reporter(),
+            compression: None,
+            current_file: PathBuf::new(),
+            decode_workers: default_decode_workers(),
+            client: None,
         }
     }
 
-    /// 处理单个文件对
+    /// 指定 slot 数据的压缩格式（魔数判断不可靠时使用），None 为自动判断
+    pub fn with_compression(mut self, compression: Option<Compression>) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    /// 解压、解析 slot 的线程数，默认为 CPU 核数；1 表示只用一个线程解码（读取仍在单独线程）
+    pub fn with_decode_workers(mut self, decode_workers: usize) -> Self {
+        self.decode_workers = decode_workers.max(1);
+        self
+    }
+
+    /// 使用指定的 ClickHouse 客户端插入（例如 `ClickHouseClient::named` 或测试用的 mock 服务）
+    pub fn with_client(mut self, client: Client) -> Self {
+        self.client = Some(client);
+        self
+    }
+
+    /// 替换进度回调（例如写日志、上报指标，或用 `NoopReporter` 关闭输出）
+    pub fn with_reporter(mut self, reporter: Arc<dyn ProgressReporter>) -> Self {
+        self.reporter = reporter;
+        self
+    }
+
+    /// 提交插入前对这些表（名称同 `ConvertedEvents::TABLE_NAMES`）做批量内去重
+    pub fn with_dedup_tables(mut self, dedup_tables: Vec<String>) -> Self {
+        self.dedup_tables = dedup_tables;
+        self
+    }
+
+    /// 启用文件内检查点：定期保存已写入 ClickHouse 的 slot 位置，中断后从该位置继续
+    pub fn with_checkpoints(mut self, checkpoints: CheckpointStore) -> Self {
+        self.checkpoints = Some(checkpoints);
+        self
+    }
+
+    /// 严格模式：遇到第一个损坏的 slot 即返回错误（文件不会被标记为已处理）
+    ///
+    /// 多个解码线程时报告的是最先解码完的损坏 slot，不一定是文件中最靠前的
+    pub fn with_strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// 并行处理另一个文件用的处理器：独立的批量缓冲和插入错误，共享 ClickHouse 插入协程池及其并发上限
+    pub fn scoped(&self) -> Self {
+        Self {
+            async_pool: self.async_pool.scoped(),
+            batch: ConvertedEvents::default(),
+            batch_size: self.batch_size,
+            spill: self.spill.clone(),
+            strict: self.strict,
+            checkpoints: self.checkpoints.clone(),
+            dedup_tables: self.dedup_tables.clone(),
+            rows_deduped: 0,
+            rows_per_table: BTreeMap::new(),
+            reporter: self.reporter.clone(),
+            compression: self.compression,
+            current_file: PathBuf::new(),
+            decode_workers: self.decode_workers,
+            client: self.client.clone(),
+        }
+    }
+
+    /// 处理单个文件对，返回 slot 统计（含因损坏跳过的数量）
     pub async fn process_file_pair(
         &mut self,
         meta_path: &Path,
         bin_path: &Path,
-    ) -> Result<(), Box<dyn std::error::Error>> {
+    ) -> Result<FileReport, Box<dyn std::error::Error>> {
+        let started = Instant::now();
+        self.current_file = bin_path.to_path_buf();
+
+        let result = self.process_slots(meta_path, bin_path).await;
+        match &result {
+            Ok(report) => self.reporter.on_complete(bin_path, report, started.elapsed()),
+            Err(e) => self.reporter.on_failed(bin_path, &e.to_string()),
+        }
+        result
+    }
+
+    async fn process_slots(
+        &mut self,
+        meta_path: &Path,
+        bin_path: &Path,
+    ) -> Result<FileReport, Box<dyn std::error::Error>> {
         let slot_meta = self.load_slot_meta(meta_path)?;
 
-        let mut f = File::open(bin_path)?;
+        let f = File::open(bin_path)?;
+        self.reporter.on_start(bin_path, slot_meta.len());
 
-        // 创建进度条
-        let pb = ProgressBar::new(slot_meta.len() as u64);
-        pb.set_style(
-            ProgressStyle::default_bar()
-                .template("[{elapsed_precise}] {bar:40.cyan/blue} {pos:>7}/{len:7} {msg} ({eta})")
-                .unwrap()
-                .progress_chars("##-"),
-        );
-        pb.set_message(format!("Processing {}", bin_path.display()));
+        // 从检查点继续（.bin 大小和修改时间与记录一致时）
+        let start_slot = self
+            .checkpoints
+            .as_ref()
+            .and_then(|checkpoints| checkpoints.load(bin_path))
+            .unwrap_or(0)
+            .min(slot_meta.len());
+        if start_slot > 0 {
+            println!("Resuming {} from slot index {}", bin_path.display(), start_slot);
+            self.reporter.on_slot(bin_path, start_slot);
+        }
+
+        self.rows_deduped = 0;
+        self.rows_per_table.clear();
+        let mut report = FileReport {
+            slots_total: slot_meta.len(),
+            slots_resumed: start_slot,
+            ..Default::default()
+        };
+        let mut corrupt_slot: Option<String> = None;
 
-        let mut packed_data = Vec::with_capacity(12 * 1024 * 1024); // 预分配12MB
+        // 读取线程按顺序读出压缩数据，解码线程并行解压、解析并转换，结果在这里合并到批量中。
+        // 提前退出时丢弃结果通道，解码线程和读取线程随之结束
+        let (job_tx, job_rx) = mpsc::sync_channel(PREFETCH_SLOTS);
+        let (outcome_tx, mut outcome_rx) = tokio::sync::mpsc::channel(PREFETCH_SLOTS);
+        tokio::task::spawn_blocking(move || read_slots(f, slot_meta, start_slot, job_tx));
+        let job_rx = Arc::new(Mutex::new(job_rx));
+        for _ in 0..self.decode_workers {
+            let job_rx = job_rx.clone();
+            let outcome_tx = outcome_tx.clone();
+            let compression = self.compression;
+            tokio::task::spawn_blocking(move || decode_slots(job_rx, outcome_tx, compression));
+        }
+        drop(outcome_tx);
 
-        for slot in &slot_meta {
-            let (offset, length) = match slot.offset {
-                Some(offset) => (offset, slot.size),
-                None => continue,
+        // 结果乱序到达：`completed` 之前的 slot 全部已合并到批量中，之后已完成的事件暂存在
+        // `done_ahead`，补齐空缺后按 slot 顺序合并。批量中只有检查点之前的 slot，
+        // 恢复时不会重复插入（暂存量不超过预取窗口）
+        let mut completed = start_slot;
+        let mut done_ahead: BTreeMap<usize, Option<ConvertedEvents>> = BTreeMap::new();
+        while let Some(outcome) = outcome_rx.recv().await {
+            let events = match outcome.result {
+                Ok(events) => events,
+                Err(skip) => {
+                    report.record_skip(skip);
+                    if self.strict {
+                        corrupt_slot = Some(format!(
+                            "Corrupt slot {} in {}: {}",
+                            outcome.slot,
+                            bin_path.display(),
+                            skip
+                        ));
+                        break;
+                    }
+                    None
+                }
             };
 
-            if f.seek(SeekFrom::Start(offset)).is_err() {
-                continue;
-            }
-            let mut compressed_data = vec![0u8; length as usize];
-            if f.read_exact(&mut compressed_data).is_err() {
-                continue;
+            let before = completed;
+            done_ahead.insert(outcome.index, events);
+            while let Some(events) = done_ahead.remove(&completed) {
+                if let Some(events) = events {
+                    self.batch.extend(events);
+                    report.slots_processed += 1;
+                    self.check_and_flush_batches().await;
+                }
+                completed += 1;
             }
+            if completed > before {
+                // 更新进度
+                self.reporter.on_slot(bin_path, completed);
 
-            // 解压数据
-            let mut decoder = match Decoder::new(&compressed_data[..]) {
-                Ok(d) => d,
-                Err(_) => continue,
-            };
-            packed_data.clear();
-            if decoder.read_to_end(&mut packed_data).is_err() {
-                continue;
+                if completed < report.slots_total
+                    && completed / CHECKPOINT_INTERVAL_SLOTS > before / CHECKPOINT_INTERVAL_SLOTS
+                {
+                    self.save_checkpoint(bin_path, completed).await?;
+                }
             }
 
-            // 解析Block
-            if let Ok(block) = from_slice::<structure::block::Block>(&packed_data) {
-                self.handle_block(&block).await;
+            // 已有插入失败，停止继续解析该文件
+            if self.has_insert_errors() {
+                break;
             }
-
-            // 更新进度条
-            pb.inc(1);
         }
+        drop(outcome_rx);
 
-        // 完成进度条
-        pb.finish_with_message(format!("Completed processing {}", bin_path.display()));
+        if report.slots_skipped() > 0 {
+            eprintln!(
+                "⚠️  Skipped {} corrupt slots in {} (seek: {}, decompress: {}, parse: {})",
+                report.slots_skipped(),
+                bin_path.display(),
+                report.slots_skipped_seek,
+                report.slots_skipped_decompress,
+                report.slots_skipped_parse
+            );
+        }
 
         // 刷新剩余的批量数据
-        self.flush_all_batches().await;
+        if self.has_insert_errors() || corrupt_slot.is_some() {
+            self.clear_batches();
+        } else {
+            self.flush_all_batches().await;
+        }
 
         // 等待所有 ClickHouse 插入任务完成
         println!("Waiting for all ClickHouse insertions to complete...");
-        self.async_pool.wait_all_tasks().await;
+        // 汇总插入失败，文件不会被标记为已处理
+        let errors = self.async_pool.wait_all_tasks().await;
+        if !errors.is_empty() {
+            return Err(Self::insert_failure(&errors, bin_path));
+        }
+        if let Some(error) = corrupt_slot {
+            return Err(error.into());
+        }
         println!("All insertions completed for this file");
 
-        Ok(())
+        if let Some(checkpoints) = &self.checkpoints {
+            checkpoints.clear(bin_path)?;
+        }
+
+        report.rows_deduped = self.rows_deduped;
+        report.rows_per_table = std::mem::take(&mut self.rows_per_table);
+        Ok(report)
+    }
+
+    /// 提交当前批量并等待插入完成，之后记录 `next_slot` 之前的 slot 已处理
+    ///
+    /// 没有配置检查点时什么也不做，不打断插入流水线
+    async fn save_checkpoint(
+        &mut self,
+        bin_path: &Path,
+        next_slot: usize,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let Some(checkpoints) = self.checkpoints.clone() else {
+            return Ok(());
+        };
+
+        self.flush_all_batches().await;
+        let errors = self.async_pool.wait_all_tasks().await;
+        if !errors.is_empty() {
+            return Err(Self::insert_failure(&errors, bin_path));
+        }
+        checkpoints.save(bin_path, next_slot)
+    }
+
+    /// 汇总插入失败为文件处理错误
+    fn insert_failure(errors: &[InsertError], bin_path: &Path) -> Box<dyn std::error::Error> {
+        for error in errors {
+            eprintln!("❌ {}", error);
+        }
+        format!(
+            "{} ClickHouse insert(s) failed for {}, first: {}",
+            errors.len(),
+            bin_path.display(),
+            errors[0]
+        )
+        .into()
+    }
+
+    /// 是否已有插入任务失败
+    pub fn has_insert_errors(&self) -> bool {
+        self.async_pool.has_errors()
+    }
+
+    /// 取出并清空已收集的插入失败
+    pub fn take_insert_errors(&mut self) -> Vec<InsertError> {
+        self.async_pool.take_errors()
+    }
+
+    /// 丢弃尚未提交的批量数据（文件处理失败后会整体重新处理）
+    fn clear_batches(&mut self) {
+        self.batch = ConvertedEvents::default();
     }
 
     /// 加载slot元数据
@@ -126,154 +426,91 @@ impl FileProcessor {
         Ok(slots)
     }
 
-    /// 处理单个block - 直接积累到批量中，减少拷贝
-    async fn handle_block(&mut self, block: &structure::block::Block) {
-        let parsed_block = Normalizer::normalize_block(block);
-
-        if let Ok(parsed_block) = parsed_block {
-            if let Some(combined_block) = SolanaCombinator::combine_block(&parsed_block) {
-                for tx in combined_block.transactions.iter() {
-                    // 直接在 batch Vec 上操作，避免临时 Vec
-                    convert_transaction::TransactionConverter::convert(
-                        tx,
-                        &mut self.pumpfun_trade_event_batch,
-                        &mut self.pumpfun_create_event_batch,
-                        &mut self.pumpfun_migrate_event_batch,
-                        &mut self.pumpfun_amm_buy_event_batch,
-                        &mut self.pumpfun_amm_sell_event_batch,
-                        &mut self.pumpfun_amm_create_pool_event_batch,
-                        &mut self.pumpfun_amm_deposit_event_batch,
-                        &mut self.pumpfun_amm_withdraw_event_batch,
-                    );
-                }
-
-                // 检查是否需要刷新批量
-                self.check_and_flush_batches().await;
-            }
-        }
-    }
-
     /// 检查批量大小并在需要时刷新
     async fn check_and_flush_batches(&mut self) {
-        let mut should_flush = false;
-
-        // 检查任意一个批量是否达到阈值
-        if self.pumpfun_trade_event_batch.len() >= self.batch_size
-            || self.pumpfun_create_event_batch.len() >= self.batch_size
-            || self.pumpfun_migrate_event_batch.len() >= self.batch_size
-            || self.pumpfun_amm_buy_event_batch.len() >= self.batch_size
-            || self.pumpfun_amm_sell_event_batch.len() >= self.batch_size
-            || self.pumpfun_amm_create_pool_event_batch.len() >= self.batch_size
-            || self.pumpfun_amm_deposit_event_batch.len() >= self.batch_size
-            || self.pumpfun_amm_withdraw_event_batch.len() >= self.batch_size
-        {
-            should_flush = true;
-        }
+        // 检查任意一种事件是否达到阈值
+        let should_flush = self
+            .batch
+            .len_per_table()
+            .iter()
+            .any(|(_, rows)| *rows >= self.batch_size);
 
         if should_flush {
             self.flush_all_batches().await;
         }
     }
 
-    /// 刷新所有批量数据到 ClickHouse
+    /// 刷新所有批量数据到 ClickHouse（按配置先去重）
     async fn flush_all_batches(&mut self) {
-        let trade_batch = std::mem::take(&mut self.pumpfun_trade_event_batch);
-        let create_batch = std::mem::take(&mut self.pumpfun_create_event_batch);
-        let migrate_batch = std::mem::take(&mut self.pumpfun_migrate_event_batch);
-        let buy_batch = std::mem::take(&mut self.pumpfun_amm_buy_event_batch);
-        let sell_batch = std::mem::take(&mut self.pumpfun_amm_sell_event_batch);
-        let create_pool_batch = std::mem::take(&mut self.pumpfun_amm_create_pool_event_batch);
-        let deposit_batch = std::mem::take(&mut self.pumpfun_amm_deposit_event_batch);
-        let withdraw_batch = std::mem::take(&mut self.pumpfun_amm_withdraw_event_batch);
-
-        self.submit_clickhouse_inserts(
-            trade_batch,
-            create_batch,
-            migrate_batch,
-            buy_batch,
-            sell_batch,
-            create_pool_batch,
-            deposit_batch,
-            withdraw_batch,
-        );
+        let mut batch = std::mem::take(&mut self.batch);
+        if !self.dedup_tables.is_empty() {
+            let deduped = batch.dedup_tables(&self.dedup_tables);
+            if deduped > 0 {
+                tracing::debug!(rows = deduped, "deduplicated rows in batch");
+                self.rows_deduped += deduped;
+            }
+        }
+        if !batch.is_empty() {
+            self.reporter.on_flush(&self.current_file, batch.len());
+        }
+        self.submit_clickhouse_inserts(batch).await;
     }
 
-    /// 提交ClickHouse插入任务  
-    fn submit_clickhouse_inserts(
-        &self,
-        pumpfun_trade_event_rows: Vec<clickhouse_events::PumpfunTradeEventV2>,
-        pumpfun_create_event_rows: Vec<clickhouse_events::PumpfunCreateEventV2>,
-        pumpfun_migrate_event_rows: Vec<clickhouse_events::PumpfunMigrateEventV2>,
-        pumpfun_amm_buy_event_rows: Vec<clickhouse_events::PumpfunAmmBuyEventV2>,
-        pumpfun_amm_sell_event_rows: Vec<clickhouse_events::PumpfunAmmSellEventV2>,
-        pumpfun_amm_create_pool_event_rows: Vec<
-            clickhouse_events::PumpfunAmmCreatePoolEventV2,
-        >,
-        pumpfun_amm_deposit_event_rows: Vec<
-            clickhouse_events::PumpfunAmmDepositEventV2,
-        >,
-        pumpfun_amm_withdraw_event_rows: Vec<
-            clickhouse_events::PumpfunAmmWithdrawEventV2,
-        >,
-    ) {
-        // 宏来减少重复代码 - 错误会打印到控制台并终止程序
+    /// 提交ClickHouse插入任务，未完成的插入达到并发上限时等待（背压）
+    async fn submit_clickhouse_inserts(&mut self, batch: ConvertedEvents) {
+        // 宏来减少重复代码 - 未配置 spill_dir 时失败记录在协程池中，由 process_file_pair 返回
         macro_rules! submit_insert {
             ($rows:expr, $table:literal) => {
                 if !$rows.is_empty() {
                     let rows = $rows;
-                    self.async_pool.submit(move || async move {
-                        let client = ClickHouseClient::instance().client();
-
-                        let mut insert = match client.insert($table) {
-                            Ok(insert) => insert,
-                            Err(e) => {
-                                eprintln!(
-                                    "❌ FATAL ERROR: Failed to create insert for table {}: {}",
-                                    $table, e
-                                );
-                                std::process::exit(1);
-                            }
-                        };
-
-                        for (i, row) in rows.iter().enumerate() {
-                            if let Err(e) = insert.write(row).await {
-                                eprintln!(
-                                    "❌ FATAL ERROR: Failed to write row {} to table {}: {}",
-                                    i, $table, e
-                                );
-                                std::process::exit(1);
-                            }
-                        }
-
-                        if let Err(e) = insert.end().await {
-                            eprintln!(
-                                "❌ FATAL ERROR: Failed to end insert for table {}: {}",
-                                $table, e
-                            );
-                            std::process::exit(1);
-                        }
-                    });
+                    *self.rows_per_table.entry($table.to_string()).or_default() += rows.len();
+                    let spill = self.spill.clone();
+                    let client = self.client.clone();
+                    self.async_pool.submit_blocking(move || async move {
+                        let client = client
+                            .unwrap_or_else(|| ClickHouseClient::instance().client().clone());
+
+                        // 配置了 spill_dir 时失败批次落盘，否则返回错误
+                        spill::insert_or_spill(&client, $table, &rows, spill.as_ref())
+                            .await
+                            .map(|_| ())
+                            .map_err(|message| InsertError {
+                                table: $table.to_string(),
+                                rows: rows.len(),
+                                message,
+                            })
+                    })
+                    .await;
                 }
             };
         }
 
-        submit_insert!(pumpfun_trade_event_rows, "pumpfun_trade_event_v2");
-        submit_insert!(pumpfun_create_event_rows, "pumpfun_create_event_v2");
-        submit_insert!(pumpfun_migrate_event_rows, "pumpfun_migrate_event_v2");
-        submit_insert!(pumpfun_amm_buy_event_rows, "pumpfun_amm_buy_event_v2");
-        submit_insert!(pumpfun_amm_sell_event_rows, "pumpfun_amm_sell_event_v2");
+        submit_insert!(batch.pumpfun_trade_event, "pumpfun_trade_event_v2");
+        submit_insert!(batch.pumpfun_create_event, "pumpfun_create_event_v2");
+        submit_insert!(batch.pumpfun_migrate_event, "pumpfun_migrate_event_v2");
+        submit_insert!(batch.pumpfun_amm_buy_event, "pumpfun_amm_buy_event_v2");
+        submit_insert!(batch.pumpfun_amm_sell_event, "pumpfun_amm_sell_event_v2");
         submit_insert!(
-            pumpfun_amm_create_pool_event_rows,
+            batch.pumpfun_amm_create_pool_event,
             "pumpfun_amm_create_pool_event_v2"
         );
         submit_insert!(
-            pumpfun_amm_deposit_event_rows,
+            batch.pumpfun_amm_deposit_event,
             "pumpfun_amm_deposit_event_v2"
         );
         submit_insert!(
-            pumpfun_amm_withdraw_event_rows,
+            batch.pumpfun_amm_withdraw_event,
             "pumpfun_amm_withdraw_event_v2"
         );
+        submit_insert!(
+            batch.pumpfun_amm_collect_coin_creator_fee_event,
+            "pumpfun_amm_collect_coin_creator_fee_event_v2"
+        );
+        submit_insert!(batch.pumpfun_amm_disable_event, "pumpfun_amm_disable_event_v2");
+        submit_insert!(
+            batch.pumpfun_amm_set_params_event,
+            "pumpfun_amm_set_params_event_v2"
+        );
     }
 
     /// 完成所有任务并等待协程池关闭
@@ -281,3 +518,108 @@ impl FileProcessor {
         self.async_pool.join();
     }
 }
+
+/// 默认解码线程数：CPU 核数
+fn default_decode_workers() -> usize {
+    std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(4)
+}
+
+/// 读取线程读出的一个 slot，`data` 为 Ok(None) 表示该 slot 没有数据
+struct SlotJob {
+    index: usize,
+    slot: u64,
+    data: Result<Option<Vec<u8>>, SlotSkip>,
+}
+
+/// 解码线程的处理结果，`result` 为 Ok(None) 表示该 slot 没有数据
+struct SlotOutcome {
+    index: usize,
+    slot: u64,
+    result: Result<Option<ConvertedEvents>, SlotSkip>,
+}
+
+/// 按顺序读出 `start_slot` 之后每个 slot 的压缩数据，解码线程全部退出后停止
+fn read_slots(mut f: File, slot_meta: Vec<SlotMeta>, start_slot: usize, jobs: mpsc::SyncSender<SlotJob>) {
+    for (index, slot) in slot_meta.into_iter().enumerate().skip(start_slot) {
+        let data = match slot.offset {
+            Some(offset) => read_slot_data(&mut f, offset, slot.size).map(Some),
+            None => Ok(None),
+        };
+        let job = SlotJob {
+            index,
+            slot: slot.slot,
+            data,
+        };
+        if jobs.send(job).is_err() {
+            break;
+        }
+    }
+}
+
+fn read_slot_data(f: &mut File, offset: u64, length: u64) -> Result<Vec<u8>, SlotSkip> {
+    f.seek(SeekFrom::Start(offset)).map_err(|_| SlotSkip::Seek)?;
+    let mut compressed_data = vec![0u8; length as usize];
+    f.read_exact(&mut compressed_data).map_err(|_| SlotSkip::Seek)?;
+    Ok(compressed_data)
+}
+
+/// 解码线程：从共享队列取 slot 解码，结果交回 `process_slots` 合并；结果通道关闭后退出
+fn decode_slots(
+    jobs: Arc<Mutex<mpsc::Receiver<SlotJob>>>,
+    outcomes: tokio::sync::mpsc::Sender<SlotOutcome>,
+    compression: Option<Compression>,
+) {
+    let mut packed_data = Vec::with_capacity(12 * 1024 * 1024); // 预分配12MB
+    loop {
+        // 只在取任务时持锁，解码期间其他线程可以继续取
+        let job = match jobs.lock().unwrap().recv() {
+            Ok(job) => job,
+            Err(_) => break,
+        };
+        let result = match job.data {
+            Ok(Some(compressed_data)) => {
+                decode_block(&compressed_data, compression, &mut packed_data).map(Some)
+            }
+            Ok(None) => Ok(None),
+            Err(skip) => Err(skip),
+        };
+        let outcome = SlotOutcome {
+            index: job.index,
+            slot: job.slot,
+            result,
+        };
+        if outcomes.blocking_send(outcome).is_err() {
+            break;
+        }
+    }
+}
+
+/// 解压、解析单个 slot 的 block 并转换为事件行
+///
+/// `compression` 为 None 时按魔数判断每个 slot 的压缩格式（zstd / gzip / 未压缩）
+fn decode_block(
+    compressed_data: &[u8],
+    compression: Option<Compression>,
+    packed_data: &mut Vec<u8>,
+) -> Result<ConvertedEvents, SlotSkip> {
+    // 解压数据
+    compression
+        .unwrap_or_else(|| Compression::detect(compressed_data))
+        .decompress_into(compressed_data, packed_data)
+        .map_err(|_| SlotSkip::Decompress)?;
+
+    // 解析Block
+    let block = from_slice::<structure::block::Block>(packed_data).map_err(|_| SlotSkip::Parse)?;
+
+    let mut events = ConvertedEvents::default();
+    if let Ok(parsed_block) = Normalizer::normalize_block(&block) {
+        if let Some(combined_block) = SolanaCombinator::combine_block(&parsed_block) {
+            for tx in combined_block.transactions.iter() {
+                TransactionConverter::convert_into(tx, &mut events);
+            }
+        }
+    }
+    Ok(events)
+}
@@ -11,11 +11,22 @@ pub struct FilePair {
 
 pub struct FileScanner {
     data_dir: PathBuf,
+    slot_range: Option<(u64, u64)>, // 只返回与该 slot 区间（含两端）有交集的文件对
 }
 
 impl FileScanner {
     pub fn new(data_dir: PathBuf) -> Self {
-        Self { data_dir }
+        Self {
+            data_dir,
+            slot_range: None,
+        }
+    }
+
+    /// 只扫描 prefix（`起始slot_结束slot`）与 `[start, end]` 有交集的文件对，
+    /// prefix 无法解析为 slot 区间的文件对也会被跳过
+    pub fn with_slot_range(mut self, slot_range: Option<(u64, u64)>) -> Self {
+        self.slot_range = slot_range;
+        self
     }
 
     /// 扫描数据目录，返回所有可用的文件对
@@ -53,6 +64,9 @@ impl FileScanner {
 
         // 匹配meta和bin文件对
         for (prefix, meta_path) in meta_files {
+            if !self.in_slot_range(&prefix) {
+                continue;
+            }
             if let Some(bin_path) = bin_files.get(&prefix) {
                 file_pairs.push(FilePair {
                     prefix: prefix.clone(),
@@ -104,6 +118,25 @@ impl FileScanner {
         prefix.split('_').next()?.parse::<u64>().ok()
     }
 
+    /// 从prefix中提取slot区间
+    /// 例如: "123_456" -> Some((123, 456))
+    ///      "123" -> None
+    pub fn extract_slot_range(prefix: &str) -> Option<(u64, u64)> {
+        let (start, end) = prefix.split_once('_')?;
+        Some((start.parse().ok()?, end.parse().ok()?))
+    }
+
+    /// prefix 是否落在配置的 slot 区间内（未配置时总是 true）
+    fn in_slot_range(&self, prefix: &str) -> bool {
+        let Some((since, until)) = self.slot_range else {
+            return true;
+        };
+        match Self::extract_slot_range(prefix) {
+            Some((start, end)) => start <= until && end >= since,
+            None => false,
+        }
+    }
+
     /// 检查给定的prefix是否有完整的文件对
     pub fn has_complete_file_pair(&self, prefix: &str) -> bool {
         let meta_path = self.data_dir.join(format!("{}.meta", prefix));
@@ -2,3 +2,6 @@ pub mod file_scanner;
 pub mod processed_tracker;
 pub mod file_processor;
 pub mod block_parser_service;
+pub mod checkpoint;
+pub mod progress;
+pub mod compression;
@@ -1,19 +1,29 @@
 use std::collections::HashSet;
 use std::fs::{File, OpenOptions};
-use std::io::{BufRead, BufReader, Write, BufWriter};
-use std::path::PathBuf;
-use chrono::Utc;
-
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use chrono::{DateTime, Utc};
+
+/// 已处理文件记录
+///
+/// 追加和清理日志时持有 processed 目录下锁文件的建议锁（flock），多个写入者（并行处理的文件、
+/// 同一目录上的多个进程）不会交错写入；每次追加用一次 write 写完整行，崩溃留下的半行在加载时跳过
 pub struct ProcessedTracker {
     log_path: PathBuf,
+    failed_log_path: PathBuf,
+    lock_path: PathBuf,
     processed_set: HashSet<String>,
 }
 
 impl ProcessedTracker {
     pub fn new(processed_dir: PathBuf) -> Self {
         let log_path = processed_dir.join("processed_files.log");
+        let failed_log_path = processed_dir.join("failed_files.log");
+        let lock_path = processed_dir.join("processed_files.lock");
         Self {
             log_path,
+            failed_log_path,
+            lock_path,
             processed_set: HashSet::new(),
         }
     }
@@ -27,24 +37,34 @@ impl ProcessedTracker {
             return Ok(());
         }
 
-        let file = File::open(&self.log_path)?;
-        let reader = BufReader::new(file);
-
-        for line in reader.lines() {
-            let line = line?;
-            let line = line.trim();
-            
+        let mut malformed = 0;
+        for line in read_log_lines(&self.log_path)? {
+            let Some(line) = line else {
+                malformed += 1;
+                continue;
+            };
             if line.is_empty() || line.starts_with('#') {
                 continue; // 跳过空行和注释行
             }
 
             // 解析日志行格式: timestamp,prefix,status
-            let parts: Vec<&str> = line.split(',').collect();
-            if parts.len() >= 3 && parts[2] == "completed" {
-                self.processed_set.insert(parts[1].to_string());
+            match parse_entry(&line) {
+                Some((prefix, "completed")) => {
+                    self.processed_set.insert(prefix.to_string());
+                }
+                Some(_) => {}
+                None => malformed += 1,
             }
         }
 
+        if malformed > 0 {
+            eprintln!(
+                "⚠️  Skipped {} malformed lines in {}",
+                malformed,
+                self.log_path.display()
+            );
+        }
+
         Ok(())
     }
 
@@ -55,19 +75,9 @@ impl ProcessedTracker {
 
     /// 标记文件为已处理
     pub fn mark_as_processed(&mut self, prefix: &str) -> Result<(), Box<dyn std::error::Error>> {
-        // 确保processed目录存在
-        if let Some(parent) = self.log_path.parent() {
-            std::fs::create_dir_all(parent)?;
-        }
-
         // 追加写入日志文件
-        let mut file = OpenOptions::new()
-            .create(true)
-            .append(true)
-            .open(&self.log_path)?;
-
         let timestamp = Utc::now().to_rfc3339();
-        writeln!(file, "{},{},completed", timestamp, prefix)?;
+        self.append_locked(&self.log_path, &format!("{},{},completed\n", timestamp, prefix))?;
 
         // 同时更新内存中的集合
         self.processed_set.insert(prefix.to_string());
@@ -75,6 +85,15 @@ impl ProcessedTracker {
         Ok(())
     }
 
+    /// 记录处理失败的文件到 failed_files.log（不影响 is_processed）
+    ///
+    /// 格式: timestamp,prefix,error，错误信息中的换行和逗号会被替换
+    pub fn mark_as_failed(&self, prefix: &str, error: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let timestamp = Utc::now().to_rfc3339();
+        let error = error.replace(['\n', '\r'], " ").replace(',', ";");
+        self.append_locked(&self.failed_log_path, &format!("{},{},{}\n", timestamp, prefix, error))
+    }
+
     /// 获取已处理文件的数量
     pub fn processed_count(&self) -> usize {
         self.processed_set.len()
@@ -89,25 +108,15 @@ impl ProcessedTracker {
 
     /// 批量标记多个文件为已处理
     pub fn mark_batch_as_processed(&mut self, prefixes: &[String]) -> Result<(), Box<dyn std::error::Error>> {
-        // 确保processed目录存在
-        if let Some(parent) = self.log_path.parent() {
-            std::fs::create_dir_all(parent)?;
-        }
-
-        let file = OpenOptions::new()
-            .create(true)
-            .append(true)
-            .open(&self.log_path)?;
-        
-        let mut writer = BufWriter::new(file);
+        // 整批一次写入
         let timestamp = Utc::now().to_rfc3339();
-
+        let mut lines = String::new();
         for prefix in prefixes {
-            writeln!(writer, "{},{},completed", timestamp, prefix)?;
-            self.processed_set.insert(prefix.clone());
+            lines.push_str(&format!("{},{},completed\n", timestamp, prefix));
         }
+        self.append_locked(&self.log_path, &lines)?;
 
-        writer.flush()?;
+        self.processed_set.extend(prefixes.iter().cloned());
         Ok(())
     }
 
@@ -117,22 +126,11 @@ impl ProcessedTracker {
             return Ok(());
         }
 
-        // 读取所有日志条目
-        let file = File::open(&self.log_path)?;
-        let reader = BufReader::new(file);
-        let mut entries = Vec::new();
+        // 重写期间其他写入者不能追加，否则追加的内容会随旧文件一起被替换掉
+        let _lock = self.lock()?;
 
-        for line in reader.lines() {
-            let line = line?;
-            let line = line.trim();
-            
-            if line.is_empty() || line.starts_with('#') {
-                entries.push(line.to_string());
-                continue;
-            }
-
-            entries.push(line.to_string());
-        }
+        // 读取所有日志条目（跳过无法解码的行）
+        let entries: Vec<String> = read_log_lines(&self.log_path)?.into_iter().flatten().collect();
 
         // 重新创建日志文件，只保留每个prefix的最新条目
         let temp_path = self.log_path.with_extension("log.tmp");
@@ -169,7 +167,76 @@ impl ProcessedTracker {
         }
 
         // 替换原文件
+        temp_file.sync_all()?;
         std::fs::rename(temp_path, &self.log_path)?;
         Ok(())
     }
+
+    /// 获取 processed 目录的建议锁，返回的文件关闭时释放
+    fn lock(&self) -> Result<File, Box<dyn std::error::Error>> {
+        // 确保processed目录存在
+        if let Some(parent) = self.lock_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let lock_file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(false)
+            .open(&self.lock_path)?;
+        lock_file.lock()?;
+        Ok(lock_file)
+    }
+
+    /// 持锁追加 `text`（一行或多行，以换行结尾），一次 write 写完
+    fn append_locked(&self, path: &Path, text: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let _lock = self.lock()?;
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .read(true)
+            .append(true)
+            .open(path)?;
+
+        // 上次写入中途崩溃留下的半行没有换行符，先补上，避免和新记录拼成一行
+        let mut buf = String::with_capacity(text.len() + 1);
+        if file.metadata()?.len() > 0 {
+            let mut last = [0u8; 1];
+            file.seek(SeekFrom::End(-1))?;
+            file.read_exact(&mut last)?;
+            if last[0] != b'\n' {
+                buf.push('\n');
+            }
+        }
+        buf.push_str(text);
+
+        file.write_all(buf.as_bytes())?;
+        file.sync_data()?;
+        Ok(())
+    }
+}
+
+/// 读取日志的所有行（已去掉首尾空白），无法按 UTF-8 解码的行（崩溃时写了一半）为 None
+fn read_log_lines(path: &Path) -> std::io::Result<Vec<Option<String>>> {
+    let data = std::fs::read(path)?;
+    let data = data.strip_suffix(b"\n").unwrap_or(&data);
+    if data.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    Ok(data
+        .split(|b| *b == b'\n')
+        .map(|line| std::str::from_utf8(line).ok().map(|line| line.trim().to_string()))
+        .collect())
+}
+
+/// 解析 `timestamp,prefix,status`，字段数不对、时间戳无效或 prefix 为空（通常是写了一半的行）时返回 None
+fn parse_entry(line: &str) -> Option<(&str, &str)> {
+    let mut parts = line.split(',');
+    let (timestamp, prefix, status) = (parts.next()?, parts.next()?, parts.next()?);
+    if parts.next().is_some() || prefix.is_empty() {
+        return None;
+    }
+    DateTime::parse_from_rfc3339(timestamp).ok()?;
+    Some((prefix, status))
 }
@@ -0,0 +1,107 @@
+use super::file_processor::FileReport;
+use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
+use std::collections::HashMap;
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// 文件处理进度回调，`file` 为正在处理的 `.bin` 路径
+///
+/// 并行处理多个文件时同一个 reporter 会被多个处理器共享，实现需要按 `file` 区分
+pub trait ProgressReporter: Send + Sync {
+    /// 开始处理文件，`total_slots` 为 meta 中的 slot 数
+    fn on_start(&self, _file: &Path, _total_slots: usize) {}
+
+    /// 已处理到第 `position` 个 slot（从检查点继续时第一次调用即为检查点位置）
+    fn on_slot(&self, file: &Path, position: usize);
+
+    /// 一批数据提交插入
+    fn on_flush(&self, file: &Path, rows: usize);
+
+    /// 文件处理成功（所有插入已完成）
+    fn on_complete(&self, file: &Path, report: &FileReport, elapsed: Duration);
+
+    /// 文件处理失败
+    fn on_failed(&self, _file: &Path, _error: &str) {}
+}
+
+/// 不输出任何进度
+pub struct NoopReporter;
+
+impl ProgressReporter for NoopReporter {
+    fn on_slot(&self, _file: &Path, _position: usize) {}
+
+    fn on_flush(&self, _file: &Path, _rows: usize) {}
+
+    fn on_complete(&self, _file: &Path, _report: &FileReport, _elapsed: Duration) {}
+}
+
+/// 终端进度条，并行处理的每个文件各占一行
+#[derive(Default)]
+pub struct StdoutReporter {
+    multi: MultiProgress,
+    bars: Mutex<HashMap<PathBuf, ProgressBar>>,
+}
+
+impl StdoutReporter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn bar(&self, file: &Path) -> Option<ProgressBar> {
+        self.bars.lock().unwrap().get(file).cloned()
+    }
+
+    fn remove_bar(&self, file: &Path) -> Option<ProgressBar> {
+        self.bars.lock().unwrap().remove(file)
+    }
+}
+
+impl ProgressReporter for StdoutReporter {
+    fn on_start(&self, file: &Path, total_slots: usize) {
+        let pb = self.multi.add(ProgressBar::new(total_slots as u64));
+        pb.set_style(
+            ProgressStyle::default_bar()
+                .template("[{elapsed_precise}] {bar:40.cyan/blue} {pos:>7}/{len:7} {msg} ({eta})")
+                .unwrap()
+                .progress_chars("##-"),
+        );
+        pb.set_message(format!("Processing {}", file.display()));
+        self.bars.lock().unwrap().insert(file.to_path_buf(), pb);
+    }
+
+    fn on_slot(&self, file: &Path, position: usize) {
+        if let Some(pb) = self.bar(file) {
+            pb.set_position(position as u64);
+        }
+    }
+
+    fn on_flush(&self, _file: &Path, _rows: usize) {}
+
+    fn on_complete(&self, file: &Path, report: &FileReport, elapsed: Duration) {
+        if let Some(pb) = self.remove_bar(file) {
+            let rate = report.slots_processed as f64 / elapsed.as_secs_f64().max(0.001);
+            pb.finish_with_message(format!(
+                "Completed processing {} ({:.0} slots/s)",
+                file.display(),
+                rate
+            ));
+        }
+    }
+
+    fn on_failed(&self, file: &Path, _error: &str) {
+        if let Some(pb) = self.remove_bar(file) {
+            pb.abandon_with_message(format!("Failed processing {}", file.display()));
+        }
+    }
+}
+
+/// 默认 reporter：终端中运行时显示进度条，否则（后台服务、重定向到日志）不输出
+pub fn default_reporter() -> Arc<dyn ProgressReporter> {
+    if std::io::stdout().is_terminal() {
+        Arc::new(StdoutReporter::new())
+    } else {
+        Arc::new(NoopReporter)
+    }
+}
@@ -1,2 +1,3 @@
 pub mod block_parser;
 pub mod transaction_subscriber;
+pub mod spill;
@@ -1,10 +1,21 @@
 use std::env;
 use squirrel::block_parser::block_parser_service::{BlockParserService, Config as BlockParserConfig};
 use squirrel::transaction_subscriber::transaction_subscriber_service::{TransactionSubscriberService, Config as TransactionSubscriberConfig};
+use squirrel::spill;
+use std::path::PathBuf;
+use utils::clickhouse_client::ClickHouseClient;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // 错误日志输出到 stderr，带上所在消息 span 的 trace_id
+    tracing_subscriber::fmt().with_writer(std::io::stderr).init();
+
     let args: Vec<String> = env::args().collect();
+
+    // 子命令：回放 spill 目录
+    if args.get(1).map(|s| s.as_str()) == Some("replay-spill") {
+        return replay_spill(&args[2..]).await;
+    }
     
     if args.len() < 3 {
         print_usage();
@@ -13,6 +24,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     
     let mut mode: Option<String> = None;
     let mut config_path: Option<String> = None;
+    let mut since: Option<u64> = None;
+    let mut until: Option<u64> = None;
     
     // 解析命令行参数
     for i in 1..args.len() {
@@ -21,6 +34,10 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             mode = Some(arg.trim_start_matches("--mode=").to_string());
         } else if arg.starts_with("--config=") {
             config_path = Some(arg.trim_start_matches("--config=").to_string());
+        } else if let Some(slot) = arg.strip_prefix("--since=") {
+            since = Some(slot.parse().map_err(|_| format!("Invalid --since slot: {}", slot))?);
+        } else if let Some(slot) = arg.strip_prefix("--until=") {
+            until = Some(slot.parse().map_err(|_| format!("Invalid --until slot: {}", slot))?);
         }
     }
     
@@ -33,8 +50,12 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             println!("Config file: {}", config_path);
             
             // 加载配置文件
-            let config = BlockParserConfig::from_toml_file(&config_path)?;
+            let mut config = BlockParserConfig::from_toml_file(&config_path)?;
+            config.override_slot_range(since, until)?;
             println!("Configuration loaded successfully");
+            if let Some((start, end)) = config.slot_range {
+                println!("Only processing file pairs within slots {}..={}", start, end);
+            }
             
             // 创建并启动服务
             let service = BlockParserService::new(config)?;
@@ -68,13 +89,56 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+/// 回放 spill 目录中的失败批次，成功的文件会被删除
+async fn replay_spill(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let mut dir: Option<String> = None;
+
+    let mut i = 0;
+    while i < args.len() {
+        let arg = &args[i];
+        if arg.starts_with("--dir=") {
+            dir = Some(arg.trim_start_matches("--dir=").to_string());
+        } else if arg == "--dir" {
+            dir = args.get(i + 1).cloned();
+            i += 1;
+        }
+        i += 1;
+    }
+
+    let dir = dir.ok_or("Missing --dir parameter")?;
+    println!("Replaying spill files from: {}", dir);
+
+    let client = ClickHouseClient::instance().client();
+    let stats = spill::replay_spill_dir(client, &PathBuf::from(&dir)).await?;
+
+    println!(
+        "Replay finished: {} files, {} rows, {} failed",
+        stats.files_replayed,
+        stats.rows_replayed,
+        stats.failed_files.len()
+    );
+
+    if !stats.failed_files.is_empty() {
+        return Err(format!("{} spill files failed to replay", stats.failed_files.len()).into());
+    }
+
+    Ok(())
+}
+
 fn print_usage() {
-    println!("Usage: squirrel --mode=<MODE> --config=<CONFIG_FILE>");
+    println!("Usage: squirrel --mode=<MODE> --config=<CONFIG_FILE> [--since=<SLOT>] [--until=<SLOT>]");
+    println!("       squirrel replay-spill --dir <SPILL_DIR>");
     println!("Modes:");
     println!("  block_parser            Start the block parser service");
     println!("  transaction_subscriber  Start the transaction subscriber service");
     println!("");
+    println!("Options (block_parser):");
+    println!("  --since=<SLOT>          Skip file pairs that end before this slot");
+    println!("  --until=<SLOT>          Skip file pairs that start after this slot");
+    println!("");
     println!("Examples:");
     println!("  squirrel --mode=block_parser --config=config/block_parser_config.toml");
+    println!("  squirrel --mode=block_parser --config=config/block_parser_config.toml --since=300000000 --until=300100000");
     println!("  squirrel --mode=transaction_subscriber --config=config/transaction_subscriber.toml");
+    println!("  squirrel replay-spill --dir spill");
 }
@@ -0,0 +1,269 @@
+use clickhouse::{Client, Row};
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use parquet::arrow::ArrowWriter;
+use parquet::file::metadata::KeyValue;
+use parquet::file::properties::WriterProperties;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use utils::clickhouse_events::*;
+
+const META_TABLE: &str = "squirrel.table";
+const META_EVENT_TYPE: &str = "squirrel.event_type";
+
+static SPILL_SEQ: AtomicU64 = AtomicU64::new(0);
+
+/// 插入失败时的本地落盘器
+///
+/// 文件布局: {spill_dir}/{table}/{table}_{纳秒时间戳}_{序号}.parquet
+/// 表名和事件类型写入 parquet 的 key-value 元数据，供 replay 使用
+#[derive(Debug, Clone)]
+pub struct SpillWriter {
+    dir: PathBuf,
+}
+
+/// 插入结果
+#[derive(Debug)]
+pub enum InsertOutcome {
+    Inserted,
+    Spilled(PathBuf),
+}
+
+/// replay 统计信息
+#[derive(Debug, Default)]
+pub struct ReplayStats {
+    pub files_replayed: usize,
+    pub rows_replayed: u64,
+    pub failed_files: Vec<(PathBuf, String)>,
+}
+
+/// 从 spill 文件读出的内容
+pub struct SpillFile {
+    pub table: String,
+    pub event_type: String,
+    pub batch: arrow::record_batch::RecordBatch,
+}
+
+impl SpillWriter {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// 将一批行写入 spill 文件，返回文件路径
+    pub fn spill<T: Serialize + for<'de> Deserialize<'de>>(
+        &self,
+        table: &str,
+        rows: &[T],
+    ) -> Result<PathBuf, Box<dyn std::error::Error>> {
+        let table_dir = self.dir.join(table);
+        fs::create_dir_all(&table_dir)?;
+
+        let now = chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default();
+        let seq = SPILL_SEQ.fetch_add(1, Ordering::Relaxed);
+        let file_path = table_dir.join(format!("{}_{}_{}.parquet", table, now, seq));
+        let tmp_path = file_path.with_extension("parquet.tmp");
+
+        let batch = try_vec_to_arrow_batch(rows)?;
+        let props = WriterProperties::builder()
+            .set_key_value_metadata(Some(vec![
+                KeyValue::new(META_TABLE.to_string(), table.to_string()),
+                KeyValue::new(META_EVENT_TYPE.to_string(), event_type_name::<T>().to_string()),
+            ]))
+            .build();
+
+        // 先写临时文件再 rename，避免 replay 读到写了一半的文件
+        let file = File::create(&tmp_path)?;
+        let mut writer = ArrowWriter::try_new(file, batch.schema(), Some(props))?;
+        writer.write(&batch)?;
+        writer.close()?;
+        fs::rename(&tmp_path, &file_path)?;
+
+        Ok(file_path)
+    }
+}
+
+/// 取类型名最后一段，例如 "utils::clickhouse_events::PumpfunTradeEventV2" -> "PumpfunTradeEventV2"
+fn event_type_name<T>() -> &'static str {
+    let full = std::any::type_name::<T>();
+    full.rsplit("::").next().unwrap_or(full)
+}
+
+/// 写入一批行到 ClickHouse
+pub async fn insert_rows<T: Row + Serialize>(
+    client: &Client,
+    table: &str,
+    rows: &[T],
+) -> Result<(), String> {
+    let mut insert = client
+        .insert::<T>(table)
+        .map_err(|e| format!("Failed to create insert for table {}: {}", table, e))?;
+
+    for (i, row) in rows.iter().enumerate() {
+        insert
+            .write(row)
+            .await
+            .map_err(|e| format!("Failed to write row {} to table {}: {}", i, table, e))?;
+    }
+
+    insert
+        .end()
+        .await
+        .map_err(|e| format!("Failed to end insert for table {}: {}", table, e))
+}
+
+/// 写入 ClickHouse，失败时若配置了 spill 则落盘
+pub async fn insert_or_spill<T: Row + Serialize + for<'de> Deserialize<'de>>(
+    client: &Client,
+    table: &str,
+    rows: &[T],
+    spill: Option<&SpillWriter>,
+) -> Result<InsertOutcome, String> {
+    match insert_rows(client, table, rows).await {
+        Ok(()) => Ok(InsertOutcome::Inserted),
+        Err(e) => match spill {
+            Some(spill) => {
+                let path = spill
+                    .spill(table, rows)
+                    .map_err(|se| format!("{}; spill also failed: {}", e, se))?;
+                eprintln!(
+                    "⚠️  {}; spilled {} rows to {}",
+                    e,
+                    rows.len(),
+                    path.display()
+                );
+                Ok(InsertOutcome::Spilled(path))
+            }
+            None => Err(e),
+        },
+    }
+}
+
+/// 读取单个 spill 文件
+pub fn read_spill_file(path: &Path) -> Result<SpillFile, Box<dyn std::error::Error>> {
+    let file = File::open(path)?;
+    let builder = ParquetRecordBatchReaderBuilder::try_new(file)?;
+
+    let kv = builder
+        .metadata()
+        .file_metadata()
+        .key_value_metadata()
+        .cloned()
+        .unwrap_or_default();
+    let lookup = |key: &str| {
+        kv.iter()
+            .find(|entry| entry.key == key)
+            .and_then(|entry| entry.value.clone())
+            .ok_or_else(|| format!("Missing '{}' metadata in {}", key, path.display()))
+    };
+    let table = lookup(META_TABLE)?;
+    let event_type = lookup(META_EVENT_TYPE)?;
+
+    let schema = builder.schema().clone();
+    let reader = builder.build()?;
+    let mut batches = Vec::new();
+    for batch in reader {
+        batches.push(batch?);
+    }
+    let batch = arrow::compute::concat_batches(&schema, &batches)?;
+
+    Ok(SpillFile {
+        table,
+        event_type,
+        batch,
+    })
+}
+
+/// 扫描 spill 目录，返回所有待 replay 的文件（按文件名排序）
+pub fn list_spill_files(dir: &Path) -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
+    let mut files = Vec::new();
+    if !dir.exists() {
+        return Ok(files);
+    }
+
+    for table_entry in fs::read_dir(dir)? {
+        let table_path = table_entry?.path();
+        if !table_path.is_dir() {
+            continue;
+        }
+        for entry in fs::read_dir(&table_path)? {
+            let path = entry?.path();
+            if path.extension().and_then(|ext| ext.to_str()) == Some("parquet") {
+                files.push(path);
+            }
+        }
+    }
+
+    files.sort();
+    Ok(files)
+}
+
+/// 宏：根据事件类型反序列化 spill 批次并写入 ClickHouse
+macro_rules! replay_batch {
+    ($client:expr, $spill:expr, $( $variant:literal => $type:ty ),* $(,)?) => {
+        match $spill.event_type.as_str() {
+            $(
+                $variant => {
+                    let rows: Vec<$type> =
+                        try_arrow_batch_to_vec(&$spill.batch).map_err(|e| e.to_string())?;
+                    insert_rows($client, &$spill.table, &rows).await?;
+                    Ok(rows.len() as u64)
+                }
+            )*
+            other => Err(format!("Unknown event type: {}", other)),
+        }
+    };
+}
+
+/// replay 单个 spill 文件，成功后删除
+async fn replay_file(client: &Client, path: &Path) -> Result<u64, String> {
+    let spill = read_spill_file(path).map_err(|e| e.to_string())?;
+
+    let rows: Result<u64, String> = replay_batch!(
+        client,
+        spill,
+        "PumpfunTradeEventV2" => PumpfunTradeEventV2,
+        "PumpfunCreateEventV2" => PumpfunCreateEventV2,
+        "PumpfunMigrateEventV2" => PumpfunMigrateEventV2,
+        "PumpfunAmmBuyEventV2" => PumpfunAmmBuyEventV2,
+        "PumpfunAmmSellEventV2" => PumpfunAmmSellEventV2,
+        "PumpfunAmmCreatePoolEventV2" => PumpfunAmmCreatePoolEventV2,
+        "PumpfunAmmDepositEventV2" => PumpfunAmmDepositEventV2,
+        "PumpfunAmmWithdrawEventV2" => PumpfunAmmWithdrawEventV2,
+        "PumpfunAmmCollectCoinCreatorFeeEventV2" => PumpfunAmmCollectCoinCreatorFeeEventV2,
+        "PumpfunAmmDisableEventV2" => PumpfunAmmDisableEventV2,
+        "PumpfunAmmSetParamsEventV2" => PumpfunAmmSetParamsEventV2,
+    );
+    let rows = rows?;
+
+    fs::remove_file(path).map_err(|e| e.to_string())?;
+    Ok(rows)
+}
+
+/// replay 目录下所有 spill 文件，成功的文件会被删除，失败的保留以便下次重试
+pub async fn replay_spill_dir(
+    client: &Client,
+    dir: &Path,
+) -> Result<ReplayStats, Box<dyn std::error::Error>> {
+    let mut stats = ReplayStats::default();
+
+    for path in list_spill_files(dir)? {
+        match replay_file(client, &path).await {
+            Ok(rows) => {
+                println!("✓ Replayed {} ({} rows)", path.display(), rows);
+                stats.files_replayed += 1;
+                stats.rows_replayed += rows;
+            }
+            Err(e) => {
+                eprintln!("✗ Failed to replay {}: {}", path.display(), e);
+                stats.failed_files.push((path, e));
+            }
+        }
+    }
+
+    Ok(stats)
+}
@@ -0,0 +1,68 @@
+//! NATS 连接与断线重连
+//!
+//! async-nats 客户端断线后会在后台自动重连并恢复订阅，这里统一配置重连退避，
+//! 并把连接状态变化以 `ConnectionEvent` 回调给服务（日志、健康检查等）
+
+use async_nats::connection::State;
+use async_nats::{Client, ConnectOptions, Event};
+use std::sync::Arc;
+use std::time::Duration;
+use utils::reconnect::ReconnectBackoff;
+
+/// 第一次重连前的等待时间，之后每次翻倍
+const RECONNECT_BASE_DELAY: Duration = Duration::from_millis(100);
+/// 重连等待时间上限
+const RECONNECT_MAX_DELAY: Duration = Duration::from_secs(10);
+
+/// 连接状态变化
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConnectionEvent {
+    /// 已连接（包括断线后重连成功）
+    Connected,
+    /// 连接断开，后台开始重连
+    Disconnected,
+    /// 服务器即将下线（lame duck），会被迁移到其他服务器
+    LameDuck,
+    /// 连接已关闭，不再重连
+    Closed,
+    /// 其他客户端 / 服务器错误
+    Error(String),
+}
+
+impl From<Event> for ConnectionEvent {
+    fn from(event: Event) -> Self {
+        match event {
+            Event::Connected => ConnectionEvent::Connected,
+            Event::Disconnected => ConnectionEvent::Disconnected,
+            Event::LameDuckMode => ConnectionEvent::LameDuck,
+            Event::Closed => ConnectionEvent::Closed,
+            other => ConnectionEvent::Error(other.to_string()),
+        }
+    }
+}
+
+/// 第 attempts 次重连前的等待时间（与 misaka_signal 重新订阅共用 `ReconnectBackoff`）
+pub fn reconnect_delay(attempts: usize) -> Duration {
+    ReconnectBackoff::with_delays(0, RECONNECT_BASE_DELAY, RECONNECT_MAX_DELAY).client_delay(attempts)
+}
+
+/// 连接 NATS，断线后按 `reconnect_delay` 退避重连，状态变化时调用 `on_event`
+pub async fn connect<F>(url: &str, on_event: F) -> Result<Client, async_nats::ConnectError>
+where
+    F: Fn(ConnectionEvent) + Send + Sync + 'static,
+{
+    let on_event = Arc::new(on_event);
+    ConnectOptions::new()
+        .reconnect_delay_callback(reconnect_delay)
+        .event_callback(move |event| {
+            let on_event = Arc::clone(&on_event);
+            async move { on_event(ConnectionEvent::from(event)) }
+        })
+        .connect(url)
+        .await
+}
+
+/// 客户端当前是否处于已连接状态
+pub fn is_connected(client: &Client) -> bool {
+    client.connection_state() == State::Connected
+}
@@ -0,0 +1,117 @@
+//! TransactionSubscriber 的 Prometheus 指标
+//!
+//! 启用 `metrics` feature 时注册到 prometheus 默认 registry，否则所有函数为空操作
+
+use utils::convert_transaction::ConvertedEvents;
+
+#[cfg(feature = "metrics")]
+mod imp {
+    use prometheus::{
+        Histogram, IntCounter, IntCounterVec, exponential_buckets, register_histogram,
+        register_int_counter, register_int_counter_vec,
+    };
+    use std::sync::LazyLock;
+
+    pub static TX_RECEIVED: LazyLock<IntCounter> = LazyLock::new(|| {
+        register_int_counter!("tx_received_total", "Transactions received from NATS").unwrap()
+    });
+
+    pub static EVENTS_CONVERTED: LazyLock<IntCounterVec> = LazyLock::new(|| {
+        register_int_counter_vec!(
+            "events_converted_total",
+            "Event rows produced by the converter",
+            &["event_type"]
+        )
+        .unwrap()
+    });
+
+    pub static ROWS_FLUSHED: LazyLock<IntCounterVec> = LazyLock::new(|| {
+        register_int_counter_vec!(
+            "rows_flushed_total",
+            "Rows submitted to ClickHouse inserts",
+            &["table"]
+        )
+        .unwrap()
+    });
+
+    pub static INSERT_ERRORS: LazyLock<IntCounter> = LazyLock::new(|| {
+        register_int_counter!(
+            "clickhouse_insert_errors_total",
+            "Failed ClickHouse inserts (including spilled batches)"
+        )
+        .unwrap()
+    });
+
+    pub static DECODE_ERRORS: LazyLock<IntCounter> = LazyLock::new(|| {
+        register_int_counter!(
+            "decode_errors_total",
+            "NATS messages that failed to decode as transactions"
+        )
+        .unwrap()
+    });
+
+    // 10μs ~ 约 160ms
+    pub static PROCESSING_TIME: LazyLock<Histogram> = LazyLock::new(|| {
+        register_histogram!(
+            "tx_processing_seconds",
+            "Time spent converting a single transaction",
+            exponential_buckets(0.00001, 2.0, 15).unwrap()
+        )
+        .unwrap()
+    });
+}
+
+/// 单笔交易处理完成
+pub fn record_transaction(_processing_time_micros: u64) {
+    #[cfg(feature = "metrics")]
+    {
+        imp::TX_RECEIVED.inc();
+        imp::PROCESSING_TIME.observe(_processing_time_micros as f64 / 1_000_000.0);
+    }
+}
+
+/// 消息无法解码为交易
+pub fn record_decode_error() {
+    #[cfg(feature = "metrics")]
+    imp::DECODE_ERRORS.inc();
+}
+
+/// 转换得到的事件行（按事件类型计数）
+pub fn record_events(_events: &ConvertedEvents) {
+    #[cfg(feature = "metrics")]
+    for (event_type, rows) in _events.len_per_table() {
+        if rows > 0 {
+            imp::EVENTS_CONVERTED
+                .with_label_values(&[event_type])
+                .inc_by(rows as u64);
+        }
+    }
+}
+
+/// 提交到某张表的插入行数
+pub fn record_rows_flushed(_table: &str, _rows: usize) {
+    #[cfg(feature = "metrics")]
+    imp::ROWS_FLUSHED
+        .with_label_values(&[_table])
+        .inc_by(_rows as u64);
+}
+
+/// 插入失败（无论之后是否落盘成功）
+pub fn record_insert_error() {
+    #[cfg(feature = "metrics")]
+    imp::INSERT_ERRORS.inc();
+}
+
+/// 按配置启动指标端点；未启用 `metrics` feature 时只输出警告
+pub async fn start_endpoint(addr: &str) -> std::io::Result<()> {
+    #[cfg(feature = "metrics")]
+    {
+        utils::metrics_server::serve_metrics(addr).await?;
+    }
+    #[cfg(not(feature = "metrics"))]
+    tracing::warn!(
+        metrics_addr = addr,
+        "metrics_addr is set but squirrel was built without the `metrics` feature"
+    );
+    Ok(())
+}
@@ -1,4 +1,6 @@
 pub mod transaction_subscriber_service;
-mod transaction_processor;
+pub mod transaction_processor;
+pub mod metrics;
+pub mod connection;
 
 pub use transaction_subscriber_service::{TransactionSubscriberService, Config, TableNames};
@@ -1,32 +1,105 @@
+use super::metrics;
 use super::transaction_subscriber_service::TableNames;
+use crate::spill::{self, SpillWriter};
+use clickhouse::Client;
 use common::async_pool::AsyncPool;
 use proto_lib::transaction::solana::Transaction;
+use std::fmt;
+use std::path::PathBuf;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::time::Duration;
-use tokio::sync::mpsc;
-use utils::clickhouse_client::ClickHouseClient;
-use utils::clickhouse_events;
-use utils::convert_transaction::TransactionConverter;
+use tokio::sync::{mpsc, oneshot};
+use utils::convert_transaction::{
+    ConversionMetrics, ConvertedEvents, ConverterOptions, TransactionConverter,
+};
 
-const BATCH_SIZE: usize = 100;
-const FLUSH_INTERVAL_MS: u64 = 100;
+/// 批次刷新阈值，任一条件满足即刷新
+#[derive(Debug, Clone, Copy)]
+pub struct BatchLimits {
+    /// 定时刷新间隔，未达到行数/字节阈值的批次最多等待这么久
+    pub flush_interval: Duration,
+    /// 任意一张表累积的行数
+    pub max_rows: usize,
+    /// 整个批次的近似插入字节数（见 `EstimatedSize`）
+    pub max_bytes: usize,
+}
+
+/// 批处理任务已退出，数据无法再提交
+#[derive(Debug)]
+pub struct ChannelClosed {
+    pub channel: &'static str,
+}
+
+impl fmt::Display for ChannelClosed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} channel closed, batch flusher task is gone", self.channel)
+    }
+}
+
+impl std::error::Error for ChannelClosed {}
+
+/// 统计发送失败次数的 channel 发送端
+///
+/// 接收端（批处理任务）退出后发送的数据被丢弃并计数，第一次丢弃时输出警告
+pub struct CountingSender<T> {
+    sender: mpsc::UnboundedSender<T>,
+    channel: &'static str,
+    dropped: AtomicUsize,
+}
+
+impl<T> CountingSender<T> {
+    pub fn new(sender: mpsc::UnboundedSender<T>, channel: &'static str) -> Self {
+        Self {
+            sender,
+            channel,
+            dropped: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn send(&self, value: T) -> Result<(), ChannelClosed> {
+        if self.sender.send(value).is_ok() {
+            return Ok(());
+        }
+        if self.dropped.fetch_add(1, Ordering::Relaxed) == 0 {
+            tracing::warn!(
+                channel = self.channel,
+                "⚠️  Receiver is gone, dropping messages (batch flusher task exited?)"
+            );
+        }
+        Err(ChannelClosed {
+            channel: self.channel,
+        })
+    }
+
+    /// 已丢弃的消息数
+    pub fn dropped(&self) -> usize {
+        self.dropped.load(Ordering::Relaxed)
+    }
+}
 
 pub struct TransactionProcessor {
-    event_sender: mpsc::UnboundedSender<ProcessedEvents>,
+    event_sender: CountingSender<ProcessedEvents>,
     async_pool: Arc<AsyncPool>,
-    stats_sender: mpsc::UnboundedSender<ProcessingStats>,
+    stats_sender: CountingSender<ProcessingStats>,
+    flush_sender: mpsc::UnboundedSender<oneshot::Sender<usize>>, // 立即刷新请求，回复刷新的行数
 }
 
+/// 一条（或一批合并后的）消息转换出的事件
 #[derive(Default)]
-struct ProcessedEvents {
-    pumpfun_trade_event: Vec<clickhouse_events::PumpfunTradeEventV2>,
-    pumpfun_create_event: Vec<clickhouse_events::PumpfunCreateEventV2>,
-    pumpfun_migrate_event: Vec<clickhouse_events::PumpfunMigrateEventV2>,
-    pumpfun_amm_buy_event: Vec<clickhouse_events::PumpfunAmmBuyEventV2>,
-    pumpfun_amm_sell_event: Vec<clickhouse_events::PumpfunAmmSellEventV2>,
-    pumpfun_amm_create_pool_event: Vec<clickhouse_events::PumpfunAmmCreatePoolEventV2>,
-    pumpfun_amm_deposit_event: Vec<clickhouse_events::PumpfunAmmDepositEventV2>,
-    pumpfun_amm_withdraw_event: Vec<clickhouse_events::PumpfunAmmWithdrawEventV2>,
+pub struct ProcessedEvents {
+    /// 消息的 trace id，合并后的批次为逗号分隔的列表
+    pub trace_id: String,
+    pub events: ConvertedEvents,
+}
+
+/// 批处理任务刷新批次时用到的配置和资源
+struct FlushContext {
+    client: Client,
+    async_pool: Arc<AsyncPool>,
+    table_names: TableNames,
+    spill: Option<SpillWriter>,
+    batch_limits: BatchLimits,
 }
 
 /// 处理统计信息
@@ -36,146 +109,212 @@ struct ProcessingStats {
     processing_time_micros: u64,
 }
 
-impl ProcessedEvents {
-    fn is_empty(&self) -> bool {
-        self.pumpfun_trade_event.is_empty()
-            && self.pumpfun_create_event.is_empty()
-            && self.pumpfun_migrate_event.is_empty()
-            && self.pumpfun_amm_buy_event.is_empty()
-            && self.pumpfun_amm_sell_event.is_empty()
-            && self.pumpfun_amm_create_pool_event.is_empty()
-            && self.pumpfun_amm_deposit_event.is_empty()
-            && self.pumpfun_amm_withdraw_event.is_empty()
+/// 各表刷新的行数（字段与 TableNames 一一对应）
+#[derive(Debug, Default, Clone, Copy)]
+struct TableRows {
+    pumpfun_trade_event: usize,
+    pumpfun_create_event: usize,
+    pumpfun_migrate_event: usize,
+    pumpfun_amm_buy_event: usize,
+    pumpfun_amm_sell_event: usize,
+    pumpfun_amm_create_pool_event: usize,
+    pumpfun_amm_deposit_event: usize,
+    pumpfun_amm_withdraw_event: usize,
+    pumpfun_amm_collect_coin_creator_fee_event: usize,
+    pumpfun_amm_disable_event: usize,
+    pumpfun_amm_set_params_event: usize,
+}
+
+impl TableRows {
+    fn add(&mut self, other: &TableRows) {
+        self.pumpfun_trade_event += other.pumpfun_trade_event;
+        self.pumpfun_create_event += other.pumpfun_create_event;
+        self.pumpfun_migrate_event += other.pumpfun_migrate_event;
+        self.pumpfun_amm_buy_event += other.pumpfun_amm_buy_event;
+        self.pumpfun_amm_sell_event += other.pumpfun_amm_sell_event;
+        self.pumpfun_amm_create_pool_event += other.pumpfun_amm_create_pool_event;
+        self.pumpfun_amm_deposit_event += other.pumpfun_amm_deposit_event;
+        self.pumpfun_amm_withdraw_event += other.pumpfun_amm_withdraw_event;
+        self.pumpfun_amm_collect_coin_creator_fee_event +=
+            other.pumpfun_amm_collect_coin_creator_fee_event;
+        self.pumpfun_amm_disable_event += other.pumpfun_amm_disable_event;
+        self.pumpfun_amm_set_params_event += other.pumpfun_amm_set_params_event;
+    }
+
+    fn total(&self) -> usize {
+        self.pumpfun_trade_event
+            + self.pumpfun_create_event
+            + self.pumpfun_migrate_event
+            + self.pumpfun_amm_buy_event
+            + self.pumpfun_amm_sell_event
+            + self.pumpfun_amm_create_pool_event
+            + self.pumpfun_amm_deposit_event
+            + self.pumpfun_amm_withdraw_event
+            + self.pumpfun_amm_collect_coin_creator_fee_event
+            + self.pumpfun_amm_disable_event
+            + self.pumpfun_amm_set_params_event
+    }
+
+    /// 汇总行末尾的 `key=value` 计数，便于日志解析
+    fn breakdown(&self) -> String {
+        format!(
+            "trade={} create={} migrate={} amm_buy={} amm_sell={} amm_create_pool={} amm_deposit={} amm_withdraw={} \
+             amm_collect_coin_creator_fee={} amm_disable={} amm_set_params={}",
+            self.pumpfun_trade_event,
+            self.pumpfun_create_event,
+            self.pumpfun_migrate_event,
+            self.pumpfun_amm_buy_event,
+            self.pumpfun_amm_sell_event,
+            self.pumpfun_amm_create_pool_event,
+            self.pumpfun_amm_deposit_event,
+            self.pumpfun_amm_withdraw_event,
+            self.pumpfun_amm_collect_coin_creator_fee_event,
+            self.pumpfun_amm_disable_event,
+            self.pumpfun_amm_set_params_event,
+        )
     }
 }
 
+/// 批处理任务中累积的待插入事件，达到 `BatchLimits` 任一阈值时刷新
 #[derive(Default)]
-struct BatchAccumulator {
-    pumpfun_trade_event: Vec<clickhouse_events::PumpfunTradeEventV2>,
-    pumpfun_create_event: Vec<clickhouse_events::PumpfunCreateEventV2>,
-    pumpfun_migrate_event: Vec<clickhouse_events::PumpfunMigrateEventV2>,
-    pumpfun_amm_buy_event: Vec<clickhouse_events::PumpfunAmmBuyEventV2>,
-    pumpfun_amm_sell_event: Vec<clickhouse_events::PumpfunAmmSellEventV2>,
-    pumpfun_amm_create_pool_event: Vec<clickhouse_events::PumpfunAmmCreatePoolEventV2>,
-    pumpfun_amm_deposit_event: Vec<clickhouse_events::PumpfunAmmDepositEventV2>,
-    pumpfun_amm_withdraw_event: Vec<clickhouse_events::PumpfunAmmWithdrawEventV2>,
+pub struct BatchAccumulator {
+    trace_ids: Vec<String>, // 批次内各消息的 trace id，插入失败时输出
+    events: ConvertedEvents,
+    estimated_bytes: usize,
 }
 
 impl BatchAccumulator {
-    fn add(&mut self, events: ProcessedEvents) {
-        self.pumpfun_trade_event.extend(events.pumpfun_trade_event);
-        self.pumpfun_create_event
-            .extend(events.pumpfun_create_event);
-        self.pumpfun_migrate_event
-            .extend(events.pumpfun_migrate_event);
-        self.pumpfun_amm_buy_event
-            .extend(events.pumpfun_amm_buy_event);
-        self.pumpfun_amm_sell_event
-            .extend(events.pumpfun_amm_sell_event);
-        self.pumpfun_amm_create_pool_event
-            .extend(events.pumpfun_amm_create_pool_event);
-        self.pumpfun_amm_deposit_event
-            .extend(events.pumpfun_amm_deposit_event);
-        self.pumpfun_amm_withdraw_event
-            .extend(events.pumpfun_amm_withdraw_event);
+    pub fn add(&mut self, events: ProcessedEvents) {
+        self.trace_ids.push(events.trace_id);
+        // 每条消息的事件很少，在合并前估算，避免每次重新遍历整个批次
+        self.estimated_bytes += events.events.estimated_bytes();
+        self.events.extend(events.events);
     }
 
-    fn should_flush(&self) -> bool {
-        self.pumpfun_trade_event.len() >= BATCH_SIZE
-            || self.pumpfun_create_event.len() >= BATCH_SIZE
-            || self.pumpfun_migrate_event.len() >= BATCH_SIZE
-            || self.pumpfun_amm_buy_event.len() >= BATCH_SIZE
-            || self.pumpfun_amm_sell_event.len() >= BATCH_SIZE
-            || self.pumpfun_amm_create_pool_event.len() >= BATCH_SIZE
-            || self.pumpfun_amm_deposit_event.len() >= BATCH_SIZE
-            || self.pumpfun_amm_withdraw_event.len() >= BATCH_SIZE
+    pub fn should_flush(&self, limits: &BatchLimits) -> bool {
+        self.estimated_bytes >= limits.max_bytes
+            || self
+                .events
+                .len_per_table()
+                .iter()
+                .any(|(_, rows)| *rows >= limits.max_rows)
     }
 
-    fn is_empty(&self) -> bool {
-        self.pumpfun_trade_event.is_empty()
-            && self.pumpfun_create_event.is_empty()
-            && self.pumpfun_migrate_event.is_empty()
-            && self.pumpfun_amm_buy_event.is_empty()
-            && self.pumpfun_amm_sell_event.is_empty()
-            && self.pumpfun_amm_create_pool_event.is_empty()
-            && self.pumpfun_amm_deposit_event.is_empty()
-            && self.pumpfun_amm_withdraw_event.is_empty()
+    /// 当前批次的近似插入字节数
+    pub fn estimated_bytes(&self) -> usize {
+        self.estimated_bytes
     }
 
-    fn take(&mut self) -> ProcessedEvents {
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    pub fn take(&mut self) -> ProcessedEvents {
+        self.estimated_bytes = 0;
         ProcessedEvents {
-            pumpfun_trade_event: std::mem::take(&mut self.pumpfun_trade_event),
-            pumpfun_create_event: std::mem::take(&mut self.pumpfun_create_event),
-            pumpfun_migrate_event: std::mem::take(&mut self.pumpfun_migrate_event),
-            pumpfun_amm_buy_event: std::mem::take(&mut self.pumpfun_amm_buy_event),
-            pumpfun_amm_sell_event: std::mem::take(&mut self.pumpfun_amm_sell_event),
-            pumpfun_amm_create_pool_event: std::mem::take(&mut self.pumpfun_amm_create_pool_event),
-            pumpfun_amm_deposit_event: std::mem::take(&mut self.pumpfun_amm_deposit_event),
-            pumpfun_amm_withdraw_event: std::mem::take(&mut self.pumpfun_amm_withdraw_event),
+            trace_id: std::mem::take(&mut self.trace_ids).join(","),
+            events: std::mem::take(&mut self.events),
         }
     }
 }
 
 impl TransactionProcessor {
-    pub fn new(max_concurrent_clickhouse_tasks: usize, table_names: TableNames) -> Self {
+    /// `client` 为插入使用的 ClickHouse 客户端
+    pub fn new(
+        client: Client,
+        max_concurrent_clickhouse_tasks: usize,
+        table_names: TableNames,
+        spill_dir: Option<PathBuf>,
+        batch_limits: BatchLimits,
+    ) -> Self {
         let (tx, rx) = mpsc::unbounded_channel();
         let (stats_tx, stats_rx) = mpsc::unbounded_channel();
+        let (flush_tx, flush_rx) = mpsc::unbounded_channel();
 
         let async_pool = Arc::new(AsyncPool::new(max_concurrent_clickhouse_tasks));
         let pool_clone = Arc::clone(&async_pool);
+        let context = FlushContext {
+            client,
+            async_pool: pool_clone,
+            table_names,
+            spill: spill_dir.map(SpillWriter::new),
+            batch_limits,
+        };
         tokio::spawn(async move {
-            Self::batch_flusher_task(rx, stats_rx, pool_clone, table_names).await;
+            Self::batch_flusher_task(context, rx, stats_rx, flush_rx).await;
         });
 
         Self {
-            event_sender: tx,
+            event_sender: CountingSender::new(tx, "events"),
             async_pool,
-            stats_sender: stats_tx,
+            stats_sender: CountingSender::new(stats_tx, "stats"),
+            flush_sender: flush_tx,
         }
     }
 
-    pub fn process_transaction(&self, parsed_tx: Transaction, payload_size: usize) {
+    /// 转换单笔交易，调用方应处于该消息的 trace span 内
+    ///
+    /// 批处理任务已退出时返回 `ChannelClosed`，事件被丢弃并计入 `dropped_events`
+    pub fn process_transaction(
+        &self,
+        parsed_tx: Transaction,
+        payload_size: usize,
+        trace_id: String,
+    ) -> Result<(), ChannelClosed> {
         let start = std::time::Instant::now();
-        let mut events = ProcessedEvents::default();
-        
-        TransactionConverter::convert(
+        let mut events = ProcessedEvents {
+            trace_id,
+            ..Default::default()
+        };
+        let mut metrics = ConversionMetrics::default();
+
+        TransactionConverter::convert_into_with_options(
             &parsed_tx,
-            &mut events.pumpfun_trade_event,
-            &mut events.pumpfun_create_event,
-            &mut events.pumpfun_migrate_event,
-            &mut events.pumpfun_amm_buy_event,
-            &mut events.pumpfun_amm_sell_event,
-            &mut events.pumpfun_amm_create_pool_event,
-            &mut events.pumpfun_amm_deposit_event,
-            &mut events.pumpfun_amm_withdraw_event,
+            &ConverterOptions::default(),
+            &mut metrics,
+            &mut events.events,
         );
 
+        if metrics.dropped_instructions > 0 {
+            tracing::warn!(
+                slot = parsed_tx.slot,
+                dropped_instructions = metrics.dropped_instructions,
+                "⚠️  Instruction stack overflow during conversion, some events may be missing"
+            );
+        }
+
         let processing_time = start.elapsed().as_micros() as u64;
         
+        // 先发送事件：批处理任务退出时以事件 channel 的错误为准
+        if !events.events.is_empty() {
+            self.event_sender.send(events)?;
+        }
+
         // 发送统计信息（即使没有事件也要统计）
-        let _ = self.stats_sender.send(ProcessingStats {
+        self.stats_sender.send(ProcessingStats {
             payload_size,
             processing_time_micros: processing_time,
-        });
+        })
+    }
 
-        if !events.is_empty() {
-            let _ = self.event_sender.send(events);
-        }
+    /// 因批处理任务退出而丢弃的事件消息数
+    pub fn dropped_events(&self) -> usize {
+        self.event_sender.dropped()
     }
 
     async fn batch_flusher_task(
+        context: FlushContext,
         mut receiver: mpsc::UnboundedReceiver<ProcessedEvents>,
         mut stats_receiver: mpsc::UnboundedReceiver<ProcessingStats>,
-        async_pool: Arc<AsyncPool>,
-        table_names: TableNames,
+        mut flush_receiver: mpsc::UnboundedReceiver<oneshot::Sender<usize>>,
     ) {
         let mut batches = BatchAccumulator::default();
-        let mut interval = tokio::time::interval(Duration::from_millis(FLUSH_INTERVAL_MS));
+        let mut interval = tokio::time::interval(context.batch_limits.flush_interval);
 
         // 周期内的增量统计
         let mut period_transactions = 0usize;
         let mut period_events = 0usize;
-        let mut period_rows_flushed = 0usize;
+        let mut period_rows_flushed = TableRows::default();
         let mut period_bytes_received = 0usize;
         let mut period_processing_time_micros = 0u64;
         
@@ -191,22 +330,39 @@ impl TransactionProcessor {
         loop {
             tokio::select! {
                 Some(stats) = stats_receiver.recv() => {
+                    metrics::record_transaction(stats.processing_time_micros);
                     period_transactions += 1;
                     period_bytes_received += stats.payload_size;
                     period_processing_time_micros += stats.processing_time_micros;
                 }
                 Some(events) = receiver.recv() => {
+                    metrics::record_events(&events.events);
                     period_events += 1;
                     batches.add(events);
-                    if batches.should_flush() {
-                        let rows = Self::flush_batches(&mut batches, &async_pool, &table_names);
-                        period_rows_flushed += rows;
+                    if batches.should_flush(&context.batch_limits) {
+                        let rows = Self::flush_batches(&mut batches, &context);
+                        period_rows_flushed.add(&rows);
+                    }
+                }
+                Some(reply) = flush_receiver.recv() => {
+                    // 先收完请求之前已发送的事件，再整体刷新
+                    while let Ok(events) = receiver.try_recv() {
+                        metrics::record_events(&events.events);
+                        period_events += 1;
+                        batches.add(events);
                     }
+                    let rows = if batches.is_empty() {
+                        TableRows::default()
+                    } else {
+                        Self::flush_batches(&mut batches, &context)
+                    };
+                    period_rows_flushed.add(&rows);
+                    let _ = reply.send(rows.total());
                 }
                 _ = interval.tick() => {
                     if !batches.is_empty() {
-                        let rows = Self::flush_batches(&mut batches, &async_pool, &table_names);
-                        period_rows_flushed += rows;
+                        let rows = Self::flush_batches(&mut batches, &context);
+                        period_rows_flushed.add(&rows);
                     }
                     
                     // 定期打印汇总信息
@@ -219,22 +375,23 @@ impl TransactionProcessor {
                             0.0
                         };
                         
-                        println!("📈 [{}s] TX: {} ({:.0}/s) | Events: {} | Rows: {} | Data: {:.2}MB ({:.2}MB/s) | Avg processing: {:.1}μs | Uptime: {:.1}min",
+                        println!("📈 [{}s] TX: {} ({:.0}/s) | Events: {} | Rows: {} | Data: {:.2}MB ({:.2}MB/s) | Avg processing: {:.1}μs | Uptime: {:.1}min | {}",
                             SUMMARY_INTERVAL_SECS,
                             period_transactions,
                             period_transactions as f64 / period_duration,
                             period_events,
-                            period_rows_flushed,
+                            period_rows_flushed.total(),
                             period_bytes_received as f64 / (1024.0 * 1024.0),
                             (period_bytes_received as f64 / (1024.0 * 1024.0)) / period_duration,
                             avg_processing_time,
-                            total_uptime / 60.0
+                            total_uptime / 60.0,
+                            period_rows_flushed.breakdown()
                         );
                         
                         // 重置周期统计
                         period_transactions = 0;
                         period_events = 0;
-                        period_rows_flushed = 0;
+                        period_rows_flushed = TableRows::default();
                         period_bytes_received = 0;
                         period_processing_time_micros = 0;
                         last_summary_time = std::time::Instant::now();
@@ -244,75 +401,77 @@ impl TransactionProcessor {
         }
     }
 
-    fn flush_batches(
-        batches: &mut BatchAccumulator,
-        async_pool: &Arc<AsyncPool>,
-        table_names: &TableNames,
-    ) -> usize {
+    fn flush_batches(batches: &mut BatchAccumulator, context: &FlushContext) -> TableRows {
         let data = batches.take();
-        let mut total_rows = 0usize;
+        let mut flushed = TableRows::default();
 
         macro_rules! submit_insert {
             ($rows:expr, $table_field:ident) => {
                 if !$rows.is_empty() {
                     let row_count = $rows.len();
-                    total_rows += row_count;
-                    let table_name = table_names.$table_field.clone();
+                    flushed.$table_field += row_count;
+                    let table_name = context.table_names.$table_field.clone();
+                    metrics::record_rows_flushed(&table_name, row_count);
                     
                     // Debug模式下打印详细信息
                     #[cfg(debug_assertions)]
                     println!("📊 Flushing {} rows to table: {}", row_count, table_name);
 
                     let rows = $rows;
-                    async_pool.submit(move || async move {
-                        let client = ClickHouseClient::instance().client();
-                    
-                        let mut insert = match client.insert(&table_name) {
-                            Ok(insert) => insert,
+                    let spill = context.spill.clone();
+                    let trace_ids = data.trace_id.clone();
+                    let client = context.client.clone();
+                    context.async_pool.submit(move || async move {
+                        // 配置了 spill_dir 时失败批次落盘，否则终止程序
+                        match spill::insert_or_spill(&client, &table_name, &rows, spill.as_ref()).await {
+                            Ok(spill::InsertOutcome::Inserted) => {}
+                            Ok(spill::InsertOutcome::Spilled(_)) => metrics::record_insert_error(),
                             Err(e) => {
-                                eprintln!(
-                                    "❌ FATAL ERROR: Failed to create insert for table {}: {}",
-                                    table_name, e
-                                );
-                                std::process::exit(1);
-                            }
-                        };
-                    
-                        for (i, row) in rows.iter().enumerate() {
-                            if let Err(e) = insert.write(row).await {
-                                eprintln!(
-                                    "❌ FATAL ERROR: Failed to write row {} to table {}: {}",
-                                    i, table_name, e
+                                metrics::record_insert_error();
+                                tracing::error!(
+                                    table = %table_name,
+                                    trace_ids = %trace_ids,
+                                    "❌ FATAL ERROR: {}",
+                                    e
                                 );
                                 std::process::exit(1);
                             }
                         }
-                    
-                        if let Err(e) = insert.end().await {
-                            eprintln!(
-                                "❌ FATAL ERROR: Failed to end insert for table {}: {}",
-                                table_name, e
-                            );
-                            std::process::exit(1);
-                        }
                     });
                 }
             };
         }
 
-        submit_insert!(data.pumpfun_trade_event, pumpfun_trade_event);
-        submit_insert!(data.pumpfun_create_event, pumpfun_create_event);
-        submit_insert!(data.pumpfun_migrate_event, pumpfun_migrate_event);
-        submit_insert!(data.pumpfun_amm_buy_event, pumpfun_amm_buy_event);
-        submit_insert!(data.pumpfun_amm_sell_event, pumpfun_amm_sell_event);
+        submit_insert!(data.events.pumpfun_trade_event, pumpfun_trade_event);
+        submit_insert!(data.events.pumpfun_create_event, pumpfun_create_event);
+        submit_insert!(data.events.pumpfun_migrate_event, pumpfun_migrate_event);
+        submit_insert!(data.events.pumpfun_amm_buy_event, pumpfun_amm_buy_event);
+        submit_insert!(data.events.pumpfun_amm_sell_event, pumpfun_amm_sell_event);
         submit_insert!(
-            data.pumpfun_amm_create_pool_event,
+            data.events.pumpfun_amm_create_pool_event,
             pumpfun_amm_create_pool_event
         );
-        submit_insert!(data.pumpfun_amm_deposit_event, pumpfun_amm_deposit_event);
-        submit_insert!(data.pumpfun_amm_withdraw_event, pumpfun_amm_withdraw_event);
+        submit_insert!(data.events.pumpfun_amm_deposit_event, pumpfun_amm_deposit_event);
+        submit_insert!(data.events.pumpfun_amm_withdraw_event, pumpfun_amm_withdraw_event);
+        submit_insert!(
+            data.events.pumpfun_amm_collect_coin_creator_fee_event,
+            pumpfun_amm_collect_coin_creator_fee_event
+        );
+        submit_insert!(data.events.pumpfun_amm_disable_event, pumpfun_amm_disable_event);
+        submit_insert!(data.events.pumpfun_amm_set_params_event, pumpfun_amm_set_params_event);
 
-        total_rows
+        flushed
+    }
+
+    /// 立即刷新已累积的事件（包括已发送但批处理任务尚未接收的），返回提交插入的行数
+    ///
+    /// 只负责提交插入任务，需要等待插入完成时再调用 `wait_all_tasks`
+    pub async fn flush(&self) -> usize {
+        let (reply_tx, reply_rx) = oneshot::channel();
+        if self.flush_sender.send(reply_tx).is_err() {
+            return 0;
+        }
+        reply_rx.await.unwrap_or(0)
     }
 
     /// 等待所有ClickHouse插入任务完成
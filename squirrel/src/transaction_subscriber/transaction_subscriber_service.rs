@@ -1,26 +1,62 @@
-use super::transaction_processor::TransactionProcessor;
-use common::nats_client::NatsClient;
+use super::connection::{self, ConnectionEvent};
+use super::metrics;
+use super::transaction_processor::{BatchLimits, TransactionProcessor};
 use prost::Message;
 use proto_lib::transaction::solana::Transaction;
+use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio_stream::StreamExt;
 use toml;
+use utils::env_expand::read_toml_value;
+use utils::clickhouse_client::ClickHouseClient;
+use utils::clickhouse_events::*;
+use utils::dead_letter::DeadLetter;
+use utils::event_registry::{EventRegistry, verify_schema};
+use utils::schema::ensure_tables;
+use utils::trace::{TRACE_ID_HEADER, message_span, resolve_trace_id};
 
 /// TransactionSubscriber服务 - 从NATS订阅交易数据并处理
 pub struct TransactionSubscriberService {
-    nats_client: NatsClient,
+    // 直接使用 async-nats 客户端：common 的 NatsClient 不支持 queue group 订阅
+    nats_client: async_nats::Client,
     processor: Arc<TransactionProcessor>,
     topic: String,
+    queue_group: Option<String>,
+    dead_letter: DeadLetter, // 无法解码的消息
 }
 
 #[derive(Debug, Clone)]
 pub struct Config {
     pub nats_url: String,
     pub topic: String,
+    pub queue_group: Option<String>, // NATS queue group，多个副本使用同一个组时每条消息只被其中一个消费
     pub max_concurrent_clickhouse_tasks: usize,
+    pub clickhouse: Option<String>, // 命名 ClickHouse 客户端（读取 CLICKHOUSE_<NAME>_* 环境变量），不配置则使用默认的 CLICKHOUSE_*
     pub table_names: TableNames,
+    pub spill_dir: Option<String>, // 插入失败时批次落盘目录，不配置则失败直接退出
+    pub verify_schema: bool,       // 启动时校验 ClickHouse 表结构
+    pub create_tables: bool,       // 启动时按事件结构体创建缺失的表
+    pub batch_size: usize,         // 任意一张表累积到该行数即刷新
+    pub batch_max_bytes: usize,    // 批次近似插入字节数达到该值即刷新
+    pub flush_interval_ms: u64,    // 定时刷新间隔（毫秒）
+    pub metrics_addr: Option<String>, // Prometheus 指标端点地址（需 metrics feature），不配置则不启动
+    pub dead_letter_dir: Option<String>, // 无法解码的消息写入该目录，不配置则只记录日志后丢弃
+    pub max_decode_errors: u64, // 一个窗口内解码失败超过该数量时停止订阅并正常关闭，0 表示不限
+    pub decode_error_window_secs: u64, // 解码失败的统计窗口（秒）
 }
 
+/// 默认批次行数
+pub const DEFAULT_BATCH_SIZE: usize = 100;
+/// 默认批次字节数上限（4 MiB）
+pub const DEFAULT_BATCH_MAX_BYTES: usize = 4 * 1024 * 1024;
+/// 默认定时刷新间隔（毫秒）
+pub const DEFAULT_FLUSH_INTERVAL_MS: u64 = 100;
+/// 默认每个窗口允许的解码失败数
+pub const DEFAULT_MAX_DECODE_ERRORS: u64 = 100;
+/// 默认解码失败统计窗口（秒）
+pub const DEFAULT_DECODE_ERROR_WINDOW_SECS: u64 = 60;
+
 #[derive(Debug, Clone)]
 pub struct TableNames {
     pub pumpfun_trade_event: String,
@@ -31,13 +67,63 @@ pub struct TableNames {
     pub pumpfun_amm_create_pool_event: String,
     pub pumpfun_amm_deposit_event: String,
     pub pumpfun_amm_withdraw_event: String,
+    pub pumpfun_amm_collect_coin_creator_fee_event: String,
+    pub pumpfun_amm_disable_event: String,
+    pub pumpfun_amm_set_params_event: String,
+}
+
+impl TableNames {
+    /// 按配置的表名构建事件注册表
+    pub fn event_registry(&self) -> Result<EventRegistry, String> {
+        let mut registry = EventRegistry::new();
+        registry
+            .register::<PumpfunTradeEventV2>("PumpfunTradeEventV2", &self.pumpfun_trade_event)?
+            .register::<PumpfunCreateEventV2>("PumpfunCreateEventV2", &self.pumpfun_create_event)?
+            .register::<PumpfunMigrateEventV2>("PumpfunMigrateEventV2", &self.pumpfun_migrate_event)?
+            .register::<PumpfunAmmBuyEventV2>("PumpfunAmmBuyEventV2", &self.pumpfun_amm_buy_event)?
+            .register::<PumpfunAmmSellEventV2>("PumpfunAmmSellEventV2", &self.pumpfun_amm_sell_event)?
+            .register::<PumpfunAmmCreatePoolEventV2>(
+                "PumpfunAmmCreatePoolEventV2",
+                &self.pumpfun_amm_create_pool_event,
+            )?
+            .register::<PumpfunAmmDepositEventV2>(
+                "PumpfunAmmDepositEventV2",
+                &self.pumpfun_amm_deposit_event,
+            )?
+            .register::<PumpfunAmmWithdrawEventV2>(
+                "PumpfunAmmWithdrawEventV2",
+                &self.pumpfun_amm_withdraw_event,
+            )?
+            .register::<PumpfunAmmCollectCoinCreatorFeeEventV2>(
+                "PumpfunAmmCollectCoinCreatorFeeEventV2",
+                &self.pumpfun_amm_collect_coin_creator_fee_event,
+            )?
+            .register::<PumpfunAmmDisableEventV2>(
+                "PumpfunAmmDisableEventV2",
+                &self.pumpfun_amm_disable_event,
+            )?
+            .register::<PumpfunAmmSetParamsEventV2>(
+                "PumpfunAmmSetParamsEventV2",
+                &self.pumpfun_amm_set_params_event,
+            )?;
+        Ok(registry)
+    }
+}
+
+/// 读取整数配置项并转换为目标类型，负数或超出范围时返回错误而不是回绕
+fn config_integer<T: TryFrom<i64>>(toml_value: &toml::Value, key: &str) -> Result<Option<T>, String> {
+    match toml_value.get(key).and_then(|v| v.as_integer()) {
+        Some(value) => T::try_from(value)
+            .map(Some)
+            .map_err(|_| format!("{} must be a non-negative integer in range, got {}", key, value)),
+        None => Ok(None),
+    }
 }
 
 impl Config {
     /// 从TOML文件加载配置
     pub fn from_toml_file(config_path: &str) -> Result<Self, Box<dyn std::error::Error>> {
-        let config_content = std::fs::read_to_string(config_path)?;
-        let toml_value: toml::Value = toml::from_str(&config_content)?;
+        let toml_value = read_toml_value(config_path)?;
         Self::from_toml_value(&toml_value)
     }
 
@@ -89,6 +175,21 @@ impl Config {
                 .and_then(|v| v.as_str())
                 .unwrap_or("pumpfun_amm_withdraw_event_v2")
                 .to_string(),
+            pumpfun_amm_collect_coin_creator_fee_event: tables
+                .get("pumpfun_amm_collect_coin_creator_fee_event")
+                .and_then(|v| v.as_str())
+                .unwrap_or("pumpfun_amm_collect_coin_creator_fee_event_v2")
+                .to_string(),
+            pumpfun_amm_disable_event: tables
+                .get("pumpfun_amm_disable_event")
+                .and_then(|v| v.as_str())
+                .unwrap_or("pumpfun_amm_disable_event_v2")
+                .to_string(),
+            pumpfun_amm_set_params_event: tables
+                .get("pumpfun_amm_set_params_event")
+                .and_then(|v| v.as_str())
+                .unwrap_or("pumpfun_amm_set_params_event_v2")
+                .to_string(),
         };
 
         let config = Config {
@@ -102,76 +203,289 @@ impl Config {
                 .and_then(|v| v.as_str())
                 .ok_or("Missing 'topic' in config")?
                 .to_string(),
-            max_concurrent_clickhouse_tasks: toml_value
-                .get("max_concurrent_clickhouse_tasks")
-                .and_then(|v| v.as_integer())
-                .unwrap_or(10) as usize,
+            queue_group: toml_value
+                .get("queue_group")
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string()),
+            max_concurrent_clickhouse_tasks: config_integer(toml_value, "max_concurrent_clickhouse_tasks")?
+                .unwrap_or(10),
+            clickhouse: toml_value
+                .get("clickhouse")
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string()),
             table_names,
+            spill_dir: toml_value
+                .get("spill_dir")
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string()),
+            verify_schema: toml_value
+                .get("verify_schema")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false),
+            create_tables: toml_value
+                .get("create_tables")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false),
+            batch_size: config_integer(toml_value, "batch_size")?
+                .unwrap_or(DEFAULT_BATCH_SIZE),
+            batch_max_bytes: config_integer(toml_value, "batch_max_bytes")?
+                .unwrap_or(DEFAULT_BATCH_MAX_BYTES),
+            flush_interval_ms: config_integer(toml_value, "flush_interval_ms")?
+                .unwrap_or(DEFAULT_FLUSH_INTERVAL_MS),
+            metrics_addr: toml_value
+                .get("metrics_addr")
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string()),
+            dead_letter_dir: toml_value
+                .get("dead_letter_dir")
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string()),
+            max_decode_errors: config_integer(toml_value, "max_decode_errors")?
+                .unwrap_or(DEFAULT_MAX_DECODE_ERRORS),
+            decode_error_window_secs: config_integer(toml_value, "decode_error_window_secs")?
+                .unwrap_or(DEFAULT_DECODE_ERROR_WINDOW_SECS),
         };
 
+        config.validate()?;
         Ok(config)
     }
+
+    /// 校验批次参数：刷新间隔、批次行数、字节数和解码失败窗口都必须大于 0，queue_group 和 clickhouse 不能为空
+    pub fn validate(&self) -> Result<(), String> {
+        if self.flush_interval_ms == 0 {
+            return Err("flush_interval_ms must be greater than 0".to_string());
+        }
+        if self.batch_size == 0 {
+            return Err("batch_size must be greater than 0".to_string());
+        }
+        if self.batch_max_bytes == 0 {
+            return Err("batch_max_bytes must be greater than 0".to_string());
+        }
+        if self.decode_error_window_secs == 0 {
+            return Err("decode_error_window_secs must be greater than 0".to_string());
+        }
+        if self.queue_group.as_deref().is_some_and(|group| group.trim().is_empty()) {
+            return Err("queue_group must not be empty".to_string());
+        }
+        if self.clickhouse.as_deref().is_some_and(|name| name.trim().is_empty()) {
+            return Err("clickhouse must not be empty".to_string());
+        }
+        Ok(())
+    }
+
+    fn batch_limits(&self) -> BatchLimits {
+        BatchLimits {
+            flush_interval: Duration::from_millis(self.flush_interval_ms),
+            max_rows: self.batch_size,
+            max_bytes: self.batch_max_bytes,
+        }
+    }
 }
 
 impl TransactionSubscriberService {
     /// 创建新的TransactionSubscriber服务
     pub async fn new(config: Config) -> Result<Self, Box<dyn std::error::Error>> {
+        config.validate()?;
+
+        let clickhouse = match &config.clickhouse {
+            Some(name) => ClickHouseClient::named(name)?,
+            None => ClickHouseClient::instance(),
+        };
+
+        // 可选：新环境中按事件结构体建表，已存在的表不做修改
+        if config.create_tables {
+            let registry = config.table_names.event_registry()?;
+            for table in ensure_tables(clickhouse.client(), &registry).await? {
+                println!("✓ Created ClickHouse table {}", table);
+            }
+        }
+
+        // 可选：处理任何消息之前校验表结构，发现表被改动立即失败
+        if config.verify_schema {
+            let registry = config.table_names.event_registry()?;
+            verify_schema(clickhouse.client(), &registry).await?;
+            println!("✓ ClickHouse schema verified ({} tables)", registry.schemas().len());
+        }
+
+        if let Some(addr) = &config.metrics_addr {
+            metrics::start_endpoint(addr).await?;
+        }
+
         // 连接NATS
-        let nats_client = NatsClient::new(&config.nats_url).await?;
+        let nats_client = connection::connect(&config.nats_url, log_connection_event).await?;
 
         // 创建处理器，传入表名配置
         let processor = Arc::new(TransactionProcessor::new(
+            clickhouse.client().clone(),
             config.max_concurrent_clickhouse_tasks,
             config.table_names.clone(),
+            config.spill_dir.as_ref().map(PathBuf::from),
+            config.batch_limits(),
         ));
 
+        let dead_letter = DeadLetter::new(
+            config.dead_letter_dir.as_ref().map(PathBuf::from),
+            config.max_decode_errors,
+            Duration::from_secs(config.decode_error_window_secs),
+        );
+
         Ok(Self {
             nats_client,
             processor,
             topic: config.topic,
+            queue_group: config.queue_group,
+            dead_letter,
         })
     }
 
+    /// NATS 连接是否正常（断线期间后台在重连，订阅恢复后继续收到消息）
+    pub fn is_connected(&self) -> bool {
+        connection::is_connected(&self.nats_client)
+    }
+
     /// 主运行循环 - 订阅NATS并处理交易
     /// 架构：
     /// - 主循环：从NATS接收消息并快速反序列化
     /// - process_transaction：快速解析并通过channel发送到批处理任务
-    /// - 独立批处理任务：累积事件，100ms或100条触发刷新到ClickHouse
+    /// - 独立批处理任务：累积事件，flush_interval_ms、batch_size 行或 batch_max_bytes 字节触发刷新到ClickHouse
+    ///
+    /// 收到 Ctrl-C / SIGTERM 或 NATS 流结束时退出循环，刷新剩余批次后返回
     pub async fn run(self) -> Result<(), Box<dyn std::error::Error>> {
+        self.run_until(shutdown_signal()).await
+    }
+
+    /// 与 `run` 相同，但由调用方提供停止信号（例如 CancellationToken::cancelled()）
+    pub async fn run_until(
+        self,
+        shutdown: impl Future<Output = ()>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
         println!("TransactionSubscriberService starting...");
         println!("NATS topic: {}", self.topic);
 
-        // 订阅NATS主题
-        let mut subscriber = self.nats_client.subscribe(&self.topic).await?;
+        // 订阅NATS主题；配置了 queue group 时同组的副本分摊消息，不会重复插入
+        let mut subscriber = match &self.queue_group {
+            Some(group) => {
+                println!("NATS queue group: {}", group);
+                self.nats_client
+                    .queue_subscribe(self.topic.clone(), group.clone())
+                    .await?
+            }
+            None => self.nats_client.subscribe(self.topic.clone()).await?,
+        };
+        tokio::pin!(shutdown);
+
+        // 主循环：持续接收NATS消息，直到收到停止信号
+        let mut too_many_decode_errors = false;
+        loop {
+            let message = tokio::select! {
+                message = subscriber.next() => match message {
+                    Some(message) => message,
+                    None => {
+                        println!("NATS stream ended");
+                        break;
+                    }
+                },
+                _ = &mut shutdown => {
+                    println!("Shutdown signal received, stopping subscription");
+                    break;
+                }
+            };
+
+            // 链路 id：优先使用发布端的 header，没有则生成
+            let trace_id = resolve_trace_id(
+                message
+                    .headers
+                    .as_ref()
+                    .and_then(|headers| headers.get(TRACE_ID_HEADER))
+                    .map(|value| value.as_str()),
+            );
+            let span = message_span(&trace_id);
+            let _guard = span.enter();
 
-        // 主循环：持续接收NATS消息
-        while let Some(message) = subscriber.next().await {
             let payload_size = message.payload.len();
-            // 反序列化protobuf消息（失败时打印堆栈并退出进程）
-            let parsed_tx = Self::deserialize_transaction(&message.payload);
+            // 反序列化protobuf消息，失败的消息进入死信目录后继续
+            let parsed_tx = match Transaction::decode(message.payload.as_ref()) {
+                Ok(tx) => tx,
+                Err(e) => {
+                    metrics::record_decode_error();
+                    if self.dead_letter.record(&message.payload, &e.to_string()) {
+                        tracing::error!(
+                            decode_errors = self.dead_letter.total(),
+                            "❌ Too many undecodable messages, stopping subscription"
+                        );
+                        too_many_decode_errors = true;
+                        break;
+                    }
+                    continue;
+                }
+            };
             // 直接处理（process_transaction 内部会通过 channel 异步发送）
-            self.processor.process_transaction(parsed_tx, payload_size);
+            // 批处理任务已退出时继续消费只会丢数据，停止订阅
+            if let Err(e) = self.processor.process_transaction(parsed_tx, payload_size, trace_id) {
+                tracing::error!(
+                    dropped_events = self.processor.dropped_events(),
+                    "❌ {}, stopping subscription",
+                    e
+                );
+                break;
+            }
         }
 
-        println!("NATS stream ended");
+        drop(subscriber);
+        let decode_errors = self.dead_letter.total();
+        self.shutdown().await;
+        if too_many_decode_errors {
+            return Err(format!("Stopped after {} undecodable messages", decode_errors).into());
+        }
         Ok(())
     }
 
-    /// 反序列化SubscribeUpdateTransaction (使用prost protobuf)
-    /// 失败时打印堆栈并退出进程
-    fn deserialize_transaction(payload: &[u8]) -> Transaction {
-        
-        Transaction::decode(payload).unwrap_or_else(|e| {
-            eprintln!("❌ FATAL: Failed to deserialize transaction: {:?}", e);
-            eprintln!("Payload length: {} bytes", payload.len());
-            std::process::exit(1);
-        })
-    }
-
-    /// 优雅关闭：等待所有任务完成
+    /// 优雅关闭：刷新累积中的批次并等待所有插入任务完成
     pub async fn shutdown(self) {
         println!("Shutting down TransactionSubscriberService...");
+        let rows = self.processor.flush().await;
         self.processor.wait_all_tasks().await;
-        println!("All tasks completed");
+        println!("All tasks completed, {} rows flushed during shutdown", rows);
+    }
+}
+
+/// 记录 NATS 连接状态变化
+fn log_connection_event(event: ConnectionEvent) {
+    match event {
+        ConnectionEvent::Connected => tracing::info!("✅ NATS connected"),
+        ConnectionEvent::Disconnected => tracing::warn!("⚠️  NATS disconnected, reconnecting..."),
+        ConnectionEvent::LameDuck => tracing::warn!("⚠️  NATS server entering lame duck mode"),
+        ConnectionEvent::Closed => tracing::error!("❌ NATS connection closed"),
+        ConnectionEvent::Error(e) => tracing::warn!("⚠️  NATS connection error: {}", e),
+    }
+}
+
+/// 等待 Ctrl-C 或 SIGTERM（非 unix 平台只监听 Ctrl-C）
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            tracing::error!("Failed to listen for Ctrl-C: {}", e);
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(e) => {
+                tracing::error!("Failed to listen for SIGTERM: {}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate => {}
     }
 }
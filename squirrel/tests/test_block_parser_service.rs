@@ -1,4 +1,5 @@
-use squirrel::block_parser::block_parser_service::{BlockParserService, Config};
+use squirrel::block_parser::block_parser_service::{BlockParserService, Config, ErrorPolicy};
+use squirrel::block_parser::compression::Compression;
 use utils::slot_meta::SlotMeta;
 use tempfile::TempDir;
 use std::fs::File;
@@ -12,6 +13,10 @@ async fn test_config_from_toml_value() {
         scan_interval_seconds = 300
         enable_watch = false
         max_concurrent_clickhouse_tasks = 5
+        max_concurrent_files = 4
+        on_error = "quarantine"
+        dedup_tables = ["pumpfun_trade_event", "pumpfun_amm_buy_event"]
+        compression = "gzip"
     "#;
     
     let toml_value: toml::Value = toml::from_str(toml_str).unwrap();
@@ -22,6 +27,10 @@ async fn test_config_from_toml_value() {
     assert_eq!(config.scan_interval_seconds, 300);
     assert_eq!(config.enable_watch, false);
     assert_eq!(config.max_concurrent_clickhouse_tasks, 5);
+    assert_eq!(config.max_concurrent_files, 4);
+    assert_eq!(config.on_error, ErrorPolicy::Quarantine);
+    assert_eq!(config.dedup_tables, vec!["pumpfun_trade_event", "pumpfun_amm_buy_event"]);
+    assert_eq!(config.compression, Some(Compression::Gzip));
 }
 
 #[tokio::test]
@@ -39,6 +48,58 @@ async fn test_config_with_defaults() {
     assert_eq!(config.scan_interval_seconds, 600); // 默认值
     assert_eq!(config.enable_watch, true); // 默认值
     assert_eq!(config.max_concurrent_clickhouse_tasks, 3); // 默认值
+    assert_eq!(config.max_concurrent_files, 1); // 默认值
+    assert_eq!(config.on_error, ErrorPolicy::Abort); // 默认值
+    assert!(config.dedup_tables.is_empty()); // 默认不去重
+    assert_eq!(config.compression, None); // 默认自动判断
+}
+
+#[test]
+fn test_config_rejects_unknown_error_policy() {
+    let toml_str = r#"
+        data_dir = "/tmp/data"
+        processed_dir = "/tmp/processed"
+        on_error = "ignore"
+    "#;
+
+    let toml_value: toml::Value = toml::from_str(toml_str).unwrap();
+    let error = Config::from_toml_value(&toml_value).unwrap_err();
+    assert!(error.to_string().contains("on_error"));
+}
+
+#[test]
+fn test_config_rejects_unknown_dedup_table() {
+    let toml_str = r#"
+        data_dir = "/tmp/data"
+        processed_dir = "/tmp/processed"
+        dedup_tables = ["pumpfun_trade"]
+    "#;
+
+    let toml_value: toml::Value = toml::from_str(toml_str).unwrap();
+    let error = Config::from_toml_value(&toml_value).unwrap_err();
+    assert!(error.to_string().contains("pumpfun_trade"));
+}
+
+#[test]
+fn test_config_slot_range() {
+    let toml_str = r#"
+        data_dir = "/tmp/data"
+        processed_dir = "/tmp/processed"
+        slot_range = [100, 200]
+    "#;
+
+    let toml_value: toml::Value = toml::from_str(toml_str).unwrap();
+    let mut config = Config::from_toml_value(&toml_value).unwrap();
+    assert_eq!(config.slot_range, Some((100, 200)));
+
+    // 命令行只给出一端时另一端沿用配置
+    config.override_slot_range(Some(150), None).unwrap();
+    assert_eq!(config.slot_range, Some((150, 200)));
+    assert!(config.override_slot_range(Some(300), None).is_err());
+
+    let toml_value: toml::Value = toml::from_str(&toml_str.replace("[100, 200]", "[200, 100]")).unwrap();
+    let error = Config::from_toml_value(&toml_value).unwrap_err();
+    assert!(error.to_string().contains("slot_range"));
 }
 
 #[tokio::test]
@@ -56,6 +117,14 @@ async fn test_service_creation() {
         scan_interval_seconds: 60,
         enable_watch: false,
         max_concurrent_clickhouse_tasks: 2,
+        spill_dir: None,
+        max_concurrent_files: 1,
+        on_error: ErrorPolicy::Abort,
+        strict: false,
+        dedup_tables: Vec::new(),
+        compression: None,
+        decode_workers: None,
+        slot_range: None,
     };
     
     let service = BlockParserService::new(config).unwrap();
@@ -80,6 +149,14 @@ async fn test_process_empty_directory() {
         scan_interval_seconds: 60,
         enable_watch: false,
         max_concurrent_clickhouse_tasks: 2,
+        spill_dir: None,
+        max_concurrent_files: 1,
+        on_error: ErrorPolicy::Abort,
+        strict: false,
+        dedup_tables: Vec::new(),
+        compression: None,
+        decode_workers: None,
+        slot_range: None,
     };
     
     let mut service = BlockParserService::new(config).unwrap();
@@ -112,6 +189,14 @@ async fn test_process_single_file_pair() {
         scan_interval_seconds: 60,
         enable_watch: false,
         max_concurrent_clickhouse_tasks: 2,
+        spill_dir: None,
+        max_concurrent_files: 1,
+        on_error: ErrorPolicy::Abort,
+        strict: false,
+        dedup_tables: Vec::new(),
+        compression: None,
+        decode_workers: None,
+        slot_range: None,
     };
     
     let mut service = BlockParserService::new(config).unwrap();
@@ -148,6 +233,14 @@ async fn test_skip_already_processed_files() {
         scan_interval_seconds: 60,
         enable_watch: false,
         max_concurrent_clickhouse_tasks: 2,
+        spill_dir: None,
+        max_concurrent_files: 1,
+        on_error: ErrorPolicy::Abort,
+        strict: false,
+        dedup_tables: Vec::new(),
+        compression: None,
+        decode_workers: None,
+        slot_range: None,
     };
     
     let mut service = BlockParserService::new(config).unwrap();
@@ -190,6 +283,14 @@ async fn test_service_stats() {
         scan_interval_seconds: 60,
         enable_watch: false,
         max_concurrent_clickhouse_tasks: 2,
+        spill_dir: None,
+        max_concurrent_files: 1,
+        on_error: ErrorPolicy::Abort,
+        strict: false,
+        dedup_tables: Vec::new(),
+        compression: None,
+        decode_workers: None,
+        slot_range: None,
     };
     
     let mut service = BlockParserService::new(config).unwrap();
@@ -199,7 +300,104 @@ async fn test_service_stats() {
     
     let stats = service.get_stats();
     assert_eq!(stats.processed_count, 3);
+    assert_eq!(stats.files_this_run, 3);
+    // 空文件不产出任何行
+    assert!(stats.rows_per_table.is_empty());
     
     // 验证stats的打印功能不会panic
     stats.print_summary();
-}
\ No newline at end of file
+}
+#[tokio::test]
+async fn test_concurrent_files_failure_only_skips_failed_file() {
+    let temp_dir = TempDir::new().unwrap();
+    let data_dir = temp_dir.path().join("data");
+    let processed_dir = temp_dir.path().join("processed");
+
+    std::fs::create_dir_all(&data_dir).unwrap();
+    std::fs::create_dir_all(&processed_dir).unwrap();
+
+    // 三个正常文件对
+    for i in 1..=3 {
+        let empty_slots: Vec<SlotMeta> = vec![];
+        let serialized = rmp_serde::to_vec(&empty_slots).unwrap();
+        std::fs::write(data_dir.join(format!("{}_{}.meta", i * 100, i * 100 + 99)), serialized).unwrap();
+        File::create(data_dir.join(format!("{}_{}.bin", i * 100, i * 100 + 99))).unwrap();
+    }
+    // 一个 meta 无法解析的文件对
+    std::fs::write(data_dir.join("900_999.meta"), b"not msgpack").unwrap();
+    File::create(data_dir.join("900_999.bin")).unwrap();
+
+    let config = Config {
+        data_dir: data_dir.to_string_lossy().to_string(),
+        processed_dir: processed_dir.to_string_lossy().to_string(),
+        scan_interval_seconds: 60,
+        enable_watch: false,
+        max_concurrent_clickhouse_tasks: 2,
+        spill_dir: None,
+        max_concurrent_files: 4,
+        on_error: ErrorPolicy::Abort,
+        strict: false,
+        dedup_tables: Vec::new(),
+        compression: None,
+        decode_workers: None,
+        slot_range: None,
+    };
+
+    let mut service = BlockParserService::new(config).unwrap();
+    let error = service.process_pending_files().await.unwrap_err();
+    assert!(error.to_string().contains("900_999"));
+
+    // 同时在处理的其他文件正常完成并被标记，失败的文件不被标记
+    let stats = service.get_stats();
+    assert_eq!(stats.processed_count, 3);
+    assert!(!stats.processed_prefixes.contains(&"900_999".to_string()));
+}
+
+#[tokio::test]
+async fn test_quarantine_moves_bad_file_and_continues() {
+    let temp_dir = TempDir::new().unwrap();
+    let data_dir = temp_dir.path().join("data");
+    let processed_dir = temp_dir.path().join("processed");
+
+    std::fs::create_dir_all(&data_dir).unwrap();
+    std::fs::create_dir_all(&processed_dir).unwrap();
+
+    let empty_slots: Vec<SlotMeta> = vec![];
+    std::fs::write(data_dir.join("100_199.meta"), rmp_serde::to_vec(&empty_slots).unwrap()).unwrap();
+    File::create(data_dir.join("100_199.bin")).unwrap();
+    std::fs::write(data_dir.join("900_999.meta"), b"not msgpack").unwrap();
+    File::create(data_dir.join("900_999.bin")).unwrap();
+
+    let config = Config {
+        data_dir: data_dir.to_string_lossy().to_string(),
+        processed_dir: processed_dir.to_string_lossy().to_string(),
+        scan_interval_seconds: 60,
+        enable_watch: false,
+        max_concurrent_clickhouse_tasks: 2,
+        spill_dir: None,
+        max_concurrent_files: 1,
+        on_error: ErrorPolicy::Quarantine,
+        strict: false,
+        dedup_tables: Vec::new(),
+        compression: None,
+        decode_workers: None,
+        slot_range: None,
+    };
+
+    let mut service = BlockParserService::new(config).unwrap();
+    assert_eq!(service.process_pending_files().await.unwrap(), 1);
+
+    // 坏文件对移到 failed/，不会再被扫描到
+    assert!(data_dir.join("failed/900_999.meta").exists());
+    assert!(data_dir.join("failed/900_999.bin").exists());
+    assert!(!data_dir.join("900_999.meta").exists());
+    assert_eq!(service.process_pending_files().await.unwrap(), 0);
+
+    let stats = service.get_stats();
+    assert_eq!(stats.processed_count, 1);
+    assert_eq!(stats.failed_files.len(), 1);
+    assert_eq!(stats.failed_files[0].prefix, "900_999");
+
+    let failed_log = std::fs::read_to_string(processed_dir.join("failed_files.log")).unwrap();
+    assert!(failed_log.contains(",900_999,"));
+}
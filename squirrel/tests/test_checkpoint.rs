@@ -0,0 +1,49 @@
+use squirrel::block_parser::checkpoint::CheckpointStore;
+use tempfile::TempDir;
+
+#[test]
+fn test_checkpoint_round_trip_and_clear() {
+    let temp_dir = TempDir::new().unwrap();
+    let bin_path = temp_dir.path().join("100_200.bin");
+    std::fs::write(&bin_path, b"block data").unwrap();
+
+    let store = CheckpointStore::new(temp_dir.path().join("checkpoints"));
+    assert_eq!(store.load(&bin_path), None);
+
+    store.save(&bin_path, 800).unwrap();
+    assert_eq!(store.load(&bin_path), Some(800));
+    assert!(temp_dir.path().join("checkpoints/100_200.checkpoint").exists());
+
+    store.clear(&bin_path).unwrap();
+    assert_eq!(store.load(&bin_path), None);
+    // 不存在时 clear 不报错
+    store.clear(&bin_path).unwrap();
+}
+
+#[test]
+fn test_checkpoint_ignored_when_bin_changes() {
+    let temp_dir = TempDir::new().unwrap();
+    let bin_path = temp_dir.path().join("100_200.bin");
+    std::fs::write(&bin_path, b"block data").unwrap();
+
+    let store = CheckpointStore::new(temp_dir.path().join("checkpoints"));
+    store.save(&bin_path, 800).unwrap();
+
+    // 文件被重写（大小变化）后不再信任检查点
+    std::fs::write(&bin_path, b"rewritten block data").unwrap();
+    assert_eq!(store.load(&bin_path), None);
+}
+
+#[test]
+fn test_malformed_checkpoint_ignored() {
+    let temp_dir = TempDir::new().unwrap();
+    let bin_path = temp_dir.path().join("100_200.bin");
+    std::fs::write(&bin_path, b"block data").unwrap();
+
+    let checkpoint_dir = temp_dir.path().join("checkpoints");
+    std::fs::create_dir_all(&checkpoint_dir).unwrap();
+    std::fs::write(checkpoint_dir.join("100_200.checkpoint"), "garbage").unwrap();
+
+    let store = CheckpointStore::new(checkpoint_dir);
+    assert_eq!(store.load(&bin_path), None);
+}
@@ -0,0 +1,47 @@
+use flate2::Compression as GzLevel;
+use flate2::write::GzEncoder;
+use squirrel::block_parser::compression::Compression;
+use std::io::Write;
+
+const BLOCK: &[u8] = b"tiny msgpack block fixture";
+
+fn gzip(data: &[u8]) -> Vec<u8> {
+    let mut encoder = GzEncoder::new(Vec::new(), GzLevel::default());
+    encoder.write_all(data).unwrap();
+    encoder.finish().unwrap()
+}
+
+#[test]
+fn test_detect_by_magic_bytes() {
+    assert_eq!(Compression::detect(&zstd::encode_all(BLOCK, 3).unwrap()), Compression::Zstd);
+    assert_eq!(Compression::detect(&gzip(BLOCK)), Compression::Gzip);
+    assert_eq!(Compression::detect(BLOCK), Compression::None);
+    assert_eq!(Compression::detect(&[]), Compression::None);
+}
+
+#[test]
+fn test_decompress_each_format() {
+    let mut out = Vec::new();
+
+    for (compression, data) in [
+        (Compression::Zstd, zstd::encode_all(BLOCK, 3).unwrap()),
+        (Compression::Gzip, gzip(BLOCK)),
+        (Compression::None, BLOCK.to_vec()),
+    ] {
+        compression.decompress_into(&data, &mut out).unwrap();
+        assert_eq!(out, BLOCK, "{:?}", compression);
+    }
+}
+
+#[test]
+fn test_forced_compression_mismatch_fails() {
+    let mut out = Vec::new();
+    assert!(Compression::Gzip.decompress_into(BLOCK, &mut out).is_err());
+}
+
+#[test]
+fn test_parse_compression_name() {
+    assert_eq!("gzip".parse::<Compression>().unwrap(), Compression::Gzip);
+    assert_eq!("none".parse::<Compression>().unwrap(), Compression::None);
+    assert!("lz4".parse::<Compression>().is_err());
+}
@@ -1,4 +1,6 @@
-use squirrel::block_parser::file_processor::FileProcessor;
+use squirrel::block_parser::checkpoint::CheckpointStore;
+use squirrel::block_parser::progress::ProgressReporter;
+use squirrel::block_parser::file_processor::{FileProcessor, FileReport, InsertError};
 use utils::slot_meta::SlotMeta;
 use tempfile::TempDir;
 use std::fs::File;
@@ -123,4 +125,210 @@ async fn test_concurrent_processing() {
     }
     
     processor.finish().await;
-}
\ No newline at end of file
+}
+
+#[tokio::test]
+async fn test_no_insert_errors_after_empty_file() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut processor = FileProcessor::new(1);
+
+    let meta_path = temp_dir.path().join("empty.meta");
+    let bin_path = temp_dir.path().join("empty.bin");
+    std::fs::write(&meta_path, rmp_serde::to_vec(&Vec::<SlotMeta>::new()).unwrap()).unwrap();
+    File::create(&bin_path).unwrap();
+
+    processor.process_file_pair(&meta_path, &bin_path).await.unwrap();
+    assert!(!processor.has_insert_errors());
+    assert!(processor.take_insert_errors().is_empty());
+
+    processor.finish().await;
+}
+
+#[test]
+fn test_insert_error_message() {
+    let error = InsertError {
+        table: "pumpfun_trade_event_v2".to_string(),
+        rows: 1000,
+        message: "Connection refused".to_string(),
+    };
+
+    assert_eq!(
+        error.to_string(),
+        "Failed to insert 1000 rows into pumpfun_trade_event_v2: Connection refused"
+    );
+}
+
+/// 写入三个损坏的 slot：截断的 zstd 帧、无法解析的 block、超出文件范围的 offset
+fn write_corrupt_file_pair(dir: &std::path::Path) -> (std::path::PathBuf, std::path::PathBuf) {
+    let meta_path = dir.join("corrupt.meta");
+    let bin_path = dir.join("corrupt.bin");
+
+    let frame = zstd::encode_all(&vec![7u8; 4096][..], 3).unwrap();
+    let truncated = &frame[..frame.len() / 2];
+    let not_a_block = zstd::encode_all(&b"not a block"[..], 3).unwrap();
+
+    let mut bin = Vec::new();
+    bin.extend_from_slice(truncated);
+    bin.extend_from_slice(&not_a_block);
+    std::fs::write(&bin_path, &bin).unwrap();
+
+    let slots = vec![
+        SlotMeta { slot: 1, offset: Some(0), size: truncated.len() as u64 },
+        SlotMeta { slot: 2, offset: Some(truncated.len() as u64), size: not_a_block.len() as u64 },
+        SlotMeta { slot: 3, offset: Some(bin.len() as u64 + 100), size: 10 },
+        SlotMeta { slot: 4, offset: None, size: 0 },
+    ];
+    std::fs::write(&meta_path, rmp_serde::to_vec(&slots).unwrap()).unwrap();
+
+    (meta_path, bin_path)
+}
+
+#[tokio::test]
+async fn test_corrupt_slots_are_counted() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut processor = FileProcessor::new(1);
+    let (meta_path, bin_path) = write_corrupt_file_pair(temp_dir.path());
+
+    let report = processor.process_file_pair(&meta_path, &bin_path).await.unwrap();
+    assert_eq!(
+        report,
+        FileReport {
+            slots_total: 4,
+            slots_processed: 0,
+            slots_skipped_seek: 1,
+            slots_skipped_decompress: 1,
+            slots_skipped_parse: 1,
+            slots_resumed: 0,
+            rows_deduped: 0,
+            rows_per_table: Default::default(),
+        }
+    );
+    assert_eq!(report.slots_skipped(), 3);
+
+    processor.finish().await;
+}
+
+#[tokio::test]
+async fn test_parallel_decode_matches_single_worker() {
+    let temp_dir = TempDir::new().unwrap();
+    let (meta_path, bin_path) = write_corrupt_file_pair(temp_dir.path());
+
+    let mut single = FileProcessor::new(1).with_decode_workers(1);
+    let mut parallel = FileProcessor::new(1).with_decode_workers(4);
+    let expected = single.process_file_pair(&meta_path, &bin_path).await.unwrap();
+    let report = parallel.process_file_pair(&meta_path, &bin_path).await.unwrap();
+    assert_eq!(report, expected);
+
+    single.finish().await;
+    parallel.finish().await;
+}
+
+#[tokio::test]
+async fn test_strict_mode_fails_on_first_corrupt_slot() {
+    let temp_dir = TempDir::new().unwrap();
+    // 单个解码线程时结果按 slot 顺序到达，第一个损坏的就是 slot 1
+    let mut processor = FileProcessor::new(1).with_strict(true).with_decode_workers(1);
+    let (meta_path, bin_path) = write_corrupt_file_pair(temp_dir.path());
+
+    let error = processor.process_file_pair(&meta_path, &bin_path).await.unwrap_err();
+    assert!(error.to_string().contains("Corrupt slot 1"));
+
+    processor.finish().await;
+}
+
+#[tokio::test]
+async fn test_resume_from_checkpoint_skips_done_slots() {
+    let temp_dir = TempDir::new().unwrap();
+    let checkpoints = CheckpointStore::new(temp_dir.path().join("checkpoints"));
+    let mut processor = FileProcessor::new(1).with_checkpoints(checkpoints.clone());
+    let (meta_path, bin_path) = write_corrupt_file_pair(temp_dir.path());
+
+    // 前两个 slot 已处理过
+    checkpoints.save(&bin_path, 2).unwrap();
+
+    let report = processor.process_file_pair(&meta_path, &bin_path).await.unwrap();
+    assert_eq!(report.slots_resumed, 2);
+    assert_eq!(report.slots_skipped_decompress, 0);
+    assert_eq!(report.slots_skipped_parse, 0);
+    assert_eq!(report.slots_skipped_seek, 1);
+
+    // 处理完成后检查点被删除
+    assert_eq!(checkpoints.load(&bin_path), None);
+
+    processor.finish().await;
+}
+
+#[derive(Default)]
+struct RecordingReporter {
+    events: std::sync::Mutex<Vec<String>>,
+}
+
+impl ProgressReporter for RecordingReporter {
+    fn on_start(&self, _file: &std::path::Path, total_slots: usize) {
+        self.events.lock().unwrap().push(format!("start {}", total_slots));
+    }
+
+    fn on_slot(&self, _file: &std::path::Path, position: usize) {
+        self.events.lock().unwrap().push(format!("slot {}", position));
+    }
+
+    fn on_flush(&self, _file: &std::path::Path, rows: usize) {
+        self.events.lock().unwrap().push(format!("flush {}", rows));
+    }
+
+    fn on_complete(&self, _file: &std::path::Path, report: &FileReport, _elapsed: std::time::Duration) {
+        self.events.lock().unwrap().push(format!("complete {}", report.slots_skipped()));
+    }
+}
+
+#[tokio::test]
+async fn test_progress_reporter_receives_callbacks() {
+    let temp_dir = TempDir::new().unwrap();
+    let reporter = std::sync::Arc::new(RecordingReporter::default());
+    let mut processor = FileProcessor::new(1)
+        .with_reporter(reporter.clone())
+        .with_decode_workers(1);
+    let (meta_path, bin_path) = write_corrupt_file_pair(temp_dir.path());
+
+    processor.process_file_pair(&meta_path, &bin_path).await.unwrap();
+
+    // 进度为连续完成的 slot 数（含没有数据的 slot），没有行时不会 flush
+    assert_eq!(
+        *reporter.events.lock().unwrap(),
+        vec!["start 4", "slot 1", "slot 2", "slot 3", "slot 4", "complete 3"]
+    );
+
+    processor.finish().await;
+}
+
+#[tokio::test]
+async fn test_gzip_and_plain_slots_are_decompressed() {
+    use flate2::write::GzEncoder;
+
+    let temp_dir = TempDir::new().unwrap();
+    let mut processor = FileProcessor::new(1);
+    let meta_path = temp_dir.path().join("mixed.meta");
+    let bin_path = temp_dir.path().join("mixed.bin");
+
+    // 内容不是合法 block：解压成功后应在解析阶段被跳过，而不是解压阶段
+    let mut gzip = GzEncoder::new(Vec::new(), flate2::Compression::default());
+    gzip.write_all(b"not a block").unwrap();
+    let gzip = gzip.finish().unwrap();
+    let plain = b"not a block either".to_vec();
+
+    let mut bin = gzip.clone();
+    bin.extend_from_slice(&plain);
+    std::fs::write(&bin_path, &bin).unwrap();
+
+    let slots = vec![
+        SlotMeta { slot: 1, offset: Some(0), size: gzip.len() as u64 },
+        SlotMeta { slot: 2, offset: Some(gzip.len() as u64), size: plain.len() as u64 },
+    ];
+    std::fs::write(&meta_path, rmp_serde::to_vec(&slots).unwrap()).unwrap();
+
+    let report = processor.process_file_pair(&meta_path, &bin_path).await.unwrap();
+    assert_eq!(report.slots_skipped_decompress, 0);
+    assert_eq!(report.slots_skipped_parse, 2);
+
+    processor.finish().await;
+}
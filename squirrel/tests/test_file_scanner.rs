@@ -97,4 +97,25 @@ fn test_has_complete_file_pair_edge_cases() {
     
     // 应该返回false，因为是目录不是文件
     assert!(!scanner.has_complete_file_pair("dir_test"));
-}
\ No newline at end of file
+}
+#[test]
+fn test_slot_range_skips_pairs_outside_window() {
+    let temp_dir = TempDir::new().unwrap();
+    for prefix in ["100_200", "200_300", "300_400", "500_600", "not_slots"] {
+        File::create(temp_dir.path().join(format!("{}.meta", prefix))).unwrap();
+        File::create(temp_dir.path().join(format!("{}.bin", prefix))).unwrap();
+    }
+
+    // 区间两端都包含：与 [250, 300] 有交集的是 200_300 和 300_400
+    let scanner = FileScanner::new(temp_dir.path().to_path_buf()).with_slot_range(Some((250, 300)));
+    let prefixes: Vec<String> = scanner
+        .scan_available_files()
+        .unwrap()
+        .into_iter()
+        .map(|pair| pair.prefix)
+        .collect();
+    assert_eq!(prefixes, vec!["300_400", "200_300"]);
+
+    assert_eq!(FileScanner::extract_slot_range("100_200"), Some((100, 200)));
+    assert_eq!(FileScanner::extract_slot_range("not_slots"), None);
+}
@@ -0,0 +1,66 @@
+use squirrel::transaction_subscriber::connection::{self, ConnectionEvent, reconnect_delay};
+use std::process::{Child, Command};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+#[test]
+fn test_reconnect_delay_backs_off_to_cap() {
+    assert_eq!(reconnect_delay(0), Duration::from_millis(100));
+    assert_eq!(reconnect_delay(1), Duration::from_millis(200));
+    assert_eq!(reconnect_delay(3), Duration::from_millis(800));
+    assert_eq!(reconnect_delay(10), Duration::from_secs(10));
+    assert_eq!(reconnect_delay(usize::MAX), Duration::from_secs(10));
+}
+
+fn start_nats_server(port: u16) -> Child {
+    Command::new("nats-server")
+        .args(["-a", "127.0.0.1", "-p", &port.to_string()])
+        .spawn()
+        .expect("nats-server binary not found in PATH")
+}
+
+async fn wait_for(events: &Mutex<Vec<ConnectionEvent>>, expected: ConnectionEvent) {
+    for _ in 0..100 {
+        if events.lock().unwrap().contains(&expected) {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    panic!("did not observe {:?}, got {:?}", expected, events.lock().unwrap());
+}
+
+#[tokio::test]
+#[ignore = "integration test, requires nats-server in PATH"]
+async fn test_reconnects_after_server_restart() {
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let mut server = start_nats_server(port);
+    tokio::time::sleep(Duration::from_millis(500)).await;
+
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let recorded = Arc::clone(&events);
+    let client = connection::connect(&format!("nats://127.0.0.1:{}", port), move |event| {
+        recorded.lock().unwrap().push(event);
+    })
+    .await
+    .unwrap();
+    assert!(connection::is_connected(&client));
+
+    // 杀掉服务器：客户端收到断开事件
+    server.kill().unwrap();
+    server.wait().unwrap();
+    wait_for(&events, ConnectionEvent::Disconnected).await;
+    assert!(!connection::is_connected(&client));
+
+    // 恢复服务器：后台重连成功
+    events.lock().unwrap().clear();
+    let mut server = start_nats_server(port);
+    wait_for(&events, ConnectionEvent::Connected).await;
+    assert!(connection::is_connected(&client));
+
+    server.kill().unwrap();
+    server.wait().unwrap();
+}
@@ -139,4 +139,58 @@ fn test_mark_batch_as_processed() {
     for prefix in &batch {
         assert!(new_tracker.is_processed(prefix));
     }
-}
\ No newline at end of file
+}
+#[test]
+fn test_concurrent_writers_do_not_interleave() {
+    let temp_dir = TempDir::new().unwrap();
+
+    // 每个线程一个 tracker，相当于多个进程同时写同一个日志
+    let handles: Vec<_> = (0..8)
+        .map(|writer| {
+            let dir = temp_dir.path().to_path_buf();
+            std::thread::spawn(move || {
+                let mut tracker = ProcessedTracker::new(dir);
+                for i in 0..50 {
+                    tracker.mark_as_processed(&format!("writer{}_{:03}", writer, i)).unwrap();
+                }
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    let content = fs::read_to_string(temp_dir.path().join("processed_files.log")).unwrap();
+    assert_eq!(content.lines().count(), 400);
+    for line in content.lines() {
+        let parts: Vec<&str> = line.split(',').collect();
+        assert_eq!(parts.len(), 3, "interleaved line: {}", line);
+        assert_eq!(parts[2], "completed");
+    }
+
+    let mut tracker = ProcessedTracker::new(temp_dir.path().to_path_buf());
+    tracker.load_processed_list().unwrap();
+    assert_eq!(tracker.processed_count(), 400);
+}
+
+#[test]
+fn test_partial_trailing_line_is_skipped() {
+    let temp_dir = TempDir::new().unwrap();
+    let log_path = temp_dir.path().join("processed_files.log");
+
+    // 模拟写到一半崩溃：最后一行没有换行符，还带着半个多字节字符
+    let mut file = fs::File::create(&log_path).unwrap();
+    writeln!(file, "2025-01-01T00:00:00Z,done_001,completed").unwrap();
+    file.write_all(b"2025-01-01T00:00:00Z,\xe6\x96").unwrap();
+    drop(file);
+
+    let mut tracker = ProcessedTracker::new(temp_dir.path().to_path_buf());
+    tracker.load_processed_list().unwrap();
+    assert_eq!(tracker.get_processed_prefixes(), vec!["done_001"]);
+
+    // 之后追加的记录另起一行，不会和半行拼在一起
+    tracker.mark_as_processed("done_002").unwrap();
+    let mut reloaded = ProcessedTracker::new(temp_dir.path().to_path_buf());
+    reloaded.load_processed_list().unwrap();
+    assert_eq!(reloaded.get_processed_prefixes(), vec!["done_001", "done_002"]);
+}
@@ -1,4 +1,4 @@
-use squirrel::block_parser::block_parser_service::{BlockParserService, Config};
+use squirrel::block_parser::block_parser_service::{BlockParserService, Config, ErrorPolicy};
 use tempfile::TempDir;
 use std::fs;
 use std::path::Path;
@@ -26,6 +26,14 @@ async fn test_real_cank_data_processing() {
         scan_interval_seconds: 5, // 短间隔用于测试
         enable_watch: false, // 禁用监控模式，只处理一次
         max_concurrent_clickhouse_tasks: 10, // 提高并发数
+        spill_dir: None,
+        max_concurrent_files: 1,
+        on_error: ErrorPolicy::Abort,
+        strict: false,
+        dedup_tables: Vec::new(),
+        compression: None,
+        decode_workers: None,
+        slot_range: None,
     };
 
     println!("=== Real Cank Data Processing Test ===");
@@ -157,6 +165,14 @@ async fn test_limited_cank_data_processing() {
         scan_interval_seconds: 5,
         enable_watch: false,
         max_concurrent_clickhouse_tasks: 10, // 提高并发数
+        spill_dir: None,
+        max_concurrent_files: 1,
+        on_error: ErrorPolicy::Abort,
+        strict: false,
+        dedup_tables: Vec::new(),
+        compression: None,
+        decode_workers: None,
+        slot_range: None,
     };
 
     let start_time = Instant::now();
@@ -183,6 +199,14 @@ async fn test_limited_cank_data_processing() {
                 scan_interval_seconds: 5,
                 enable_watch: false,
                 max_concurrent_clickhouse_tasks: 10,
+                spill_dir: None,
+                max_concurrent_files: 1,
+                on_error: ErrorPolicy::Abort,
+                strict: false,
+                dedup_tables: Vec::new(),
+                compression: None,
+                decode_workers: None,
+                slot_range: None,
             }).unwrap();
             
             let stats = service.get_stats();
@@ -220,6 +244,14 @@ async fn test_service_with_watch_mode_brief() {
         scan_interval_seconds: 2, // 2秒扫描间隔
         enable_watch: true, // 启用监控模式
         max_concurrent_clickhouse_tasks: 10,
+        spill_dir: None,
+        max_concurrent_files: 1,
+        on_error: ErrorPolicy::Abort,
+        strict: false,
+        dedup_tables: Vec::new(),
+        compression: None,
+        decode_workers: None,
+        slot_range: None,
     };
 
     println!("=== Watch Mode Brief Test ===");
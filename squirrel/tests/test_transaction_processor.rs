@@ -0,0 +1,96 @@
+use squirrel::transaction_subscriber::transaction_processor::{
+    BatchAccumulator, BatchLimits, CountingSender, ProcessedEvents,
+};
+use std::time::Duration;
+use tokio::sync::mpsc;
+use utils::clickhouse_events::{EstimatedSize, PumpfunTradeEventV2};
+use utils::convert_transaction::ConvertedEvents;
+
+#[test]
+fn test_send_succeeds_while_receiver_alive() {
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let sender = CountingSender::new(tx, "events");
+
+    sender.send(1u32).unwrap();
+
+    assert_eq!(rx.try_recv().unwrap(), 1);
+    assert_eq!(sender.dropped(), 0);
+}
+
+#[test]
+fn test_drop_is_counted_after_receiver_closed() {
+    let (tx, rx) = mpsc::unbounded_channel();
+    let sender = CountingSender::new(tx, "events");
+    drop(rx);
+
+    let error = sender.send(1u32).unwrap_err();
+    assert_eq!(error.channel, "events");
+    assert_eq!(sender.dropped(), 1);
+
+    assert!(sender.send(2u32).is_err());
+    assert_eq!(sender.dropped(), 2);
+}
+
+fn trade_message(signature: &str) -> ProcessedEvents {
+    let event = PumpfunTradeEventV2 {
+        signature: signature.to_string(),
+        slot: 1,
+        transaction_index: 0,
+        instruction_index: 0,
+        mint: "mint".to_string(),
+        sol_amount: 100,
+        token_amount: 200,
+        is_buy: 1,
+        user: "user".to_string(),
+        timestamp: 1,
+        virtual_sol_reserves: 10,
+        virtual_token_reserves: 20,
+        real_sol_reserves: 30,
+        real_token_reserves: 40,
+        fee_recipient: "fee".to_string(),
+        fee_basis_points: 5,
+        fee: 6,
+        creator: "creator".to_string(),
+        creator_fee_basis_points: 7,
+        creator_fee: 8,
+        track_volume: 1,
+        total_unclaimed_tokens: 9,
+        total_claimed_tokens: 10,
+        current_sol_volume: 11,
+        last_update_timestamp: 12,
+        current_sol_volume_128: 11,
+    };
+    ProcessedEvents {
+        trace_id: signature.to_string(),
+        events: ConvertedEvents {
+            pumpfun_trade_event: vec![event],
+            ..Default::default()
+        },
+    }
+}
+
+#[test]
+fn test_byte_threshold_triggers_flush() {
+    let row_bytes = trade_message("sig1").events.pumpfun_trade_event[0].estimated_size();
+    // 行数阈值足够大，只有字节阈值会触发
+    let limits = BatchLimits {
+        flush_interval: Duration::from_secs(60),
+        max_rows: 1000,
+        max_bytes: row_bytes * 2,
+    };
+
+    let mut batch = BatchAccumulator::default();
+    batch.add(trade_message("sig1"));
+    assert_eq!(batch.estimated_bytes(), row_bytes);
+    assert!(!batch.should_flush(&limits));
+
+    batch.add(trade_message("sig2"));
+    assert_eq!(batch.estimated_bytes(), row_bytes * 2);
+    assert!(batch.should_flush(&limits));
+
+    let taken = batch.take();
+    assert_eq!(taken.trace_id, "sig1,sig2");
+    assert_eq!(taken.events.pumpfun_trade_event.len(), 2);
+    assert_eq!(batch.estimated_bytes(), 0);
+    assert!(!batch.should_flush(&limits));
+}
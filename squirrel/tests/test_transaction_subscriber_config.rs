@@ -0,0 +1,111 @@
+use squirrel::transaction_subscriber::Config;
+use squirrel::transaction_subscriber::transaction_subscriber_service::{
+    DEFAULT_BATCH_MAX_BYTES, DEFAULT_BATCH_SIZE, DEFAULT_DECODE_ERROR_WINDOW_SECS,
+    DEFAULT_FLUSH_INTERVAL_MS, DEFAULT_MAX_DECODE_ERRORS,
+};
+
+const BASE_CONFIG: &str = r#"
+nats_url = "nats://localhost:4222"
+topic = "geyser.shyft_finalized"
+
+[tables]
+pumpfun_trade_event = "pumpfun_trade_event_v2"
+"#;
+
+fn parse(extra: &str) -> Result<Config, Box<dyn std::error::Error>> {
+    // 顶层键必须出现在 [tables] 之前
+    let value: toml::Value = toml::from_str(&format!("{}{}", extra, BASE_CONFIG)).unwrap();
+    Config::from_toml_value(&value)
+}
+
+#[test]
+fn test_batch_settings_default() {
+    let config = parse("").unwrap();
+
+    assert_eq!(config.batch_size, DEFAULT_BATCH_SIZE);
+    assert_eq!(config.batch_max_bytes, DEFAULT_BATCH_MAX_BYTES);
+    assert_eq!(config.flush_interval_ms, DEFAULT_FLUSH_INTERVAL_MS);
+    assert_eq!(config.metrics_addr, None);
+}
+
+#[test]
+fn test_batch_settings_override() {
+    let config = parse("batch_size = 500\nbatch_max_bytes = 1048576\nflush_interval_ms = 20\n").unwrap();
+
+    assert_eq!(config.batch_size, 500);
+    assert_eq!(config.batch_max_bytes, 1048576);
+    assert_eq!(config.flush_interval_ms, 20);
+}
+
+#[test]
+fn test_metrics_addr_from_config() {
+    let config = parse("metrics_addr = \"127.0.0.1:9100\"\n").unwrap();
+
+    assert_eq!(config.metrics_addr.as_deref(), Some("127.0.0.1:9100"));
+}
+
+#[test]
+fn test_zero_batch_settings_rejected() {
+    let error = parse("flush_interval_ms = 0\n").err().unwrap();
+    assert!(error.to_string().contains("flush_interval_ms"));
+
+    let error = parse("batch_size = 0\n").err().unwrap();
+    assert!(error.to_string().contains("batch_size"));
+}
+
+#[test]
+fn test_dead_letter_settings() {
+    let config = parse("").unwrap();
+    assert_eq!(config.dead_letter_dir, None);
+    assert_eq!(config.max_decode_errors, DEFAULT_MAX_DECODE_ERRORS);
+    assert_eq!(config.decode_error_window_secs, DEFAULT_DECODE_ERROR_WINDOW_SECS);
+
+    let config = parse("dead_letter_dir = \"bad_messages\"\nmax_decode_errors = 0\n").unwrap();
+    assert_eq!(config.dead_letter_dir.as_deref(), Some("bad_messages"));
+    assert_eq!(config.max_decode_errors, 0);
+
+    let error = parse("decode_error_window_secs = 0\n").err().unwrap();
+    assert!(error.to_string().contains("decode_error_window_secs"));
+}
+
+#[test]
+fn test_queue_group_setting() {
+    let config = parse("").unwrap();
+    assert_eq!(config.queue_group, None);
+
+    let config = parse("queue_group = \"squirrel\"\n").unwrap();
+    assert_eq!(config.queue_group.as_deref(), Some("squirrel"));
+
+    let error = parse("queue_group = \"\"\n").err().unwrap();
+    assert!(error.to_string().contains("queue_group"));
+}
+
+#[test]
+fn test_clickhouse_client_name() {
+    let config = parse("").unwrap();
+    assert_eq!(config.clickhouse, None);
+
+    let config = parse("clickhouse = \"analytics\"\n").unwrap();
+    assert_eq!(config.clickhouse.as_deref(), Some("analytics"));
+
+    let error = parse("clickhouse = \" \"\n").err().unwrap();
+    assert!(error.to_string().contains("clickhouse"));
+}
+
+#[test]
+fn test_create_tables_setting() {
+    assert!(!parse("").unwrap().create_tables);
+    assert!(parse("create_tables = true\n").unwrap().create_tables);
+}
+
+#[test]
+fn test_negative_integer_settings_rejected() {
+    let error = parse("batch_size = -1\n").err().unwrap();
+    assert!(error.to_string().contains("batch_size"), "{}", error);
+
+    let error = parse("flush_interval_ms = -100\n").err().unwrap();
+    assert!(error.to_string().contains("flush_interval_ms"), "{}", error);
+
+    let error = parse("max_concurrent_clickhouse_tasks = -4\n").err().unwrap();
+    assert!(error.to_string().contains("max_concurrent_clickhouse_tasks"), "{}", error);
+}
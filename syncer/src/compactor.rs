@@ -0,0 +1,191 @@
+use arrow::compute::{SortColumn, concat_batches, lexsort_to_indices, take_record_batch};
+use arrow::record_batch::RecordBatch;
+use chrono::{Datelike, NaiveDate};
+use futures::StreamExt;
+use parquet::arrow::arrow_reader::ArrowReaderMetadata;
+use std::error::Error;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+
+use crate::parquet_helper::ParquetHelper;
+
+pub type Result<T> = std::result::Result<T, Box<dyn Error>>;
+
+/// 重新排序时使用的列（与提取时的 ORDER BY 一致）
+const SORT_COLUMNS: [&str; 3] = ["slot", "transaction_index", "instruction_index"];
+
+/// 压缩选项
+#[derive(Debug, Clone, Default)]
+pub struct CompactOptions {
+    /// 合并后按 slot, transaction_index, instruction_index 重新排序
+    pub sort: bool,
+    /// 校验通过后删除原始日文件
+    pub remove_originals: bool,
+}
+
+/// 压缩结果
+#[derive(Debug)]
+pub struct CompactReport {
+    pub output_path: PathBuf,
+    pub input_files: Vec<PathBuf>,
+    pub rows: u64,
+}
+
+/// 日文件 -> 月文件压缩器
+pub struct ParquetCompactor {
+    parquet_helper: ParquetHelper,
+}
+
+impl ParquetCompactor {
+    pub fn new() -> Self {
+        Self::with_helper(ParquetHelper::new())
+    }
+
+    pub fn with_helper(parquet_helper: ParquetHelper) -> Self {
+        Self { parquet_helper }
+    }
+
+    /// 解析 "YYYY-MM" 格式的月份，返回该月 1 号
+    pub fn parse_month(month: &str) -> Result<NaiveDate> {
+        NaiveDate::parse_from_str(&format!("{}-01", month), "%Y-%m-%d")
+            .map_err(|e| format!("Invalid month '{}', expected YYYY-MM: {}", month, e).into())
+    }
+
+    /// 列出某表某月的所有日文件（按文件名排序）
+    ///
+    /// 日文件命名: {root}/{table}/{table}_{YYYY-MM-DD}.parquet
+    pub fn list_daily_files(root: &Path, table: &str, month: NaiveDate) -> Result<Vec<PathBuf>> {
+        let table_dir = root.join(table);
+        let prefix = format!("{}_{}-", table, month.format("%Y-%m"));
+
+        let mut files: Vec<PathBuf> = fs::read_dir(&table_dir)?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| {
+                path.file_name()
+                    .and_then(|name| name.to_str())
+                    .map(|name| {
+                        name.starts_with(&prefix)
+                            && name.ends_with(".parquet")
+                            // 只匹配 YYYY-MM-DD 日文件
+                            && name.len() == prefix.len() + "DD.parquet".len()
+                    })
+                    .unwrap_or(false)
+            })
+            .collect();
+
+        files.sort();
+        Ok(files)
+    }
+
+    /// 将某表某月的日文件合并为 {root}/{table}/{table}_{YYYY-MM}.parquet
+    ///
+    /// # Arguments
+    /// * `root` - 存储根目录（包含各表子目录）
+    /// * `table` - 表名
+    /// * `month` - 月份中任意一天
+    /// * `options` - 压缩选项
+    ///
+    /// # Returns
+    /// * `CompactReport` - 输出文件、输入文件和总行数
+    pub async fn compact_month(
+        &self,
+        root: &Path,
+        table: &str,
+        month: NaiveDate,
+        options: &CompactOptions,
+    ) -> Result<CompactReport> {
+        let month = NaiveDate::from_ymd_opt(month.year(), month.month(), 1)
+            .ok_or("Invalid month")?;
+        let input_files = Self::list_daily_files(root, table, month)?;
+        if input_files.is_empty() {
+            return Err(format!(
+                "No daily parquet files found for {} in {}",
+                table,
+                month.format("%Y-%m")
+            )
+            .into());
+        }
+
+        // 1. 读取所有日文件
+        let schema = ArrowReaderMetadata::load(&File::open(&input_files[0])?, Default::default())?
+            .schema()
+            .clone();
+        let mut expected_rows = 0u64;
+        let mut batches: Vec<RecordBatch> = Vec::new();
+        for file_path in &input_files {
+            expected_rows += self.parquet_helper.parquet_row_count(file_path)?;
+
+            let mut stream = Box::pin(self.parquet_helper.read_parquet_batches(file_path));
+            while let Some(batch) = stream.next().await {
+                let batch = batch?;
+                if batch.schema() != schema {
+                    return Err(format!("Schema mismatch in {:?}", file_path).into());
+                }
+                batches.push(batch);
+            }
+        }
+
+        // 2. 合并（可选排序）
+        let mut merged = concat_batches(&schema, &batches)?;
+        drop(batches);
+        if options.sort {
+            merged = Self::sort_batch(&merged)?;
+        }
+
+        // 3. 写入月文件
+        let output_path = root
+            .join(table)
+            .join(format!("{}_{}.parquet", table, month.format("%Y-%m")));
+        self.parquet_helper.write_parquet_file(&merged, &output_path)?;
+
+        // 4. 校验行数
+        let written_rows = self.parquet_helper.parquet_row_count(&output_path)?;
+        if written_rows != expected_rows {
+            fs::remove_file(&output_path)?;
+            return Err(format!(
+                "Row count mismatch for {:?}: inputs {} vs output {}",
+                output_path, expected_rows, written_rows
+            )
+            .into());
+        }
+
+        // 5. 可选删除原始日文件
+        if options.remove_originals {
+            for file_path in &input_files {
+                fs::remove_file(file_path)?;
+            }
+        }
+
+        Ok(CompactReport {
+            output_path,
+            input_files,
+            rows: written_rows,
+        })
+    }
+
+    /// 按 SORT_COLUMNS 中存在的列排序
+    fn sort_batch(batch: &RecordBatch) -> Result<RecordBatch> {
+        let sort_columns: Vec<SortColumn> = SORT_COLUMNS
+            .iter()
+            .filter_map(|name| batch.column_by_name(name))
+            .map(|column| SortColumn {
+                values: column.clone(),
+                options: None,
+            })
+            .collect();
+
+        if sort_columns.is_empty() {
+            return Ok(batch.clone());
+        }
+
+        let indices = lexsort_to_indices(&sort_columns, None)?;
+        Ok(take_record_batch(batch, &indices)?)
+    }
+}
+
+impl Default for ParquetCompactor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -3,6 +3,11 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::error::Error;
 use std::path::PathBuf;
+use utils::env_expand::parse_toml;
+
+use crate::parquet_helper::PartitionLayout;
+use crate::s3_transport::S3Config;
+use crate::transport::RsyncOptions;
 
 pub type Result<T> = std::result::Result<T, Box<dyn Error>>;
 
@@ -17,12 +22,45 @@ pub struct LocalConfig {
     
     /// 导出起始时间（chrono 自动处理 "2025-10-01" 格式）
     pub start_time: NaiveDate,
+
+    /// 导出结束时间（含当天），不配置则导出到今天
+    #[serde(default)]
+    pub end_time: Option<NaiveDate>,
     
     /// 本地存储路径
     pub local_storage_path: PathBuf,
     
-    /// 远程服务器配置
-    pub remote_server: RemoteServerConfig,
+    /// 只提取并统计行数和预估文件大小，不写入、不传输、不删除
+    #[serde(default)]
+    pub dry_run: bool,
+
+    /// 传输成功后保留本地 Parquet 文件
+    #[serde(default)]
+    pub keep_local: bool,
+
+    /// 只提取并写入本地 Parquet，不传输也不删除（传输另行执行时使用）
+    #[serde(default)]
+    pub no_transfer: bool,
+
+    /// 传输失败后的重试次数
+    #[serde(default = "default_transfer_retries")]
+    pub transfer_retries: usize,
+
+    /// 首次重试前的等待秒数（之后指数退避）
+    #[serde(default = "default_transfer_retry_delay_secs")]
+    pub transfer_retry_delay_secs: u64,
+
+    /// rsync 参数（限速、续传、压缩、额外参数）
+    #[serde(default)]
+    pub rsync: RsyncOptions,
+
+    /// 远程服务器配置（rsync 传输）
+    #[serde(default)]
+    pub remote_server: Option<RemoteServerConfig>,
+
+    /// S3 兼容对象存储配置，配置后替代 rsync 传输
+    #[serde(default)]
+    pub s3: Option<S3Config>,
 }
 
 /// 远程模式配置
@@ -36,6 +74,66 @@ pub struct RemoteConfig {
     
     /// 表名 -> 事件类型映射（用于反序列化）
     pub table_event_mappings: HashMap<String, String>,
+    
+    /// 文件目录布局（"flat" 或 "hive_daily"，默认 flat）
+    #[serde(default)]
+    pub partition_layout: PartitionLayout,
+
+    /// 忽略各目录下的 .imported.log 清单，重新导入所有文件
+    #[serde(default)]
+    pub force_reimport: bool,
+
+    /// 同时导入的最大文件数（默认 1，即串行）
+    #[serde(default = "default_max_concurrent_imports")]
+    pub max_concurrent_imports: usize,
+
+    /// 同一目录内按日期顺序串行导入，只在目录之间并发
+    #[serde(default)]
+    pub preserve_order: bool,
+
+    /// 导入完成后按天核对 Parquet 行数与目标表行数，不一致的写入汇总
+    #[serde(default)]
+    pub verify_after_import: bool,
+
+    /// ClickHouse 中的导入台账表名，设置后按文件内容哈希跳过已导入的文件
+    #[serde(default)]
+    pub import_ledger_table: Option<String>,
+
+    /// ClickHouse 写入失败后整个文件的重试次数
+    #[serde(default = "default_import_retries")]
+    pub import_retries: usize,
+
+    /// 首次重试前的等待秒数（之后指数退避）
+    #[serde(default = "default_import_retry_delay_secs")]
+    pub import_retry_delay_secs: u64,
+
+    /// 不小于该大小（字节）的文件按 row group 逐个提交（默认 256 MiB）
+    #[serde(default = "default_streaming_import_min_bytes")]
+    pub streaming_import_min_bytes: u64,
+}
+
+fn default_transfer_retries() -> usize {
+    5
+}
+
+fn default_transfer_retry_delay_secs() -> u64 {
+    5
+}
+
+fn default_max_concurrent_imports() -> usize {
+    1
+}
+
+fn default_import_retries() -> usize {
+    3
+}
+
+fn default_import_retry_delay_secs() -> u64 {
+    2
+}
+
+fn default_streaming_import_min_bytes() -> u64 {
+    256 * 1024 * 1024
 }
 
 /// 远程服务器配置（用于 rsync/SSH）
@@ -52,7 +150,32 @@ impl LocalConfig {
     /// 从 TOML 文件加载本地配置
     pub fn from_file(path: &str) -> Result<Self> {
         let content = std::fs::read_to_string(path)?;
-        Ok(toml::from_str(&content)?)
+        parse_toml(&content)
+    }
+
+    /// 用命令行 `--only-table` 覆盖 `tables`，为空时保持配置不变
+    ///
+    /// 每个表都必须在 `table_event_mappings` 中（可以不在原 `tables` 列表里），重复的表只保留一次
+    pub fn override_tables(&mut self, only_tables: &[String]) -> Result<()> {
+        if only_tables.is_empty() {
+            return Ok(());
+        }
+
+        let mut tables: Vec<String> = Vec::new();
+        for table in only_tables {
+            if !self.table_event_mappings.contains_key(table) {
+                return Err(format!(
+                    "--only-table {} has no entry in table_event_mappings",
+                    table
+                )
+                .into());
+            }
+            if !tables.contains(table) {
+                tables.push(table.clone());
+            }
+        }
+        self.tables = tables;
+        Ok(())
     }
 }
 
@@ -60,6 +183,6 @@ impl RemoteConfig {
     /// 从 TOML 文件加载远程配置
     pub fn from_file(path: &str) -> Result<Self> {
         let content = std::fs::read_to_string(path)?;
-        Ok(toml::from_str(&content)?)
+        parse_toml(&content)
     }
 }
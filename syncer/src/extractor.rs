@@ -1,24 +1,29 @@
+use arrow::compute::cast;
+use arrow::datatypes::{FieldRef, Schema};
+use arrow::ipc::reader::StreamReader;
 use arrow::record_batch::RecordBatch;
 use chrono::NaiveDate;
+use clickhouse::Client;
+use serde_arrow::schema::TracingOptions;
 use std::error::Error;
-use utils::clickhouse_client::ClickHouseClient;
+use std::io::Cursor;
+use std::sync::Arc;
+use utils::clickhouse_client::ClickHouseConfig;
 use utils::clickhouse_events::*;
 
 pub type Result<T> = std::result::Result<T, Box<dyn Error>>;
 
 /// 宏：简化事件类型的查询和转换逻辑
 macro_rules! query_and_convert {
-    ($self:ident, $query:expr, $event_type:expr, $($type_name:literal => $struct_type:ty),+ $(,)?) => {
+    ($client:expr, $query:expr, $event_type:expr, $($type_name:literal => $struct_type:ty),+ $(,)?) => {
         match $event_type {
             $(
                 $type_name => {
-                    let rows = $self
-                        .client
-                        .client()
+                    let rows = $client
                         .query($query)
                         .fetch_all::<$struct_type>()
                         .await?;
-                    vec_to_arrow_batch(&rows)
+                    try_vec_to_arrow_batch(&rows)?
                 }
             )+
             _ => {
@@ -28,16 +33,96 @@ macro_rules! query_and_convert {
     };
 }
 
+/// 宏：根据事件类型获取其 Arrow schema 字段
+macro_rules! known_fields {
+    ($event_type:expr, $($type_name:literal => $struct_type:ty),+ $(,)?) => {
+        match $event_type {
+            $(
+                $type_name => event_fields::<$struct_type>(TracingOptions::default())?,
+            )+
+            _ => {
+                return Err(format!("Unknown event type: {}", $event_type).into());
+            }
+        }
+    };
+}
+
+/// 获取事件类型的已知列（与 `vec_to_arrow_batch` 生成的 schema 一致）
+pub fn event_type_fields(event_type: &str) -> Result<Vec<FieldRef>> {
+    let fields = known_fields!(
+        event_type,
+        "PumpfunTradeEventV2" => PumpfunTradeEventV2,
+        "PumpfunCreateEventV2" => PumpfunCreateEventV2,
+        "PumpfunMigrateEventV2" => PumpfunMigrateEventV2,
+        "PumpfunAmmBuyEventV2" => PumpfunAmmBuyEventV2,
+        "PumpfunAmmSellEventV2" => PumpfunAmmSellEventV2,
+        "PumpfunAmmCreatePoolEventV2" => PumpfunAmmCreatePoolEventV2,
+        "PumpfunAmmDepositEventV2" => PumpfunAmmDepositEventV2,
+        "PumpfunAmmWithdrawEventV2" => PumpfunAmmWithdrawEventV2,
+        "PumpfunAmmCollectCoinCreatorFeeEventV2" => PumpfunAmmCollectCoinCreatorFeeEventV2,
+        "PumpfunAmmDisableEventV2" => PumpfunAmmDisableEventV2,
+        "PumpfunAmmSetParamsEventV2" => PumpfunAmmSetParamsEventV2,
+    );
+    Ok(fields)
+}
+
+/// `extract_query` 拒绝的关键字（写入、DDL 和系统语句）
+const WRITE_KEYWORDS: &[&str] = &[
+    "INSERT", "ALTER", "DROP", "TRUNCATE", "CREATE", "RENAME", "DELETE", "UPDATE", "OPTIMIZE",
+    "SYSTEM", "KILL", "ATTACH", "DETACH", "GRANT", "REVOKE", "OUTFILE",
+];
+
+/// 检查 SQL 是否为单条只读查询（以 SELECT 或 WITH 开头，不含写入类关键字）
+///
+/// 按单词匹配，字符串字面量里出现关键字也会被拒绝，宁可误拒
+pub fn ensure_read_only(sql: &str) -> Result<()> {
+    let statement = sql.trim().trim_end_matches(';').trim_end();
+    if statement.contains(';') {
+        return Err("Only a single statement is allowed".into());
+    }
+
+    let words: Vec<String> = statement
+        .split(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+        .filter(|word| !word.is_empty())
+        .map(|word| word.to_ascii_uppercase())
+        .collect();
+
+    match words.first().map(String::as_str) {
+        Some("SELECT") | Some("WITH") => {}
+        _ => return Err(format!("Only SELECT queries are allowed: {}", statement).into()),
+    }
+    if let Some(keyword) = words.iter().find(|word| WRITE_KEYWORDS.contains(&word.as_str())) {
+        return Err(format!("Query is not read-only ({}): {}", keyword, statement).into());
+    }
+    Ok(())
+}
+
+/// 提取器连接配置（与共享 ClickHouseClient 使用同一套配置）
+pub type ExtractorConfig = ClickHouseConfig;
+
 /// ClickHouse 数据提取器
 pub struct ClickHouseExtractor {
-    client: &'static ClickHouseClient,
+    client: Client,
 }
 
 impl ClickHouseExtractor {
+    /// 使用环境变量中的连接配置创建提取器
     pub fn new() -> Self {
-        Self {
-            client: ClickHouseClient::instance(),
-        }
+        Self::from_config(&ExtractorConfig::from_env())
+            .unwrap_or_else(|e| panic!("Invalid ClickHouse extractor config: {}", e))
+    }
+
+    /// 根据连接配置创建提取器
+    ///
+    /// URL 指向原生协议端口等配置错误时返回错误
+    pub fn from_config(config: &ExtractorConfig) -> Result<Self> {
+        let client = config.build_client()?;
+        Ok(Self::with_client(client))
+    }
+
+    /// 使用已有的 ClickHouse 客户端创建提取器（例如指向只读副本）
+    pub fn with_client(client: Client) -> Self {
+        Self { client }
     }
 
     /// 提取单天的事件数据
@@ -55,13 +140,225 @@ impl ClickHouseExtractor {
         event_type: &str,
         date: NaiveDate,
     ) -> Result<RecordBatch> {
-        // 计算起始和结束时间戳（UTC）
+        let mut batches = self
+            .extract_range_events(table, event_type, date, date)
+            .await?;
+        batches
+            .pop()
+            .ok_or_else(|| format!("No batch extracted for {}", date).into())
+    }
+
+    /// 统计单天的事件行数，不拉取数据
+    ///
+    /// # Arguments
+    /// * `table` - ClickHouse 表名
+    /// * `date` - 目标日期
+    ///
+    /// # Returns
+    /// * `u64` - [00:00, 24:00) 内的行数，与 `extract_daily_events` 的时间范围一致
+    pub async fn count_daily_events(&self, table: &str, date: NaiveDate) -> Result<u64> {
+        let (start_timestamp, end_timestamp) = Self::day_bounds(date)?;
+
+        let query = format!(
+            "SELECT count() FROM {} WHERE timestamp >= {} AND timestamp < {}",
+            table, start_timestamp, end_timestamp
+        );
+
+        Ok(self.client.query(&query).fetch_one::<u64>().await?)
+    }
+
+    /// 提取日期区间内（含首尾）的事件数据，每天一个批次
+    /// 
+    /// # Arguments
+    /// * `table` - ClickHouse 表名
+    /// * `event_type` - 事件类型名（用于反序列化）
+    /// * `start` - 起始日期（含）
+    /// * `end` - 结束日期（含）
+    /// 
+    /// # Returns
+    /// * `Vec<RecordBatch>` - 按日期顺序排列的批次
+    pub async fn extract_range_events(
+        &self,
+        table: &str,
+        event_type: &str,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> Result<Vec<RecordBatch>> {
+        if start > end {
+            return Err(format!(
+                "Invalid date range: start {} is after end {}",
+                start, end
+            )
+            .into());
+        }
+
+        let mut batches = Vec::new();
+        let mut current_date = start;
+        while current_date <= end {
+            let query_start = std::time::Instant::now();
+            let batch = self.query_day(table, event_type, current_date).await?;
+            tracing::debug!(
+                table,
+                date = %current_date,
+                rows = batch.num_rows(),
+                duration_ms = query_start.elapsed().as_millis() as u64,
+                "queried day"
+            );
+            batches.push(batch);
+            current_date = current_date
+                .succ_opt()
+                .ok_or("Failed to get next date")?;
+        }
+
+        Ok(batches)
+    }
+
+    /// 执行任意只读 SELECT，按事件类型反序列化（用于临时导出，例如单个 mint 的交易）
+    ///
+    /// 查询结果的列必须与事件结构体一致（通常是 `SELECT *`）。非 SELECT 语句在发送前
+    /// 被拒绝，查询本身也以 `readonly = 2` 执行
+    ///
+    /// # Arguments
+    /// * `sql` - SELECT 语句
+    /// * `event_type` - 事件类型名（用于反序列化）
+    ///
+    /// # Returns
+    /// * `RecordBatch` - Arrow 格式的数据批次
+    pub async fn extract_query(&self, sql: &str, event_type: &str) -> Result<RecordBatch> {
+        event_type_fields(event_type)?;
+        ensure_read_only(sql)?;
+
+        // 服务端同样只允许读（readonly = 2 仍允许客户端附带的设置）
+        let client = self.client.clone().with_option("readonly", "2");
+        let query_start = std::time::Instant::now();
+        let batch = query_and_convert!(
+            client,
+            sql,
+            event_type,
+            "PumpfunTradeEventV2" => PumpfunTradeEventV2,
+            "PumpfunCreateEventV2" => PumpfunCreateEventV2,
+            "PumpfunMigrateEventV2" => PumpfunMigrateEventV2,
+            "PumpfunAmmBuyEventV2" => PumpfunAmmBuyEventV2,
+            "PumpfunAmmSellEventV2" => PumpfunAmmSellEventV2,
+            "PumpfunAmmCreatePoolEventV2" => PumpfunAmmCreatePoolEventV2,
+            "PumpfunAmmDepositEventV2" => PumpfunAmmDepositEventV2,
+            "PumpfunAmmWithdrawEventV2" => PumpfunAmmWithdrawEventV2,
+            "PumpfunAmmCollectCoinCreatorFeeEventV2" => PumpfunAmmCollectCoinCreatorFeeEventV2,
+            "PumpfunAmmDisableEventV2" => PumpfunAmmDisableEventV2,
+            "PumpfunAmmSetParamsEventV2" => PumpfunAmmSetParamsEventV2,
+        );
+        tracing::debug!(
+            event_type,
+            rows = batch.num_rows(),
+            duration_ms = query_start.elapsed().as_millis() as u64,
+            "ad-hoc query"
+        );
+
+        Ok(batch)
+    }
+
+    /// 提取单天的事件数据，只查询指定的列
+    /// 
+    /// # Arguments
+    /// * `table` - ClickHouse 表名
+    /// * `event_type` - 事件类型名（用于校验列名和确定列类型）
+    /// * `date` - 目标日期
+    /// * `columns` - 需要的列，按给定顺序输出
+    /// 
+    /// # Returns
+    /// * `RecordBatch` - 只包含指定列的数据批次，列类型与完整提取时一致
+    pub async fn extract_daily_events_projected(
+        &self,
+        table: &str,
+        event_type: &str,
+        date: NaiveDate,
+        columns: &[&str],
+    ) -> Result<RecordBatch> {
+        let schema = Self::projected_schema(event_type, columns)?;
+        let (start_timestamp, end_timestamp) = Self::day_bounds(date)?;
+
+        let query = format!(
+            "SELECT {} FROM {} WHERE timestamp >= {} AND timestamp < {} ORDER BY slot, transaction_index, instruction_index",
+            columns.join(", "),
+            table,
+            start_timestamp,
+            end_timestamp
+        );
+
+        // 投影查询无法映射到固定的 Row 结构体，直接以 ArrowStream 格式拉取
+        let bytes = self
+            .client
+            .query(&query)
+            .with_option("output_format_arrow_string_as_string", "1")
+            .fetch_bytes("ArrowStream")?
+            .collect()
+            .await?;
+
+        let mut batches = Vec::new();
+        if !bytes.is_empty() {
+            let reader = StreamReader::try_new(Cursor::new(bytes), None)?;
+            for batch in reader {
+                batches.push(Self::cast_to_schema(&batch?, &schema)?);
+            }
+        }
+
+        Ok(arrow::compute::concat_batches(&schema, &batches)?)
+    }
+
+    /// 校验列名并构造投影后的 schema
+    fn projected_schema(event_type: &str, columns: &[&str]) -> Result<Arc<Schema>> {
+        if columns.is_empty() {
+            return Err("Projection requires at least one column".into());
+        }
+
+        let fields = event_type_fields(event_type)?;
+        let projected = columns
+            .iter()
+            .map(|column| {
+                fields
+                    .iter()
+                    .find(|field| field.name() == column)
+                    .cloned()
+                    .ok_or_else(|| {
+                        format!("Unknown column '{}' for event type {}", column, event_type)
+                    })
+            })
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        Ok(Arc::new(Schema::new(projected)))
+    }
+
+    /// 将 ClickHouse 返回的 Arrow 类型转换为事件结构体对应的类型
+    fn cast_to_schema(batch: &RecordBatch, schema: &Arc<Schema>) -> Result<RecordBatch> {
+        let columns = schema
+            .fields()
+            .iter()
+            .zip(batch.columns())
+            .map(|(field, column)| cast(column, field.data_type()))
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        Ok(RecordBatch::try_new(schema.clone(), columns)?)
+    }
+
+    /// 计算单天 [00:00, 24:00) 的时间戳范围（UTC）
+    fn day_bounds(date: NaiveDate) -> Result<(u32, u32)> {
         let start_timestamp = date
             .and_hms_opt(0, 0, 0)
             .ok_or("Invalid date")?
             .and_utc()
             .timestamp() as u32;
-        let end_timestamp = start_timestamp + 86400; // +24小时
+        Ok((start_timestamp, start_timestamp + 86400))
+    }
+
+    /// 查询单天 [00:00, 24:00) 的数据
+    async fn query_day(
+        &self,
+        table: &str,
+        event_type: &str,
+        date: NaiveDate,
+    ) -> Result<RecordBatch> {
+        // 计算起始和结束时间戳（UTC）
+        let (start_timestamp, end_timestamp) = Self::day_bounds(date)?;
 
         // 构造 SQL 查询
         let query = format!(
@@ -71,7 +368,7 @@ impl ClickHouseExtractor {
 
         // 使用宏处理所有事件类型
         let batch = query_and_convert!(
-            self,
+            self.client,
             &query,
             event_type,
             "PumpfunTradeEventV2" => PumpfunTradeEventV2,
@@ -82,6 +379,9 @@ impl ClickHouseExtractor {
             "PumpfunAmmCreatePoolEventV2" => PumpfunAmmCreatePoolEventV2,
             "PumpfunAmmDepositEventV2" => PumpfunAmmDepositEventV2,
             "PumpfunAmmWithdrawEventV2" => PumpfunAmmWithdrawEventV2,
+            "PumpfunAmmCollectCoinCreatorFeeEventV2" => PumpfunAmmCollectCoinCreatorFeeEventV2,
+            "PumpfunAmmDisableEventV2" => PumpfunAmmDisableEventV2,
+            "PumpfunAmmSetParamsEventV2" => PumpfunAmmSetParamsEventV2,
         );
 
         Ok(batch)
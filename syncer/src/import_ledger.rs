@@ -0,0 +1,200 @@
+use clickhouse::{Client, Row};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+
+use crate::sync_checker::quote_table;
+
+/// 导入台账：存放在 ClickHouse 中的已导入文件记录
+///
+/// 与本地的 `.imported.log` 不同，台账和数据在同一个库里，远程主机丢失后
+/// 重跑也不会重复导入。按 (目标表, 文件内容哈希) 去重
+///
+/// 流式导入每提交一个 row group 记录一次进度（`complete = 0`），中途崩溃后从
+/// 已记录的 row group 之后继续。唯一的重复窗口是某个 row group 的 INSERT 已完成、
+/// 进度还没写入时崩溃：恢复后这一个 row group 会再导入一次
+pub struct ImportLedger {
+    client: Client,
+    table: String,
+}
+
+#[derive(Debug, Row, Serialize)]
+struct LedgerEntry {
+    target_table: String,
+    content_hash: String,
+    file_name: String,
+    row_count: u64,
+    row_groups: u64,
+    complete: u8,
+}
+
+#[derive(Debug, Row, Deserialize)]
+struct LedgerRow {
+    row_count: u64,
+    row_groups: u64,
+    complete: u8,
+}
+
+/// 台账中一个文件的最新记录
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LedgerState {
+    /// 已提交的行数
+    pub row_count: u64,
+    /// 已提交的 row group 数（从文件开头连续）
+    pub row_groups: u64,
+    /// 整个文件是否已导入完成
+    pub complete: bool,
+}
+
+impl ImportLedger {
+    /// 台账写入不走 async_insert，插入返回时记录已经落盘
+    pub fn new(client: Client, table: &str) -> Self {
+        Self {
+            client: client.with_option("async_insert", "0"),
+            table: table.to_string(),
+        }
+    }
+
+    pub fn table(&self) -> &str {
+        &self.table
+    }
+
+    /// 台账表的建表语句
+    pub fn create_table_sql(table: &str) -> String {
+        format!(
+            "CREATE TABLE IF NOT EXISTS {} (\n    target_table String,\n    content_hash String,\n    file_name String,\n    row_count UInt64,\n    row_groups UInt64 DEFAULT 0,\n    complete UInt8 DEFAULT 1,\n    imported_at DateTime DEFAULT now()\n)\nENGINE = ReplacingMergeTree\nORDER BY (target_table, content_hash)",
+            quote_table(table)
+        )
+    }
+
+    /// 给早期版本建的台账表补上进度列，已有记录都是完整导入
+    pub fn migrate_table_sql(table: &str) -> String {
+        format!(
+            "ALTER TABLE {} ADD COLUMN IF NOT EXISTS row_groups UInt64 DEFAULT 0, ADD COLUMN IF NOT EXISTS complete UInt8 DEFAULT 1",
+            quote_table(table)
+        )
+    }
+
+    /// 台账表不存在时创建，已存在时补齐缺少的列
+    pub async fn ensure_table(&self) -> Result<(), clickhouse::error::Error> {
+        self.client
+            .query(&Self::create_table_sql(&self.table))
+            .execute()
+            .await?;
+        self.client
+            .query(&Self::migrate_table_sql(&self.table))
+            .execute()
+            .await
+    }
+
+    /// 查询文件导入到目标表的最新记录（完成记录优先，其次是进度最大的记录）
+    pub async fn state(
+        &self,
+        target_table: &str,
+        content_hash: &str,
+    ) -> Result<Option<LedgerState>, clickhouse::error::Error> {
+        let query = format!(
+            "SELECT row_count, row_groups, complete FROM {} \
+             WHERE target_table = ? AND content_hash = ? \
+             ORDER BY complete DESC, row_groups DESC LIMIT 1",
+            quote_table(&self.table)
+        );
+        let row = self
+            .client
+            .query(&query)
+            .bind(target_table)
+            .bind(content_hash)
+            .fetch_optional::<LedgerRow>()
+            .await?;
+        Ok(row.map(|row| LedgerState {
+            row_count: row.row_count,
+            row_groups: row.row_groups,
+            complete: row.complete != 0,
+        }))
+    }
+
+    /// 查询文件是否已完整导入到目标表，返回当时导入的行数
+    pub async fn imported_rows(
+        &self,
+        target_table: &str,
+        content_hash: &str,
+    ) -> Result<Option<u64>, clickhouse::error::Error> {
+        Ok(self
+            .state(target_table, content_hash)
+            .await?
+            .filter(|state| state.complete)
+            .map(|state| state.row_count))
+    }
+
+    /// 记录一次成功的导入（应在数据写入完成后调用）
+    pub async fn record(
+        &self,
+        target_table: &str,
+        content_hash: &str,
+        file_name: &str,
+        row_count: u64,
+    ) -> Result<(), clickhouse::error::Error> {
+        self.write(target_table, content_hash, file_name, row_count, 0, true)
+            .await
+    }
+
+    /// 记录流式导入的进度：文件开头的 `row_groups` 个 row group（共 `row_count` 行）已提交
+    pub async fn record_progress(
+        &self,
+        target_table: &str,
+        content_hash: &str,
+        file_name: &str,
+        row_count: u64,
+        row_groups: u64,
+    ) -> Result<(), clickhouse::error::Error> {
+        self.write(
+            target_table,
+            content_hash,
+            file_name,
+            row_count,
+            row_groups,
+            false,
+        )
+        .await
+    }
+
+    async fn write(
+        &self,
+        target_table: &str,
+        content_hash: &str,
+        file_name: &str,
+        row_count: u64,
+        row_groups: u64,
+        complete: bool,
+    ) -> Result<(), clickhouse::error::Error> {
+        let mut insert = self.client.insert(&self.table)?;
+        insert
+            .write(&LedgerEntry {
+                target_table: target_table.to_string(),
+                content_hash: content_hash.to_string(),
+                file_name: file_name.to_string(),
+                row_count,
+                row_groups,
+                complete: complete as u8,
+            })
+            .await?;
+        insert.end().await
+    }
+}
+
+/// 文件内容的 SHA-256（十六进制小写），流式读取不整体加载
+pub fn content_hash(file_path: &Path) -> io::Result<String> {
+    let mut file = File::open(file_path)?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0u8; 64 * 1024];
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
@@ -0,0 +1,86 @@
+use chrono::Utc;
+use std::collections::HashSet;
+use std::error::Error;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+pub type Result<T> = std::result::Result<T, Box<dyn Error>>;
+
+/// 每个导入目录下的清单文件名
+pub const MANIFEST_FILE_NAME: &str = ".imported.log";
+
+/// 导入清单：记录某个目录下已成功导入的文件，用于中断后续跑
+///
+/// 日志行格式: timestamp,relative_path,completed
+pub struct ImportManifest {
+    log_path: PathBuf,
+    imported_set: HashSet<String>,
+}
+
+impl ImportManifest {
+    /// 加载目录下的清单（不存在则为空）
+    pub fn load(folder: &Path) -> Result<Self> {
+        let mut manifest = Self::empty(folder);
+
+        if !manifest.log_path.exists() {
+            return Ok(manifest);
+        }
+
+        let reader = BufReader::new(File::open(&manifest.log_path)?);
+        for line in reader.lines() {
+            let line = line?;
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            // 文件名中可能含逗号，取第一个和最后一个逗号之间的部分
+            if let Some((_, rest)) = line.split_once(',')
+                && let Some((name, status)) = rest.rsplit_once(',')
+                && status == "completed"
+            {
+                manifest.imported_set.insert(name.to_string());
+            }
+        }
+
+        Ok(manifest)
+    }
+
+    /// 忽略已有记录的空清单（force_reimport），新的导入仍会追加到同一文件
+    pub fn empty(folder: &Path) -> Self {
+        Self {
+            log_path: folder.join(MANIFEST_FILE_NAME),
+            imported_set: HashSet::new(),
+        }
+    }
+
+    /// 检查文件是否已导入
+    pub fn is_imported(&self, name: &str) -> bool {
+        self.imported_set.contains(name)
+    }
+
+    /// 标记文件已导入（立即追加写入，崩溃后不丢失）
+    pub fn mark_imported(&mut self, name: &str) -> Result<()> {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.log_path)?;
+
+        writeln!(file, "{},{},completed", Utc::now().to_rfc3339(), name)?;
+        file.sync_data()?;
+
+        self.imported_set.insert(name.to_string());
+        Ok(())
+    }
+
+    /// 已导入文件数
+    pub fn imported_count(&self) -> usize {
+        self.imported_set.len()
+    }
+
+    pub fn log_path(&self) -> &Path {
+        &self.log_path
+    }
+}
@@ -1,50 +1,346 @@
+use arrow::datatypes::Schema;
+use arrow::record_batch::RecordBatch;
+use clickhouse::sql::Identifier;
+use futures::StreamExt;
+use parquet::arrow::arrow_reader::ArrowReaderMetadata;
+use serde::de::DeserializeOwned;
+use serde_arrow::schema::TracingOptions;
 use std::error::Error;
-use std::path::Path;
+use std::fmt;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 use utils::clickhouse_client::ClickHouseClient;
 use utils::clickhouse_events::*;
+use utils::event_registry::{LiveColumn, clickhouse_type, normalize_type};
 
+use crate::import_ledger::{self, ImportLedger};
 use crate::parquet_helper::ParquetHelper;
 
 pub type Result<T> = std::result::Result<T, Box<dyn Error>>;
 
-/// 宏：根据事件类型反序列化并批量插入 ClickHouse
+/// 导入错误
+///
+/// 调用方可以按原因决定跳过单个文件还是中止整批导入
+#[derive(Debug)]
+pub enum ImportError {
+    /// 未知的事件类型
+    UnknownEventType(String),
+    /// Parquet 文件无法打开或解码
+    CorruptParquet(PathBuf, Box<dyn Error>),
+    /// Parquet 列与目标结构不一致
+    SchemaMismatch {
+        expected: Vec<String>,
+        found: Vec<String>,
+    },
+    /// ClickHouse 写入失败
+    ClickHouse(clickhouse::error::Error),
+}
+
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImportError::UnknownEventType(event_type) => {
+                write!(f, "Unknown event type: {}", event_type)
+            }
+            ImportError::CorruptParquet(path, source) => {
+                write!(f, "Corrupt Parquet file {:?}: {}", path, source)
+            }
+            ImportError::SchemaMismatch { expected, found } => write!(
+                f,
+                "Schema mismatch: expected columns [{}], found [{}]",
+                expected.join(", "),
+                found.join(", ")
+            ),
+            ImportError::ClickHouse(source) => write!(f, "ClickHouse insert failed: {}", source),
+        }
+    }
+}
+
+impl Error for ImportError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ImportError::CorruptParquet(_, source) => Some(source.as_ref()),
+            ImportError::ClickHouse(source) => Some(source),
+            _ => None,
+        }
+    }
+}
+
+impl From<clickhouse::error::Error> for ImportError {
+    fn from(source: clickhouse::error::Error) -> Self {
+        ImportError::ClickHouse(source)
+    }
+}
+
+/// 将 RecordBatch 反序列化为事件，列不匹配时返回 SchemaMismatch 而不是 panic
+fn deserialize_batch<T: DeserializeOwned>(
+    batch: &RecordBatch,
+) -> std::result::Result<Vec<T>, ImportError> {
+    try_arrow_batch_to_vec(batch).map_err(|_| {
+        let expected = event_fields::<T>(TracingOptions::default())
+            .map(|fields| fields.iter().map(|f| f.name().clone()).collect())
+            .unwrap_or_default();
+        let found = batch
+            .schema()
+            .fields()
+            .iter()
+            .map(|f| f.name().clone())
+            .collect();
+        ImportError::SchemaMismatch { expected, found }
+    })
+}
+
+/// 已提交到 ClickHouse 的进度（跨重试保留）
+#[derive(Debug, Default)]
+struct ImportProgress {
+    row_groups: usize,
+    rows: u64,
+}
+
+/// 宏：根据事件类型逐个 row group 反序列化并写入 ClickHouse
+///
+/// `$client` 只在第一次写入时求值，事件类型或文件错误不需要连接 ClickHouse。
+/// `$per_row_group` 为 true 时每个 row group 单独提交一次 INSERT，重试时跳过已提交的 row group；
+/// `$ledger` 为 `Some((台账, 文件哈希))` 时每提交一个 row group 记录一次进度
 macro_rules! deserialize_and_insert {
-    ($batch:expr, $event_type:expr, $table:expr, $client:expr, $( $variant:literal => $type:ty ),* $(,)?) => {
+    ($importer:ident, $batches:expr, $file_path:expr, $event_type:expr, $table:expr, $client:expr, $progress:ident, $per_row_group:expr, $ledger:expr, $( $variant:literal => $type:ty ),* $(,)?) => {
         match $event_type {
             $(
                 $variant => {
-                    // 使用 utils 提供的转换函数
-                    let events: Vec<$type> = arrow_batch_to_vec(&$batch);
-                    let row_count = events.len() as u64;
-                    
-                    // 批量插入
-                    let mut insert = $client.insert($table)?;
-                    for event in events {
-                        insert.write(&event).await?;
+                    // 严格模式下写入前先校验表结构
+                    if $importer.strict {
+                        $importer.validate_schema($file_path, $table).await?;
+                    }
+
+                    let mut batches = Box::pin($batches);
+                    let mut row_group = 0;
+                    let mut pending_rows = 0u64;
+                    let mut insert = None;
+
+                    // 每次只持有一个 row group 的数据
+                    while let Some(batch) = batches.next().await {
+                        let batch = batch
+                            .map_err(|e| ImportError::CorruptParquet($file_path.to_path_buf(), e))?;
+                        row_group += 1;
+                        if row_group <= $progress.row_groups {
+                            continue;
+                        }
+
+                        let events: Vec<$type> = deserialize_batch(&batch)?;
+                        pending_rows += events.len() as u64;
+
+                        if insert.is_none() {
+                            insert = Some($client.insert($table)?);
+                        }
+                        let writer = insert.as_mut().unwrap();
+                        for event in events {
+                            writer.write(&event).await?;
+                        }
+
+                        if $per_row_group {
+                            insert.take().unwrap().end().await?;
+                            $progress.row_groups = row_group;
+                            $progress.rows += std::mem::take(&mut pending_rows);
+                            if let Some((ledger, hash)) = $ledger {
+                                ledger
+                                    .record_progress(
+                                        $table,
+                                        hash,
+                                        &$file_path.display().to_string(),
+                                        $progress.rows,
+                                        $progress.row_groups as u64,
+                                    )
+                                    .await?;
+                            }
+                        }
+                    }
+
+                    if let Some(insert) = insert {
+                        insert.end().await?;
                     }
-                    insert.end().await?;
-                    
-                    Ok(row_count)
+                    $progress.row_groups = row_group;
+                    $progress.rows += pending_rows;
+
+                    Ok(())
                 }
             )*
-            _ => Err(format!("Unknown event type: {}", $event_type).into()),
+            _ => Err(ImportError::UnknownEventType($event_type.to_string())),
         }
     };
 }
 
+/// 列描述："name Type"
+fn describe_column(name: &str, column_type: &str) -> String {
+    format!("{} {}", name, column_type)
+}
+
 /// ClickHouse 导入器
 pub struct ClickHouseImporter {
     parquet_helper: ParquetHelper,
+    strict: bool,
+    ledger: Option<ImportLedger>,
+    client: Option<clickhouse::Client>, // None 时使用 ClickHouseClient::instance()
+    max_retries: usize,
+    initial_retry_delay: u64,
 }
 
 impl ClickHouseImporter {
+    /// 默认严格模式，ClickHouse 错误重试 3 次（首次等待 2 秒，之后指数退避）
     pub fn new() -> Self {
         Self {
             parquet_helper: ParquetHelper::new(),
+            strict: true,
+            ledger: None,
+            client: None,
+            max_retries: 3,
+            initial_retry_delay: 2,
+        }
+    }
+
+    /// 使用指定的 ClickHouse 客户端写入，而不是全局的 `ClickHouseClient::instance()`
+    pub fn with_client(mut self, client: clickhouse::Client) -> Self {
+        self.client = Some(client);
+        self
+    }
+
+    /// 设置 ClickHouse 错误时整个文件的重试次数和首次重试前的等待秒数
+    pub fn with_retry_config(mut self, max_retries: usize, initial_retry_delay: u64) -> Self {
+        self.max_retries = max_retries;
+        self.initial_retry_delay = initial_retry_delay;
+        self
+    }
+
+    pub fn max_retries(&self) -> usize {
+        self.max_retries
+    }
+
+    fn client(&self) -> &clickhouse::Client {
+        match &self.client {
+            Some(client) => client,
+            None => ClickHouseClient::instance().client(),
+        }
+    }
+
+    /// 设置是否在导入前校验表结构
+    ///
+    /// 有意只导入部分列时关闭（关闭后由 ClickHouse 自行决定能否写入）
+    pub fn with_strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    pub fn is_strict(&self) -> bool {
+        self.strict
+    }
+
+    /// 使用 ClickHouse 中的导入台账，跳过内容哈希已导入过的文件
+    pub fn with_ledger(mut self, ledger: ImportLedger) -> Self {
+        self.ledger = Some(ledger);
+        self
+    }
+
+    pub fn ledger(&self) -> Option<&ImportLedger> {
+        self.ledger.as_ref()
+    }
+
+    /// 写入数据用的客户端
+    ///
+    /// 有台账时等待异步插入完成，保证台账记录的文件确实已经写入
+    fn insert_client(&self) -> clickhouse::Client {
+        let client = self.client().clone();
+        if self.ledger.is_some() {
+            client.with_option("wait_for_async_insert", "1")
+        } else {
+            client
+        }
+    }
+
+    /// 写入前校验 Parquet schema 与目标表结构（DESCRIBE TABLE）
+    ///
+    /// # Arguments
+    /// * `file_path` - Parquet 文件路径
+    /// * `target_table` - 目标表名
+    ///
+    /// # Returns
+    /// * `SchemaMismatch` - 列名、类型或顺序不一致时列出所有不同的列
+    pub async fn validate_schema(
+        &self,
+        file_path: &Path,
+        target_table: &str,
+    ) -> std::result::Result<(), ImportError> {
+        // 先读文件 schema，损坏的文件不需要连接 ClickHouse
+        let schema = File::open(file_path)
+            .map_err(|e| ImportError::CorruptParquet(file_path.to_path_buf(), e.into()))
+            .and_then(|file| {
+                ArrowReaderMetadata::load(&file, Default::default())
+                    .map_err(|e| ImportError::CorruptParquet(file_path.to_path_buf(), e.into()))
+            })?
+            .schema()
+            .clone();
+
+        let table_columns = self
+            .client()
+            .query("SELECT name, type, default_type AS default_kind FROM (DESCRIBE TABLE ?)")
+            .bind(Identifier(target_table))
+            .fetch_all::<LiveColumn>()
+            .await?;
+
+        Self::compare_schema(&table_columns, &schema)
+    }
+
+    /// 按位置比较表列（忽略有默认值的列）与 Parquet 列的名称和类型
+    pub fn compare_schema(
+        table_columns: &[LiveColumn],
+        schema: &Schema,
+    ) -> std::result::Result<(), ImportError> {
+        let table_columns: Vec<&LiveColumn> = table_columns
+            .iter()
+            .filter(|c| c.default_kind.is_empty())
+            .collect();
+        let fields = schema.fields();
+
+        let mut expected = Vec::new();
+        let mut found = Vec::new();
+        for position in 0..table_columns.len().max(fields.len()) {
+            let column = table_columns.get(position);
+            let field = fields.get(position);
+
+            let field_type = field.map(|f| {
+                clickhouse_type(f.data_type())
+                    .map(|t| t.to_string())
+                    .unwrap_or_else(|| format!("{:?}", f.data_type()))
+            });
+            let same = match (column, field, &field_type) {
+                (Some(column), Some(field), Some(field_type)) => {
+                    column.name == *field.name()
+                        && normalize_type(&column.column_type) == normalize_type(field_type)
+                }
+                _ => false,
+            };
+
+            if !same {
+                if let Some(column) = column {
+                    expected.push(describe_column(&column.name, &column.column_type));
+                }
+                if let (Some(field), Some(field_type)) = (field, &field_type) {
+                    found.push(describe_column(field.name(), field_type));
+                }
+            }
+        }
+
+        if expected.is_empty() && found.is_empty() {
+            Ok(())
+        } else {
+            Err(ImportError::SchemaMismatch { expected, found })
         }
     }
 
-    /// 导入 Parquet 文件到 ClickHouse 表
+    /// 导入 Parquet 文件到 ClickHouse 表（严格模式下先校验表结构），整个文件一次 INSERT
+    ///
+    /// 配置了台账时先按文件内容哈希查询，已导入过的文件直接跳过并返回 0。
+    /// ClickHouse 错误时从头重试整个文件（写到一半的插入无法续传），
+    /// 重试耗尽后返回 `ImportError::ClickHouse`
     /// 
     /// # Arguments
     /// * `file_path` - Parquet 文件路径
@@ -53,24 +349,163 @@ impl ClickHouseImporter {
     /// 
     /// # Returns
     /// * `u64` - 导入的行数
+    /// * `ImportError` - 失败原因（未知事件类型 / 文件损坏 / 列不匹配 / ClickHouse 错误）
     pub async fn import_parquet(
         &self,
         file_path: &Path,
         target_table: &str,
         event_type: &str,
-    ) -> Result<u64> {
-        // 1. 读取 Parquet 文件
-        let batch = self.parquet_helper.read_parquet(file_path).await?;
-        
-        // 2. 获取 ClickHouse 客户端
-        let client = ClickHouseClient::instance().client();
-        
-        // 3. 根据事件类型反序列化并插入
+    ) -> std::result::Result<u64, ImportError> {
+        self.import_file(file_path, target_table, event_type, false).await
+    }
+
+    /// 流式导入：每个 row group 单独提交一次 INSERT
+    ///
+    /// 客户端和服务端都只需要持有一个 row group 的数据，适合很大的文件。
+    /// 重试时从第一个未提交的 row group 继续，不会重复写入已提交的部分；
+    /// 代价是失败时目标表里会留下部分文件的数据
+    pub async fn import_parquet_streaming(
+        &self,
+        file_path: &Path,
+        target_table: &str,
+        event_type: &str,
+    ) -> std::result::Result<u64, ImportError> {
+        self.import_file(file_path, target_table, event_type, true).await
+    }
+
+    async fn import_file(
+        &self,
+        file_path: &Path,
+        target_table: &str,
+        event_type: &str,
+        per_row_group: bool,
+    ) -> std::result::Result<u64, ImportError> {
+        let start = std::time::Instant::now();
+
+        // 0. 查询台账：已导入完成的文件不再写入，流式导入中断的文件从已提交的 row group 之后继续
+        let mut progress = ImportProgress::default();
+        let content_hash = match &self.ledger {
+            Some(ledger) => {
+                let hash = import_ledger::content_hash(file_path)
+                    .map_err(|e| ImportError::CorruptParquet(file_path.to_path_buf(), e.into()))?;
+                match ledger.state(target_table, &hash).await? {
+                    Some(state) if state.complete => {
+                        tracing::info!(
+                            table = target_table,
+                            file = %file_path.display(),
+                            rows = state.row_count,
+                            "file already in import ledger, skipping"
+                        );
+                        return Ok(0);
+                    }
+                    Some(state) => {
+                        tracing::info!(
+                            table = target_table,
+                            file = %file_path.display(),
+                            committed_row_groups = state.row_groups,
+                            committed_rows = state.row_count,
+                            "resuming partially imported file from import ledger"
+                        );
+                        progress.row_groups = state.row_groups as usize;
+                        progress.rows = state.row_count;
+                    }
+                    None => {}
+                }
+                Some(hash)
+            }
+            None => None,
+        };
+        let resumed_rows = progress.rows;
+        let ledger_progress = self.ledger.as_ref().zip(content_hash.as_deref());
+
+        // 1. 写入，ClickHouse 错误时按指数退避重试（流式模式下跳过已提交的 row group）
+        let mut attempt = 0;
+        let rows = loop {
+            match self
+                .insert_file(
+                    file_path,
+                    target_table,
+                    event_type,
+                    &mut progress,
+                    per_row_group,
+                    ledger_progress,
+                )
+                .await
+            {
+                Ok(()) => {
+                    if attempt > 0 {
+                        tracing::info!(
+                            table = target_table,
+                            file = %file_path.display(),
+                            attempts = attempt,
+                            "import recovered after retries"
+                        );
+                    }
+                    break progress.rows;
+                }
+                Err(ImportError::ClickHouse(e)) if attempt < self.max_retries => {
+                    attempt += 1;
+                    let delay = self.initial_retry_delay * 2_u64.pow(attempt as u32 - 1);
+                    tracing::warn!(
+                        table = target_table,
+                        file = %file_path.display(),
+                        attempt,
+                        max_attempts = self.max_retries + 1,
+                        committed_row_groups = progress.row_groups,
+                        retry_in_secs = delay,
+                        error = %e,
+                        "clickhouse insert failed, retrying file"
+                    );
+                    tokio::time::sleep(Duration::from_secs(delay)).await;
+                }
+                Err(e) => return Err(e),
+            }
+        };
+
+        // 2. 数据写入完成后记账
+        if let (Some(ledger), Some(hash)) = (&self.ledger, &content_hash) {
+            ledger
+                .record(target_table, hash, &file_path.display().to_string(), rows)
+                .await?;
+        }
+
+        // 返回本次写入的行数（不含之前中断时已提交的部分）
+        let rows = rows - resumed_rows;
+        tracing::debug!(
+            table = target_table,
+            file = %file_path.display(),
+            rows,
+            per_row_group,
+            duration_ms = start.elapsed().as_millis() as u64,
+            "inserted parquet file"
+        );
+        Ok(rows)
+    }
+
+    /// 读取整个文件并写入一次（不重试），写入成功的行数记到 `progress`
+    async fn insert_file(
+        &self,
+        file_path: &Path,
+        target_table: &str,
+        event_type: &str,
+        progress: &mut ImportProgress,
+        per_row_group: bool,
+        ledger: Option<(&ImportLedger, &str)>,
+    ) -> std::result::Result<(), ImportError> {
+        // 流式读取 Parquet 文件（按 row group）
+        let batches = self.parquet_helper.read_parquet_batches(file_path);
+
+        // 根据事件类型反序列化并插入（共享 ClickHouse 客户端）
         deserialize_and_insert!(
-            batch,
+            self,
+            batches,
+            file_path,
             event_type,
             target_table,
-            client,
+            self.insert_client(),
+            progress,
+            per_row_group,
+            ledger,
             "PumpfunTradeEventV2" => PumpfunTradeEventV2,
             "PumpfunCreateEventV2" => PumpfunCreateEventV2,
             "PumpfunMigrateEventV2" => PumpfunMigrateEventV2,
@@ -79,6 +514,9 @@ impl ClickHouseImporter {
             "PumpfunAmmWithdrawEventV2" => PumpfunAmmWithdrawEventV2,
             "PumpfunAmmBuyEventV2" => PumpfunAmmBuyEventV2,
             "PumpfunAmmSellEventV2" => PumpfunAmmSellEventV2,
+            "PumpfunAmmCollectCoinCreatorFeeEventV2" => PumpfunAmmCollectCoinCreatorFeeEventV2,
+            "PumpfunAmmDisableEventV2" => PumpfunAmmDisableEventV2,
+            "PumpfunAmmSetParamsEventV2" => PumpfunAmmSetParamsEventV2,
         )
     }
 }
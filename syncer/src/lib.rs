@@ -1,18 +1,31 @@
+pub mod compactor;
 pub mod config;
 pub mod extractor;
+pub mod import_ledger;
+pub mod import_manifest;
 pub mod importer;
 pub mod parquet_helper;
 pub mod pipeline;
+pub mod s3_transport;
 pub mod transport;
 pub mod sync_checker;
 pub mod sync_config;
 
 // Re-exports for convenience
 pub use config::{LocalConfig, RemoteConfig, RemoteServerConfig};
-pub use extractor::ClickHouseExtractor;
-pub use importer::ClickHouseImporter;
-pub use parquet_helper::ParquetHelper;
-pub use pipeline::{LocalPipeline, RemotePipeline};
-pub use transport::RsyncTransport;
-pub use sync_checker::{SyncChecker, SyncStats};
+pub use extractor::{ClickHouseExtractor, ExtractorConfig};
+pub use import_ledger::ImportLedger;
+pub use import_manifest::ImportManifest;
+pub use importer::{ClickHouseImporter, ImportError};
+pub use parquet_helper::{
+    FilteredRead, ParquetColumn, ParquetHelper, ParquetInfo, ParquetWriteOptions, PartitionLayout,
+    TimestampRange,
+};
+pub use pipeline::{
+    FailedTransfer, LocalPipeline, PipelineReport, RemotePipeline, RemoteReport, RowCountMismatch,
+    TableSummary,
+};
+pub use s3_transport::{S3Config, S3Transport};
+pub use transport::{RsyncError, RsyncOptions, RsyncTransport, Transport};
+pub use sync_checker::{SyncChecker, SyncStats, TableSyncStat};
 pub use sync_config::SyncConfig;
@@ -1,7 +1,14 @@
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use std::error::Error;
+use std::path::PathBuf;
+use tracing_subscriber::EnvFilter;
 
-use syncer::{LocalConfig, LocalPipeline, RemoteConfig, RemotePipeline, SyncChecker, SyncConfig};
+use syncer::compactor::{CompactOptions, ParquetCompactor};
+use syncer::sync_config::SyncMode;
+use syncer::{
+    LocalConfig, LocalPipeline, ParquetHelper, RemoteConfig, RemotePipeline, SyncChecker,
+    SyncConfig,
+};
 
 type Result<T> = std::result::Result<T, Box<dyn Error>>;
 
@@ -9,9 +16,12 @@ type Result<T> = std::result::Result<T, Box<dyn Error>>;
 #[command(name = "syncer")]
 #[command(about = "ClickHouse data export/import/sync pipeline", long_about = None)]
 struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     /// Pipeline mode: "local", "remote", or "sync-check"
     #[arg(long)]
-    mode: String,
+    mode: Option<String>,
 
     /// Path to the configuration file (optional for sync-check)
     #[arg(short, long)]
@@ -49,34 +59,168 @@ struct Cli {
     /// Table mappings in the form local:remote (can be repeated)
     #[arg(long = "map")]
     table_mappings: Vec<String>,
+
+    /// Drill differing minutes into 10-second buckets and sync only those
+    #[arg(long)]
+    fine_grained: bool,
+
+    /// Number of tables to check concurrently (default 1)
+    #[arg(long)]
+    max_concurrent_tables: Option<usize>,
+
+    /// Only report differing minutes, never write to the remote cluster
+    #[arg(long)]
+    report_only: bool,
+
+    /// After syncing, run OPTIMIZE ... FINAL DEDUPLICATE on each remote table that received rows
+    #[arg(long)]
+    deduplicate: bool,
+
+    /// Print the sync-check summary as a single JSON line (last line of stdout)
+    #[arg(long)]
+    json: bool,
+
+    /// Local mode: extract and write parquet files only, skip the transfer
+    #[arg(long)]
+    no_transfer: bool,
+
+    /// Local mode: run only this table instead of the config's `tables` (can be repeated)
+    #[arg(long = "only-table")]
+    only_tables: Vec<String>,
+
+    /// Write logs as JSON lines (level is controlled by RUST_LOG, default info)
+    #[arg(long, global = true)]
+    log_json: bool,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Compact a month of daily parquet files into a single monthly file
+    Compact {
+        /// Storage root containing one folder per table
+        #[arg(long)]
+        dir: PathBuf,
+
+        /// Table name (folder under --dir)
+        #[arg(long)]
+        table: String,
+
+        /// Month to compact, in YYYY-MM format
+        #[arg(long)]
+        month: String,
+
+        /// Re-sort rows by slot, transaction_index, instruction_index
+        #[arg(long)]
+        sort: bool,
+
+        /// Delete the daily files after the monthly file is verified
+        #[arg(long)]
+        remove_originals: bool,
+    },
+    /// Print row count, row groups, columns, compression and size from parquet footers
+    ParquetInfo {
+        /// Parquet files to inspect
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+    },
+}
+
+/// 日志写到 stderr，stdout 只保留命令输出（如 --json 的汇总行）
+fn init_tracing(json: bool) {
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+    let builder = tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(std::io::stderr);
+    if json {
+        builder.json().init();
+    } else {
+        builder.init();
+    }
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
     let cli = Cli::parse();
+    init_tracing(cli.log_json);
+
+    if let Some(Command::Compact { dir, table, month, sort, remove_originals }) = &cli.command {
+        let month = ParquetCompactor::parse_month(month)?;
+        let options = CompactOptions {
+            sort: *sort,
+            remove_originals: *remove_originals,
+        };
+
+        tracing::info!(%table, month = %month.format("%Y-%m"), "compacting");
+        let report = ParquetCompactor::new()
+            .compact_month(dir, table, month, &options)
+            .await?;
+        println!(
+            "✅ Compacted {} daily files into {:?} ({} rows)",
+            report.input_files.len(),
+            report.output_path,
+            report.rows
+        );
+        return Ok(());
+    }
+
+    if let Some(Command::ParquetInfo { files }) = &cli.command {
+        let helper = ParquetHelper::new();
+        for file in files {
+            helper.file_info(file)?.print_summary(file);
+        }
+        return Ok(());
+    }
+
+    let mode = cli.mode.as_deref().ok_or("--mode is required")?;
 
-    match cli.mode.as_str() {
+    match mode {
         "local" => {
             let config_path = cli.config.as_ref().ok_or("--config is required for local mode")?;
-            let config = LocalConfig::from_file(config_path)?;
-            let pipeline = LocalPipeline::new(config);
+            let mut config = LocalConfig::from_file(config_path)?;
+            if cli.no_transfer {
+                config.no_transfer = true;
+            }
+            config.override_tables(&cli.only_tables)?;
+            let no_transfer = config.no_transfer;
+            let pipeline = LocalPipeline::new(config)?;
             
-            println!("Starting local mode pipeline...");
-            pipeline.run().await?;
-            println!("Local mode completed!");
+            tracing::info!("starting local mode pipeline");
+            let report = pipeline.run().await?;
+            if no_transfer {
+                println!("Written files ({}):", report.written_files.len());
+                for path in &report.written_files {
+                    println!("  {}", path.display());
+                }
+            }
+            if !report.failed_transfers.is_empty() {
+                return Err(format!(
+                    "{} file(s) failed to transfer and were kept locally",
+                    report.failed_transfers.len()
+                )
+                .into());
+            }
+            tracing::info!("local mode completed");
         }
         "remote" => {
             let config_path = cli.config.as_ref().ok_or("--config is required for remote mode")?;
             let config = RemoteConfig::from_file(config_path)?;
             let pipeline = RemotePipeline::new(config);
             
-            println!("Starting remote mode pipeline...");
-            pipeline.run().await?;
-            println!("Remote mode completed!");
+            tracing::info!("starting remote mode pipeline");
+            let report = pipeline.run().await?;
+            report.print_summary();
+            if !report.mismatches.is_empty() {
+                return Err(format!(
+                    "{} day(s) failed row count verification",
+                    report.mismatches.len()
+                )
+                .into());
+            }
+            tracing::info!("remote mode completed");
         }
         "sync-check" => {
             // build config from file if provided, otherwise from CLI flags
-            let config = if let Some(path) = &cli.config {
+            let mut config = if let Some(path) = &cli.config {
                 SyncConfig::from_file(path)?
             } else {
                 // require required flags
@@ -113,27 +257,49 @@ async fn main() -> Result<()> {
                     remote_user,
                     remote_password,
                     table_mappings: mappings,
+                    same_name: Vec::new(),
                     check_days,
                     lag_hours,
+                    dedup_keys: std::collections::HashMap::new(),
+                    fine_grained: cli.fine_grained,
+                    max_concurrent_tables: cli.max_concurrent_tables.unwrap_or(1),
+                    mode: SyncMode::default(),
+                    sync_retries: 3,
+                    sync_retry_delay_secs: 2,
+                    deduplicate_after_sync: false,
                 }
             };
+            if cli.report_only {
+                config.mode = SyncMode::ReportOnly;
+            }
+            if cli.deduplicate {
+                config.deduplicate_after_sync = true;
+            }
+            // 配置文件已在加载时校验，这里覆盖 CLI 构造和命令行覆盖后的结果
+            config.ensure_valid()?;
 
             let checker = SyncChecker::new(config);
             
-            println!("Starting sync check mode...");
+            tracing::info!("starting sync check mode");
             let stats = checker.check_and_sync().await?;
-            stats.print_summary();
+            if cli.json {
+                println!("{}", stats.to_json());
+            } else {
+                stats.print_summary();
+            }
             
             if !stats.errors.is_empty() {
                 return Err(format!("Sync completed with {} errors", stats.errors.len()).into());
             }
             
-            println!("\n✅ Sync check completed successfully!");
+            if !cli.json {
+                println!("\n✅ Sync check completed successfully!");
+            }
         }
         _ => {
             return Err(format!(
                 "Invalid mode: {}. Use 'local', 'remote', or 'sync-check'",
-                cli.mode
+                mode
             )
             .into());
         }
@@ -1,21 +1,272 @@
+use arrow::array::{Array, AsArray, BooleanArray};
+use arrow::compute::{cast, filter_record_batch};
+use arrow::datatypes::{DataType, Int64Type};
 use arrow::record_batch::RecordBatch;
 use chrono::NaiveDate;
-use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use futures::stream::{self, Stream, StreamExt};
+use parquet::arrow::arrow_reader::statistics::StatisticsConverter;
+use parquet::arrow::arrow_reader::{ArrowReaderMetadata, ParquetRecordBatchReaderBuilder};
 use parquet::arrow::ArrowWriter;
 use parquet::basic::Compression;
-use parquet::file::properties::WriterProperties;
+use parquet::file::properties::{
+    EnabledStatistics, WriterProperties, DEFAULT_MAX_ROW_GROUP_SIZE,
+};
+use serde::{Deserialize, Serialize};
 use std::error::Error;
 use std::fs::{self, File};
 use std::path::{Path, PathBuf};
 
 pub type Result<T> = std::result::Result<T, Box<dyn Error>>;
 
+/// Parquet 写入选项
+#[derive(Debug, Clone, Copy)]
+pub struct ParquetWriteOptions {
+    /// 压缩算法（例如 `Compression::ZSTD(ZstdLevel::try_new(3)?)`）
+    pub compression: Compression,
+    /// 每个 row group 的最大行数
+    pub row_group_size: usize,
+    /// 是否写入列统计信息（min/max 等），`read_parquet_filtered` 依赖它跳过 row group
+    pub enable_statistics: bool,
+}
+
+impl Default for ParquetWriteOptions {
+    fn default() -> Self {
+        Self {
+            compression: Compression::SNAPPY,
+            row_group_size: DEFAULT_MAX_ROW_GROUP_SIZE,
+            enable_statistics: true,
+        }
+    }
+}
+
+impl ParquetWriteOptions {
+    fn writer_properties(&self) -> WriterProperties {
+        let statistics = if self.enable_statistics {
+            EnabledStatistics::Page
+        } else {
+            EnabledStatistics::None
+        };
+
+        WriterProperties::builder()
+            .set_compression(self.compression)
+            .set_max_row_group_size(self.row_group_size)
+            .set_statistics_enabled(statistics)
+            .build()
+    }
+}
+
+/// Parquet 输出目录布局
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PartitionLayout {
+    /// {root}/{table}/{table}_{YYYY-MM-DD}.parquet
+    #[default]
+    Flat,
+    /// {root}/{table}/year=YYYY/month=MM/day=DD/part.parquet
+    HiveDaily,
+}
+
+impl PartitionLayout {
+    /// 计算某天数据文件的路径（不创建目录）
+    pub fn file_path(&self, root: &Path, table: &str, date: NaiveDate) -> PathBuf {
+        let table_dir = root.join(table);
+        match self {
+            PartitionLayout::Flat => {
+                table_dir.join(format!("{}_{}.parquet", table, date.format("%Y-%m-%d")))
+            }
+            PartitionLayout::HiveDaily => table_dir
+                .join(format!("year={}", date.format("%Y")))
+                .join(format!("month={}", date.format("%m")))
+                .join(format!("day={}", date.format("%d")))
+                .join("part.parquet"),
+        }
+    }
+
+    /// 扫描表目录下的所有 .parquet 文件，按日期顺序返回
+    /// 
+    /// Flat 只扫描当前目录；HiveDaily 递归遍历 year=/month=/day= 子目录
+    pub fn discover_files(&self, table_dir: &Path) -> Result<Vec<PathBuf>> {
+        let mut files = Vec::new();
+        match self {
+            PartitionLayout::Flat => collect_parquet_files(table_dir, &mut files)?,
+            PartitionLayout::HiveDaily => {
+                for year_dir in partition_dirs(table_dir, "year=")? {
+                    for month_dir in partition_dirs(&year_dir, "month=")? {
+                        for day_dir in partition_dirs(&month_dir, "day=")? {
+                            collect_parquet_files(&day_dir, &mut files)?;
+                        }
+                    }
+                }
+            }
+        }
+
+        // 分区值均为定长补零，按路径排序即按日期排序
+        files.sort();
+        Ok(files)
+    }
+
+    /// 从文件路径解析数据日期（`file_path` 的逆操作）
+    ///
+    /// 不符合布局命名的文件（例如压缩后的月度文件）返回 None
+    pub fn file_date(&self, file_path: &Path) -> Option<NaiveDate> {
+        match self {
+            PartitionLayout::Flat => {
+                let stem = file_path.file_stem()?.to_str()?;
+                let (_, date) = stem.rsplit_once('_')?;
+                NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()
+            }
+            PartitionLayout::HiveDaily => {
+                let day_dir = file_path.parent()?;
+                let month_dir = day_dir.parent()?;
+                let year_dir = month_dir.parent()?;
+                let value = |dir: &Path, prefix: &str| -> Option<u32> {
+                    dir.file_name()?.to_str()?.strip_prefix(prefix)?.parse().ok()
+                };
+                NaiveDate::from_ymd_opt(
+                    value(year_dir, "year=")? as i32,
+                    value(month_dir, "month=")?,
+                    value(day_dir, "day=")?,
+                )
+            }
+        }
+    }
+}
+
+/// 列出目录下以 `prefix` 开头的子目录
+fn partition_dirs(dir: &Path, prefix: &str) -> Result<Vec<PathBuf>> {
+    let mut dirs = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let matches = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .map(|name| name.starts_with(prefix))
+            .unwrap_or(false);
+        if path.is_dir() && matches {
+            dirs.push(path);
+        }
+    }
+    Ok(dirs)
+}
+
+/// 收集目录下（不递归）的 .parquet 文件
+fn collect_parquet_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let is_parquet = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(|ext| ext == "parquet")
+            .unwrap_or(false);
+        if path.is_file() && is_parquet {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// 按时间过滤时使用的列
+pub const TIMESTAMP_COLUMN: &str = "timestamp";
+
+/// 时间戳区间 [start, end)，单位与 timestamp 列一致（事件表为 Unix 秒）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimestampRange {
+    pub start: i64,
+    pub end: i64,
+}
+
+impl TimestampRange {
+    pub fn new(start: i64, end: i64) -> Self {
+        Self { start, end }
+    }
+
+    /// 取值范围为 [min, max] 的 row group 是否可能包含区间内的行
+    pub fn overlaps(&self, min: i64, max: i64) -> bool {
+        min < self.end && max >= self.start
+    }
+
+    /// 只保留 timestamp 在区间内的行
+    fn filter(&self, batch: &RecordBatch) -> Result<RecordBatch> {
+        let column = batch
+            .column_by_name(TIMESTAMP_COLUMN)
+            .ok_or_else(|| format!("Column '{}' not found", TIMESTAMP_COLUMN))?;
+        let timestamps = cast(column, &DataType::Int64)?;
+        let mask: BooleanArray = timestamps
+            .as_primitive::<Int64Type>()
+            .iter()
+            .map(|value| value.map(|value| value >= self.start && value < self.end))
+            .collect();
+        Ok(filter_record_batch(batch, &mask)?)
+    }
+}
+
+/// `read_parquet_filtered` 的结果
+#[derive(Debug, Clone)]
+pub struct FilteredRead {
+    /// 区间内的行（所有读取的 row group 合并）
+    pub batch: RecordBatch,
+    pub row_groups_read: usize,
+    /// 按统计信息判断不相交、没有解码的 row group 数
+    pub row_groups_skipped: usize,
+}
+
+/// Parquet 文件概要，只读取 footer，不解码数据
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParquetInfo {
+    pub rows: u64,
+    pub row_groups: usize,
+    pub columns: Vec<ParquetColumn>,
+    /// 列块使用的压缩算法（去重，按首次出现的顺序）
+    pub compression: Vec<Compression>,
+    /// 文件大小（字节）
+    pub file_bytes: u64,
+    /// 所有 row group 未压缩的数据大小（字节）
+    pub uncompressed_bytes: u64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParquetColumn {
+    pub name: String,
+    pub data_type: DataType,
+}
+
+impl ParquetInfo {
+    pub fn print_summary(&self, file_path: &Path) {
+        let mb = |bytes: u64| bytes as f64 / (1024.0 * 1024.0);
+        println!("{}", file_path.display());
+        println!("  Rows:        {}", self.rows);
+        println!("  Row groups:  {}", self.row_groups);
+        println!(
+            "  Size:        {:.2} MB ({:.2} MB uncompressed)",
+            mb(self.file_bytes),
+            mb(self.uncompressed_bytes)
+        );
+        println!("  Compression: {:?}", self.compression);
+        println!("  Columns ({}):", self.columns.len());
+        for column in &self.columns {
+            println!("    {} {}", column.name, column.data_type);
+        }
+    }
+}
+
 /// Parquet 文件助手（读写）
-pub struct ParquetHelper;
+pub struct ParquetHelper {
+    options: ParquetWriteOptions,
+}
 
 impl ParquetHelper {
+    /// 默认选项：Snappy 压缩
     pub fn new() -> Self {
-        Self
+        Self::with_options(ParquetWriteOptions::default())
+    }
+
+    /// 使用自定义写入选项
+    pub fn with_options(options: ParquetWriteOptions) -> Self {
+        Self { options }
+    }
+
+    pub fn options(&self) -> &ParquetWriteOptions {
+        &self.options
     }
 
     /// 将 RecordBatch 写入 Parquet 文件
@@ -35,26 +286,183 @@ impl ParquetHelper {
         batch: RecordBatch,
         output_dir: &Path,
     ) -> Result<PathBuf> {
-        // 创建表目录: output_dir/table/
-        let table_dir = output_dir.join(table);
-        fs::create_dir_all(&table_dir)?;
+        // 生成文件: output_dir/{table}/{table}_{YYYY-MM-DD}.parquet
+        self.write_partitioned_parquet(table, date, batch, output_dir, PartitionLayout::Flat)
+            .await
+    }
+
+    /// 将 RecordBatch 追加到某天已有的 Parquet 文件（不存在时等同 `write_daily_parquet`）
+    ///
+    /// 已有的 row group 原样复制到临时文件，新数据作为新的 row group 写在后面，
+    /// 完成后再替换原文件，中途失败不会破坏已有文件。列名或类型与已有文件
+    /// 不一致时返回错误，不会写入
+    ///
+    /// # Returns
+    /// * `PathBuf` - 文件路径
+    pub async fn append_daily_parquet(
+        &self,
+        table: &str,
+        date: NaiveDate,
+        batch: RecordBatch,
+        output_dir: &Path,
+    ) -> Result<PathBuf> {
+        let file_path = PartitionLayout::Flat.file_path(output_dir, table, date);
+        if !file_path.exists() {
+            return self.write_daily_parquet(table, date, batch, output_dir).await;
+        }
+
+        // 1. 检查 schema 是否一致
+        let existing_schema = ArrowReaderMetadata::load(&File::open(&file_path)?, Default::default())?
+            .schema()
+            .clone();
+        if existing_schema.fields() != batch.schema().fields() {
+            let describe = |schema: &arrow::datatypes::Schema| {
+                schema
+                    .fields()
+                    .iter()
+                    .map(|f| format!("{} {}", f.name(), f.data_type()))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            };
+            return Err(format!(
+                "Schema mismatch appending to {:?}: existing [{}], new [{}]",
+                file_path,
+                describe(&existing_schema),
+                describe(&batch.schema())
+            )
+            .into());
+        }
+
+        // 2. 已有 row group + 新数据写入临时文件（统一使用已有文件的 schema）
+        let batch = RecordBatch::try_new(existing_schema.clone(), batch.columns().to_vec())?;
+        let temp_path = file_path.with_extension("parquet.tmp");
+        let result = async {
+            let file = File::create(&temp_path)?;
+            let mut writer =
+                ArrowWriter::try_new(file, existing_schema, Some(self.options.writer_properties()))?;
+            let mut existing = Box::pin(self.read_parquet_batches(&file_path));
+            while let Some(existing_batch) = existing.next().await {
+                writer.write(&existing_batch?)?;
+                // 保持原有的 row group 边界
+                writer.flush()?;
+            }
+            writer.write(&batch)?;
+            writer.close()?;
+            Ok::<(), Box<dyn Error>>(())
+        }
+        .await;
+        if let Err(e) = result {
+            let _ = fs::remove_file(&temp_path);
+            return Err(e);
+        }
+
+        // 3. 替换原文件
+        fs::rename(&temp_path, &file_path)?;
+        Ok(file_path)
+    }
+
+    /// 按指定目录布局将 RecordBatch 写入 Parquet 文件
+    /// 
+    /// # Arguments
+    /// * `table` - 表名
+    /// * `date` - 日期
+    /// * `batch` - Arrow RecordBatch 数据
+    /// * `root` - 输出根目录
+    /// * `layout` - 目录布局（Flat 或 HiveDaily）
+    /// 
+    /// # Returns
+    /// * `PathBuf` - 生成的文件路径
+    pub async fn write_partitioned_parquet(
+        &self,
+        table: &str,
+        date: NaiveDate,
+        batch: RecordBatch,
+        root: &Path,
+        layout: PartitionLayout,
+    ) -> Result<PathBuf> {
+        let file_path = layout.file_path(root, table, date);
 
-        // 生成文件名: {table}_{YYYY-MM-DD}.parquet
-        let filename = format!("{}_{}.parquet", table, date.format("%Y-%m-%d"));
-        let file_path = table_dir.join(&filename);
+        // 创建（可能嵌套的）分区目录
+        if let Some(parent) = file_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
 
-        // 配置 Snappy 压缩
-        let props = WriterProperties::builder()
-            .set_compression(Compression::SNAPPY)
-            .build();
+        self.write_parquet_file(&batch, &file_path)?;
+
+        Ok(file_path)
+    }
+
+    /// 按当前写入选项将 RecordBatch 写入指定路径
+    /// 
+    /// # Arguments
+    /// * `batch` - Arrow RecordBatch 数据
+    /// * `file_path` - 目标文件路径（父目录需已存在）
+    /// 
+    /// # Returns
+    /// * `u64` - 写入文件中记录的总行数
+    pub fn write_parquet_file(&self, batch: &RecordBatch, file_path: &Path) -> Result<u64> {
+        // 按选项配置压缩、row group 大小和统计信息
+        let props = self.options.writer_properties();
 
         // 写入 Parquet 文件
-        let file = File::create(&file_path)?;
+        let file = File::create(file_path)?;
         let mut writer = ArrowWriter::try_new(file, batch.schema(), Some(props))?;
-        writer.write(&batch)?;
+        writer.write(batch)?;
+        let metadata = writer.close()?;
+
+        Ok(metadata.num_rows as u64)
+    }
+
+    /// 按当前写入选项在内存中编码，返回 Parquet 文件的字节数（不落盘）
+    pub fn encoded_size(&self, batch: &RecordBatch) -> Result<u64> {
+        let mut buffer = Vec::new();
+        let mut writer = ArrowWriter::try_new(&mut buffer, batch.schema(), Some(self.options.writer_properties()))?;
+        writer.write(batch)?;
         writer.close()?;
+        Ok(buffer.len() as u64)
+    }
 
-        Ok(file_path)
+    /// 读取 Parquet 文件元数据中记录的总行数（不读取数据）
+    pub fn parquet_row_count(&self, file_path: &Path) -> Result<u64> {
+        Ok(self.file_info(file_path)?.rows)
+    }
+
+    /// 从 footer 读取行数、row group 数、列、压缩算法和大小（不读取数据）
+    pub fn file_info(&self, file_path: &Path) -> Result<ParquetInfo> {
+        let file = File::open(file_path)?;
+        let file_bytes = file.metadata()?.len();
+        let metadata = ArrowReaderMetadata::load(&file, Default::default())?;
+        let parquet_metadata = metadata.metadata();
+
+        let mut compression = Vec::new();
+        for row_group in parquet_metadata.row_groups() {
+            for column in row_group.columns() {
+                if !compression.contains(&column.compression()) {
+                    compression.push(column.compression());
+                }
+            }
+        }
+
+        Ok(ParquetInfo {
+            rows: parquet_metadata.file_metadata().num_rows() as u64,
+            row_groups: parquet_metadata.num_row_groups(),
+            columns: metadata
+                .schema()
+                .fields()
+                .iter()
+                .map(|field| ParquetColumn {
+                    name: field.name().clone(),
+                    data_type: field.data_type().clone(),
+                })
+                .collect(),
+            compression,
+            file_bytes,
+            uncompressed_bytes: parquet_metadata
+                .row_groups()
+                .iter()
+                .map(|row_group| row_group.total_byte_size() as u64)
+                .sum(),
+        })
     }
 
     /// 从 Parquet 文件读取数据
@@ -65,16 +473,10 @@ impl ParquetHelper {
     /// # Returns
     /// * `RecordBatch` - Arrow RecordBatch 数据（所有行合并）
     pub async fn read_parquet(&self, file_path: &Path) -> Result<RecordBatch> {
-        // 打开 Parquet 文件
-        let file = File::open(file_path)?;
-
-        // 创建 reader
-        let builder = ParquetRecordBatchReaderBuilder::try_new(file)?;
-        let mut reader = builder.build()?;
-
-        // 读取所有批次并合并（天级别数据，全加载）
+        // 读取所有 row group 并合并（天级别数据，全加载）
+        let mut batch_stream = Box::pin(self.read_parquet_batches(file_path));
         let mut batches = Vec::new();
-        while let Some(batch) = reader.next() {
+        while let Some(batch) = batch_stream.next().await {
             batches.push(batch?);
         }
 
@@ -94,6 +496,133 @@ impl ParquetHelper {
 
         Ok(merged)
     }
+
+    /// 只读取 timestamp 在 `range` 内的行
+    ///
+    /// 先按 row group 的 min/max 统计信息跳过不相交的 row group（不解码），
+    /// 再过滤读取到的行。没有统计信息的 row group（`enable_statistics = false`
+    /// 写出的文件）只能全部读取，结果相同但没有加速
+    pub fn read_parquet_filtered(&self, file_path: &Path, range: TimestampRange) -> Result<FilteredRead> {
+        let file = File::open(file_path)?;
+        let metadata = ArrowReaderMetadata::load(&file, Default::default())?;
+        let schema = metadata.schema().clone();
+        if schema.field_with_name(TIMESTAMP_COLUMN).is_err() {
+            return Err(format!("Column '{}' not found in {:?}", TIMESTAMP_COLUMN, file_path).into());
+        }
+
+        // 1. 按统计信息挑选 row group
+        let parquet_metadata = metadata.metadata().clone();
+        let row_groups = parquet_metadata.row_groups();
+        let converter = StatisticsConverter::try_new(
+            TIMESTAMP_COLUMN,
+            &schema,
+            parquet_metadata.file_metadata().schema_descr(),
+        )?;
+        let mins = cast(&converter.row_group_mins(row_groups.iter())?, &DataType::Int64)?;
+        let maxes = cast(&converter.row_group_maxes(row_groups.iter())?, &DataType::Int64)?;
+        let mins = mins.as_primitive::<Int64Type>();
+        let maxes = maxes.as_primitive::<Int64Type>();
+
+        let selected: Vec<usize> = (0..row_groups.len())
+            .filter(|&index| row_groups[index].num_rows() > 0)
+            .filter(|&index| {
+                mins.is_null(index)
+                    || maxes.is_null(index)
+                    || range.overlaps(mins.value(index), maxes.value(index))
+            })
+            .collect();
+        let row_groups_skipped = row_groups.len() - selected.len();
+
+        // 2. 只解码选中的 row group，再逐行过滤
+        let mut batches = Vec::new();
+        if !selected.is_empty() {
+            let reader = ParquetRecordBatchReaderBuilder::new_with_metadata(file, metadata)
+                .with_row_groups(selected.clone())
+                .build()?;
+            for batch in reader {
+                batches.push(range.filter(&batch?)?);
+            }
+        }
+
+        Ok(FilteredRead {
+            batch: arrow::compute::concat_batches(&schema, &batches)?,
+            row_groups_read: selected.len(),
+            row_groups_skipped,
+        })
+    }
+
+    /// 按 row group 流式读取 Parquet 文件，避免大文件一次性加载到内存
+    /// 
+    /// # Arguments
+    /// * `file_path` - Parquet 文件路径
+    /// 
+    /// # Returns
+    /// * `Stream<Item = Result<RecordBatch>>` - 每个 row group 一个批次；
+    ///   打开文件失败时流的第一个元素即为错误
+    pub fn read_parquet_batches(
+        &self,
+        file_path: &Path,
+    ) -> impl Stream<Item = Result<RecordBatch>> + use<> {
+        let row_groups: Box<dyn Iterator<Item = Result<RecordBatch>>> =
+            match RowGroupReader::open(file_path) {
+                Ok(reader) => Box::new(reader),
+                Err(e) => Box::new(std::iter::once(Err(e))),
+            };
+        stream::iter(row_groups)
+    }
+}
+
+/// 逐个 row group 读取的迭代器
+struct RowGroupReader {
+    file: File,
+    metadata: ArrowReaderMetadata,
+    next_row_group: usize,
+}
+
+impl RowGroupReader {
+    fn open(file_path: &Path) -> Result<Self> {
+        let file = File::open(file_path)?;
+        let metadata = ArrowReaderMetadata::load(&file, Default::default())?;
+        Ok(Self {
+            file,
+            metadata,
+            next_row_group: 0,
+        })
+    }
+
+    fn read_row_group(&self, index: usize) -> Result<RecordBatch> {
+        let num_rows = self.metadata.metadata().row_group(index).num_rows() as usize;
+        let reader = ParquetRecordBatchReaderBuilder::new_with_metadata(
+            self.file.try_clone()?,
+            self.metadata.clone(),
+        )
+        .with_row_groups(vec![index])
+        .with_batch_size(num_rows.max(1))
+        .build()?;
+
+        let batches = reader.collect::<std::result::Result<Vec<_>, _>>()?;
+        Ok(arrow::compute::concat_batches(
+            self.metadata.schema(),
+            &batches,
+        )?)
+    }
+}
+
+impl Iterator for RowGroupReader {
+    type Item = Result<RecordBatch>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let num_row_groups = self.metadata.metadata().num_row_groups();
+        // 跳过空 row group
+        while self.next_row_group < num_row_groups {
+            let index = self.next_row_group;
+            self.next_row_group += 1;
+            if self.metadata.metadata().row_group(index).num_rows() > 0 {
+                return Some(self.read_row_group(index));
+            }
+        }
+        None
+    }
 }
 
 impl Default for ParquetHelper {
@@ -1,13 +1,263 @@
+use futures::FutureExt;
+use futures::stream::{self, StreamExt};
+use std::collections::BTreeMap;
 use std::error::Error;
-use chrono::Utc;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use chrono::{NaiveDate, Utc};
+use tracing::Instrument;
 
 use crate::config::{LocalConfig, RemoteConfig};
 
 pub type Result<T> = std::result::Result<T, Box<dyn Error>>;
 use crate::extractor::ClickHouseExtractor;
+use crate::import_ledger::ImportLedger;
+use crate::import_manifest::ImportManifest;
 use crate::importer::ClickHouseImporter;
 use crate::parquet_helper::ParquetHelper;
-use crate::transport::RsyncTransport;
+use crate::transport::{self, Transport};
+use utils::clickhouse_client::ClickHouseClient;
+
+/// 本地流水线每天执行的步骤
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PipelineStep {
+    Extract,
+    Write,
+    Sync,
+    Cleanup,
+}
+
+impl PipelineStep {
+    pub const ALL: [PipelineStep; 4] = [
+        PipelineStep::Extract,
+        PipelineStep::Write,
+        PipelineStep::Sync,
+        PipelineStep::Cleanup,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            PipelineStep::Extract => "extract",
+            PipelineStep::Write => "write",
+            PipelineStep::Sync => "sync",
+            PipelineStep::Cleanup => "cleanup",
+        }
+    }
+}
+
+/// 单天各步骤耗时
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DayTiming {
+    pub extract: Duration,
+    pub write: Duration,
+    pub sync: Duration,
+    pub cleanup: Duration,
+}
+
+impl DayTiming {
+    pub fn get(&self, step: PipelineStep) -> Duration {
+        match step {
+            PipelineStep::Extract => self.extract,
+            PipelineStep::Write => self.write,
+            PipelineStep::Sync => self.sync,
+            PipelineStep::Cleanup => self.cleanup,
+        }
+    }
+
+    fn slot_mut(&mut self, step: PipelineStep) -> &mut Duration {
+        match step {
+            PipelineStep::Extract => &mut self.extract,
+            PipelineStep::Write => &mut self.write,
+            PipelineStep::Sync => &mut self.sync,
+            PipelineStep::Cleanup => &mut self.cleanup,
+        }
+    }
+
+    pub fn total(&self) -> Duration {
+        self.extract + self.write + self.sync + self.cleanup
+    }
+
+    /// 执行一个步骤并把耗时记到对应字段
+    pub async fn time<T, F: Future<Output = T>>(&mut self, step: PipelineStep, fut: F) -> T {
+        let start = Instant::now();
+        let output = fut.await;
+        *self.slot_mut(step) += start.elapsed();
+        output
+    }
+
+    /// 单行耗时摘要，例如 "extract 1.20s | write 0.10s | sync 3.40s | cleanup 0.00s"
+    pub fn summary(&self) -> String {
+        PipelineStep::ALL
+            .iter()
+            .map(|step| format!("{} {:.2}s", step.name(), self.get(*step).as_secs_f64()))
+            .collect::<Vec<_>>()
+            .join(" | ")
+    }
+}
+
+/// 日志中的耗时字段（毫秒）
+fn millis(duration: Duration) -> u64 {
+    duration.as_millis() as u64
+}
+
+/// 单天处理结果
+#[derive(Debug, Clone)]
+pub struct DayReport {
+    pub table: String,
+    pub date: NaiveDate,
+    pub rows: usize,
+    /// dry run 时预估的 Parquet 文件大小
+    pub estimated_bytes: Option<u64>,
+    pub timing: DayTiming,
+}
+
+/// 单表汇总
+#[derive(Debug, Clone, PartialEq)]
+pub struct TableSummary {
+    pub table: String,
+    pub days: usize,
+    pub rows: usize,
+    pub estimated_bytes: u64,
+}
+
+/// 重试后仍传输失败、保留在本地的文件
+#[derive(Debug, Clone)]
+pub struct FailedTransfer {
+    pub table: String,
+    pub date: NaiveDate,
+    pub file_path: PathBuf,
+    pub error: String,
+}
+
+/// 本地流水线运行报告
+#[derive(Debug, Clone, Default)]
+pub struct PipelineReport {
+    pub days: Vec<DayReport>,
+    pub failed_transfers: Vec<FailedTransfer>,
+    /// 本次写入的 Parquet 文件（包括传输后被删除的）
+    pub written_files: Vec<PathBuf>,
+}
+
+impl PipelineReport {
+    pub fn record_day(&mut self, table: &str, date: NaiveDate, rows: usize, timing: DayTiming) {
+        self.days.push(DayReport {
+            table: table.to_string(),
+            date,
+            rows,
+            estimated_bytes: None,
+            timing,
+        });
+    }
+
+    /// 记录 dry run 的一天（只有提取耗时和预估大小）
+    pub fn record_estimate(
+        &mut self,
+        table: &str,
+        date: NaiveDate,
+        rows: usize,
+        estimated_bytes: u64,
+        timing: DayTiming,
+    ) {
+        self.days.push(DayReport {
+            table: table.to_string(),
+            date,
+            rows,
+            estimated_bytes: Some(estimated_bytes),
+            timing,
+        });
+    }
+
+    /// 按表汇总天数、行数和预估大小（按首次出现的顺序）
+    pub fn table_summaries(&self) -> Vec<TableSummary> {
+        let mut summaries: Vec<TableSummary> = Vec::new();
+        for day in &self.days {
+            let index = match summaries.iter().position(|s| s.table == day.table) {
+                Some(index) => index,
+                None => {
+                    summaries.push(TableSummary {
+                        table: day.table.clone(),
+                        days: 0,
+                        rows: 0,
+                        estimated_bytes: 0,
+                    });
+                    summaries.len() - 1
+                }
+            };
+            let summary = &mut summaries[index];
+            summary.days += 1;
+            summary.rows += day.rows;
+            summary.estimated_bytes += day.estimated_bytes.unwrap_or(0);
+        }
+        summaries
+    }
+
+    /// 所有天数某一步骤的累计耗时
+    pub fn total_for(&self, step: PipelineStep) -> Duration {
+        self.days.iter().map(|day| day.timing.get(step)).sum()
+    }
+
+    pub fn total_rows(&self) -> usize {
+        self.days.iter().map(|day| day.rows).sum()
+    }
+
+    /// 总耗时最长的一天
+    pub fn slowest_day(&self) -> Option<&DayReport> {
+        self.days.iter().max_by_key(|day| day.timing.total())
+    }
+
+    pub fn print_summary(&self) {
+        for line in self.summary_lines() {
+            println!("{}", line);
+        }
+    }
+
+    /// `print_summary` 输出的各行
+    pub fn summary_lines(&self) -> Vec<String> {
+        let mut lines = vec![
+            "=== Local Pipeline Timing ===".to_string(),
+            format!("Days processed: {}", self.days.len()),
+            format!("Rows extracted: {}", self.total_rows()),
+        ];
+        for step in PipelineStep::ALL {
+            lines.push(format!("  {:<8} {:.2}s", step.name(), self.total_for(step).as_secs_f64()));
+        }
+        for summary in self.table_summaries() {
+            if summary.estimated_bytes > 0 {
+                lines.push(format!(
+                    "  {}: {} rows in {} days (~{:.2} MB parquet)",
+                    summary.table,
+                    summary.rows,
+                    summary.days,
+                    summary.estimated_bytes as f64 / (1024.0 * 1024.0)
+                ));
+            } else {
+                lines.push(format!("  {}: {} rows in {} days", summary.table, summary.rows, summary.days));
+            }
+        }
+        if let Some(day) = self.slowest_day() {
+            lines.push(format!(
+                "Slowest day: {} {} ({:.2}s: {})",
+                day.table,
+                day.date,
+                day.timing.total().as_secs_f64(),
+                day.timing.summary()
+            ));
+        }
+        if !self.failed_transfers.is_empty() {
+            lines.push(format!("Failed transfers ({} files kept locally):", self.failed_transfers.len()));
+            for failed in &self.failed_transfers {
+                lines.push(format!(
+                    "  {} {} {:?}: {}",
+                    failed.table, failed.date, failed.file_path, failed.error
+                ));
+            }
+        }
+        lines.push("=============================".to_string());
+        lines
+    }
+}
 
 /// 本地模式流水线
 /// 
@@ -15,140 +265,502 @@ use crate::transport::RsyncTransport;
 pub struct LocalPipeline {
     extractor: ClickHouseExtractor,
     parquet_helper: ParquetHelper,
-    transport: RsyncTransport,
+    transport: Option<Box<dyn Transport>>, // no_transfer 时为 None
     config: LocalConfig,
 }
 
 impl LocalPipeline {
-    pub fn new(config: LocalConfig) -> Self {
-        Self {
+    /// 根据配置创建流水线（配置了 `[s3]` 时上传到对象存储，否则 rsync）
+    ///
+    /// `no_transfer` 时不创建传输器，也不要求配置 `[remote_server]` / `[s3]`
+    pub fn new(config: LocalConfig) -> Result<Self> {
+        let transport = if config.no_transfer {
+            None
+        } else {
+            Some(transport::from_config(&config)?)
+        };
+        Ok(Self {
             extractor: ClickHouseExtractor::new(),
             parquet_helper: ParquetHelper::new(),
-            transport: RsyncTransport::new(),
+            transport,
             config,
-        }
+        })
     }
 
-    /// 运行本地模式流水线
-    pub async fn run(&self) -> Result<()> {
-        let today = Utc::now().date_naive();
-        
-        println!("🚀 Starting Local Pipeline");
-        println!("   Start date: {}", self.config.start_time);
-        println!("   Today: {}", today);
-        println!("   Tables: {:?}", self.config.tables);
-        println!();
+    /// 运行本地模式流水线，返回每天各步骤的耗时报告
+    pub async fn run(&self) -> Result<PipelineReport> {
+        let end_date = self.end_date()?;
+        let mut report = PipelineReport::default();
+
+        tracing::info!(
+            start = %self.config.start_time,
+            end = %end_date,
+            tables = ?self.config.tables,
+            dry_run = self.config.dry_run,
+            no_transfer = self.config.no_transfer,
+            "starting local pipeline"
+        );
 
         // 遍历所有表
         for (table_idx, table) in self.config.tables.iter().enumerate() {
-            println!("📊 Processing table {}/{}: {}", 
-                table_idx + 1, 
-                self.config.tables.len(), 
-                table
-            );
-
             // 获取事件类型
             let event_type = self.config.table_event_mappings.get(table)
                 .ok_or_else(|| format!("Event type not found for table: {}", table))?;
 
-            let table_dir = self.config.local_storage_path.join(table);
-            
-            // 计算日期范围
-            let mut current_date = self.config.start_time;
-            let mut day_count = 0;
-            
-            // 按天处理
-            while current_date <= today {
-                day_count += 1;
-                
-                println!("   📅 Day {}: {} ({})", 
-                    day_count, 
-                    current_date, 
-                    current_date.format("%A")
+            let span = tracing::info_span!("table", %table);
+            async {
+                tracing::info!(
+                    index = table_idx + 1,
+                    total = self.config.tables.len(),
+                    "processing table"
                 );
-
-                // 1. 提取数据
-                print!("      → Extracting data... ");
-                let batch = self.extractor
-                    .extract_daily_events(table, event_type, current_date)
-                    .await?;
-                println!("✓ ({} rows)", batch.num_rows());
-
-                // 2. 写入 Parquet
-                print!("      → Writing Parquet... ");
-                let file_path = self.parquet_helper
-                    .write_daily_parquet(
-                        table,
-                        current_date,
-                        batch,
-                        &self.config.local_storage_path,
-                    )
-                    .await?;
-                println!("✓ {:?}", file_path.file_name().unwrap());
-
-                // 3. 立即传输该文件
-                print!("      → Syncing to remote... ");
-                self.transport
-                    .sync_directory(&table_dir, &self.config.remote_server)
-                    .await?;
-                println!("✓");
-
-                // 4. 删除本地文件以节省空间
-                print!("      → Cleaning up local file... ");
-                std::fs::remove_file(&file_path)?;
-                println!("✓");
-
-                // 移动到下一天
-                current_date = current_date
-                    .succ_opt()
-                    .ok_or("Failed to get next date")?;
+                self.run_table(table, event_type, end_date, &mut report).await
             }
-            
-            println!("   ✅ Table {} completed ({} days)\n", table, day_count);
+            .instrument(span)
+            .await?;
         }
 
-        println!("🎉 Local Pipeline completed successfully!");
-        println!("   Total tables processed: {}", self.config.tables.len());
+        if report.failed_transfers.is_empty() {
+            tracing::info!(
+                tables = self.config.tables.len(),
+                rows = report.total_rows(),
+                "local pipeline completed"
+            );
+        } else {
+            tracing::warn!(
+                tables = self.config.tables.len(),
+                rows = report.total_rows(),
+                failed_transfers = report.failed_transfers.len(),
+                "local pipeline completed with failed transfers"
+            );
+        }
+        report.print_summary();
         
+        Ok(report)
+    }
+
+    /// 按天处理一张表
+    async fn run_table(
+        &self,
+        table: &str,
+        event_type: &str,
+        end_date: NaiveDate,
+        report: &mut PipelineReport,
+    ) -> Result<()> {
+        let mut current_date = self.config.start_time;
+        let mut day_count = 0;
+
+        while current_date <= end_date {
+            day_count += 1;
+            let span = tracing::info_span!("day", date = %current_date);
+            self.run_day(table, event_type, current_date, report)
+                .instrument(span)
+                .await?;
+
+            // 移动到下一天
+            current_date = current_date
+                .succ_opt()
+                .ok_or("Failed to get next date")?;
+        }
+
+        tracing::info!(days = day_count, "table completed");
+        Ok(())
+    }
+
+    /// 单天：提取 -> 写入 Parquet -> 传输 -> 清理
+    async fn run_day(
+        &self,
+        table: &str,
+        event_type: &str,
+        date: NaiveDate,
+        report: &mut PipelineReport,
+    ) -> Result<()> {
+        let mut timing = DayTiming::default();
+
+        // 1. 提取数据
+        let batch = timing
+            .time(
+                PipelineStep::Extract,
+                self.extractor.extract_daily_events(table, event_type, date),
+            )
+            .await?;
+        let rows = batch.num_rows();
+        tracing::info!(rows, duration_ms = millis(timing.extract), "extracted");
+
+        if self.config.dry_run {
+            // 只在内存中编码估算大小，跳过写入/传输/清理
+            let estimated_bytes = self.parquet_helper.encoded_size(&batch)?;
+            tracing::info!(rows, estimated_bytes, "dry run, write/sync/cleanup skipped");
+            report.record_estimate(table, date, rows, estimated_bytes, timing);
+            return Ok(());
+        }
+
+        // 2. 写入 Parquet
+        let file_path = timing
+            .time(
+                PipelineStep::Write,
+                self.parquet_helper.write_daily_parquet(
+                    table,
+                    date,
+                    batch,
+                    &self.config.local_storage_path,
+                ),
+            )
+            .await?;
+        tracing::info!(
+            file = %file_path.display(),
+            duration_ms = millis(timing.write),
+            "wrote parquet"
+        );
+        report.written_files.push(file_path.clone());
+
+        // no_transfer 时写完即结束，文件留在本地
+        if let Some(transport) = &self.transport {
+            // 3. 立即传输该表目录（传输器内部按配置重试），远端布局为 table/file
+            let table_dir = self.config.local_storage_path.join(table);
+            let key = format!(
+                "{}/{}",
+                table,
+                file_path.file_name().unwrap().to_string_lossy()
+            );
+            let transfer: Result<()> = timing
+                .time(PipelineStep::Sync, async {
+                    transport.sync_directory(&table_dir, table).await?;
+                    // 确认目标上确实存在该文件，才允许删除本地文件
+                    if !transport.exists(&key).await? {
+                        return Err(format!("{} not found on destination after sync", key).into());
+                    }
+                    Ok(())
+                })
+                .await;
+
+            match transfer {
+                Ok(()) => {
+                    tracing::info!(duration_ms = millis(timing.sync), "synced to remote");
+
+                    // 目录同步成功，之前失败的文件也已一并传过去
+                    let mut transferred = vec![file_path];
+                    report.failed_transfers.retain(|failed| {
+                        if failed.table == table {
+                            transferred.push(failed.file_path.clone());
+                            false
+                        } else {
+                            true
+                        }
+                    });
+
+                    // 4. 删除本地文件以节省空间（keep_local 时保留）
+                    if !self.config.keep_local {
+                        timing
+                            .time(PipelineStep::Cleanup, async {
+                                transferred
+                                    .iter()
+                                    .filter(|path| path.exists())
+                                    .try_for_each(std::fs::remove_file)
+                            })
+                            .await?;
+                        tracing::debug!(
+                            files = transferred.len(),
+                            duration_ms = millis(timing.cleanup),
+                            "removed local files"
+                        );
+                    }
+                }
+                Err(e) => {
+                    // 重试耗尽：保留本地文件，继续处理后续日期
+                    tracing::warn!(
+                        file = %file_path.display(),
+                        duration_ms = millis(timing.sync),
+                        error = %e,
+                        "transfer failed, keeping file for a later transfer"
+                    );
+                    report.failed_transfers.push(FailedTransfer {
+                        table: table.to_string(),
+                        date,
+                        file_path,
+                        error: e.to_string(),
+                    });
+                }
+            }
+        }
+
+        tracing::info!(
+            rows,
+            duration_ms = millis(timing.total()),
+            timing = %timing.summary(),
+            "day completed"
+        );
+        report.record_day(table, date, rows, timing);
         Ok(())
     }
+
+    /// 导出的最后一天：配置的 end_time，未配置时为今天
+    ///
+    /// start_time 晚于结束日期时返回错误
+    pub fn end_date(&self) -> Result<NaiveDate> {
+        let end_date = self
+            .config
+            .end_time
+            .unwrap_or_else(|| Utc::now().date_naive());
+
+        if self.config.start_time > end_date {
+            return Err(format!(
+                "Invalid date range: start_time {} is after end date {}{}",
+                self.config.start_time,
+                end_date,
+                if self.config.end_time.is_none() { " (today)" } else { "" }
+            )
+            .into());
+        }
+
+        Ok(end_date)
+    }
+}
+
+/// 导入后行数核对不一致的一天
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RowCountMismatch {
+    pub table: String,
+    pub date: NaiveDate,
+    pub parquet_rows: u64,
+    pub table_rows: u64,
+}
+
+/// 远程流水线运行报告
+#[derive(Debug, Clone, Default)]
+pub struct RemoteReport {
+    pub files: usize,
+    pub rows: u64,
+    /// 开启 `verify_after_import` 时行数不一致的天
+    pub mismatches: Vec<RowCountMismatch>,
+    /// 无法从路径解析日期、未参与核对的文件
+    pub unverified: Vec<PathBuf>,
+}
+
+impl RemoteReport {
+    pub fn print_summary(&self) {
+        println!("=== Remote Pipeline Summary ===");
+        println!("Files imported: {}", self.files);
+        println!("Rows imported: {}", self.rows);
+        if !self.mismatches.is_empty() {
+            println!("Row count mismatches ({}):", self.mismatches.len());
+            for mismatch in &self.mismatches {
+                println!(
+                    "  {} {}: parquet {} rows, table {} rows",
+                    mismatch.table, mismatch.date, mismatch.parquet_rows, mismatch.table_rows
+                );
+            }
+        }
+        if !self.unverified.is_empty() {
+            println!("Not verified ({} files without a date in the path):", self.unverified.len());
+            for path in &self.unverified {
+                println!("  {}", path.display());
+            }
+        }
+        println!("===============================");
+    }
+}
+
+/// 已成功导入的单个文件
+struct ImportedFile {
+    target_table: String,
+    file_path: PathBuf,
+}
+
+/// 远程模式流水线中一个待导入的目录
+struct FolderImport {
+    source_folder: String,
+    target_table: String,
+    event_type: String,
+    folder_path: PathBuf,
+    manifest: Mutex<ImportManifest>,
+    files: Vec<PathBuf>,
+}
+
+/// 相对目录的文件名（Hive 布局下文件名都是 part.parquet，需要带上分区路径）
+fn relative_name(folder_path: &Path, file_path: &Path) -> String {
+    file_path
+        .strip_prefix(folder_path)
+        .unwrap_or(file_path)
+        .to_str()
+        .unwrap_or("unknown")
+        .to_string()
 }
 
 /// 远程模式流水线
 /// 
 /// 负责: 扫描文件 -> 读取 Parquet -> 导入
 pub struct RemotePipeline {
-    _parquet_helper: ParquetHelper,
+    parquet_helper: ParquetHelper,
     importer: ClickHouseImporter,
     config: RemoteConfig,
 }
 
 impl RemotePipeline {
     pub fn new(config: RemoteConfig) -> Self {
+        let mut importer = ClickHouseImporter::new()
+            .with_retry_config(config.import_retries, config.import_retry_delay_secs);
+        if let Some(table) = &config.import_ledger_table {
+            let client = ClickHouseClient::instance().client().clone();
+            importer = importer.with_ledger(ImportLedger::new(client, table));
+        }
+
         Self {
-            _parquet_helper: ParquetHelper::new(),
-            importer: ClickHouseImporter::new(),
+            parquet_helper: ParquetHelper::new(),
+            importer,
             config,
         }
     }
 
     /// 运行远程模式流水线
-    pub async fn run(&self) -> Result<()> {
-        println!("🚀 Starting Remote Pipeline");
-        println!("   Storage path: {:?}", self.config.remote_storage_path);
-        println!("   Import mappings: {} folders", self.config.import_mappings.len());
-        println!();
+    ///
+    /// 最多同时导入 `max_concurrent_imports` 个文件；`preserve_order` 时
+    /// 同一目录内的文件按日期顺序串行导入，只在目录之间并发；
+    /// `verify_after_import` 时全部导入后逐天核对行数
+    pub async fn run(&self) -> Result<RemoteReport> {
+        tracing::info!(
+            storage_path = %self.config.remote_storage_path.display(),
+            folders = self.config.import_mappings.len(),
+            max_concurrent_imports = self.config.max_concurrent_imports,
+            preserve_order = self.config.preserve_order,
+            verify_after_import = self.config.verify_after_import,
+            import_ledger = ?self.config.import_ledger_table,
+            "starting remote pipeline"
+        );
+
+        if let Some(ledger) = self.importer.ledger() {
+            ledger.ensure_table().await?;
+        }
 
-        let mut total_files = 0;
-        let mut total_rows = 0u64;
+        // 1. 扫描所有目录
+        let folders = self.scan_folders()?;
+
+        // 2. 拆分导入任务：保序时一个目录一个任务，否则一个文件一个任务
+        let mut jobs: Vec<(&FolderImport, Vec<PathBuf>)> = Vec::new();
+        for folder in &folders {
+            if self.config.preserve_order {
+                jobs.push((folder, folder.files.clone()));
+            } else {
+                jobs.extend(folder.files.iter().map(|file| (folder, vec![file.clone()])));
+            }
+        }
+
+        // 3. 有界并发执行，汇总所有任务的结果
+        let mut results = stream::iter(jobs)
+            .map(|(folder, files)| {
+                let span = tracing::info_span!(
+                    "import",
+                    folder = %folder.source_folder,
+                    table = %folder.target_table
+                );
+                self.import_files(folder, files)
+                    .map(move |result| (folder, result))
+                    .instrument(span)
+            })
+            .buffer_unordered(self.config.max_concurrent_imports.max(1));
+
+        // 某个任务失败时不中断其他任务，全部结束后汇总报错
+        let mut report = RemoteReport::default();
+        let mut imported = Vec::new();
+        let mut failures = Vec::new();
+        while let Some((folder, result)) = results.next().await {
+            match result {
+                Ok((files, rows)) => {
+                    report.files += files.len();
+                    report.rows += rows;
+                    imported.extend(files);
+                }
+                Err(e) => {
+                    tracing::error!(
+                        folder = %folder.source_folder,
+                        table = %folder.target_table,
+                        error = %e,
+                        "import failed"
+                    );
+                    failures.push(e.to_string());
+                }
+            }
+        }
+
+        if !failures.is_empty() {
+            return Err(format!(
+                "{} import job(s) failed ({} files, {} rows imported): {}",
+                failures.len(),
+                report.files,
+                report.rows,
+                failures.join("; ")
+            )
+            .into());
+        }
+
+        tracing::info!(files = report.files, rows = report.rows, "remote pipeline completed");
+
+        // 4. 按天核对行数
+        if self.config.verify_after_import && !imported.is_empty() {
+            self.verify_imports(&imported, &mut report)
+                .instrument(tracing::info_span!("verify"))
+                .await?;
+        }
+
+        Ok(report)
+    }
+
+    /// 按 (目标表, 日期) 汇总 Parquet 行数，与目标表当天的行数比较
+    async fn verify_imports(&self, imported: &[ImportedFile], report: &mut RemoteReport) -> Result<()> {
+        let client = ClickHouseClient::instance().client();
+
+        // 插入走 async_insert 且不等待落盘，核对前先刷新队列
+        if let Err(e) = client.query("SYSTEM FLUSH ASYNC INSERT QUEUE").execute().await {
+            tracing::warn!(error = %e, "failed to flush async insert queue, counts may lag");
+        }
+
+        let mut expected: BTreeMap<(String, NaiveDate), u64> = BTreeMap::new();
+        for file in imported {
+            match self.config.partition_layout.file_date(&file.file_path) {
+                Some(date) => {
+                    let rows = self.parquet_helper.parquet_row_count(&file.file_path)?;
+                    *expected.entry((file.target_table.clone(), date)).or_default() += rows;
+                }
+                None => report.unverified.push(file.file_path.clone()),
+            }
+        }
+
+        let extractor = ClickHouseExtractor::with_client(client.clone());
+        for ((table, date), parquet_rows) in expected {
+            let table_rows = extractor.count_daily_events(&table, date).await?;
+            if table_rows == parquet_rows {
+                tracing::debug!(%table, %date, rows = table_rows, "row count verified");
+                continue;
+            }
+            tracing::warn!(%table, %date, parquet_rows, table_rows, "row count mismatch");
+            report.mismatches.push(RowCountMismatch {
+                table,
+                date,
+                parquet_rows,
+                table_rows,
+            });
+        }
+
+        tracing::info!(
+            mismatches = report.mismatches.len(),
+            unverified = report.unverified.len(),
+            "verification completed"
+        );
+        Ok(())
+    }
+
+    /// 扫描导入映射中的目录，过滤掉清单中已导入的文件
+    fn scan_folders(&self) -> Result<Vec<FolderImport>> {
+        let mut folders = Vec::new();
 
         // 遍历所有导入映射
         for (folder_idx, (source_folder, target_table)) in self.config.import_mappings.iter().enumerate() {
-            println!("📂 Processing folder {}/{}: {} → {}", 
-                folder_idx + 1, 
-                self.config.import_mappings.len(),
-                source_folder,
-                target_table
+            tracing::info!(
+                index = folder_idx + 1,
+                total = self.config.import_mappings.len(),
+                folder = %source_folder,
+                table = %target_table,
+                "scanning folder"
             );
 
             // 获取事件类型
@@ -159,66 +771,98 @@ impl RemotePipeline {
             let folder_path = self.config.remote_storage_path.join(source_folder);
             
             if !folder_path.exists() {
-                println!("   ⚠️  Folder not found, skipping: {:?}", folder_path);
+                tracing::warn!(path = %folder_path.display(), "folder not found, skipping");
                 continue;
             }
 
-            // 扫描并收集所有 .parquet 文件
-            let mut entries: Vec<_> = std::fs::read_dir(&folder_path)?
-                .filter_map(|entry| entry.ok())
-                .filter(|entry| {
-                    entry.path().extension()
-                        .and_then(|ext| ext.to_str())
-                        .map(|ext| ext == "parquet")
-                        .unwrap_or(false)
-                })
-                .collect();
-
-            // 按文件名排序（确保按日期顺序处理）
-            entries.sort_by_key(|entry| entry.file_name());
+            // 扫描并收集所有 .parquet 文件（按日期顺序）
+            let entries = self.config.partition_layout.discover_files(&folder_path)?;
 
             if entries.is_empty() {
-                println!("   ⚠️  No parquet files found in {:?}", folder_path);
+                tracing::warn!(path = %folder_path.display(), "no parquet files found");
                 continue;
             }
 
-            println!("   Found {} parquet files", entries.len());
-
-            // 逐个导入文件
-            for (file_idx, entry) in entries.iter().enumerate() {
-                let file_path = entry.path();
-                let file_name = file_path.file_name()
-                    .and_then(|n| n.to_str())
-                    .unwrap_or("unknown");
-
-                print!("   📄 File {}/{}: {} ... ", 
-                    file_idx + 1, 
-                    entries.len(),
-                    file_name
-                );
-
-                // 导入文件
-                let rows = self.importer
-                    .import_parquet(&file_path, target_table, event_type)
-                    .await?;
-
-                total_rows += rows;
-                total_files += 1;
+            // 加载导入清单，跳过上次已成功导入的文件
+            let manifest = if self.config.force_reimport {
+                ImportManifest::empty(&folder_path)
+            } else {
+                ImportManifest::load(&folder_path)?
+            };
+
+            let files: Vec<PathBuf> = entries
+                .iter()
+                .filter(|file_path| !manifest.is_imported(&relative_name(&folder_path, file_path)))
+                .cloned()
+                .collect();
+            tracing::info!(
+                folder = %source_folder,
+                to_import = files.len(),
+                already_imported = entries.len() - files.len(),
+                "found parquet files"
+            );
 
-                println!("✓ ({} rows)", rows);
+            let folder = FolderImport {
+                source_folder: source_folder.clone(),
+                target_table: target_table.clone(),
+                event_type: event_type.clone(),
+                folder_path,
+                manifest: Mutex::new(manifest),
+                files,
+            };
+            if !folder.files.is_empty() {
+                folders.push(folder);
             }
+        }
 
-            println!("   ✅ Folder {} completed ({} files, {} rows)\n", 
-                source_folder, 
-                entries.len(),
-                total_rows
+        Ok(folders)
+    }
+
+    /// 按顺序导入一组文件，每个文件成功后立即写入清单
+    ///
+    /// # Returns
+    /// * `(Vec<ImportedFile>, u64)` - 导入的文件和行数
+    async fn import_files(
+        &self,
+        folder: &FolderImport,
+        files: Vec<PathBuf>,
+    ) -> Result<(Vec<ImportedFile>, u64)> {
+        let mut imported_files = Vec::new();
+        let mut imported_rows = 0u64;
+
+        for file_path in files {
+            let file_name = relative_name(&folder.folder_path, &file_path);
+            let start = Instant::now();
+
+            // 导入文件，大文件按 row group 逐个提交
+            let size = std::fs::metadata(&file_path)?.len();
+            let result = if size >= self.config.streaming_import_min_bytes {
+                self.importer
+                    .import_parquet_streaming(&file_path, &folder.target_table, &folder.event_type)
+                    .await
+            } else {
+                self.importer
+                    .import_parquet(&file_path, &folder.target_table, &folder.event_type)
+                    .await
+            };
+            let rows = result.map_err(|e| format!("{}/{}: {}", folder.source_folder, file_name, e))?;
+            folder.manifest.lock().unwrap().mark_imported(&file_name)?;
+
+            imported_rows += rows;
+
+            tracing::info!(
+                file = %file_name,
+                rows,
+                bytes = size,
+                duration_ms = millis(start.elapsed()),
+                "imported file"
             );
+            imported_files.push(ImportedFile {
+                target_table: folder.target_table.clone(),
+                file_path,
+            });
         }
 
-        println!("🎉 Remote Pipeline completed successfully!");
-        println!("   Total files processed: {}", total_files);
-        println!("   Total rows imported: {}", total_rows);
-        
-        Ok(())
+        Ok((imported_files, imported_rows))
     }
 }
@@ -0,0 +1,262 @@
+use futures::FutureExt;
+use futures::future::LocalBoxFuture;
+use object_store::aws::AmazonS3Builder;
+use object_store::buffered::BufWriter;
+use object_store::path::Path as ObjectPath;
+use object_store::{Attribute, AttributeValue, Attributes, GetOptions, ObjectStore};
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::path::Path;
+use std::sync::Arc;
+use tokio::io::AsyncWriteExt;
+use tokio::time::{sleep, Duration};
+
+use crate::import_ledger::content_hash;
+use crate::transport::Transport;
+
+pub type Result<T> = std::result::Result<T, Box<dyn Error>>;
+
+/// S3 兼容对象存储配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct S3Config {
+    pub bucket: String,
+
+    /// key 前缀，最终 key 为 `{prefix}/{table}/{file_name}`
+    #[serde(default)]
+    pub prefix: String,
+
+    #[serde(default = "default_region")]
+    pub region: String,
+
+    /// 自定义 endpoint（MinIO、R2 等），不配置则使用 AWS
+    #[serde(default)]
+    pub endpoint: Option<String>,
+
+    /// 不配置时从 AWS_ACCESS_KEY_ID / AWS_SECRET_ACCESS_KEY 环境变量读取
+    #[serde(default)]
+    pub access_key_id: Option<String>,
+    #[serde(default)]
+    pub secret_access_key: Option<String>,
+
+    /// 允许非 TLS 的 endpoint（本地 MinIO）
+    #[serde(default)]
+    pub allow_http: bool,
+}
+
+fn default_region() -> String {
+    "us-east-1".to_string()
+}
+
+/// 上传时写入对象元数据的本地文件 SHA-256，再次同步时据此判断远端对象是否与本地一致
+///
+/// 不使用 ETag：分片上传的 ETag 不是文件内容的 MD5
+pub const CONTENT_HASH_METADATA: &str = "content-sha256";
+
+/// 基于对象存储的传输器
+pub struct S3Transport {
+    store: Arc<dyn ObjectStore>,
+    prefix: String,
+    /// 单个文件上传失败后的最大重试次数
+    max_retries: usize,
+    /// 初始重试延迟（秒）
+    initial_retry_delay: u64,
+}
+
+impl S3Transport {
+    pub fn new(config: &S3Config) -> Result<Self> {
+        let mut builder = AmazonS3Builder::from_env()
+            .with_bucket_name(&config.bucket)
+            .with_region(&config.region)
+            .with_allow_http(config.allow_http);
+        if let Some(endpoint) = &config.endpoint {
+            builder = builder.with_endpoint(endpoint);
+        }
+        if let Some(access_key_id) = &config.access_key_id {
+            builder = builder.with_access_key_id(access_key_id);
+        }
+        if let Some(secret_access_key) = &config.secret_access_key {
+            builder = builder.with_secret_access_key(secret_access_key);
+        }
+
+        Ok(Self::with_store(Arc::new(builder.build()?), &config.prefix))
+    }
+
+    /// 使用已有的 ObjectStore（测试时可传入 InMemory）
+    pub fn with_store(store: Arc<dyn ObjectStore>, prefix: &str) -> Self {
+        Self {
+            store,
+            prefix: prefix.trim_matches('/').to_string(),
+            max_retries: 5,
+            initial_retry_delay: 5,
+        }
+    }
+
+    /// 设置上传重试次数和初始重试延迟（秒），与 rsync 共用 transfer_retries 配置
+    pub fn with_retry_config(mut self, max_retries: usize, initial_retry_delay: u64) -> Self {
+        self.max_retries = max_retries;
+        self.initial_retry_delay = initial_retry_delay;
+        self
+    }
+
+    /// 相对 key -> 对象路径（加上前缀）
+    pub fn object_path(&self, key: &str) -> ObjectPath {
+        if self.prefix.is_empty() {
+            ObjectPath::from(key)
+        } else {
+            ObjectPath::from(format!("{}/{}", self.prefix, key))
+        }
+    }
+
+    /// 远端对象大小，不存在时返回 None
+    async fn remote_size(&self, path: &ObjectPath) -> Result<Option<u64>> {
+        match self.store.head(path).await {
+            Ok(meta) => Ok(Some(meta.size)),
+            Err(object_store::Error::NotFound { .. }) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// 远端对象上记录的内容哈希，对象不存在或没有记录（旧版本上传）时返回 None
+    async fn remote_content_hash(&self, path: &ObjectPath) -> Result<Option<String>> {
+        let options = GetOptions {
+            head: true,
+            ..Default::default()
+        };
+        match self.store.get_opts(path, options).await {
+            Ok(result) => Ok(result
+                .attributes
+                .get(&Attribute::Metadata(CONTENT_HASH_METADATA.into()))
+                .map(|value| {
+                    let value: &str = value.as_ref();
+                    value.to_string()
+                })),
+            Err(object_store::Error::NotFound { .. }) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// 上传单个文件（带重试），内容哈希写入对象元数据
+    async fn upload_with_retry(
+        &self,
+        local_file: &Path,
+        path: &ObjectPath,
+        hash: &str,
+    ) -> Result<()> {
+        let mut last_error = None;
+
+        for attempt in 0..=self.max_retries {
+            if attempt > 0 {
+                let delay = self.initial_retry_delay * (2_u64.pow(attempt as u32 - 1));
+                tracing::info!(attempt, max_retries = self.max_retries, delay_secs = delay, "retrying S3 upload");
+                sleep(Duration::from_secs(delay)).await;
+            }
+
+            match self.upload_file(local_file, path, hash).await {
+                Ok(()) => {
+                    if attempt > 0 {
+                        tracing::info!(attempts = attempt, "S3 upload recovered after retries");
+                    }
+                    return Ok(());
+                }
+                Err(e) => {
+                    if attempt < self.max_retries {
+                        tracing::warn!(attempt = attempt + 1, file = %local_file.display(), error = %e, "S3 upload attempt failed, will retry");
+                    }
+                    last_error = Some(e);
+                }
+            }
+        }
+
+        Err(last_error.unwrap_or_else(|| "Unknown error".into()))
+    }
+
+    /// 上传单个文件（大文件自动分片上传）
+    async fn upload_file(&self, local_file: &Path, path: &ObjectPath, hash: &str) -> Result<()> {
+        let mut attributes = Attributes::new();
+        attributes.insert(
+            Attribute::Metadata(CONTENT_HASH_METADATA.into()),
+            AttributeValue::from(hash.to_string()),
+        );
+
+        let mut file = tokio::fs::File::open(local_file).await?;
+        let mut writer =
+            BufWriter::new(self.store.clone(), path.clone()).with_attributes(attributes);
+        if let Err(e) = tokio::io::copy(&mut file, &mut writer).await {
+            writer.abort().await?;
+            return Err(e.into());
+        }
+        writer.shutdown().await?;
+        Ok(())
+    }
+
+    /// 上传目录下的文件到 `{prefix}/{dest}/`，跳过远端内容哈希与本地一致的文件
+    ///
+    /// # Arguments
+    /// * `local_dir` - 本地目录路径（表目录）
+    /// * `dest` - 目标相对目录（表名）
+    ///
+    /// # Returns
+    /// * `Result<usize>` - 实际上传的文件数
+    pub async fn upload_directory(&self, local_dir: &Path, dest: &str) -> Result<usize> {
+        if !local_dir.exists() {
+            return Err(format!("Local directory does not exist: {:?}", local_dir).into());
+        }
+
+        // 只上传普通文件，忽略 .imported.log 等隐藏文件
+        let mut files: Vec<_> = std::fs::read_dir(local_dir)?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.is_file())
+            .filter(|path| {
+                path.file_name()
+                    .and_then(|name| name.to_str())
+                    .map(|name| !name.starts_with('.'))
+                    .unwrap_or(false)
+            })
+            .collect();
+        files.sort();
+
+        tracing::debug!(
+            source = %local_dir.display(),
+            destination = %self.object_path(dest),
+            "starting S3 upload"
+        );
+
+        let mut uploaded = 0;
+        for local_file in &files {
+            let file_name = local_file
+                .file_name()
+                .and_then(|name| name.to_str())
+                .ok_or_else(|| format!("Invalid file name: {:?}", local_file))?;
+            let path = self.object_path(&format!("{}/{}", dest, file_name));
+
+            // 大小相同但内容不同（同一天重新导出）的文件也要重新上传
+            let hash = content_hash(local_file)?;
+            if self.remote_content_hash(&path).await?.as_deref() == Some(hash.as_str()) {
+                continue;
+            }
+
+            self.upload_with_retry(local_file, &path, &hash)
+                .await
+                .map_err(|e| format!("Failed to upload {:?} to {}: {}", local_file, path, e))?;
+            uploaded += 1;
+        }
+
+        tracing::debug!(uploaded, up_to_date = files.len() - uploaded, "S3 upload completed");
+        Ok(uploaded)
+    }
+}
+
+impl Transport for S3Transport {
+    fn sync_directory<'a>(&'a self, local_dir: &'a Path, dest: &'a str) -> LocalBoxFuture<'a, Result<()>> {
+        async move {
+            self.upload_directory(local_dir, dest).await?;
+            Ok(())
+        }
+        .boxed_local()
+    }
+
+    fn exists<'a>(&'a self, key: &'a str) -> LocalBoxFuture<'a, Result<bool>> {
+        async move { Ok(self.remote_size(&self.object_path(key)).await?.is_some()) }.boxed_local()
+    }
+}
@@ -1,10 +1,13 @@
 use chrono::{Duration, NaiveDateTime, Utc};
 use clickhouse::{Client, Row};
+use futures::{StreamExt, stream};
+use tokio::time::sleep;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::error::Error;
+use tracing::Instrument;
 
-use crate::sync_config::SyncConfig;
+use crate::sync_config::{SyncConfig, SyncMode};
 
 pub type Result<T> = std::result::Result<T, Box<dyn Error>>;
 
@@ -22,23 +25,254 @@ struct MinuteCount {
     unique_count: u64,
 }
 
+/// 秒级分桶对比结果
+#[derive(Debug, Row, Serialize, Deserialize)]
+struct BucketCount {
+    bucket: u32,  // Unix timestamp
+    unique_count: u64,
+}
+
+/// fine_grained 模式下的分桶大小（秒）
+pub const FINE_BUCKET_SECS: u32 = 10;
+
+/// 对比和同步所依据的时间列
+pub const TIMESTAMP_COLUMN: &str = "timestamp";
+
+/// 用反引号引用单个标识符（列名、表名），转义其中的反斜杠和反引号
+pub fn quote_ident(ident: &str) -> String {
+    format!("`{}`", ident.replace('\\', "\\\\").replace('`', "\\`"))
+}
+
+/// 拆分可带数据库前缀的表名 "db.table" -> (Some("db"), "table")
+pub fn split_table(table: &str) -> (Option<&str>, &str) {
+    match table.split_once('.') {
+        Some((database, table)) => (Some(database), table),
+        None => (None, table),
+    }
+}
+
+/// 引用表名，"db.table" 会被引用为 `db`.`table`
+pub fn quote_table(table: &str) -> String {
+    match split_table(table) {
+        (Some(database), table) => format!("{}.{}", quote_ident(database), quote_ident(table)),
+        (None, table) => quote_ident(table),
+    }
+}
+
+/// 单引号字符串字面量，转义其中的反斜杠和单引号
+fn quote_str(value: &str) -> String {
+    format!("'{}'", value.replace('\\', "\\\\").replace('\'', "\\'"))
+}
+
+/// `DEDUPLICATE BY` 使用的列：去重键加上表的排序键、分区键中的其他列
+///
+/// ClickHouse 要求 DEDUPLICATE BY 包含排序键和分区键的全部列；重复同步产生的
+/// 副本这些列的值也相同，加进去不影响去重结果
+pub fn deduplicate_columns(dedup_key: &[String], table_key_columns: &[String]) -> Vec<String> {
+    let mut columns = dedup_key.to_vec();
+    for column in table_key_columns {
+        if !columns.contains(column) {
+            columns.push(column.clone());
+        }
+    }
+    columns
+}
+
+/// 生成 `OPTIMIZE TABLE ... FINAL DEDUPLICATE BY ...` 语句
+pub fn optimize_deduplicate_sql(table: &str, columns: &[String]) -> String {
+    let columns: Vec<String> = columns.iter().map(|column| quote_ident(column)).collect();
+    format!(
+        "OPTIMIZE TABLE {} FINAL DEDUPLICATE BY {}",
+        quote_table(table),
+        columns.join(", ")
+    )
+}
+
+/// 按时间桶对比本地和远程计数的结果
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct CountDiff {
+    /// 本地有数据且与远程计数不同的桶: (bucket, 本地数, 远程数)
+    pub differing: Vec<(u32, u64, u64)>,
+    /// 只在远程存在的桶: (bucket, 远程数)，本地没有数据可同步
+    pub remote_only: Vec<(u32, u64)>,
+}
+
+/// 对比按时间桶分组的本地/远程计数，结果按桶排序
+pub fn diff_counts(
+    local: impl IntoIterator<Item = (u32, u64)>,
+    remote: impl IntoIterator<Item = (u32, u64)>,
+) -> CountDiff {
+    let mut remote_map: HashMap<u32, u64> = remote.into_iter().collect();
+
+    let mut diff = CountDiff::default();
+    for (bucket, local_count) in local {
+        let remote_count = remote_map.remove(&bucket).unwrap_or(0);
+        if local_count != remote_count {
+            diff.differing.push((bucket, local_count, remote_count));
+        }
+    }
+    diff.remote_only.extend(remote_map);
+
+    diff.differing.sort();
+    diff.remote_only.sort();
+    diff
+}
+
+/// 按 FINE_BUCKET_SECS 分桶统计 [start_ts, end_ts) 内去重键数的查询
+pub fn bucket_counts_sql(table: &str, key_expr: &str, start_ts: u32, end_ts: u32) -> String {
+    let ts = quote_ident(TIMESTAMP_COLUMN);
+    format!(
+        "SELECT 
+            toUnixTimestamp(toStartOfInterval(toDateTime({ts}), INTERVAL {} SECOND)) as bucket,
+            uniqExact({}) as unique_count
+        FROM {}
+        WHERE {ts} >= {} AND {ts} < {}
+        GROUP BY bucket
+        ORDER BY bucket",
+        FINE_BUCKET_SECS, key_expr, quote_table(table), start_ts, end_ts
+    )
+}
+
+/// fine_grained 模式下需要同步的时间窗口 [start, end)，每个有差异的桶一个
+///
+/// 远程独有的桶本地没有数据可同步，不产生窗口
+pub fn fine_sync_windows(
+    local: impl IntoIterator<Item = (u32, u64)>,
+    remote: impl IntoIterator<Item = (u32, u64)>,
+) -> Vec<(u32, u32)> {
+    diff_counts(local, remote)
+        .differing
+        .into_iter()
+        .map(|(bucket, _, _)| (bucket, bucket + FINE_BUCKET_SECS))
+        .collect()
+}
+
+/// INSERT ... SELECT 报错后，根据远程窗口行数的变化判断写入是否生效
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InsertOutcome {
+    /// 远程行数没有变化，可以安全重试
+    NotApplied,
+    /// 远程行数正好增加了本次写入的行数，写入实际已完成
+    Applied,
+    /// 远程行数有其他变化（部分写入或有其他写入方），重试可能产生重复行
+    PartiallyApplied,
+}
+
+/// 对比写入前后的远程窗口行数
+pub fn insert_outcome(remote_before: u64, remote_after: u64, inserted_rows: u64) -> InsertOutcome {
+    if remote_after == remote_before {
+        InsertOutcome::NotApplied
+    } else if remote_after == remote_before + inserted_rows {
+        InsertOutcome::Applied
+    } else {
+        InsertOutcome::PartiallyApplied
+    }
+}
+
+/// 单张表的同步统计
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TableSyncStat {
+    pub local_table: String,
+    pub remote_table: String,
+    pub diff_hours: usize,
+    pub diff_minutes: usize,
+    pub synced_buckets: usize,
+    pub synced_records: u64,
+    pub remote_surplus: usize,
+    /// deduplicate_after_sync 去掉的重复行数
+    pub deduplicated_rows: u64,
+    pub errors: usize,
+}
+
 /// 同步统计信息
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct SyncStats {
     pub total_tables: usize,
     pub diff_hours: usize,
     pub diff_minutes: usize,
+    /// fine_grained 模式下实际同步的 10 秒桶数
+    pub synced_buckets: usize,
     pub synced_records: u64,
+    /// 只在远程存在的分钟数（本地没有数据，不同步）
+    pub remote_surplus: usize,
+    /// deduplicate_after_sync 去掉的重复行数
+    pub deduplicated_rows: u64,
     pub errors: Vec<String>,
+    /// 按本地表名排序的各表统计
+    pub per_table: Vec<TableSyncStat>,
 }
 
 impl SyncStats {
+    /// 记录单张表的统计并合并到汇总计数中
+    pub fn record_table(&mut self, local_table: &str, remote_table: &str, table_stats: SyncStats) {
+        let position = self
+            .per_table
+            .partition_point(|t| t.local_table.as_str() < local_table);
+        self.per_table.insert(
+            position,
+            TableSyncStat {
+                local_table: local_table.to_string(),
+                remote_table: remote_table.to_string(),
+                diff_hours: table_stats.diff_hours,
+                diff_minutes: table_stats.diff_minutes,
+                synced_buckets: table_stats.synced_buckets,
+                synced_records: table_stats.synced_records,
+                remote_surplus: table_stats.remote_surplus,
+                deduplicated_rows: table_stats.deduplicated_rows,
+                errors: table_stats.errors.len(),
+            },
+        );
+        self.merge(table_stats);
+    }
+
+    /// 序列化为 JSON（供监控/看板使用）
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("SyncStats is always serializable")
+    }
+
+    /// 合并另一张表的统计（total_tables 由调用方统计）
+    pub fn merge(&mut self, other: SyncStats) {
+        self.diff_hours += other.diff_hours;
+        self.diff_minutes += other.diff_minutes;
+        self.synced_buckets += other.synced_buckets;
+        self.synced_records += other.synced_records;
+        self.remote_surplus += other.remote_surplus;
+        self.deduplicated_rows += other.deduplicated_rows;
+        self.errors.extend(other.errors);
+        self.per_table.extend(other.per_table);
+    }
+
     pub fn print_summary(&self) {
         println!("\n📊 Sync Summary:");
         println!("   Total tables checked: {}", self.total_tables);
         println!("   Hours with differences: {}", self.diff_hours);
-        println!("   Minutes synced: {}", self.diff_minutes);
+        println!("   Minutes with differences: {}", self.diff_minutes);
+        if self.synced_buckets > 0 {
+            println!("   Sub-minute buckets synced: {}", self.synced_buckets);
+        }
         println!("   Total records synced: {}", self.synced_records);
+        if self.remote_surplus > 0 {
+            println!("   Remote-only minutes (not synced): {}", self.remote_surplus);
+        }
+        if self.deduplicated_rows > 0 {
+            println!("   Duplicate rows removed: {}", self.deduplicated_rows);
+        }
+
+        for table in &self.per_table {
+            if table.diff_hours == 0 && table.errors == 0 {
+                println!("   ✅ {} -> {}", table.local_table, table.remote_table);
+            } else {
+                println!(
+                    "   ⚠️  {} -> {}: {} hours, {} minutes, {} records synced, {} errors",
+                    table.local_table,
+                    table.remote_table,
+                    table.diff_hours,
+                    table.diff_minutes,
+                    table.synced_records,
+                    table.errors
+                );
+            }
+        }
         
         if !self.errors.is_empty() {
             println!("   ⚠️  Errors: {}", self.errors.len());
@@ -84,72 +318,252 @@ impl SyncChecker {
 
     /// 主入口：检查并同步所有表
     pub async fn check_and_sync(&self) -> Result<SyncStats> {
+        self.validate_dedup_keys().await?;
+
         let mut stats = SyncStats::default();
         let (start_time, end_time) = self.calculate_time_range();
 
-        println!("🚀 Starting Sync Checker");
-        println!("   Time range: {} to {}", start_time, end_time);
-        println!("   Tables to check: {}", self.config.table_mappings.len());
-        println!();
+        tracing::info!(
+            start = %start_time,
+            end = %end_time,
+            tables = self.config.table_mappings.len(),
+            report_only = self.config.mode == SyncMode::ReportOnly,
+            "starting sync checker"
+        );
 
         stats.total_tables = self.config.table_mappings.len();
 
-        // 遍历所有表映射
-        for (local_table, remote_table) in &self.config.table_mappings {
-            println!("🔍 Checking: {} -> {}", local_table, remote_table);
+        // 有界并发检查各表，每张表独立统计，最后合并
+        let mut results = stream::iter(&self.config.table_mappings)
+            .map(|(local_table, remote_table)| {
+                let span = tracing::info_span!("table", local = %local_table, remote = %remote_table);
+                async move {
+                    let table_stats = self
+                        .check_table(local_table, remote_table, start_time, end_time)
+                        .await;
+                    (local_table, remote_table, table_stats)
+                }
+                .instrument(span)
+            })
+            .buffer_unordered(self.config.max_concurrent_tables.max(1));
 
-            // 1. 小时级对比
-            match self
-                .compare_hourly(local_table, remote_table, start_time, end_time)
+        while let Some((local_table, remote_table, table_stats)) = results.next().await {
+            stats.record_table(local_table, remote_table, table_stats);
+        }
+
+        Ok(stats)
+    }
+
+    /// 检查并同步单张表，错误记录在返回的统计中
+    async fn check_table(
+        &self,
+        local_table: &str,
+        remote_table: &str,
+        start_time: NaiveDateTime,
+        end_time: NaiveDateTime,
+    ) -> SyncStats {
+        let mut stats = SyncStats::default();
+        tracing::info!("checking table");
+
+        // 1. 小时级对比
+        let diff_hours = match self
+            .compare_hourly(local_table, remote_table, start_time, end_time)
+            .await
+        {
+            Ok(diff_hours) => diff_hours,
+            Err(e) => {
+                let error_msg = format!("{} -> {}: {}", local_table, remote_table, e);
+                tracing::error!(error = %error_msg, "failed to compare hours");
+                stats.errors.push(error_msg);
+                return stats;
+            }
+        };
+
+        if diff_hours.is_empty() {
+            tracing::info!("no differences found");
+            return stats;
+        }
+
+        tracing::warn!(diff_hours = diff_hours.len(), "found hours with differences");
+        stats.diff_hours += diff_hours.len();
+
+        // 2. 对每个有差异的小时，进行分钟级对比和同步
+        for hour_ts in diff_hours {
+            let hour_start = chrono::DateTime::from_timestamp(hour_ts as i64, 0)
+                .unwrap()
+                .naive_utc();
+            let hour_end = hour_start + Duration::hours(1);
+
+            if let Err(e) = self
+                .compare_and_sync_minutely(
+                    local_table,
+                    remote_table,
+                    hour_start,
+                    hour_end,
+                    &mut stats,
+                )
                 .await
             {
-                Ok(diff_hours) => {
-                    if diff_hours.is_empty() {
-                        println!("   ✅ No differences found");
-                        continue;
-                    }
+                let error_msg =
+                    format!("{} -> {}: hour {}: {}", local_table, remote_table, hour_start, e);
+                tracing::error!(hour = %hour_start, error = %error_msg, "failed to sync hour");
+                stats.errors.push(error_msg);
+            }
+        }
 
-                    println!("   ⚠️  Found {} hours with differences", diff_hours.len());
-                    stats.diff_hours += diff_hours.len();
-
-                    // 2. 对每个有差异的小时，进行分钟级对比和同步
-                    for hour_ts in diff_hours {
-                        let hour_start = chrono::DateTime::from_timestamp(hour_ts as i64, 0)
-                            .unwrap()
-                            .naive_utc();
-                        let hour_end = hour_start + Duration::hours(1);
-
-                        match self
-                            .compare_and_sync_minutely(
-                                local_table,
-                                remote_table,
-                                hour_start,
-                                hour_end,
-                                &mut stats,
-                            )
-                            .await
-                        {
-                            Ok(_) => {}
-                            Err(e) => {
-                                let error_msg =
-                                    format!("{} -> {}: hour {}: {}", local_table, remote_table, hour_start, e);
-                                stats.errors.push(error_msg.clone());
-                                eprintln!("      ✗ Error: {}", error_msg);
-                            }
-                        }
-                    }
-                }
+        // 3. 可选：立即去掉重复同步的行
+        if self.config.deduplicate_after_sync && stats.synced_records > 0 {
+            match self.deduplicate_remote(local_table, remote_table).await {
+                Ok(removed) => stats.deduplicated_rows += removed,
                 Err(e) => {
-                    let error_msg = format!("{} -> {}: {}", local_table, remote_table, e);
-                    stats.errors.push(error_msg.clone());
-                    eprintln!("   ✗ Error comparing hours: {}", error_msg);
+                    let error_msg =
+                        format!("{} -> {}: deduplicate: {}", local_table, remote_table, e);
+                    tracing::error!(error = %error_msg, "failed to deduplicate remote table");
+                    stats.errors.push(error_msg);
                 }
             }
+        }
+
+        stats
+    }
 
-            println!();
+    /// 对远程表执行 OPTIMIZE ... FINAL DEDUPLICATE BY，不等后台合并直接去掉重复行
+    ///
+    /// 只支持 MergeTree 系列引擎（其他引擎没有 DEDUPLICATE）。返回去掉的行数，
+    /// 即执行前后 count() 之差，期间有其他写入时只是近似值
+    pub async fn deduplicate_remote(&self, local_table: &str, remote_table: &str) -> Result<u64> {
+        let engine = Self::table_engine(&self.remote_client, remote_table).await?;
+        if !engine.ends_with("MergeTree") {
+            return Err(format!(
+                "remote table {} uses engine {}, which does not support DEDUPLICATE",
+                remote_table, engine
+            )
+            .into());
         }
 
-        Ok(stats)
+        let key_columns = Self::table_key_columns(&self.remote_client, remote_table).await?;
+        let columns = deduplicate_columns(&self.config.dedup_key(local_table), &key_columns);
+        let count_query = format!("SELECT count() FROM {}", quote_table(remote_table));
+
+        let before: u64 = self.remote_client.query(&count_query).fetch_one().await?;
+        self.remote_client
+            .query(&optimize_deduplicate_sql(remote_table, &columns))
+            .execute()
+            .await?;
+        let after: u64 = self.remote_client.query(&count_query).fetch_one().await?;
+
+        let removed = before.saturating_sub(after);
+        tracing::info!(table = remote_table, removed, ?columns, "deduplicated remote table");
+        Ok(removed)
+    }
+
+    /// 查询表引擎（表名可带数据库前缀）
+    async fn table_engine(client: &Client, table: &str) -> Result<String> {
+        let (database, name) = split_table(table);
+        let engines: Vec<String> = match database {
+            Some(database) => {
+                client
+                    .query("SELECT engine FROM system.tables WHERE database = ? AND name = ?")
+                    .bind(database)
+                    .bind(name)
+                    .fetch_all()
+                    .await?
+            }
+            None => {
+                client
+                    .query("SELECT engine FROM system.tables WHERE database = currentDatabase() AND name = ?")
+                    .bind(name)
+                    .fetch_all()
+                    .await?
+            }
+        };
+        engines
+            .into_iter()
+            .next()
+            .ok_or_else(|| format!("table {} does not exist", table).into())
+    }
+
+    /// 查询排序键和分区键包含的列（按表中的列顺序）
+    async fn table_key_columns(client: &Client, table: &str) -> Result<Vec<String>> {
+        let (database, name) = split_table(table);
+        let filter = "(is_in_sorting_key OR is_in_partition_key) ORDER BY position";
+        let columns: Vec<String> = match database {
+            Some(database) => {
+                client
+                    .query(&format!(
+                        "SELECT name FROM system.columns WHERE database = ? AND table = ? AND {}",
+                        filter
+                    ))
+                    .bind(database)
+                    .bind(name)
+                    .fetch_all()
+                    .await?
+            }
+            None => {
+                client
+                    .query(&format!(
+                        "SELECT name FROM system.columns WHERE database = currentDatabase() AND table = ? AND {}",
+                        filter
+                    ))
+                    .bind(name)
+                    .fetch_all()
+                    .await?
+            }
+        };
+        Ok(columns)
+    }
+
+    /// 启动时校验每张表的去重键列在本地表和远程表中都存在
+    pub async fn validate_dedup_keys(&self) -> Result<()> {
+        for (local_table, remote_table) in &self.config.table_mappings {
+            let key = self.config.dedup_key(local_table);
+            for (client, table, side) in [
+                (&self.local_client, local_table, "local"),
+                (&self.remote_client, remote_table, "remote"),
+            ] {
+                let columns = Self::table_columns(client, table).await?;
+                if columns.is_empty() {
+                    return Err(format!("{} table {} does not exist", side, table).into());
+                }
+                let missing: Vec<&String> = key.iter().filter(|c| !columns.contains(c)).collect();
+                if !missing.is_empty() {
+                    return Err(format!(
+                        "Dedup key column(s) {:?} not found in {} table {}",
+                        missing, side, table
+                    )
+                    .into());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// 查询表的列名（表名可带数据库前缀，否则为当前数据库）
+    async fn table_columns(client: &Client, table: &str) -> Result<Vec<String>> {
+        #[derive(Row, Deserialize)]
+        struct ColumnName {
+            name: String,
+        }
+
+        let (database, table) = split_table(table);
+        let columns: Vec<ColumnName> = match database {
+            Some(database) => {
+                client
+                    .query("SELECT name FROM system.columns WHERE database = ? AND table = ?")
+                    .bind(database)
+                    .bind(table)
+                    .fetch_all()
+                    .await?
+            }
+            None => {
+                client
+                    .query("SELECT name FROM system.columns WHERE database = currentDatabase() AND table = ?")
+                    .bind(table)
+                    .fetch_all()
+                    .await?
+            }
+        };
+        Ok(columns.into_iter().map(|c| c.name).collect())
     }
 
     /// 计算时间范围：now() - lag_hours 到 check_days 天前
@@ -170,17 +584,19 @@ impl SyncChecker {
     ) -> Result<Vec<u32>> {
         let start_ts = start_time.and_utc().timestamp() as u32;
         let end_ts = end_time.and_utc().timestamp() as u32;
+        let key_expr = self.config.dedup_key_expr(local_table);
+        let ts = quote_ident(TIMESTAMP_COLUMN);
 
         // 查询本地小时级统计
         let query = format!(
             "SELECT 
-                toUnixTimestamp(toStartOfHour(toDateTime(timestamp))) as hour,
-                uniqExact(tuple(signature, instruction_index)) as unique_count
+                toUnixTimestamp(toStartOfHour(toDateTime({ts}))) as hour,
+                uniqExact({}) as unique_count
             FROM {}
-            WHERE timestamp >= {} AND timestamp < {}
+            WHERE {ts} >= {} AND {ts} < {}
             GROUP BY hour
             ORDER BY hour",
-            local_table, start_ts, end_ts
+            key_expr, quote_table(local_table), start_ts, end_ts
         );
 
         let local_counts: Vec<HourCount> = self.local_client.query(&query).fetch_all().await?;
@@ -188,13 +604,13 @@ impl SyncChecker {
         // 查询远程小时级统计
         let query = format!(
             "SELECT 
-                toUnixTimestamp(toStartOfHour(toDateTime(timestamp))) as hour,
-                uniqExact(tuple(signature, instruction_index)) as unique_count
+                toUnixTimestamp(toStartOfHour(toDateTime({ts}))) as hour,
+                uniqExact({}) as unique_count
             FROM {}
-            WHERE timestamp >= {} AND timestamp < {}
+            WHERE {ts} >= {} AND {ts} < {}
             GROUP BY hour
             ORDER BY hour",
-            remote_table, start_ts, end_ts
+            key_expr, quote_table(remote_table), start_ts, end_ts
         );
 
         let remote_counts: Vec<HourCount> = self.remote_client.query(&query).fetch_all().await?;
@@ -236,22 +652,22 @@ impl SyncChecker {
     ) -> Result<()> {
         let start_ts = hour_start.and_utc().timestamp() as u32;
         let end_ts = hour_end.and_utc().timestamp() as u32;
+        let key_expr = self.config.dedup_key_expr(local_table);
+        let ts = quote_ident(TIMESTAMP_COLUMN);
 
-        println!(
-            "      📅 Processing hour: {}",
-            hour_start.format("%Y-%m-%d %H:00")
-        );
+        let hour = hour_start.format("%Y-%m-%d %H:00");
+        tracing::debug!(%hour, "processing hour");
 
         // 查询本地分钟级统计
         let query = format!(
             "SELECT 
-                toUnixTimestamp(toStartOfMinute(toDateTime(timestamp))) as minute,
-                uniqExact(tuple(signature, instruction_index)) as unique_count
+                toUnixTimestamp(toStartOfMinute(toDateTime({ts}))) as minute,
+                uniqExact({}) as unique_count
             FROM {}
-            WHERE timestamp >= {} AND timestamp < {}
+            WHERE {ts} >= {} AND {ts} < {}
             GROUP BY minute
             ORDER BY minute",
-            local_table, start_ts, end_ts
+            key_expr, quote_table(local_table), start_ts, end_ts
         );
 
         let local_counts: Vec<MinuteCount> = self.local_client.query(&query).fetch_all().await?;
@@ -259,125 +675,270 @@ impl SyncChecker {
         // 查询远程分钟级统计
         let query = format!(
             "SELECT 
-                toUnixTimestamp(toStartOfMinute(toDateTime(timestamp))) as minute,
-                uniqExact(tuple(signature, instruction_index)) as unique_count
+                toUnixTimestamp(toStartOfMinute(toDateTime({ts}))) as minute,
+                uniqExact({}) as unique_count
             FROM {}
-            WHERE timestamp >= {} AND timestamp < {}
+            WHERE {ts} >= {} AND {ts} < {}
             GROUP BY minute
             ORDER BY minute",
-            remote_table, start_ts, end_ts
+            key_expr, quote_table(remote_table), start_ts, end_ts
         );
 
         let remote_counts: Vec<MinuteCount> = self.remote_client.query(&query).fetch_all().await?;
 
-        // 转换为 HashMap 便于对比
-        let mut remote_map: HashMap<u32, u64> = remote_counts
-            .into_iter()
-            .map(|m| (m.minute, m.unique_count))
-            .collect();
-
-        // 找出有差异的分钟并同步
-        let mut diff_count = 0;
-        for local in local_counts {
-            let remote_count = remote_map.remove(&local.minute).unwrap_or(0);
-            if local.unique_count != remote_count {
-                diff_count += 1;
-                // 同步这一分钟的数据
-                match self
-                    .sync_minute_data(local_table, remote_table, local.minute)
-                    .await
-                {
-                    Ok(count) => {
-                        stats.synced_records += count;
-                        let minute_time = chrono::DateTime::from_timestamp(local.minute as i64, 0)
-                            .unwrap()
-                            .naive_utc();
-                        println!(
-                            "         ✓ Synced minute {} ({} records)",
-                            minute_time.format("%H:%M"),
-                            count
-                        );
-                    }
-                    Err(e) => {
-                        let error_msg = format!("minute {}: {}", local.minute, e);
-                        stats.errors.push(error_msg.clone());
-                        eprintln!("         ✗ Error: {}", error_msg);
-                    }
-                }
-            }
+        // 只有本地有数据的分钟才需要同步；远程独有的分钟单独计为 remote_surplus
+        let diff = diff_counts(
+            local_counts.into_iter().map(|m| (m.minute, m.unique_count)),
+            remote_counts.into_iter().map(|m| (m.minute, m.unique_count)),
+        );
+        for (minute, remote_count) in &diff.remote_only {
+            let minute_time = chrono::DateTime::from_timestamp(*minute as i64, 0)
+                .unwrap()
+                .naive_utc();
+            tracing::info!(
+                minute = %minute_time.format("%Y-%m-%d %H:%M"),
+                remote_rows = remote_count,
+                "minute only exists on remote, nothing to sync"
+            );
         }
+        stats.remote_surplus += diff.remote_only.len();
+
+        let diff_minutes = diff.differing;
+        let diff_count = diff_minutes.len();
+
+        for (minute, local_count, remote_count) in diff_minutes {
+            let minute_time = chrono::DateTime::from_timestamp(minute as i64, 0)
+                .unwrap()
+                .naive_utc();
+
+            // 只读模式：只记录差异，不写入
+            if self.config.mode == SyncMode::ReportOnly {
+                tracing::info!(
+                    minute = %minute_time.format("%Y-%m-%d %H:%M"),
+                    local_rows = local_count,
+                    remote_rows = remote_count,
+                    "minute differs"
+                );
+                continue;
+            }
 
-        // 远程有但本地没有的分钟（理论上不应该发生）
-        for (minute, _) in remote_map {
-            diff_count += 1;
-            match self.sync_minute_data(local_table, remote_table, minute).await {
+            // 同步这一分钟的数据
+            match self.sync_minute(local_table, remote_table, minute, stats).await {
                 Ok(count) => {
                     stats.synced_records += count;
-                    let minute_time = chrono::DateTime::from_timestamp(minute as i64, 0)
-                        .unwrap()
-                        .naive_utc();
-                    println!(
-                        "         ✓ Synced minute {} ({} records)",
-                        minute_time.format("%H:%M"),
-                        count
+                    tracing::info!(
+                        minute = %minute_time.format("%Y-%m-%d %H:%M"),
+                        rows = count,
+                        "synced minute"
                     );
                 }
                 Err(e) => {
-                    let error_msg = format!("minute {}: {}", minute, e);
-                    stats.errors.push(error_msg.clone());
-                    eprintln!("         ✗ Error: {}", error_msg);
+                    let error_msg = format!("{} -> {}: minute {}: {}", local_table, remote_table, minute, e);
+                    tracing::error!(
+                        minute = %minute_time.format("%Y-%m-%d %H:%M"),
+                        error = %error_msg,
+                        "failed to sync minute"
+                    );
+                    stats.errors.push(error_msg);
                 }
             }
         }
 
         stats.diff_minutes += diff_count;
-        println!("      → {} minutes with differences", diff_count);
+        tracing::info!(%hour, diff_minutes = diff_count, "hour processed");
 
         Ok(())
     }
 
-    /// 同步单个分钟的数据
-    async fn sync_minute_data(
+    /// 同步一个有差异的分钟
+    ///
+    /// fine_grained 时先按 10 秒分桶对比，只同步有差异的桶；否则整分钟同步
+    async fn sync_minute(
         &self,
         local_table: &str,
         remote_table: &str,
         minute_ts: u32,
+        stats: &mut SyncStats,
     ) -> Result<u64> {
-        let minute_start = minute_ts;
-        let minute_end = minute_ts + 60;
+        if !self.config.fine_grained {
+            return self
+                .sync_window(local_table, remote_table, minute_ts, minute_ts + 60)
+                .await;
+        }
 
-        // 查询本地数据的记录数
-        let count_query = format!(
-            "SELECT count() as cnt FROM {} WHERE timestamp >= {} AND timestamp < {}",
-            local_table, minute_start, minute_end
-        );
-        
-        #[derive(Row, Deserialize)]
-        struct CountResult {
-            cnt: u64,
+        let windows = self
+            .compare_buckets(local_table, remote_table, minute_ts, minute_ts + 60)
+            .await?;
+
+        let mut synced = 0;
+        for (start_ts, end_ts) in windows {
+            synced += self
+                .sync_window(local_table, remote_table, start_ts, end_ts)
+                .await?;
+            stats.synced_buckets += 1;
         }
-        
-        let count_result: Vec<CountResult> = self.local_client.query(&count_query).fetch_all().await?;
-        let record_count = count_result.first().map(|r| r.cnt).unwrap_or(0);
+        Ok(synced)
+    }
+
+    /// 按 FINE_BUCKET_SECS 分桶对比 [start_ts, end_ts)，返回有差异的桶对应的同步窗口
+    async fn compare_buckets(
+        &self,
+        local_table: &str,
+        remote_table: &str,
+        start_ts: u32,
+        end_ts: u32,
+    ) -> Result<Vec<(u32, u32)>> {
+        let key_expr = self.config.dedup_key_expr(local_table);
+        let local_query = bucket_counts_sql(local_table, &key_expr, start_ts, end_ts);
+        let remote_query = bucket_counts_sql(remote_table, &key_expr, start_ts, end_ts);
+
+        let local_counts: Vec<BucketCount> =
+            self.local_client.query(&local_query).fetch_all().await?;
+        let remote_counts: Vec<BucketCount> =
+            self.remote_client.query(&remote_query).fetch_all().await?;
+
+        Ok(fine_sync_windows(
+            local_counts.into_iter().map(|b| (b.bucket, b.unique_count)),
+            remote_counts.into_iter().map(|b| (b.bucket, b.unique_count)),
+        ))
+    }
+
+    /// 同步 [start_ts, end_ts) 时间窗口内的数据
+    ///
+    /// # Arguments
+    /// * `local_table` - 本地表名（数据来源）
+    /// * `remote_table` - 远程表名（写入目标）
+    /// * `start_ts` - 窗口起始 Unix timestamp（含）
+    /// * `end_ts` - 窗口结束 Unix timestamp（不含）
+    ///
+    /// # Returns
+    /// * `Result<u64>` - 本地窗口内的记录数（即同步的记录数）
+    pub async fn sync_window(
+        &self,
+        local_table: &str,
+        remote_table: &str,
+        start_ts: u32,
+        end_ts: u32,
+    ) -> Result<u64> {
+        // 查询本地数据的记录数
+        let record_count =
+            Self::window_count(&self.local_client, local_table, start_ts, end_ts).await?;
 
         // 如果有数据，则通过 remote INSERT ... SELECT 直接从本地拉取并插入
         if record_count > 0 {
             // 使用 remote() 函数让远程 ClickHouse 直接从本地查询数据
+            let source_table = match split_table(local_table) {
+                (Some(_), _) => quote_table(local_table),
+                (None, table) => format!("{}.{}", quote_ident(&self.config.local_database), quote_ident(table)),
+            };
+            let ts = quote_ident(TIMESTAMP_COLUMN);
             let insert_query = format!(
-                "INSERT INTO {} SELECT * FROM remote('{}', {}, {}, '{}', '{}') WHERE timestamp >= {} AND timestamp < {}",
-                remote_table,
-                self.config.local_url.trim_start_matches("http://").trim_start_matches("https://"),
-                self.config.local_database,
-                local_table,
-                self.config.local_user,
-                self.config.local_password,
-                minute_start,
-                minute_end
+                "INSERT INTO {} SELECT * FROM remote({}, {}, {}, {}) WHERE {ts} >= {} AND {ts} < {}",
+                quote_table(remote_table),
+                quote_str(self.config.local_url.trim_start_matches("http://").trim_start_matches("https://")),
+                source_table,
+                quote_str(&self.config.local_user),
+                quote_str(&self.config.local_password),
+                start_ts,
+                end_ts
             );
             
-            self.remote_client.query(&insert_query).execute().await?;
+            self.execute_with_retry(&insert_query, remote_table, start_ts, end_ts, record_count)
+                .await?;
         }
 
         Ok(record_count)
     }
+
+    /// [start_ts, end_ts) 窗口内的行数
+    async fn window_count(client: &Client, table: &str, start_ts: u32, end_ts: u32) -> Result<u64> {
+        let ts = quote_ident(TIMESTAMP_COLUMN);
+        let query = format!(
+            "SELECT count() as cnt FROM {} WHERE {ts} >= {} AND {ts} < {}",
+            quote_table(table), start_ts, end_ts
+        );
+
+        #[derive(Row, Deserialize)]
+        struct CountResult {
+            cnt: u64,
+        }
+
+        let result: Vec<CountResult> = client.query(&query).fetch_all().await?;
+        Ok(result.first().map(|r| r.cnt).unwrap_or(0))
+    }
+
+    /// 在远程执行 INSERT ... SELECT，失败后按指数退避重试，重试耗尽才返回错误
+    ///
+    /// INSERT ... SELECT 不是幂等的：超时、断连等失败时写入可能已经生效。每次失败后
+    /// 重新统计远程窗口行数，只有确认没有写入任何行时才重试；已全部写入视为成功，
+    /// 写入了一部分则直接报错，避免重试产生重复行
+    async fn execute_with_retry(
+        &self,
+        query: &str,
+        remote_table: &str,
+        start_ts: u32,
+        end_ts: u32,
+        expected_rows: u64,
+    ) -> Result<()> {
+        let max_retries = self.config.sync_retries;
+        let remote_before =
+            Self::window_count(&self.remote_client, remote_table, start_ts, end_ts).await?;
+        let mut attempt = 0;
+        loop {
+            let error = match self.remote_client.query(query).execute().await {
+                Ok(()) => {
+                    if attempt > 0 {
+                        tracing::info!(window = start_ts, attempts = attempt, "recovered after retries");
+                    }
+                    return Ok(());
+                }
+                Err(e) => e,
+            };
+
+            let remote_after =
+                match Self::window_count(&self.remote_client, remote_table, start_ts, end_ts).await {
+                    Ok(count) => count,
+                    Err(count_error) => {
+                        return Err(format!(
+                            "sync insert failed ({}) and the remote row count could not be checked ({}), not retrying",
+                            error, count_error
+                        )
+                        .into());
+                    }
+                };
+
+            match insert_outcome(remote_before, remote_after, expected_rows) {
+                InsertOutcome::Applied => {
+                    tracing::warn!(
+                        window = start_ts,
+                        rows = expected_rows,
+                        error = %error,
+                        "sync insert reported an error but all rows were written"
+                    );
+                    return Ok(());
+                }
+                InsertOutcome::PartiallyApplied => {
+                    return Err(format!(
+                        "sync insert failed after remote rows changed from {} to {} (expected +{}), not retrying: {}",
+                        remote_before, remote_after, expected_rows, error
+                    )
+                    .into());
+                }
+                InsertOutcome::NotApplied if attempt < max_retries => {
+                    attempt += 1;
+                    let delay = self.config.sync_retry_delay_secs * 2_u64.pow(attempt as u32 - 1);
+                    tracing::warn!(
+                        window = start_ts,
+                        attempt,
+                        max_attempts = max_retries + 1,
+                        retry_in_secs = delay,
+                        error = %error,
+                        "sync insert failed, retrying"
+                    );
+                    sleep(std::time::Duration::from_secs(delay)).await;
+                }
+                InsertOutcome::NotApplied => return Err(error.into()),
+            }
+        }
+    }
 }
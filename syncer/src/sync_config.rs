@@ -1,6 +1,9 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::error::Error;
+use utils::env_expand::parse_toml;
+
+use crate::sync_checker::quote_ident;
 
 pub type Result<T> = std::result::Result<T, Box<dyn Error>>;
 
@@ -32,7 +35,12 @@ pub struct SyncConfig {
     pub remote_password: String,
     
     /// 表映射：本地表名 -> 远程表名
+    #[serde(default)]
     pub table_mappings: HashMap<String, String>,
+
+    /// 本地和远程同名的表，加载时并入 table_mappings（table_mappings 中显式配置的优先）
+    #[serde(default)]
+    pub same_name: Vec<String>,
     
     /// 检查天数（默认 7 天）
     #[serde(default = "default_check_days")]
@@ -41,8 +49,55 @@ pub struct SyncConfig {
     /// 本地延迟小时数（默认 2 小时）
     #[serde(default = "default_lag_hours")]
     pub lag_hours: u32,
+
+    /// 去重键：本地表名 -> 键列，"*" 为所有未单独配置的表的默认键
+    ///
+    /// 优先级：表名条目 > "*" 条目 > (signature, instruction_index)
+    #[serde(default)]
+    pub dedup_keys: HashMap<String, Vec<String>>,
+
+    /// 分钟有差异时再按 10 秒分桶对比，只同步有差异的桶
+    #[serde(default)]
+    pub fine_grained: bool,
+
+    /// 同时检查的最大表数（默认 1，即串行）
+    #[serde(default = "default_max_concurrent_tables")]
+    pub max_concurrent_tables: usize,
+
+    /// 运行模式（"sync" 或 "report_only"，默认 sync）
+    #[serde(default)]
+    pub mode: SyncMode,
+
+    /// 写入失败后的重试次数（默认 3）
+    #[serde(default = "default_sync_retries")]
+    pub sync_retries: usize,
+
+    /// 首次重试前的等待秒数（之后指数退避，默认 2）
+    #[serde(default = "default_sync_retry_delay_secs")]
+    pub sync_retry_delay_secs: u64,
+
+    /// 同步过的远程表执行 OPTIMIZE ... FINAL DEDUPLICATE BY 去重键，不等后台合并
+    #[serde(default)]
+    pub deduplicate_after_sync: bool,
 }
 
+/// 同步检查器运行模式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncMode {
+    /// 只对比并记录差异，从不执行 INSERT ... SELECT
+    ReportOnly,
+    /// 对比并同步有差异的分钟
+    #[default]
+    Sync,
+}
+
+/// 未配置 dedup_keys 时使用的默认去重键
+pub const DEFAULT_DEDUP_KEY: [&str; 2] = ["signature", "instruction_index"];
+
+/// dedup_keys 中作为默认键的通配条目
+pub const DEDUP_KEY_WILDCARD: &str = "*";
+
 fn default_check_days() -> u32 {
     7
 }
@@ -51,10 +106,148 @@ fn default_lag_hours() -> u32 {
     2
 }
 
+fn default_max_concurrent_tables() -> usize {
+    1
+}
+
+fn default_sync_retries() -> usize {
+    3
+}
+
+fn default_sync_retry_delay_secs() -> u64 {
+    2
+}
+
 impl SyncConfig {
     /// 从 TOML 文件加载配置
     pub fn from_file(path: &str) -> Result<Self> {
         let content = std::fs::read_to_string(path)?;
-        Ok(toml::from_str(&content)?)
+        let mut config: Self = parse_toml(&content)?;
+        config.expand_same_name();
+        config.ensure_valid()?;
+        Ok(config)
+    }
+
+    /// 把 same_name 中的表并入 table_mappings，已显式映射的表保持不变
+    pub fn expand_same_name(&mut self) {
+        for table in self.same_name.drain(..) {
+            self.table_mappings.entry(table.clone()).or_insert(table);
+        }
+    }
+
+    /// 校验配置，返回发现的全部问题（为空表示配置有效）
+    ///
+    /// 一次列出所有问题，而不是改一处、运行、再报下一处
+    pub fn validate(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+
+        for (field, url) in [("local_url", &self.local_url), ("remote_url", &self.remote_url)] {
+            if let Err(reason) = check_http_url(url) {
+                problems.push(format!("{} '{}' is invalid: {}", field, url, reason));
+            }
+        }
+        for (field, database) in [
+            ("local_database", &self.local_database),
+            ("remote_database", &self.remote_database),
+        ] {
+            if database.trim().is_empty() {
+                problems.push(format!("{} must not be empty", field));
+            }
+        }
+        if self.local_url.trim_end_matches('/') == self.remote_url.trim_end_matches('/')
+            && self.local_database == self.remote_database
+        {
+            problems.push("local and remote point to the same ClickHouse database".to_string());
+        }
+
+        if self.table_mappings.is_empty() {
+            problems.push("table_mappings must contain at least one table".to_string());
+        }
+        let mut local_tables: Vec<&String> = self.table_mappings.keys().collect();
+        local_tables.sort();
+        for local_table in local_tables {
+            if local_table.trim().is_empty() || self.table_mappings[local_table].trim().is_empty() {
+                problems.push(format!(
+                    "table_mappings entry '{}' = '{}' has an empty table name",
+                    local_table, self.table_mappings[local_table]
+                ));
+            }
+        }
+        let mut unknown: Vec<&String> = self
+            .dedup_keys
+            .keys()
+            .filter(|table| {
+                table.as_str() != DEDUP_KEY_WILDCARD && !self.table_mappings.contains_key(*table)
+            })
+            .collect();
+        unknown.sort();
+        for table in unknown {
+            problems.push(format!("dedup_keys entry '{}' is not in table_mappings", table));
+        }
+
+        if self.check_days == 0 {
+            problems.push("check_days must be greater than 0".to_string());
+        }
+        if self.max_concurrent_tables == 0 {
+            problems.push("max_concurrent_tables must be at least 1".to_string());
+        }
+        problems
+    }
+
+    /// 配置无效时返回列出全部问题的错误
+    pub fn ensure_valid(&self) -> Result<()> {
+        let problems = self.validate();
+        if problems.is_empty() {
+            return Ok(());
+        }
+        Err(format!(
+            "Invalid sync config ({} problems):\n  - {}",
+            problems.len(),
+            problems.join("\n  - ")
+        )
+        .into())
+    }
+
+    /// 某张本地表的去重键列（表名条目 > "*" 条目 > 内置默认键）
+    pub fn dedup_key(&self, local_table: &str) -> Vec<String> {
+        [local_table, DEDUP_KEY_WILDCARD]
+            .iter()
+            .filter_map(|table| self.dedup_keys.get(*table))
+            .find(|columns| !columns.is_empty())
+            .cloned()
+            .unwrap_or_else(|| DEFAULT_DEDUP_KEY.iter().map(|c| c.to_string()).collect())
+    }
+
+    /// 某张本地表的去重键表达式，例如 "tuple(`signature`, `instruction_index`)"
+    pub fn dedup_key_expr(&self, local_table: &str) -> String {
+        let columns: Vec<String> = self
+            .dedup_key(local_table)
+            .iter()
+            .map(|column| quote_ident(column))
+            .collect();
+        format!("tuple({})", columns.join(", "))
+    }
+}
+
+/// 检查 `http(s)://host[:port][/path]` 形式的 URL
+fn check_http_url(url: &str) -> std::result::Result<(), String> {
+    let rest = url
+        .strip_prefix("http://")
+        .or_else(|| url.strip_prefix("https://"))
+        .ok_or("expected an http:// or https:// URL")?;
+    let authority = rest.split('/').next().unwrap_or_default();
+    let host_port = authority.rsplit('@').next().unwrap_or_default();
+    let (host, port) = match host_port.rsplit_once(':') {
+        Some((host, port)) => (host, Some(port)),
+        None => (host_port, None),
+    };
+    if host.is_empty() {
+        return Err("missing host".to_string());
+    }
+    if let Some(port) = port
+        && port.parse::<u16>().is_err()
+    {
+        return Err(format!("invalid port '{}'", port));
     }
+    Ok(())
 }
@@ -1,25 +1,192 @@
+use futures::FutureExt;
+use futures::future::LocalBoxFuture;
+use serde::{Deserialize, Serialize};
 use std::error::Error;
+use std::fmt;
 use std::path::Path;
-use crate::config::RemoteServerConfig;
+use crate::config::{LocalConfig, RemoteServerConfig};
+use crate::s3_transport::S3Transport;
 use tokio::process::Command;
 use tokio::time::{sleep, Duration};
 
 pub type Result<T> = std::result::Result<T, Box<dyn Error>>;
 
+/// 文件传输目标（rsync 远程目录、S3 bucket 等）
+///
+/// 目标路径统一使用 `{dest}/{file_name}` 的相对 key，
+/// 本地表目录同步后远端布局为 `table/table_YYYY-MM-DD.parquet`
+pub trait Transport {
+    /// 将本地目录下的文件同步到目标的 `dest` 子目录
+    ///
+    /// # Arguments
+    /// * `local_dir` - 本地目录路径（表目录）
+    /// * `dest` - 目标下的相对目录（表名）
+    fn sync_directory<'a>(&'a self, local_dir: &'a Path, dest: &'a str) -> LocalBoxFuture<'a, Result<()>>;
+
+    /// 检查目标上是否存在 `key`（相对路径，如 `table/table_2025-10-01.parquet`）
+    fn exists<'a>(&'a self, key: &'a str) -> LocalBoxFuture<'a, Result<bool>>;
+}
+
+/// 根据本地模式配置选择传输器：配置了 `[s3]` 时上传到对象存储，否则使用 rsync
+pub fn from_config(config: &LocalConfig) -> Result<Box<dyn Transport>> {
+    if let Some(s3_config) = &config.s3 {
+        return Ok(Box::new(
+            S3Transport::new(s3_config)?
+                .with_retry_config(config.transfer_retries, config.transfer_retry_delay_secs),
+        ));
+    }
+
+    let remote_server = config
+        .remote_server
+        .clone()
+        .ok_or("Either [remote_server] or [s3] must be configured")?;
+    Ok(Box::new(
+        RsyncTransport::with_retry_config(config.transfer_retries, config.transfer_retry_delay_secs)
+            .with_options(config.rsync.clone())
+            .with_remote(remote_server),
+    ))
+}
+
+/// 按 POSIX shell 单引号规则转义，结果可以安全地拼进远程命令
+pub fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
+/// 错误信息中保留的 stderr 末尾行数
+pub const STDERR_TAIL_LINES: usize = 20;
+
+/// rsync 进程以非零状态退出
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RsyncError {
+    /// 退出码（被信号终止时为 None）
+    pub exit_code: Option<i32>,
+    /// stderr 的最后 STDERR_TAIL_LINES 行（去掉空行）
+    pub stderr_tail: Vec<String>,
+}
+
+impl RsyncError {
+    pub fn new(exit_code: Option<i32>, stderr: &str) -> Self {
+        let lines: Vec<&str> = stderr
+            .lines()
+            .map(str::trim_end)
+            .filter(|line| !line.is_empty())
+            .collect();
+        let start = lines.len().saturating_sub(STDERR_TAIL_LINES);
+
+        Self {
+            exit_code,
+            stderr_tail: lines[start..].iter().map(|line| line.to_string()).collect(),
+        }
+    }
+
+    /// rsync 常见退出码的含义（见 man rsync 的 EXIT VALUES）
+    pub fn exit_code_meaning(code: i32) -> Option<&'static str> {
+        match code {
+            1 => Some("syntax or usage error"),
+            2 => Some("protocol incompatibility"),
+            3 => Some("errors selecting input/output files, dirs"),
+            5 => Some("error starting client-server protocol"),
+            10 => Some("error in socket I/O"),
+            11 => Some("error in file I/O"),
+            12 => Some("error in rsync protocol data stream"),
+            20 => Some("received SIGUSR1 or SIGINT"),
+            23 => Some("partial transfer due to error"),
+            24 => Some("partial transfer due to vanished source files"),
+            30 => Some("timeout in data send/receive"),
+            35 => Some("timeout waiting for daemon connection"),
+            255 => Some("ssh connection or authentication failed"),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for RsyncError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.exit_code {
+            Some(code) => match Self::exit_code_meaning(code) {
+                Some(meaning) => write!(f, "rsync failed: exit code {} ({})", code, meaning)?,
+                None => write!(f, "rsync failed: exit code {}", code)?,
+            },
+            None => write!(f, "rsync failed: terminated by signal")?,
+        }
+        // 单行输出，方便在日志里直接 grep
+        if !self.stderr_tail.is_empty() {
+            write!(f, "; stderr: {}", self.stderr_tail.join(" | "))?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for RsyncError {}
+
+/// rsync 传输选项
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RsyncOptions {
+    /// 带宽上限（KB/s），None 或 0 表示不限速
+    pub bwlimit_kbps: Option<u32>,
+    /// 保留部分传输的文件（--partial）
+    pub partial: bool,
+    /// 续传时只追加缺少的部分并整体校验（--append-verify），默认关闭
+    ///
+    /// 远程文件不短于本地时不会重新传输，同名文件被重新导出（内容变化）时需保持关闭
+    pub append_verify: bool,
+    /// 传输时压缩（-z）
+    pub compress: bool,
+    /// 追加到命令末尾（源/目标路径之前）的额外参数
+    pub extra_args: Vec<String>,
+}
+
+impl Default for RsyncOptions {
+    fn default() -> Self {
+        Self {
+            bwlimit_kbps: Some(2048),
+            partial: true,
+            append_verify: false,
+            compress: true,
+            extra_args: Vec::new(),
+        }
+    }
+}
+
+impl RsyncOptions {
+    /// 生成 rsync 参数（不含 -e 和源/目标路径）
+    pub fn to_args(&self) -> Vec<String> {
+        let mut args = vec![
+            if self.compress { "-avz" } else { "-av" }.to_string(), // archive, verbose, compress
+            "--progress".to_string(),                               // 显示进度
+            "--timeout=3000".to_string(),                           // 设置超时时间（秒）
+        ];
+        if self.partial {
+            // 保留部分传输的文件
+            args.push("--partial".to_string());
+        }
+        if self.append_verify {
+            args.push("--append-verify".to_string());
+        }
+        if let Some(kbps) = self.bwlimit_kbps {
+            args.push(format!("--bwlimit={}", kbps));
+        }
+        args.extend(self.extra_args.iter().cloned());
+        args
+    }
+}
+
 /// 基于 rsync 的传输器
 pub struct RsyncTransport {
     /// 最大重试次数
     max_retries: usize,
     /// 初始重试延迟（秒）
     initial_retry_delay: u64,
+    /// rsync 参数选项
+    options: RsyncOptions,
+    /// 作为 `Transport` 使用时的远程服务器
+    remote: Option<RemoteServerConfig>,
 }
 
 impl RsyncTransport {
     pub fn new() -> Self {
-        Self {
-            max_retries: 5,
-            initial_retry_delay: 5,
-        }
+        Self::with_retry_config(5, 5)
     }
 
     /// 创建带自定义重试配置的传输器
@@ -27,6 +194,63 @@ impl RsyncTransport {
         Self {
             max_retries,
             initial_retry_delay,
+            options: RsyncOptions::default(),
+            remote: None,
+        }
+    }
+
+    /// 设置 rsync 参数选项（限速、续传、压缩、额外参数）
+    pub fn with_options(mut self, options: RsyncOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    pub fn options(&self) -> &RsyncOptions {
+        &self.options
+    }
+
+    /// 设置远程服务器，之后可以作为 `Transport` 使用
+    pub fn with_remote(mut self, remote: RemoteServerConfig) -> Self {
+        self.remote = Some(remote);
+        self
+    }
+
+    fn remote(&self) -> Result<&RemoteServerConfig> {
+        self.remote
+            .as_ref()
+            .ok_or_else(|| "RsyncTransport has no remote server configured".into())
+    }
+
+    /// 构建 SSH 命令（rsync -e 使用）
+    fn ssh_command(remote_config: &RemoteServerConfig) -> String {
+        format!(
+            "ssh -p {} -i {} -o ConnectTimeout=30 -o ServerAliveInterval=60 -o ServerAliveCountMax=3 -o TCPKeepAlive=yes",
+            remote_config.port,
+            remote_config.private_key_path.display()
+        )
+    }
+
+    /// 通过 SSH 检查远程文件是否存在
+    pub async fn remote_exists(&self, remote_config: &RemoteServerConfig, key: &str) -> Result<bool> {
+        let remote_file = remote_config.remote_path.join(key);
+        let output = Command::new("ssh")
+            .arg("-p")
+            .arg(remote_config.port.to_string())
+            .arg("-i")
+            .arg(&remote_config.private_key_path)
+            .arg("-o")
+            .arg("ConnectTimeout=30")
+            .arg(format!("{}@{}", remote_config.username, remote_config.address))
+            .arg(format!("test -f {}", shell_quote(&remote_file.display().to_string())))
+            .output()
+            .await
+            .map_err(|e| format!("Failed to spawn ssh: {}", e))?;
+
+        // test -f: 0 存在，1 不存在，255 为 SSH 自身失败
+        match output.status.code() {
+            Some(0) => Ok(true),
+            Some(1) => Ok(false),
+            code => Err(RsyncError::new(code, &String::from_utf8_lossy(&output.stderr)).into()),
         }
     }
 
@@ -49,11 +273,7 @@ impl RsyncTransport {
         }
 
         // 构建 SSH 选项（添加连接超时和重连参数）
-        let ssh_opts = format!(
-            "ssh -p {} -i {} -o ConnectTimeout=30 -o ServerAliveInterval=60 -o ServerAliveCountMax=3 -o TCPKeepAlive=yes",
-            remote_config.port,
-            remote_config.private_key_path.display()
-        );
+        let ssh_opts = Self::ssh_command(remote_config);
 
         // 构建源路径（添加尾部斜杠以同步目录内容）
         let local_src = format!("{}/", local_dir.display());
@@ -66,32 +286,29 @@ impl RsyncTransport {
             remote_config.remote_path.display()
         );
 
-        println!("🚀 Starting rsync transfer...");
-        println!("   Source: {}", local_src);
-        println!("   Destination: {}", remote_dest);
+        tracing::debug!(source = %local_src, destination = %remote_dest, "starting rsync transfer");
 
         // 带重试的执行逻辑
         let mut last_error = None;
         for attempt in 0..=self.max_retries {
             if attempt > 0 {
                 let delay = self.initial_retry_delay * (2_u64.pow(attempt as u32 - 1));
-                println!("   ⏳ Retry attempt {}/{} after {} seconds...", 
-                    attempt, self.max_retries, delay);
+                tracing::info!(attempt, max_retries = self.max_retries, delay_secs = delay, "retrying rsync");
                 sleep(Duration::from_secs(delay)).await;
             }
 
             match self.execute_rsync(&local_src, &remote_dest, &ssh_opts).await {
                 Ok(()) => {
                     if attempt > 0 {
-                        println!("   ✅ Successfully recovered after {} retry attempts", attempt);
+                        tracing::info!(attempts = attempt, "rsync recovered after retries");
                     }
                     return Ok(());
                 }
                 Err(e) => {
-                    last_error = Some(e);
                     if attempt < self.max_retries {
-                        eprintln!("   ⚠️  Attempt {} failed, will retry...", attempt + 1);
+                        tracing::warn!(attempt = attempt + 1, error = %e, "rsync attempt failed, will retry");
                     }
+                    last_error = Some(e);
                 }
             }
         }
@@ -107,49 +324,60 @@ impl RsyncTransport {
         remote_dest: &str,
         ssh_opts: &str,
     ) -> Result<()> {
-        // 执行 rsync 命令
+        // 执行 rsync 命令，捕获 stdout/stderr
         let output = Command::new("rsync")
-            .arg("-avz")           // archive, verbose, compress
-            .arg("--progress")     // 显示进度
-            .arg("--partial")      // 保留部分传输的文件（断点续传）
-            .arg("--timeout=3000")  // 设置超时时间（秒）
-            .arg("--bwlimit=2048")
+            .args(self.options.to_args())
             .arg("-e")
             .arg(ssh_opts)         // SSH 选项
             .arg(local_src)        // 源路径
             .arg(remote_dest)      // 目标路径
             .output()
-            .await?;
+            .await
+            .map_err(|e| format!("Failed to spawn rsync: {}", e))?;
 
         // 检查退出码
         if !output.status.success() {
             let stderr = String::from_utf8_lossy(&output.stderr);
             let stdout = String::from_utf8_lossy(&output.stdout);
             
-            eprintln!("❌ rsync failed with exit code: {:?}", output.status.code());
-            eprintln!("STDOUT:\n{}", stdout);
-            eprintln!("STDERR:\n{}", stderr);
-            
-            return Err(format!(
-                "rsync failed: exit code {:?}\nSTDERR: {}",
-                output.status.code(),
-                stderr
-            ).into());
+            tracing::error!(
+                exit_code = ?output.status.code(),
+                stdout = %stdout,
+                stderr = %stderr,
+                "rsync failed"
+            );
+
+            return Err(RsyncError::new(output.status.code(), &stderr).into());
         }
 
-        // 输出成功信息
+        // 解析传输统计（如果有）
         let stdout = String::from_utf8_lossy(&output.stdout);
-        println!("✅ rsync completed successfully");
-        
-        // 解析并显示传输统计（如果有）
-        if let Some(stats_line) = stdout.lines().find(|line| line.contains("sent") || line.contains("total size")) {
-            println!("   📊 {}", stats_line.trim());
-        }
+        let stats_line = stdout
+            .lines()
+            .find(|line| line.contains("sent") || line.contains("total size"))
+            .map(str::trim);
+        tracing::debug!(stats = stats_line.unwrap_or_default(), "rsync completed");
 
         Ok(())
     }
 }
 
+impl Transport for RsyncTransport {
+    fn sync_directory<'a>(&'a self, local_dir: &'a Path, dest: &'a str) -> LocalBoxFuture<'a, Result<()>> {
+        async move {
+            // 同步到 remote_path/{dest}，保持与本地相同的表目录布局
+            let mut remote_config = self.remote()?.clone();
+            remote_config.remote_path = remote_config.remote_path.join(dest);
+            RsyncTransport::sync_directory(self, local_dir, &remote_config).await
+        }
+        .boxed_local()
+    }
+
+    fn exists<'a>(&'a self, key: &'a str) -> LocalBoxFuture<'a, Result<bool>> {
+        async move { self.remote_exists(self.remote()?, key).await }.boxed_local()
+    }
+}
+
 impl Default for RsyncTransport {
     fn default() -> Self {
         Self::new()
@@ -0,0 +1,112 @@
+use arrow::array::{StringArray, UInt32Array, UInt64Array};
+use arrow::datatypes::{DataType, Field, Schema};
+use arrow::record_batch::RecordBatch;
+use chrono::NaiveDate;
+use std::sync::Arc;
+use syncer::compactor::{CompactOptions, ParquetCompactor};
+use syncer::parquet_helper::ParquetHelper;
+use tempfile::tempdir;
+
+fn event_batch(slots: Vec<u64>) -> RecordBatch {
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("signature", DataType::Utf8, false),
+        Field::new("slot", DataType::UInt64, false),
+        Field::new("transaction_index", DataType::UInt32, false),
+    ]));
+    let signatures: Vec<String> = slots.iter().map(|s| format!("sig{}", s)).collect();
+    let tx_indexes: Vec<u32> = slots.iter().map(|_| 0).collect();
+
+    RecordBatch::try_new(
+        schema,
+        vec![
+            Arc::new(StringArray::from(signatures)),
+            Arc::new(UInt64Array::from(slots)),
+            Arc::new(UInt32Array::from(tx_indexes)),
+        ],
+    )
+    .unwrap()
+}
+
+#[tokio::test]
+async fn test_compact_two_daily_files_into_month() {
+    let temp_dir = tempdir().unwrap();
+    let root = temp_dir.path();
+    let helper = ParquetHelper::new();
+
+    // 两个日文件，第二天的 slot 更小，用来验证排序
+    let day1 = NaiveDate::from_ymd_opt(2025, 10, 1).unwrap();
+    let day2 = NaiveDate::from_ymd_opt(2025, 10, 2).unwrap();
+    let file1 = helper
+        .write_daily_parquet("compact_test", day1, event_batch(vec![30, 40, 50]), root)
+        .await
+        .unwrap();
+    let file2 = helper
+        .write_daily_parquet("compact_test", day2, event_batch(vec![10, 20]), root)
+        .await
+        .unwrap();
+    // 其它月份的文件不应被包含
+    helper
+        .write_daily_parquet(
+            "compact_test",
+            NaiveDate::from_ymd_opt(2025, 11, 1).unwrap(),
+            event_batch(vec![99]),
+            root,
+        )
+        .await
+        .unwrap();
+
+    let options = CompactOptions {
+        sort: true,
+        remove_originals: true,
+    };
+    let month = ParquetCompactor::parse_month("2025-10").unwrap();
+    let report = ParquetCompactor::new()
+        .compact_month(root, "compact_test", month, &options)
+        .await
+        .unwrap();
+
+    assert_eq!(report.input_files, vec![file1.clone(), file2.clone()]);
+    assert_eq!(report.rows, 5, "Monthly file should contain the combined row count");
+    assert_eq!(
+        report.output_path,
+        root.join("compact_test").join("compact_test_2025-10.parquet")
+    );
+
+    let monthly = helper.read_parquet(&report.output_path).await.unwrap();
+    assert_eq!(monthly.num_rows(), 5);
+    let slots = monthly
+        .column_by_name("slot")
+        .unwrap()
+        .as_any()
+        .downcast_ref::<UInt64Array>()
+        .unwrap();
+    assert_eq!(slots.values().to_vec(), vec![10, 20, 30, 40, 50]);
+
+    // 原始日文件已删除，其它月份保留
+    assert!(!file1.exists());
+    assert!(!file2.exists());
+    assert!(root.join("compact_test").join("compact_test_2025-11-01.parquet").exists());
+}
+
+#[tokio::test]
+async fn test_compact_month_without_files_fails() {
+    let temp_dir = tempdir().unwrap();
+    std::fs::create_dir_all(temp_dir.path().join("empty_table")).unwrap();
+
+    let month = ParquetCompactor::parse_month("2025-10").unwrap();
+    let result = ParquetCompactor::new()
+        .compact_month(temp_dir.path(), "empty_table", month, &CompactOptions::default())
+        .await;
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_parse_month_rejects_bad_format() {
+    assert!(ParquetCompactor::parse_month("2025-13").is_err());
+    assert!(ParquetCompactor::parse_month("October").is_err());
+    assert_eq!(
+        ParquetCompactor::parse_month("2025-10").unwrap(),
+        NaiveDate::from_ymd_opt(2025, 10, 1).unwrap()
+    );
+}
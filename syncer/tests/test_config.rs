@@ -4,7 +4,8 @@ mod test_config {
     use std::collections::HashMap;
     use std::fs;
     use std::path::PathBuf;
-    use syncer::{LocalConfig, RemoteConfig};
+    use syncer::{LocalConfig, PartitionLayout, RemoteConfig, RsyncOptions, SyncConfig};
+    use syncer::sync_config::SyncMode;
     use tempfile::NamedTempFile;
 
     #[test]
@@ -41,8 +42,193 @@ remote_path = "/remote/data/imports"
             NaiveDate::from_ymd_opt(2025, 10, 1).unwrap()
         );
         assert_eq!(config.local_storage_path, PathBuf::from("/data/exports"));
-        assert_eq!(config.remote_server.address, "192.168.1.100");
-        assert_eq!(config.remote_server.port, 22);
+        let remote_server = config.remote_server.as_ref().unwrap();
+        assert_eq!(remote_server.address, "192.168.1.100");
+        assert_eq!(remote_server.port, 22);
+        assert!(config.s3.is_none());
+        assert_eq!(config.end_time, None);
+        assert!(!config.keep_local);
+        assert!(!config.no_transfer);
+        assert_eq!(config.transfer_retries, 5);
+        assert_eq!(config.transfer_retry_delay_secs, 5);
+        assert_eq!(config.rsync, RsyncOptions::default());
+    }
+
+    #[test]
+    fn test_local_config_transfer_options() {
+        let toml_content = r#"
+tables = ["table_a"]
+start_time = "2025-10-01"
+local_storage_path = "/data/exports"
+keep_local = true
+transfer_retries = 3
+transfer_retry_delay_secs = 10
+
+[table_event_mappings]
+table_a = "EventTypeA"
+
+[remote_server]
+address = "192.168.1.100"
+port = 22
+username = "datauser"
+private_key_path = "/home/user/.ssh/id_rsa"
+remote_path = "/remote/data/imports"
+"#;
+
+        let temp_file = NamedTempFile::new().unwrap();
+        fs::write(temp_file.path(), toml_content).unwrap();
+
+        let config = LocalConfig::from_file(temp_file.path().to_str().unwrap()).unwrap();
+        assert!(config.keep_local);
+        assert_eq!(config.transfer_retries, 3);
+        assert_eq!(config.transfer_retry_delay_secs, 10);
+    }
+
+    #[test]
+    fn test_local_config_no_transfer_without_destination() {
+        let toml_content = r#"
+tables = ["table_a"]
+start_time = "2025-10-01"
+local_storage_path = "/data/exports"
+no_transfer = true
+
+[table_event_mappings]
+table_a = "EventTypeA"
+"#;
+
+        let temp_file = NamedTempFile::new().unwrap();
+        fs::write(temp_file.path(), toml_content).unwrap();
+
+        let config = LocalConfig::from_file(temp_file.path().to_str().unwrap()).unwrap();
+        assert!(config.no_transfer);
+        assert!(config.remote_server.is_none());
+        assert!(config.s3.is_none());
+    }
+
+    #[test]
+    fn test_local_config_override_tables() {
+        let toml_content = r#"
+tables = ["table_a", "table_b"]
+start_time = "2025-10-01"
+local_storage_path = "/data/exports"
+
+[table_event_mappings]
+table_a = "EventTypeA"
+table_b = "EventTypeB"
+table_c = "EventTypeC"
+"#;
+
+        let temp_file = NamedTempFile::new().unwrap();
+        fs::write(temp_file.path(), toml_content).unwrap();
+        let mut config = LocalConfig::from_file(temp_file.path().to_str().unwrap()).unwrap();
+
+        config.override_tables(&[]).unwrap();
+        assert_eq!(config.tables, vec!["table_a", "table_b"]);
+
+        // 映射中存在即可，不要求在原 tables 列表里
+        config
+            .override_tables(&["table_c".to_string(), "table_a".to_string(), "table_c".to_string()])
+            .unwrap();
+        assert_eq!(config.tables, vec!["table_c", "table_a"]);
+
+        let error = config
+            .override_tables(&["table_x".to_string()])
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("table_x"), "{}", error);
+        assert_eq!(config.tables, vec!["table_c", "table_a"]);
+    }
+
+    #[test]
+    fn test_local_config_s3_without_remote_server() {
+        let toml_content = r#"
+tables = ["table_a"]
+start_time = "2025-10-01"
+local_storage_path = "/data/exports"
+
+[table_event_mappings]
+table_a = "EventTypeA"
+
+[s3]
+bucket = "exports"
+prefix = "heaven"
+endpoint = "http://127.0.0.1:9000"
+allow_http = true
+"#;
+
+        let temp_file = NamedTempFile::new().unwrap();
+        fs::write(temp_file.path(), toml_content).unwrap();
+
+        let config = LocalConfig::from_file(temp_file.path().to_str().unwrap()).unwrap();
+        assert!(config.remote_server.is_none());
+        let s3 = config.s3.unwrap();
+        assert_eq!(s3.bucket, "exports");
+        assert_eq!(s3.prefix, "heaven");
+        assert_eq!(s3.region, "us-east-1");
+        assert_eq!(s3.endpoint.as_deref(), Some("http://127.0.0.1:9000"));
+        assert!(s3.access_key_id.is_none());
+        assert!(s3.allow_http);
+    }
+
+    #[test]
+    fn test_local_config_rsync_options() {
+        let toml_content = r#"
+tables = ["table_a"]
+start_time = "2025-10-01"
+local_storage_path = "/data/exports"
+
+[table_event_mappings]
+table_a = "EventTypeA"
+
+[rsync]
+bwlimit_kbps = 512
+compress = false
+extra_args = ["--checksum"]
+
+[remote_server]
+address = "192.168.1.100"
+port = 22
+username = "datauser"
+private_key_path = "/home/user/.ssh/id_rsa"
+remote_path = "/remote/data/imports"
+"#;
+
+        let temp_file = NamedTempFile::new().unwrap();
+        fs::write(temp_file.path(), toml_content).unwrap();
+
+        let config = LocalConfig::from_file(temp_file.path().to_str().unwrap()).unwrap();
+        assert_eq!(config.rsync.bwlimit_kbps, Some(512));
+        assert!(!config.rsync.compress);
+        // 未配置的字段使用默认值
+        assert!(config.rsync.partial);
+        assert!(!config.rsync.append_verify);
+        assert_eq!(config.rsync.extra_args, vec!["--checksum"]);
+    }
+
+    #[test]
+    fn test_local_config_end_time() {
+        let toml_content = r#"
+tables = ["table_a"]
+start_time = "2025-10-01"
+end_time = "2025-10-15"
+local_storage_path = "/data/exports"
+
+[table_event_mappings]
+table_a = "EventTypeA"
+
+[remote_server]
+address = "192.168.1.100"
+port = 22
+username = "datauser"
+private_key_path = "/home/user/.ssh/id_rsa"
+remote_path = "/remote/data/imports"
+"#;
+
+        let temp_file = NamedTempFile::new().unwrap();
+        fs::write(temp_file.path(), toml_content).unwrap();
+
+        let config = LocalConfig::from_file(temp_file.path().to_str().unwrap()).unwrap();
+        assert_eq!(config.end_time, NaiveDate::from_ymd_opt(2025, 10, 15));
     }
 
     #[test]
@@ -73,6 +259,79 @@ source_b = "EventTypeB"
             config.table_event_mappings.get("source_a").unwrap(),
             "EventTypeA"
         );
+        assert_eq!(config.partition_layout, PartitionLayout::Flat);
+        assert!(!config.force_reimport);
+        assert_eq!(config.max_concurrent_imports, 1);
+        assert!(!config.preserve_order);
+        assert!(!config.verify_after_import);
+        assert_eq!(config.import_ledger_table, None);
+        assert_eq!(config.import_retries, 3);
+        assert_eq!(config.import_retry_delay_secs, 2);
+        assert_eq!(config.streaming_import_min_bytes, 256 * 1024 * 1024);
+    }
+
+    #[test]
+    fn test_remote_config_concurrent_imports() {
+        let toml_content = r#"
+remote_storage_path = "/remote/data/imports"
+max_concurrent_imports = 8
+preserve_order = true
+
+[import_mappings]
+source_a = "target_a"
+
+[table_event_mappings]
+source_a = "EventTypeA"
+"#;
+
+        let temp_file = NamedTempFile::new().unwrap();
+        fs::write(temp_file.path(), toml_content).unwrap();
+
+        let config = RemoteConfig::from_file(temp_file.path().to_str().unwrap()).unwrap();
+        assert_eq!(config.max_concurrent_imports, 8);
+        assert!(config.preserve_order);
+    }
+
+    #[test]
+    fn test_remote_config_verify_after_import() {
+        let toml_content = r#"
+remote_storage_path = "/remote/data/imports"
+verify_after_import = true
+import_ledger_table = "syncer_import_ledger"
+
+[import_mappings]
+source_a = "target_a"
+
+[table_event_mappings]
+source_a = "EventTypeA"
+"#;
+
+        let temp_file = NamedTempFile::new().unwrap();
+        fs::write(temp_file.path(), toml_content).unwrap();
+
+        let config = RemoteConfig::from_file(temp_file.path().to_str().unwrap()).unwrap();
+        assert!(config.verify_after_import);
+        assert_eq!(config.import_ledger_table.as_deref(), Some("syncer_import_ledger"));
+    }
+
+    #[test]
+    fn test_remote_config_hive_partition_layout() {
+        let toml_content = r#"
+remote_storage_path = "/remote/data/imports"
+partition_layout = "hive_daily"
+
+[import_mappings]
+source_a = "target_a"
+
+[table_event_mappings]
+source_a = "EventTypeA"
+"#;
+
+        let temp_file = NamedTempFile::new().unwrap();
+        fs::write(temp_file.path(), toml_content).unwrap();
+
+        let config = RemoteConfig::from_file(temp_file.path().to_str().unwrap()).unwrap();
+        assert_eq!(config.partition_layout, PartitionLayout::HiveDaily);
     }
 
     #[test]
@@ -152,14 +411,22 @@ remote_storage_path = "/remote/data/imports"
             tables: vec!["table_a".to_string()],
             table_event_mappings,
             start_time: NaiveDate::from_ymd_opt(2025, 10, 1).unwrap(),
+            end_time: None,
             local_storage_path: PathBuf::from("/data/exports"),
-            remote_server: syncer::RemoteServerConfig {
+            dry_run: false,
+            keep_local: false,
+            no_transfer: false,
+            transfer_retries: 0,
+            transfer_retry_delay_secs: 1,
+            rsync: RsyncOptions::default(),
+            remote_server: Some(syncer::RemoteServerConfig {
                 address: "192.168.1.100".to_string(),
                 port: 22,
                 username: "datauser".to_string(),
                 private_key_path: PathBuf::from("/home/user/.ssh/id_rsa"),
                 remote_path: PathBuf::from("/remote/data/imports"),
-            },
+            }),
+            s3: None,
         };
 
         let toml_str = toml::to_string(&config).unwrap();
@@ -196,4 +463,213 @@ remote_path = "/remote/data/imports"
             NaiveDate::from_ymd_opt(2025, 12, 31).unwrap()
         );
     }
+
+    #[test]
+    fn test_sync_config_dedup_keys() {
+        let toml_content = r#"
+local_url = "http://localhost:18123"
+local_database = "default"
+local_user = "default"
+local_password = ""
+remote_url = "http://remote-host:28123"
+remote_database = "default"
+remote_user = "default"
+remote_password = ""
+
+[table_mappings]
+trade = "trade"
+logs = "logs"
+other = "other"
+
+[dedup_keys]
+trade = ["signature", "transaction_index", "instruction_index"]
+logs = ["signature"]
+"#;
+
+        let temp_file = NamedTempFile::new().unwrap();
+        fs::write(temp_file.path(), toml_content).unwrap();
+
+        let config = SyncConfig::from_file(temp_file.path().to_str().unwrap()).unwrap();
+
+        assert_eq!(
+            config.dedup_key_expr("trade"),
+            "tuple(`signature`, `transaction_index`, `instruction_index`)"
+        );
+        assert_eq!(config.dedup_key_expr("logs"), "tuple(`signature`)");
+        // 未配置的表使用默认键
+        assert_eq!(config.dedup_key_expr("other"), "tuple(`signature`, `instruction_index`)");
+        assert!(!config.fine_grained);
+        assert_eq!(config.max_concurrent_tables, 1);
+        assert_eq!(config.mode, SyncMode::Sync);
+        assert_eq!(config.sync_retries, 3);
+        assert_eq!(config.sync_retry_delay_secs, 2);
+        assert!(!config.deduplicate_after_sync);
+    }
+
+    #[test]
+    fn test_sync_config_report_only_mode() {
+        let toml_content = r#"
+local_url = "http://localhost:18123"
+local_database = "default"
+local_user = "default"
+local_password = ""
+remote_url = "http://remote-host:28123"
+remote_database = "default"
+remote_user = "default"
+remote_password = ""
+mode = "report_only"
+deduplicate_after_sync = true
+
+[table_mappings]
+trade = "trade"
+"#;
+
+        let temp_file = NamedTempFile::new().unwrap();
+        fs::write(temp_file.path(), toml_content).unwrap();
+
+        let config = SyncConfig::from_file(temp_file.path().to_str().unwrap()).unwrap();
+        assert_eq!(config.mode, SyncMode::ReportOnly);
+        assert!(config.deduplicate_after_sync);
+    }
+
+    #[test]
+    fn test_sync_config_validate_lists_all_problems() {
+        let toml_content = r#"
+local_url = "localhost:18123"
+local_database = "default"
+local_user = "default"
+local_password = ""
+remote_url = "http://remote-host:notaport"
+remote_database = ""
+remote_user = "default"
+remote_password = ""
+check_days = 0
+
+[table_mappings]
+
+[dedup_keys]
+trade = ["signature"]
+"#;
+
+        let temp_file = NamedTempFile::new().unwrap();
+        fs::write(temp_file.path(), toml_content).unwrap();
+
+        let error = SyncConfig::from_file(temp_file.path().to_str().unwrap())
+            .unwrap_err()
+            .to_string();
+        for expected in [
+            "local_url",
+            "remote_url",
+            "remote_database",
+            "table_mappings",
+            "dedup_keys entry 'trade'",
+            "check_days",
+        ] {
+            assert!(error.contains(expected), "missing '{}' in: {}", expected, error);
+        }
+        assert!(error.contains("6 problems"), "{}", error);
+    }
+
+    #[test]
+    fn test_sync_config_validate_same_database() {
+        let toml_content = r#"
+local_url = "http://localhost:18123"
+local_database = "default"
+local_user = "default"
+local_password = ""
+remote_url = "http://localhost:18123/"
+remote_database = "default"
+remote_user = "default"
+remote_password = ""
+
+[table_mappings]
+trade = "trade"
+"#;
+
+        let temp_file = NamedTempFile::new().unwrap();
+        fs::write(temp_file.path(), toml_content).unwrap();
+
+        let mut config: SyncConfig = toml::from_str(toml_content).unwrap();
+        assert_eq!(config.validate().len(), 1);
+        assert!(SyncConfig::from_file(temp_file.path().to_str().unwrap()).is_err());
+
+        config.remote_database = "replica".to_string();
+        assert!(config.validate().is_empty());
+        config.ensure_valid().unwrap();
+    }
+
+    #[test]
+    fn test_sync_config_same_name_and_default_dedup_key() {
+        let toml_content = r#"
+local_url = "http://localhost:18123"
+local_database = "default"
+local_user = "default"
+local_password = ""
+remote_url = "http://remote-host:28123"
+remote_database = "default"
+remote_user = "default"
+remote_password = ""
+same_name = ["trade", "logs", "create"]
+
+[table_mappings]
+logs = "logs_archive"
+
+[dedup_keys]
+"*" = ["signature", "transaction_index", "instruction_index"]
+logs = ["signature"]
+"#;
+
+        let temp_file = NamedTempFile::new().unwrap();
+        fs::write(temp_file.path(), toml_content).unwrap();
+
+        let config = SyncConfig::from_file(temp_file.path().to_str().unwrap()).unwrap();
+
+        assert_eq!(config.table_mappings.len(), 3);
+        assert_eq!(config.table_mappings["trade"], "trade");
+        assert_eq!(config.table_mappings["create"], "create");
+        // 显式映射优先于 same_name
+        assert_eq!(config.table_mappings["logs"], "logs_archive");
+        assert!(config.same_name.is_empty());
+
+        // 表名条目优先于 "*" 条目
+        assert_eq!(config.dedup_key_expr("logs"), "tuple(`signature`)");
+        assert_eq!(
+            config.dedup_key_expr("trade"),
+            "tuple(`signature`, `transaction_index`, `instruction_index`)"
+        );
+    }
+
+    #[test]
+    fn test_config_env_var_expansion() {
+        let toml_content = r#"
+remote_storage_path = "${CARGO_MANIFEST_DIR}/imports"
+
+[import_mappings]
+source_a = "target_a"
+
+[table_event_mappings]
+source_a = "EventTypeA"
+"#;
+
+        let temp_file = NamedTempFile::new().unwrap();
+        fs::write(temp_file.path(), toml_content).unwrap();
+
+        let config = RemoteConfig::from_file(temp_file.path().to_str().unwrap()).unwrap();
+        assert_eq!(
+            config.remote_storage_path,
+            PathBuf::from(format!("{}/imports", env!("CARGO_MANIFEST_DIR")))
+        );
+
+        // 引用未设置的变量时报错并指出字段
+        fs::write(
+            temp_file.path(),
+            toml_content.replace("${CARGO_MANIFEST_DIR}", "${SYNCER_UNSET_TEST_VAR}"),
+        )
+        .unwrap();
+        let error = RemoteConfig::from_file(temp_file.path().to_str().unwrap())
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("remote_storage_path"), "{}", error);
+        assert!(error.contains("SYNCER_UNSET_TEST_VAR"), "{}", error);
+    }
 }
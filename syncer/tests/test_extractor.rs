@@ -1,5 +1,6 @@
 use chrono::NaiveDate;
-use syncer::extractor::ClickHouseExtractor;
+use syncer::extractor::{ClickHouseExtractor, ExtractorConfig, ensure_read_only};
+use utils::clickhouse_client::{DEFAULT_CONNECT_TIMEOUT_MS, DEFAULT_POOL_SIZE, DEFAULT_REQUEST_TIMEOUT_MS};
 use utils::clickhouse_events::*;
 
 #[tokio::test]
@@ -435,3 +436,234 @@ async fn test_data_integrity_round_trip() {
         }
     }
 }
+
+#[tokio::test]
+async fn test_extract_range_rejects_inverted_bounds() {
+    let start = NaiveDate::from_ymd_opt(2025, 10, 5).unwrap();
+    let end = NaiveDate::from_ymd_opt(2025, 10, 1).unwrap();
+
+    let extractor = ClickHouseExtractor::new();
+
+    let result = extractor
+        .extract_range_events("pumpfun_trade_event_v2", "PumpfunTradeEventV2", start, end)
+        .await;
+
+    assert!(result.is_err(), "Should reject start > end");
+
+    let error_msg = result.unwrap_err().to_string();
+    assert!(
+        error_msg.contains("2025-10-05") && error_msg.contains("2025-10-01"),
+        "Error should name the offending bounds: {}",
+        error_msg
+    );
+    println!("✓ Correctly rejected inverted range: {}", error_msg);
+}
+
+#[tokio::test]
+async fn test_extract_range_returns_one_batch_per_day() {
+    let start = NaiveDate::from_ymd_opt(2025, 10, 1).unwrap();
+    let end = NaiveDate::from_ymd_opt(2025, 10, 3).unwrap();
+
+    let extractor = ClickHouseExtractor::new();
+
+    let result = extractor
+        .extract_range_events("pumpfun_create_event_v2", "PumpfunCreateEventV2", start, end)
+        .await;
+
+    match result {
+        Ok(batches) => {
+            assert_eq!(batches.len(), 3, "Should return one batch per day");
+            for (i, batch) in batches.iter().enumerate() {
+                println!("  Day {}: {} rows", i + 1, batch.num_rows());
+            }
+        }
+        Err(e) => {
+            println!("✗ Error: {} (OK if no data)", e);
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_from_config_uses_given_url() {
+    // 指向不可达地址，查询必须失败而不是落到环境变量配置的集群上
+    let config = ExtractorConfig {
+        url: "http://127.0.0.1:1".to_string(),
+        user: "default".to_string(),
+        password: String::new(),
+        database: "default".to_string(),
+        connect_timeout_ms: DEFAULT_CONNECT_TIMEOUT_MS,
+        request_timeout_ms: DEFAULT_REQUEST_TIMEOUT_MS,
+        pool_size: DEFAULT_POOL_SIZE,
+    };
+    let extractor = ClickHouseExtractor::from_config(&config).unwrap();
+    let date = NaiveDate::from_ymd_opt(2025, 10, 1).unwrap();
+
+    let result = extractor
+        .extract_daily_events("pumpfun_trade_event_v2", "PumpfunTradeEventV2", date)
+        .await;
+
+    assert!(result.is_err(), "Should fail against unreachable URL");
+    println!("✓ Query failed as expected: {}", result.unwrap_err());
+}
+
+#[tokio::test]
+async fn test_with_client_rejects_unknown_event_type() {
+    let client = clickhouse::Client::default().with_url("http://127.0.0.1:1");
+    let extractor = ClickHouseExtractor::with_client(client);
+    let date = NaiveDate::from_ymd_opt(2025, 10, 1).unwrap();
+
+    let result = extractor
+        .extract_daily_events("some_table", "InvalidEventType", date)
+        .await;
+
+    let error_msg = result.unwrap_err().to_string();
+    assert!(
+        error_msg.contains("Unknown event type"),
+        "Event type should be checked before connecting: {}",
+        error_msg
+    );
+}
+
+#[tokio::test]
+async fn test_projected_extraction_rejects_unknown_column() {
+    let client = clickhouse::Client::default().with_url("http://127.0.0.1:1");
+    let extractor = ClickHouseExtractor::with_client(client);
+    let date = NaiveDate::from_ymd_opt(2025, 10, 1).unwrap();
+
+    let result = extractor
+        .extract_daily_events_projected(
+            "pumpfun_amm_buy_event_v2",
+            "PumpfunAmmBuyEventV2",
+            date,
+            &["signature", "slot", "quote_amout_in"],
+        )
+        .await;
+
+    let error_msg = result.unwrap_err().to_string();
+    assert!(
+        error_msg.contains("quote_amout_in"),
+        "Error should name the unknown column: {}",
+        error_msg
+    );
+}
+
+#[tokio::test]
+async fn test_projected_extraction_amm_buy() {
+    let date = NaiveDate::from_ymd_opt(2025, 10, 1).unwrap();
+    let extractor = ClickHouseExtractor::new();
+    let columns = ["signature", "slot", "timestamp", "quote_amount_in"];
+
+    let result = extractor
+        .extract_daily_events_projected(
+            "pumpfun_amm_buy_event_v2",
+            "PumpfunAmmBuyEventV2",
+            date,
+            &columns,
+        )
+        .await;
+
+    match result {
+        Ok(batch) => {
+            let schema = batch.schema();
+            let names: Vec<&str> = schema.fields().iter().map(|f| f.name().as_str()).collect();
+            assert_eq!(names, columns, "Schema should contain only the projected columns");
+            println!("✓ Projected extraction returned {} rows", batch.num_rows());
+        }
+        Err(e) => {
+            println!("✗ Error: {} (OK if no data)", e);
+        }
+    }
+}
+
+#[test]
+fn test_from_config_rejects_native_port() {
+    let config = ExtractorConfig {
+        url: "http://127.0.0.1:9000".to_string(),
+        user: "default".to_string(),
+        password: String::new(),
+        database: "default".to_string(),
+        connect_timeout_ms: DEFAULT_CONNECT_TIMEOUT_MS,
+        request_timeout_ms: DEFAULT_REQUEST_TIMEOUT_MS,
+        pool_size: DEFAULT_POOL_SIZE,
+    };
+
+    let error_msg = ClickHouseExtractor::from_config(&config)
+        .err()
+        .expect("native protocol port should be rejected")
+        .to_string();
+    assert!(error_msg.contains("native protocol port"), "{}", error_msg);
+}
+
+#[tokio::test]
+#[ignore = "integration test, requires ClickHouse"]
+async fn test_count_daily_events_matches_extract() {
+    let date = NaiveDate::from_ymd_opt(2025, 10, 1).unwrap();
+    let extractor = ClickHouseExtractor::new();
+
+    let count = extractor
+        .count_daily_events("pumpfun_trade_event_v2", date)
+        .await
+        .unwrap();
+    let batch = extractor
+        .extract_daily_events("pumpfun_trade_event_v2", "PumpfunTradeEventV2", date)
+        .await
+        .unwrap();
+
+    println!("✓ {} rows on {}", count, date);
+    assert_eq!(count, batch.num_rows() as u64);
+}
+
+#[test]
+fn test_ensure_read_only() {
+    assert!(ensure_read_only("SELECT * FROM pumpfun_trade_event_v2 WHERE mint = 'abc'").is_ok());
+    assert!(ensure_read_only("  select count() from t;  ").is_ok());
+    assert!(ensure_read_only("WITH 1 AS x SELECT x").is_ok());
+    // 列名中包含关键字子串不受影响
+    assert!(ensure_read_only("SELECT updated_at, created_slot FROM t").is_ok());
+
+    for sql in [
+        "INSERT INTO t SELECT * FROM s",
+        "ALTER TABLE t DELETE WHERE 1",
+        "DROP TABLE t",
+        "SELECT 1; DROP TABLE t",
+        "SHOW TABLES",
+        "",
+    ] {
+        assert!(ensure_read_only(sql).is_err(), "should reject: {}", sql);
+    }
+}
+
+#[tokio::test]
+async fn test_extract_query_validates_before_querying() {
+    // 无效的事件类型和非只读语句都在连接 ClickHouse 之前被拒绝
+    let client = clickhouse::Client::default().with_url("http://127.0.0.1:1");
+    let extractor = ClickHouseExtractor::with_client(client);
+
+    let error = extractor
+        .extract_query("SELECT * FROM pumpfun_trade_event_v2", "InvalidEventType")
+        .await
+        .unwrap_err();
+    assert!(error.to_string().contains("Unknown event type"), "{}", error);
+
+    let error = extractor
+        .extract_query("DROP TABLE pumpfun_trade_event_v2", "PumpfunTradeEventV2")
+        .await
+        .unwrap_err();
+    assert!(error.to_string().contains("SELECT"), "{}", error);
+}
+
+#[tokio::test]
+#[ignore = "integration test, requires ClickHouse"]
+async fn test_extract_query_single_mint() {
+    let extractor = ClickHouseExtractor::new();
+
+    let batch = extractor
+        .extract_query(
+            "SELECT * FROM pumpfun_trade_event_v2 WHERE timestamp >= 1759276800 AND timestamp < 1759363200 LIMIT 10",
+            "PumpfunTradeEventV2",
+        )
+        .await
+        .expect("ad-hoc query failed");
+
+    assert!(batch.num_rows() <= 10);
+}
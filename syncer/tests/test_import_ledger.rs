@@ -0,0 +1,120 @@
+use std::fs;
+use syncer::import_ledger::{ImportLedger, LedgerState, content_hash};
+use tempfile::tempdir;
+use utils::clickhouse_client::ClickHouseClient;
+
+#[test]
+fn test_content_hash_depends_only_on_content() {
+    let temp_dir = tempdir().unwrap();
+    let a = temp_dir.path().join("a.parquet");
+    let b = temp_dir.path().join("b.parquet");
+    let c = temp_dir.path().join("c.parquet");
+    fs::write(&a, b"same bytes").unwrap();
+    fs::write(&b, b"same bytes").unwrap();
+    fs::write(&c, b"other bytes").unwrap();
+
+    let hash = content_hash(&a).unwrap();
+    assert_eq!(hash.len(), 64);
+    assert_eq!(hash, content_hash(&b).unwrap());
+    assert_ne!(hash, content_hash(&c).unwrap());
+}
+
+#[test]
+fn test_content_hash_matches_known_digest() {
+    let temp_dir = tempdir().unwrap();
+    let path = temp_dir.path().join("empty.parquet");
+    fs::write(&path, b"").unwrap();
+
+    assert_eq!(
+        content_hash(&path).unwrap(),
+        "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+    );
+}
+
+#[test]
+fn test_content_hash_missing_file() {
+    let temp_dir = tempdir().unwrap();
+    assert!(content_hash(&temp_dir.path().join("missing.parquet")).is_err());
+}
+
+#[test]
+fn test_create_table_sql() {
+    let sql = ImportLedger::create_table_sql("syncer_import_ledger");
+
+    assert!(sql.starts_with("CREATE TABLE IF NOT EXISTS `syncer_import_ledger` ("));
+    assert!(sql.contains("row_groups UInt64 DEFAULT 0"));
+    assert!(sql.contains("complete UInt8 DEFAULT 1"));
+    assert!(sql.contains("ReplacingMergeTree"));
+    assert!(sql.contains("ORDER BY (target_table, content_hash)"));
+}
+
+#[test]
+fn test_create_table_sql_quotes_qualified_name() {
+    let sql = ImportLedger::create_table_sql("staging.import-ledger");
+
+    assert!(sql.starts_with("CREATE TABLE IF NOT EXISTS `staging`.`import-ledger` ("));
+}
+
+#[test]
+fn test_migrate_table_sql() {
+    let sql = ImportLedger::migrate_table_sql("staging.syncer_import_ledger");
+
+    assert!(sql.starts_with("ALTER TABLE `staging`.`syncer_import_ledger` "));
+    assert!(sql.contains("ADD COLUMN IF NOT EXISTS row_groups UInt64 DEFAULT 0"));
+    assert!(sql.contains("ADD COLUMN IF NOT EXISTS complete UInt8 DEFAULT 1"));
+}
+
+#[tokio::test]
+#[ignore = "integration test, requires ClickHouse"]
+async fn test_record_and_lookup() {
+    let ledger = ImportLedger::new(
+        ClickHouseClient::instance().client().clone(),
+        "syncer_import_ledger_test",
+    );
+    ledger.ensure_table().await.unwrap();
+
+    let hash = format!("{:064x}", chrono::Utc::now().timestamp_nanos_opt().unwrap());
+    assert_eq!(ledger.imported_rows("target_table", &hash).await.unwrap(), None);
+
+    ledger.record("target_table", &hash, "a.parquet", 42).await.unwrap();
+    assert_eq!(ledger.imported_rows("target_table", &hash).await.unwrap(), Some(42));
+    // 同一文件导入到其他表不受影响
+    assert_eq!(ledger.imported_rows("other_table", &hash).await.unwrap(), None);
+}
+
+#[tokio::test]
+#[ignore = "integration test, requires ClickHouse"]
+async fn test_progress_is_not_a_completed_import() {
+    let ledger = ImportLedger::new(
+        ClickHouseClient::instance().client().clone(),
+        "syncer_import_ledger_test",
+    );
+    ledger.ensure_table().await.unwrap();
+
+    let hash = format!("{:064x}", chrono::Utc::now().timestamp_nanos_opt().unwrap());
+    ledger
+        .record_progress("target_table", &hash, "a.parquet", 10, 1)
+        .await
+        .unwrap();
+    ledger
+        .record_progress("target_table", &hash, "a.parquet", 25, 2)
+        .await
+        .unwrap();
+
+    // 只有进度记录时不算导入完成，恢复时从最大的进度继续
+    assert_eq!(ledger.imported_rows("target_table", &hash).await.unwrap(), None);
+    assert_eq!(
+        ledger.state("target_table", &hash).await.unwrap(),
+        Some(LedgerState {
+            row_count: 25,
+            row_groups: 2,
+            complete: false
+        })
+    );
+
+    ledger
+        .record("target_table", &hash, "a.parquet", 40)
+        .await
+        .unwrap();
+    assert_eq!(ledger.imported_rows("target_table", &hash).await.unwrap(), Some(40));
+}
@@ -1,8 +1,14 @@
+use arrow::datatypes::{FieldRef, Schema};
+use arrow::record_batch::RecordBatch;
 use chrono::NaiveDate;
+use serde_arrow::schema::{SchemaLike, TracingOptions};
+use std::sync::Arc;
 use syncer::extractor::ClickHouseExtractor;
-use syncer::importer::ClickHouseImporter;
-use syncer::parquet_helper::ParquetHelper;
+use syncer::importer::{ClickHouseImporter, ImportError};
+use syncer::parquet_helper::{ParquetHelper, ParquetWriteOptions};
 use tempfile::tempdir;
+use utils::clickhouse_events::PumpfunMigrateEventV2;
+use utils::event_registry::LiveColumn;
 
 #[tokio::test]
 #[ignore = "integration test, requires ClickHouse"]
@@ -197,6 +203,30 @@ async fn test_invalid_parquet_file() {
             error_msg
         );
         println!("✓ Correctly rejected corrupted parquet file: {}", error_msg);
+        assert!(
+            matches!(e, ImportError::CorruptParquet(ref path, _) if path == &fake_file),
+            "Expected CorruptParquet, got {:?}",
+            e
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_unknown_event_type_is_structured() {
+    // 事件类型在读取文件和连接 ClickHouse 之前检查
+    let temp_dir = tempdir().unwrap();
+    let missing_file = temp_dir.path().join("missing.parquet");
+
+    let importer = ClickHouseImporter::new();
+    let result = importer
+        .import_parquet(&missing_file, "test_table", "InvalidEventType")
+        .await;
+
+    match result {
+        Err(ImportError::UnknownEventType(event_type)) => {
+            assert_eq!(event_type, "InvalidEventType");
+        }
+        other => panic!("Expected UnknownEventType, got {:?}", other),
     }
 }
 
@@ -279,3 +309,143 @@ async fn test_import_empty_parquet() {
         println!("⊘ Skipping empty test, date {} has {} rows", date, batch.num_rows());
     }
 }
+
+fn describe_row(name: &str, column_type: &str) -> LiveColumn {
+    LiveColumn {
+        name: name.to_string(),
+        column_type: column_type.to_string(),
+        default_kind: String::new(),
+    }
+}
+
+fn migrate_schema() -> Arc<Schema> {
+    let fields = Vec::<FieldRef>::from_type::<PumpfunMigrateEventV2>(TracingOptions::default())
+        .unwrap();
+    Arc::new(Schema::new(fields))
+}
+
+fn migrate_table_columns() -> Vec<LiveColumn> {
+    vec![
+        describe_row("signature", "String"),
+        describe_row("slot", "UInt64"),
+        describe_row("transaction_index", "UInt32"),
+        describe_row("instruction_index", "UInt32"),
+        describe_row("user", "String"),
+        describe_row("mint", "String"),
+        describe_row("mint_amount", "UInt64"),
+        describe_row("sol_amount", "UInt64"),
+        describe_row("pool_migration_fee", "UInt64"),
+        describe_row("bonding_curve", "String"),
+        describe_row("timestamp", "DateTime"),
+        describe_row("pool", "String"),
+    ]
+}
+
+#[test]
+fn test_compare_schema_accepts_matching_table() {
+    let mut columns = migrate_table_columns();
+    columns.push(LiveColumn {
+        name: "inserted_at".to_string(),
+        column_type: "DateTime".to_string(),
+        default_kind: "DEFAULT".to_string(),
+    });
+
+    assert!(ClickHouseImporter::compare_schema(&columns, &migrate_schema()).is_ok());
+}
+
+#[test]
+fn test_compare_schema_lists_differing_columns() {
+    let mut columns = migrate_table_columns();
+    columns[9].name = "curve".to_string(); // 列被重命名
+
+    match ClickHouseImporter::compare_schema(&columns, &migrate_schema()) {
+        Err(ImportError::SchemaMismatch { expected, found }) => {
+            assert_eq!(expected, vec!["curve String".to_string()]);
+            assert_eq!(found, vec!["bonding_curve String".to_string()]);
+        }
+        other => panic!("Expected SchemaMismatch, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_strict_is_default_and_can_be_disabled() {
+    assert!(ClickHouseImporter::new().is_strict());
+    assert!(!ClickHouseImporter::new().with_strict(false).is_strict());
+}
+
+#[tokio::test]
+async fn test_clickhouse_error_after_retries_exhausted() {
+    let temp_dir = tempdir().unwrap();
+    let date = NaiveDate::from_ymd_opt(2025, 10, 1).unwrap();
+    let parquet_file = ParquetHelper::new()
+        .write_daily_parquet("retry_test", date, RecordBatch::new_empty(migrate_schema()), temp_dir.path())
+        .await
+        .unwrap();
+
+    // 没有服务监听的端口，每次尝试都是连接错误
+    let client = clickhouse::Client::default().with_url("http://127.0.0.1:1");
+    let importer = ClickHouseImporter::new()
+        .with_client(client)
+        .with_retry_config(2, 0);
+    assert_eq!(importer.max_retries(), 2);
+
+    match importer
+        .import_parquet(&parquet_file, "retry_test", "PumpfunMigrateEventV2")
+        .await
+    {
+        Err(ImportError::ClickHouse(_)) => {}
+        other => panic!("Expected ClickHouse error, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+#[ignore = "integration test, requires ClickHouse"]
+async fn test_streaming_import_commits_every_row_group() {
+    let temp_dir = tempdir().unwrap();
+    let date = NaiveDate::from_ymd_opt(2025, 10, 1).unwrap();
+
+    let batch = ClickHouseExtractor::new()
+        .extract_daily_events("pumpfun_trade_event_v2", "PumpfunTradeEventV2", date)
+        .await
+        .expect("Failed to extract data");
+    let expected_rows = batch.num_rows() as u64;
+
+    // 小 row group，保证文件被拆成多次 INSERT
+    let helper = ParquetHelper::with_options(ParquetWriteOptions {
+        row_group_size: 1000,
+        ..Default::default()
+    });
+    let parquet_file = helper
+        .write_daily_parquet("pumpfun_trade_event_v2", date, batch, temp_dir.path())
+        .await
+        .unwrap();
+
+    let rows = ClickHouseImporter::new()
+        .import_parquet_streaming(&parquet_file, "pumpfun_trade_event_v2_test", "PumpfunTradeEventV2")
+        .await
+        .expect("Streaming import failed");
+
+    assert_eq!(rows, expected_rows);
+}
+
+#[tokio::test]
+async fn test_streaming_import_returns_clickhouse_error() {
+    let temp_dir = tempdir().unwrap();
+    let date = NaiveDate::from_ymd_opt(2025, 10, 1).unwrap();
+    let parquet_file = ParquetHelper::new()
+        .write_daily_parquet("retry_test", date, RecordBatch::new_empty(migrate_schema()), temp_dir.path())
+        .await
+        .unwrap();
+
+    let importer = ClickHouseImporter::new()
+        .with_client(clickhouse::Client::default().with_url("http://127.0.0.1:1"))
+        .with_retry_config(1, 0);
+
+    match importer
+        .import_parquet_streaming(&parquet_file, "retry_test", "PumpfunMigrateEventV2")
+        .await
+    {
+        Err(ImportError::ClickHouse(_)) => {}
+        other => panic!("Expected ClickHouse error, got {:?}", other),
+    }
+}
@@ -1,7 +1,11 @@
 use chrono::NaiveDate;
 use std::path::PathBuf;
 use syncer::config::{LocalConfig, RemoteServerConfig};
-use syncer::pipeline::LocalPipeline;
+use syncer::transport::RsyncOptions;
+use std::time::Duration;
+use syncer::pipeline::{
+    DayTiming, FailedTransfer, LocalPipeline, PipelineReport, PipelineStep, TableSummary,
+};
 use tempfile::tempdir;
 
 #[tokio::test]
@@ -38,18 +42,26 @@ async fn test_local_pipeline_integration() {
         .into_iter()
         .collect(),
         start_time: NaiveDate::from_ymd_opt(2025, 10, 1).unwrap(),
+        end_time: None,
         local_storage_path: local_storage.clone(),
-        remote_server: RemoteServerConfig {
+        dry_run: false,
+        keep_local: false,
+        no_transfer: false,
+        transfer_retries: 5,
+        transfer_retry_delay_secs: 5,
+        rsync: RsyncOptions::default(),
+        remote_server: Some(RemoteServerConfig {
             address: ssh_host,
             port: ssh_port,
             username: ssh_user,
             private_key_path: PathBuf::from(ssh_key),
             remote_path: PathBuf::from(remote_path),
-        },
+        }),
+        s3: None,
     };
 
     // 创建并运行 pipeline
-    let pipeline = LocalPipeline::new(config);
+    let pipeline = LocalPipeline::new(config).unwrap();
 
     let result = pipeline.run().await;
 
@@ -115,17 +127,25 @@ async fn test_local_pipeline_single_day() {
         .into_iter()
         .collect(),
         start_time: NaiveDate::from_ymd_opt(2025, 10, 1).unwrap(),
+        end_time: None,
         local_storage_path: local_storage.clone(),
-        remote_server: RemoteServerConfig {
+        dry_run: false,
+        keep_local: false,
+        no_transfer: false,
+        transfer_retries: 0,
+        transfer_retry_delay_secs: 1,
+        rsync: RsyncOptions::default(),
+        remote_server: Some(RemoteServerConfig {
             address: "localhost".to_string(),
             port: 22,
             username: "test".to_string(),
             private_key_path: PathBuf::from("/tmp/fake_key"),
             remote_path: PathBuf::from("/tmp/fake"),
-        },
+        }),
+        s3: None,
     };
 
-    let pipeline = LocalPipeline::new(config);
+    let pipeline = LocalPipeline::new(config).unwrap();
 
     // 注意：这个测试需要 ClickHouse 环境变量
     // CLICKHOUSE_URL, CLICKHOUSE_USER, CLICKHOUSE_DATABASE, CLICKHOUSE_PASSWORD
@@ -135,8 +155,13 @@ async fn test_local_pipeline_single_day() {
     // 如果 ClickHouse 可用，应该成功提取和写入
     // 传输会失败（因为 SSH 配置是假的），但这是预期的
     match result {
-        Ok(_) => {
+        Ok(report) => {
             println!("✓ Pipeline completed (transmission likely failed, that's OK)");
+
+            // 传输失败的文件保留在本地并出现在报告中
+            for failed in &report.failed_transfers {
+                assert!(failed.file_path.exists(), "Failed transfer should keep {:?}", failed.file_path);
+            }
         }
         Err(e) => {
             let error_msg = e.to_string();
@@ -168,17 +193,25 @@ async fn test_local_pipeline_config_validation() {
         tables: vec!["test_table".to_string()],
         table_event_mappings: [].into_iter().collect(), // 缺少映射
         start_time: NaiveDate::from_ymd_opt(2025, 10, 1).unwrap(),
+        end_time: None,
         local_storage_path: temp_dir.path().to_path_buf(),
-        remote_server: RemoteServerConfig {
+        dry_run: false,
+        keep_local: false,
+        no_transfer: false,
+        transfer_retries: 0,
+        transfer_retry_delay_secs: 1,
+        rsync: RsyncOptions::default(),
+        remote_server: Some(RemoteServerConfig {
             address: "localhost".to_string(),
             port: 22,
             username: "test".to_string(),
             private_key_path: PathBuf::from("/tmp/key"),
             remote_path: PathBuf::from("/tmp/remote"),
-        },
+        }),
+        s3: None,
     };
 
-    let pipeline = LocalPipeline::new(config);
+    let pipeline = LocalPipeline::new(config).unwrap();
     let result = pipeline.run().await;
 
     assert!(result.is_err(), "Should fail when event type mapping is missing");
@@ -194,6 +227,47 @@ async fn test_local_pipeline_config_validation() {
     }
 }
 
+#[tokio::test]
+async fn test_local_pipeline_rejects_inverted_date_range() {
+    let temp_dir = tempdir().unwrap();
+
+    let config = LocalConfig {
+        tables: vec!["pumpfun_trade_event_v2".to_string()],
+        table_event_mappings: [(
+            "pumpfun_trade_event_v2".to_string(),
+            "PumpfunTradeEventV2".to_string(),
+        )]
+        .into_iter()
+        .collect(),
+        start_time: NaiveDate::from_ymd_opt(2025, 10, 15).unwrap(),
+        end_time: Some(NaiveDate::from_ymd_opt(2025, 10, 1).unwrap()),
+        local_storage_path: temp_dir.path().to_path_buf(),
+        dry_run: false,
+        keep_local: false,
+        no_transfer: false,
+        transfer_retries: 0,
+        transfer_retry_delay_secs: 1,
+        rsync: RsyncOptions::default(),
+        remote_server: Some(RemoteServerConfig {
+            address: "localhost".to_string(),
+            port: 22,
+            username: "test".to_string(),
+            private_key_path: PathBuf::from("/tmp/key"),
+            remote_path: PathBuf::from("/tmp/remote"),
+        }),
+        s3: None,
+    };
+
+    let pipeline = LocalPipeline::new(config).unwrap();
+    let error_msg = pipeline.run().await.unwrap_err().to_string();
+
+    assert!(
+        error_msg.contains("start_time 2025-10-15 is after end date 2025-10-01"),
+        "Error should describe the invalid range: {}",
+        error_msg
+    );
+}
+
 #[tokio::test]
 async fn test_local_pipeline_date_progression() {
     // 测试日期递增逻辑
@@ -222,3 +296,182 @@ async fn test_local_pipeline_date_progression() {
     println!("  Days to process: {}", count);
     assert!(count > 0, "Should have at least one day to process");
 }
+
+/// 桩流水线的一天：各步骤耗时固定（毫秒），不依赖真实时钟
+fn stub_day(extract: u64, write: u64, sync: u64, cleanup: u64) -> DayTiming {
+    DayTiming {
+        extract: Duration::from_millis(extract),
+        write: Duration::from_millis(write),
+        sync: Duration::from_millis(sync),
+        cleanup: Duration::from_millis(cleanup),
+    }
+}
+
+#[test]
+fn test_pipeline_report_summary_from_stubbed_run() {
+    let mut report = PipelineReport::default();
+    let first = NaiveDate::from_ymd_opt(2025, 10, 1).unwrap();
+    let second = NaiveDate::from_ymd_opt(2025, 10, 2).unwrap();
+
+    // 两张表两天，第二天 trade 表的 sync 明显更慢
+    report.record_day("pumpfun_trade_event_v2", first, 100, stub_day(300, 200, 100, 0));
+    report.record_day("pumpfun_trade_event_v2", second, 100, stub_day(300, 200, 1200, 10));
+    report.record_day("pumpfun_create_event_v2", first, 5, stub_day(50, 20, 30, 0));
+    report.failed_transfers.push(FailedTransfer {
+        table: "pumpfun_create_event_v2".to_string(),
+        date: first,
+        file_path: PathBuf::from("/data/exports/pumpfun_create_event_v2/2025-10-01.parquet"),
+        error: "connection reset".to_string(),
+    });
+
+    assert_eq!(report.total_rows(), 205);
+    assert_eq!(report.total_for(PipelineStep::Sync), Duration::from_millis(1330));
+    assert_eq!(report.slowest_day().unwrap().date, second);
+
+    assert_eq!(
+        report.summary_lines(),
+        vec![
+            "=== Local Pipeline Timing ===",
+            "Days processed: 3",
+            "Rows extracted: 205",
+            "  extract  0.65s",
+            "  write    0.42s",
+            "  sync     1.33s",
+            "  cleanup  0.01s",
+            "  pumpfun_trade_event_v2: 200 rows in 2 days",
+            "  pumpfun_create_event_v2: 5 rows in 1 days",
+            "Slowest day: pumpfun_trade_event_v2 2025-10-02 (1.71s: extract 0.30s | write 0.20s | sync 1.20s | cleanup 0.01s)",
+            "Failed transfers (1 files kept locally):",
+            "  pumpfun_create_event_v2 2025-10-01 \"/data/exports/pumpfun_create_event_v2/2025-10-01.parquet\": connection reset",
+            "=============================",
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_day_timing_records_each_step() {
+    let mut timing = DayTiming::default();
+
+    let rows = timing.time(PipelineStep::Extract, async { 100 }).await;
+    timing.time(PipelineStep::Sync, async {}).await;
+
+    assert_eq!(rows, 100);
+    assert_eq!(timing.total(), timing.extract + timing.write + timing.sync + timing.cleanup);
+    assert_eq!(timing.write, Duration::ZERO);
+    assert_eq!(timing.cleanup, Duration::ZERO);
+}
+
+#[test]
+fn test_table_summaries_aggregate_rows_per_table() {
+    let mut report = PipelineReport::default();
+    let date = NaiveDate::from_ymd_opt(2025, 10, 1).unwrap();
+
+    report.record_estimate("pumpfun_trade_event_v2", date, 100, 4096, DayTiming::default());
+    report.record_estimate("pumpfun_create_event_v2", date, 7, 512, DayTiming::default());
+    report.record_estimate(
+        "pumpfun_trade_event_v2",
+        date.succ_opt().unwrap(),
+        50,
+        2048,
+        DayTiming::default(),
+    );
+
+    assert_eq!(
+        report.table_summaries(),
+        vec![
+            TableSummary {
+                table: "pumpfun_trade_event_v2".to_string(),
+                days: 2,
+                rows: 150,
+                estimated_bytes: 6144,
+            },
+            TableSummary {
+                table: "pumpfun_create_event_v2".to_string(),
+                days: 1,
+                rows: 7,
+                estimated_bytes: 512,
+            },
+        ]
+    );
+}
+
+#[tokio::test]
+#[ignore = "integration test, requires ClickHouse"]
+async fn test_local_pipeline_dry_run_writes_nothing() {
+    let temp_dir = tempdir().unwrap();
+    let local_storage = temp_dir.path().to_path_buf();
+    let date = NaiveDate::from_ymd_opt(2025, 10, 1).unwrap();
+
+    let config = LocalConfig {
+        tables: vec!["pumpfun_trade_event_v2".to_string()],
+        table_event_mappings: [(
+            "pumpfun_trade_event_v2".to_string(),
+            "PumpfunTradeEventV2".to_string(),
+        )]
+        .into_iter()
+        .collect(),
+        start_time: date,
+        end_time: Some(date),
+        local_storage_path: local_storage.clone(),
+        dry_run: true,
+        keep_local: false,
+        no_transfer: false,
+        transfer_retries: 0,
+        transfer_retry_delay_secs: 1,
+        rsync: RsyncOptions::default(),
+        // SSH 配置是假的，dry run 不应尝试传输
+        remote_server: Some(RemoteServerConfig {
+            address: "localhost".to_string(),
+            port: 22,
+            username: "test".to_string(),
+            private_key_path: PathBuf::from("/tmp/fake_key"),
+            remote_path: PathBuf::from("/tmp/fake"),
+        }),
+        s3: None,
+    };
+
+    let report = LocalPipeline::new(config).unwrap().run().await.expect("Dry run failed");
+
+    let summaries = report.table_summaries();
+    assert_eq!(summaries.len(), 1);
+    assert_eq!(summaries[0].days, 1);
+    assert_eq!(summaries[0].rows, report.total_rows());
+    assert!(!local_storage.join("pumpfun_trade_event_v2").exists(), "Dry run must not write files");
+}
+
+#[tokio::test]
+#[ignore = "integration test, requires ClickHouse"]
+async fn test_local_pipeline_no_transfer_keeps_files() {
+    let temp_dir = tempdir().unwrap();
+    let local_storage = temp_dir.path().to_path_buf();
+    let date = NaiveDate::from_ymd_opt(2025, 10, 1).unwrap();
+
+    let config = LocalConfig {
+        tables: vec!["pumpfun_trade_event_v2".to_string()],
+        table_event_mappings: [(
+            "pumpfun_trade_event_v2".to_string(),
+            "PumpfunTradeEventV2".to_string(),
+        )]
+        .into_iter()
+        .collect(),
+        start_time: date,
+        end_time: Some(date),
+        local_storage_path: local_storage.clone(),
+        dry_run: false,
+        keep_local: false,
+        no_transfer: true,
+        transfer_retries: 0,
+        transfer_retry_delay_secs: 1,
+        rsync: RsyncOptions::default(),
+        // 不传输时无需配置目标
+        remote_server: None,
+        s3: None,
+    };
+
+    let report = LocalPipeline::new(config).unwrap().run().await.expect("Pipeline failed");
+
+    assert_eq!(report.written_files.len(), 1);
+    assert!(report.written_files[0].starts_with(&local_storage));
+    assert!(report.written_files[0].exists(), "File must be kept locally");
+    assert!(report.failed_transfers.is_empty());
+}
@@ -1,9 +1,12 @@
-use arrow::array::{StringArray, UInt32Array, UInt64Array};
+use arrow::array::{Array, StringArray, UInt32Array, UInt64Array};
 use arrow::datatypes::{DataType, Field, Schema};
 use arrow::record_batch::RecordBatch;
 use chrono::NaiveDate;
+use futures::StreamExt;
 use std::sync::Arc;
-use syncer::parquet_helper::ParquetHelper;
+use parquet::basic::{Compression, ZstdLevel};
+use parquet::file::reader::{FileReader, SerializedFileReader};
+use syncer::parquet_helper::{ParquetHelper, ParquetWriteOptions, PartitionLayout, TimestampRange};
 use tempfile::tempdir;
 
 #[tokio::test]
@@ -252,3 +255,441 @@ async fn test_read_multiple_batches() {
 
     println!("✓ Multiple batches merged correctly");
 }
+
+fn repeated_batch(rows: u64) -> RecordBatch {
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("id", DataType::UInt64, false),
+        Field::new("data", DataType::Utf8, false),
+    ]));
+
+    let ids: Vec<u64> = (0..rows).collect();
+    let data: Vec<String> = (0..rows)
+        .map(|i| format!("repeated_string_data_{}", i % 10))
+        .collect();
+
+    RecordBatch::try_new(
+        schema,
+        vec![
+            Arc::new(UInt64Array::from(ids)),
+            Arc::new(StringArray::from(data)),
+        ],
+    )
+    .unwrap()
+}
+
+#[tokio::test]
+async fn test_snappy_and_zstd_read_back_identically() {
+    let batch = repeated_batch(1000);
+    let date = NaiveDate::from_ymd_opt(2025, 6, 1).unwrap();
+
+    let snappy_dir = tempdir().unwrap();
+    let snappy = ParquetHelper::with_options(ParquetWriteOptions {
+        compression: Compression::SNAPPY,
+        ..Default::default()
+    });
+    let snappy_path = snappy
+        .write_daily_parquet("codec_test", date, batch.clone(), snappy_dir.path())
+        .await
+        .unwrap();
+
+    let zstd_dir = tempdir().unwrap();
+    let zstd = ParquetHelper::with_options(ParquetWriteOptions {
+        compression: Compression::ZSTD(ZstdLevel::try_new(9).unwrap()),
+        ..Default::default()
+    });
+    let zstd_path = zstd
+        .write_daily_parquet("codec_test", date, batch.clone(), zstd_dir.path())
+        .await
+        .unwrap();
+
+    // 文件元数据中记录了对应的压缩算法
+    let snappy_meta = SerializedFileReader::new(std::fs::File::open(&snappy_path).unwrap()).unwrap();
+    let zstd_meta = SerializedFileReader::new(std::fs::File::open(&zstd_path).unwrap()).unwrap();
+    assert_eq!(
+        snappy_meta.metadata().row_group(0).column(0).compression(),
+        Compression::SNAPPY
+    );
+    assert!(matches!(
+        zstd_meta.metadata().row_group(0).column(0).compression(),
+        Compression::ZSTD(_)
+    ));
+
+    let snappy_batch = snappy.read_parquet(&snappy_path).await.unwrap();
+    let zstd_batch = zstd.read_parquet(&zstd_path).await.unwrap();
+    assert_eq!(snappy_batch, zstd_batch, "Both codecs should read back identically");
+    assert_eq!(snappy_batch, batch);
+
+    println!("✓ SNAPPY and ZSTD files read back identically");
+}
+
+#[tokio::test]
+async fn test_row_group_size_and_statistics_options() {
+    let temp_dir = tempdir().unwrap();
+    let helper = ParquetHelper::with_options(ParquetWriteOptions {
+        compression: Compression::UNCOMPRESSED,
+        row_group_size: 100,
+        enable_statistics: false,
+    });
+    let date = NaiveDate::from_ymd_opt(2025, 6, 2).unwrap();
+
+    let file_path = helper
+        .write_daily_parquet("row_group_test", date, repeated_batch(250), temp_dir.path())
+        .await
+        .unwrap();
+
+    let reader = SerializedFileReader::new(std::fs::File::open(&file_path).unwrap()).unwrap();
+    let metadata = reader.metadata();
+    assert_eq!(metadata.num_row_groups(), 3, "250 rows / 100 per group = 3 row groups");
+
+    let column = metadata.row_group(0).column(0);
+    assert_eq!(column.compression(), Compression::UNCOMPRESSED);
+    assert!(column.statistics().is_none(), "Statistics should be disabled");
+
+    let read_batch = helper.read_parquet(&file_path).await.unwrap();
+    assert_eq!(read_batch.num_rows(), 250);
+}
+
+#[tokio::test]
+async fn test_read_parquet_batches_yields_row_groups() {
+    let temp_dir = tempdir().unwrap();
+    let helper = ParquetHelper::with_options(ParquetWriteOptions {
+        row_group_size: 100,
+        ..Default::default()
+    });
+    let date = NaiveDate::from_ymd_opt(2025, 6, 3).unwrap();
+    let batch = repeated_batch(250);
+
+    let file_path = helper
+        .write_daily_parquet("stream_test", date, batch.clone(), temp_dir.path())
+        .await
+        .unwrap();
+
+    // 每个 row group 一个批次
+    let mut stream = Box::pin(helper.read_parquet_batches(&file_path));
+    let mut row_counts = Vec::new();
+    let mut batches = Vec::new();
+    while let Some(result) = stream.next().await {
+        let b = result.unwrap();
+        row_counts.push(b.num_rows());
+        batches.push(b);
+    }
+    assert_eq!(row_counts, vec![100, 100, 50]);
+
+    // read_parquet 等价于拼接流中的所有批次
+    let streamed = arrow::compute::concat_batches(&batch.schema(), &batches).unwrap();
+    let merged = helper.read_parquet(&file_path).await.unwrap();
+    assert_eq!(streamed, merged);
+    assert_eq!(merged, batch);
+}
+
+#[tokio::test]
+async fn test_read_parquet_batches_reports_open_error() {
+    let helper = ParquetHelper::new();
+    let mut stream = Box::pin(
+        helper.read_parquet_batches(&std::path::PathBuf::from("/nonexistent/file.parquet")),
+    );
+
+    let first = stream.next().await.expect("stream should yield the open error");
+    assert!(first.is_err());
+    assert!(stream.next().await.is_none());
+}
+
+#[tokio::test]
+async fn test_write_partitioned_parquet_hive_daily() {
+    let temp_dir = tempdir().unwrap();
+    let root = temp_dir.path();
+    let helper = ParquetHelper::new();
+    let date = NaiveDate::from_ymd_opt(2025, 10, 1).unwrap();
+
+    let file_path = helper
+        .write_partitioned_parquet("hive_test", date, repeated_batch(10), root, PartitionLayout::HiveDaily)
+        .await
+        .unwrap();
+
+    let expected = root
+        .join("hive_test")
+        .join("year=2025")
+        .join("month=10")
+        .join("day=01")
+        .join("part.parquet");
+    assert_eq!(file_path, expected);
+    assert!(expected.exists());
+
+    let read_batch = helper.read_parquet(&file_path).await.unwrap();
+    assert_eq!(read_batch.num_rows(), 10);
+}
+
+#[tokio::test]
+async fn test_flat_layout_matches_write_daily_parquet() {
+    let temp_dir = tempdir().unwrap();
+    let root = temp_dir.path();
+    let helper = ParquetHelper::new();
+    let date = NaiveDate::from_ymd_opt(2025, 10, 1).unwrap();
+
+    let flat_path = helper
+        .write_partitioned_parquet("flat_test", date, repeated_batch(1), root, PartitionLayout::Flat)
+        .await
+        .unwrap();
+
+    assert_eq!(flat_path, root.join("flat_test").join("flat_test_2025-10-01.parquet"));
+}
+
+#[tokio::test]
+async fn test_discover_hive_daily_files_in_date_order() {
+    let temp_dir = tempdir().unwrap();
+    let root = temp_dir.path();
+    let helper = ParquetHelper::new();
+
+    let dates = [
+        NaiveDate::from_ymd_opt(2025, 11, 2).unwrap(),
+        NaiveDate::from_ymd_opt(2025, 9, 30).unwrap(),
+        NaiveDate::from_ymd_opt(2025, 10, 1).unwrap(),
+    ];
+    for date in dates {
+        helper
+            .write_partitioned_parquet("discover_test", date, repeated_batch(1), root, PartitionLayout::HiveDaily)
+            .await
+            .unwrap();
+    }
+
+    let table_dir = root.join("discover_test");
+    let files = PartitionLayout::HiveDaily.discover_files(&table_dir).unwrap();
+    let relative: Vec<String> = files
+        .iter()
+        .map(|p| p.strip_prefix(&table_dir).unwrap().to_string_lossy().to_string())
+        .collect();
+
+    assert_eq!(
+        relative,
+        vec![
+            "year=2025/month=09/day=30/part.parquet",
+            "year=2025/month=10/day=01/part.parquet",
+            "year=2025/month=11/day=02/part.parquet",
+        ]
+    );
+
+    // Flat 模式不会递归进入分区目录
+    assert!(PartitionLayout::Flat.discover_files(&table_dir).unwrap().is_empty());
+}
+
+#[test]
+fn test_file_date_round_trips_file_path() {
+    let root = std::path::Path::new("/data/imports");
+    let date = NaiveDate::from_ymd_opt(2025, 10, 1).unwrap();
+
+    for layout in [PartitionLayout::Flat, PartitionLayout::HiveDaily] {
+        let path = layout.file_path(root, "pumpfun_trade_event_v2", date);
+        assert_eq!(layout.file_date(&path), Some(date), "{:?}", layout);
+    }
+
+    // 压缩后的月度文件等不符合命名的文件无法核对
+    let monthly = root.join("pumpfun_trade_event_v2/pumpfun_trade_event_v2_2025-10.parquet");
+    assert_eq!(PartitionLayout::Flat.file_date(&monthly), None);
+    assert_eq!(PartitionLayout::HiveDaily.file_date(&monthly), None);
+}
+
+#[tokio::test]
+async fn test_encoded_size_matches_written_file() {
+    let temp_dir = tempdir().unwrap();
+    let helper = ParquetHelper::new();
+    let batch = repeated_batch(500);
+    let date = NaiveDate::from_ymd_opt(2025, 6, 1).unwrap();
+
+    let estimated = helper.encoded_size(&batch).unwrap();
+    let file_path = helper
+        .write_daily_parquet("size_table", date, batch, temp_dir.path())
+        .await
+        .unwrap();
+
+    assert_eq!(estimated, std::fs::metadata(&file_path).unwrap().len());
+}
+
+#[tokio::test]
+async fn test_append_daily_parquet_keeps_existing_rows() {
+    let temp_dir = tempdir().unwrap();
+    let root = temp_dir.path();
+    let helper = ParquetHelper::new();
+    let date = NaiveDate::from_ymd_opt(2025, 6, 3).unwrap();
+
+    // 文件不存在时直接写入
+    let file_path = helper
+        .append_daily_parquet("append_test", date, repeated_batch(100), root)
+        .await
+        .unwrap();
+    assert_eq!(file_path, PartitionLayout::Flat.file_path(root, "append_test", date));
+    assert_eq!(helper.parquet_row_count(&file_path).unwrap(), 100);
+
+    let appended = helper
+        .append_daily_parquet("append_test", date, repeated_batch(50), root)
+        .await
+        .unwrap();
+    assert_eq!(appended, file_path);
+    assert_eq!(helper.parquet_row_count(&file_path).unwrap(), 150);
+
+    // 新数据作为单独的 row group 追加在后面
+    let reader = SerializedFileReader::new(std::fs::File::open(&file_path).unwrap()).unwrap();
+    assert_eq!(reader.metadata().num_row_groups(), 2);
+    assert!(!file_path.with_extension("parquet.tmp").exists());
+}
+
+#[tokio::test]
+async fn test_append_daily_parquet_rejects_schema_drift() {
+    let temp_dir = tempdir().unwrap();
+    let root = temp_dir.path();
+    let helper = ParquetHelper::new();
+    let date = NaiveDate::from_ymd_opt(2025, 6, 4).unwrap();
+
+    let file_path = helper
+        .write_daily_parquet("drift_test", date, repeated_batch(10), root)
+        .await
+        .unwrap();
+
+    let drifted = RecordBatch::try_new(
+        Arc::new(Schema::new(vec![
+            Field::new("id", DataType::UInt32, false),
+            Field::new("data", DataType::Utf8, false),
+        ])),
+        vec![
+            Arc::new(UInt32Array::from(vec![1, 2])),
+            Arc::new(StringArray::from(vec!["a", "b"])),
+        ],
+    )
+    .unwrap();
+
+    let error = helper
+        .append_daily_parquet("drift_test", date, drifted, root)
+        .await
+        .unwrap_err();
+    assert!(error.to_string().contains("Schema mismatch"), "{}", error);
+
+    // 原文件不受影响
+    assert_eq!(helper.parquet_row_count(&file_path).unwrap(), 10);
+}
+
+#[tokio::test]
+async fn test_file_info_matches_written_file() {
+    let temp_dir = tempdir().unwrap();
+    let helper = ParquetHelper::with_options(ParquetWriteOptions {
+        row_group_size: 100,
+        ..Default::default()
+    });
+    let date = NaiveDate::from_ymd_opt(2025, 6, 5).unwrap();
+
+    let file_path = helper
+        .write_daily_parquet("info_test", date, repeated_batch(250), temp_dir.path())
+        .await
+        .unwrap();
+
+    let info = helper.file_info(&file_path).unwrap();
+    assert_eq!(info.rows, 250);
+    assert_eq!(info.row_groups, 3);
+    assert_eq!(info.compression, vec![Compression::SNAPPY]);
+    assert_eq!(info.file_bytes, std::fs::metadata(&file_path).unwrap().len());
+    assert!(info.uncompressed_bytes > 0);
+
+    let columns: Vec<(&str, &DataType)> = info
+        .columns
+        .iter()
+        .map(|column| (column.name.as_str(), &column.data_type))
+        .collect();
+    assert_eq!(columns, vec![("id", &DataType::UInt64), ("data", &DataType::Utf8)]);
+}
+
+/// timestamp 从 `start` 起每行加 1 秒
+fn timestamp_batch(start: u32, rows: u32) -> RecordBatch {
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("slot", DataType::UInt64, false),
+        Field::new("timestamp", DataType::UInt32, false),
+    ]));
+    RecordBatch::try_new(
+        schema,
+        vec![
+            Arc::new(UInt64Array::from_iter_values((0..rows as u64).map(|i| 1000 + i))),
+            Arc::new(UInt32Array::from_iter_values((0..rows).map(|i| start + i))),
+        ],
+    )
+    .unwrap()
+}
+
+#[tokio::test]
+async fn test_read_parquet_filtered_skips_row_groups() {
+    let temp_dir = tempdir().unwrap();
+    let date = NaiveDate::from_ymd_opt(2025, 6, 6).unwrap();
+    let day_start = 1_749_168_000u32;
+
+    // 一天 4 个小时的数据，每小时一个 row group
+    let helper = ParquetHelper::with_options(ParquetWriteOptions {
+        row_group_size: 3600,
+        ..Default::default()
+    });
+    let file_path = helper
+        .write_daily_parquet("filter_test", date, timestamp_batch(day_start, 4 * 3600), temp_dir.path())
+        .await
+        .unwrap();
+
+    // 只取第二个小时
+    let range = TimestampRange::new(day_start as i64 + 3600, day_start as i64 + 7200);
+    let result = helper.read_parquet_filtered(&file_path, range).unwrap();
+
+    assert_eq!(result.row_groups_read, 1);
+    assert_eq!(result.row_groups_skipped, 3);
+    assert_eq!(result.batch.num_rows(), 3600);
+    let timestamps = result.batch.column(1).as_any().downcast_ref::<UInt32Array>().unwrap();
+    assert_eq!(timestamps.value(0), day_start + 3600);
+    assert_eq!(timestamps.value(3599), day_start + 7199);
+
+    // 跨 row group 边界的区间读两个 row group，只返回区间内的行
+    let range = TimestampRange::new(day_start as i64 + 3000, day_start as i64 + 4000);
+    let result = helper.read_parquet_filtered(&file_path, range).unwrap();
+    assert_eq!(result.row_groups_read, 2);
+    assert_eq!(result.batch.num_rows(), 1000);
+}
+
+#[tokio::test]
+async fn test_read_parquet_filtered_without_statistics() {
+    let temp_dir = tempdir().unwrap();
+    let date = NaiveDate::from_ymd_opt(2025, 6, 7).unwrap();
+    let helper = ParquetHelper::with_options(ParquetWriteOptions {
+        row_group_size: 100,
+        enable_statistics: false,
+        ..Default::default()
+    });
+    let file_path = helper
+        .write_daily_parquet("no_stats_test", date, timestamp_batch(0, 300), temp_dir.path())
+        .await
+        .unwrap();
+
+    // 没有统计信息时无法跳过，但结果一样
+    let result = helper
+        .read_parquet_filtered(&file_path, TimestampRange::new(150, 160))
+        .unwrap();
+    assert_eq!(result.row_groups_read, 3);
+    assert_eq!(result.row_groups_skipped, 0);
+    assert_eq!(result.batch.num_rows(), 10);
+}
+
+#[tokio::test]
+async fn test_read_parquet_filtered_requires_timestamp_column() {
+    let temp_dir = tempdir().unwrap();
+    let date = NaiveDate::from_ymd_opt(2025, 6, 8).unwrap();
+    let helper = ParquetHelper::new();
+    let file_path = helper
+        .write_daily_parquet("no_timestamp", date, repeated_batch(10), temp_dir.path())
+        .await
+        .unwrap();
+
+    let error = helper
+        .read_parquet_filtered(&file_path, TimestampRange::new(0, 10))
+        .unwrap_err();
+    assert!(error.to_string().contains("timestamp"), "{}", error);
+}
+
+#[test]
+fn test_timestamp_range_overlaps() {
+    let range = TimestampRange::new(100, 200);
+    assert!(range.overlaps(50, 100));
+    assert!(range.overlaps(199, 300));
+    assert!(range.overlaps(120, 150));
+    assert!(!range.overlaps(0, 99));
+    assert!(!range.overlaps(200, 300));
+}
@@ -2,7 +2,8 @@ use chrono::NaiveDate;
 use std::collections::HashMap;
 use syncer::config::RemoteConfig;
 use syncer::extractor::ClickHouseExtractor;
-use syncer::parquet_helper::ParquetHelper;
+use syncer::import_manifest::{ImportManifest, MANIFEST_FILE_NAME};
+use syncer::parquet_helper::{ParquetHelper, PartitionLayout};
 use syncer::pipeline::RemotePipeline;
 use tempfile::tempdir;
 use utils::clickhouse_client::ClickHouseClient;
@@ -109,6 +110,15 @@ async fn test_remote_pipeline_integration() {
         ]
         .into_iter()
         .collect(),
+        partition_layout: PartitionLayout::Flat,
+        force_reimport: false,
+        max_concurrent_imports: 1,
+        preserve_order: false,
+        verify_after_import: false,
+        import_ledger_table: None,
+        import_retries: 0,
+        import_retry_delay_secs: 0,
+        streaming_import_min_bytes: u64::MAX,
     };
     
     // 3. 运行 RemotePipeline
@@ -200,6 +210,15 @@ async fn test_remote_pipeline_multiple_tables() {
         ]
         .into_iter()
         .collect(),
+        partition_layout: PartitionLayout::Flat,
+        force_reimport: false,
+        max_concurrent_imports: 1,
+        preserve_order: false,
+        verify_after_import: false,
+        import_ledger_table: None,
+        import_retries: 0,
+        import_retry_delay_secs: 0,
+        streaming_import_min_bytes: u64::MAX,
     };
     
     let pipeline = RemotePipeline::new(config);
@@ -250,6 +269,15 @@ async fn test_remote_pipeline_empty_folder() {
         ]
         .into_iter()
         .collect(),
+        partition_layout: PartitionLayout::Flat,
+        force_reimport: false,
+        max_concurrent_imports: 1,
+        preserve_order: false,
+        verify_after_import: false,
+        import_ledger_table: None,
+        import_retries: 0,
+        import_retry_delay_secs: 0,
+        streaming_import_min_bytes: u64::MAX,
     };
     
     let pipeline = RemotePipeline::new(config);
@@ -278,6 +306,15 @@ async fn test_remote_pipeline_missing_folder() {
         ]
         .into_iter()
         .collect(),
+        partition_layout: PartitionLayout::Flat,
+        force_reimport: false,
+        max_concurrent_imports: 1,
+        preserve_order: false,
+        verify_after_import: false,
+        import_ledger_table: None,
+        import_retries: 0,
+        import_retry_delay_secs: 0,
+        streaming_import_min_bytes: u64::MAX,
     };
     
     let pipeline = RemotePipeline::new(config);
@@ -301,6 +338,15 @@ async fn test_remote_pipeline_missing_event_type() {
         .into_iter()
         .collect(),
         table_event_mappings: HashMap::new(), // 没有事件类型映射
+        partition_layout: PartitionLayout::Flat,
+        force_reimport: false,
+        max_concurrent_imports: 1,
+        preserve_order: false,
+        verify_after_import: false,
+        import_ledger_table: None,
+        import_retries: 0,
+        import_retry_delay_secs: 0,
+        streaming_import_min_bytes: u64::MAX,
     };
     
     let pipeline = RemotePipeline::new(config);
@@ -374,6 +420,15 @@ async fn test_remote_pipeline_file_ordering() {
         ]
         .into_iter()
         .collect(),
+        partition_layout: PartitionLayout::Flat,
+        force_reimport: false,
+        max_concurrent_imports: 1,
+        preserve_order: false,
+        verify_after_import: false,
+        import_ledger_table: None,
+        import_retries: 0,
+        import_retry_delay_secs: 0,
+        streaming_import_min_bytes: u64::MAX,
     };
     
     let pipeline = RemotePipeline::new(config);
@@ -395,3 +450,109 @@ async fn test_remote_pipeline_file_ordering() {
     println!("\n🧹 Cleaning up...");
     drop_test_table(test_table).await.ok();
 }
+
+/// 辅助函数：单目录配置
+fn single_folder_config(storage_path: std::path::PathBuf, force_reimport: bool) -> RemoteConfig {
+    RemoteConfig {
+        remote_storage_path: storage_path,
+        import_mappings: [
+            ("pumpfun_trade_event_v2".to_string(), "test_table".to_string()),
+        ]
+        .into_iter()
+        .collect(),
+        table_event_mappings: [
+            ("pumpfun_trade_event_v2".to_string(), "PumpfunTradeEventV2".to_string()),
+        ]
+        .into_iter()
+        .collect(),
+        partition_layout: PartitionLayout::Flat,
+        force_reimport,
+        max_concurrent_imports: 1,
+        preserve_order: false,
+        verify_after_import: false,
+        import_ledger_table: None,
+        import_retries: 0,
+        import_retry_delay_secs: 0,
+        streaming_import_min_bytes: u64::MAX,
+    }
+}
+
+#[tokio::test]
+async fn test_remote_pipeline_skips_files_in_manifest() {
+    let temp_dir = tempdir().unwrap();
+    let folder = temp_dir.path().join("pumpfun_trade_event_v2");
+    std::fs::create_dir_all(&folder).unwrap();
+
+    // 文件内容无效：只要被读取就会失败，用来证明清单中的文件没有被导入
+    let file_name = "pumpfun_trade_event_v2_2025-10-01.parquet";
+    std::fs::write(folder.join(file_name), b"not a parquet file").unwrap();
+
+    let mut manifest = ImportManifest::load(&folder).unwrap();
+    manifest.mark_imported(file_name).unwrap();
+    assert!(folder.join(MANIFEST_FILE_NAME).exists());
+
+    // 重新加载后仍记得该文件
+    let reloaded = ImportManifest::load(&folder).unwrap();
+    assert!(reloaded.is_imported(file_name));
+    assert_eq!(reloaded.imported_count(), 1);
+
+    let pipeline = RemotePipeline::new(single_folder_config(temp_dir.path().to_path_buf(), false));
+    assert!(pipeline.run().await.is_ok(), "Imported files should be skipped");
+
+    // force_reimport 忽略清单，会真正读取文件并失败
+    let pipeline = RemotePipeline::new(single_folder_config(temp_dir.path().to_path_buf(), true));
+    assert!(pipeline.run().await.is_err(), "force_reimport should ignore the manifest");
+}
+
+#[tokio::test]
+async fn test_remote_pipeline_concurrent_failure_names_file() {
+    let temp_dir = tempdir().unwrap();
+    let folder = temp_dir.path().join("pumpfun_trade_event_v2");
+    std::fs::create_dir_all(&folder).unwrap();
+
+    // 多个损坏文件并发导入，错误需要指明是哪个文件
+    for day in 1..=4 {
+        let file_name = format!("pumpfun_trade_event_v2_2025-10-0{}.parquet", day);
+        std::fs::write(folder.join(file_name), b"not a parquet file").unwrap();
+    }
+
+    let mut config = single_folder_config(temp_dir.path().to_path_buf(), false);
+    config.max_concurrent_imports = 4;
+
+    let error_msg = RemotePipeline::new(config)
+        .run()
+        .await
+        .unwrap_err()
+        .to_string();
+    assert!(error_msg.contains("pumpfun_trade_event_v2/pumpfun_trade_event_v2_2025-10-0"), "{}", error_msg);
+
+    // 失败的文件不能写入清单
+    assert_eq!(ImportManifest::load(&folder).unwrap().imported_count(), 0);
+}
+
+#[tokio::test]
+async fn test_remote_pipeline_reports_every_failed_job() {
+    let temp_dir = tempdir().unwrap();
+    let folder = temp_dir.path().join("pumpfun_trade_event_v2");
+    std::fs::create_dir_all(&folder).unwrap();
+
+    for day in 1..=3 {
+        let file_name = format!("pumpfun_trade_event_v2_2025-10-0{}.parquet", day);
+        std::fs::write(folder.join(file_name), b"not a parquet file").unwrap();
+    }
+
+    let mut config = single_folder_config(temp_dir.path().to_path_buf(), false);
+    config.max_concurrent_imports = 2;
+
+    // 第一个失败不中断其他导入，所有失败的文件都出现在错误中
+    let error_msg = RemotePipeline::new(config)
+        .run()
+        .await
+        .unwrap_err()
+        .to_string();
+    assert!(error_msg.starts_with("3 import job(s) failed"), "{}", error_msg);
+    for day in 1..=3 {
+        let file_name = format!("pumpfun_trade_event_v2_2025-10-0{}.parquet", day);
+        assert!(error_msg.contains(&file_name), "{}", error_msg);
+    }
+}
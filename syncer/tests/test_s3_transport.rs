@@ -0,0 +1,136 @@
+use chrono::NaiveDate;
+use object_store::memory::InMemory;
+use object_store::ObjectStore;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+use syncer::config::LocalConfig;
+use syncer::s3_transport::{S3Config, S3Transport};
+use syncer::transport::{self, RsyncOptions, Transport};
+use tempfile::tempdir;
+
+fn local_config(s3: Option<S3Config>) -> LocalConfig {
+    LocalConfig {
+        tables: vec!["table_a".to_string()],
+        table_event_mappings: [("table_a".to_string(), "EventTypeA".to_string())]
+            .into_iter()
+            .collect(),
+        start_time: NaiveDate::from_ymd_opt(2025, 10, 1).unwrap(),
+        end_time: None,
+        local_storage_path: PathBuf::from("/tmp/exports"),
+        dry_run: false,
+        keep_local: false,
+        no_transfer: false,
+        transfer_retries: 0,
+        transfer_retry_delay_secs: 1,
+        rsync: RsyncOptions::default(),
+        remote_server: None,
+        s3,
+    }
+}
+
+#[tokio::test]
+async fn test_upload_preserves_table_layout() {
+    let temp_dir = tempdir().unwrap();
+    let table_dir = temp_dir.path().join("table_a");
+    fs::create_dir_all(&table_dir).unwrap();
+    fs::write(table_dir.join("table_a_2025-10-01.parquet"), b"day one").unwrap();
+    fs::write(table_dir.join("table_a_2025-10-02.parquet"), b"day two").unwrap();
+    fs::write(table_dir.join(".imported.log"), b"ignored").unwrap();
+
+    let store = Arc::new(InMemory::new());
+    let transport = S3Transport::with_store(store.clone(), "/exports/");
+
+    let uploaded = transport.upload_directory(&table_dir, "table_a").await.unwrap();
+    assert_eq!(uploaded, 2);
+
+    // key 布局为 {prefix}/{table}/{file}
+    let object = store
+        .get(&transport.object_path("table_a/table_a_2025-10-01.parquet"))
+        .await
+        .unwrap();
+    assert_eq!(object.bytes().await.unwrap().as_ref(), b"day one");
+    assert_eq!(
+        transport.object_path("table_a/table_a_2025-10-01.parquet").as_ref(),
+        "exports/table_a/table_a_2025-10-01.parquet"
+    );
+
+    assert!(transport.exists("table_a/table_a_2025-10-02.parquet").await.unwrap());
+    assert!(!transport.exists("table_a/.imported.log").await.unwrap());
+    assert!(!transport.exists("table_a/table_a_2025-10-03.parquet").await.unwrap());
+}
+
+#[tokio::test]
+async fn test_upload_skips_unchanged_files() {
+    let temp_dir = tempdir().unwrap();
+    let table_dir = temp_dir.path().join("table_a");
+    fs::create_dir_all(&table_dir).unwrap();
+    fs::write(table_dir.join("table_a_2025-10-01.parquet"), b"day one").unwrap();
+
+    let transport = S3Transport::with_store(Arc::new(InMemory::new()), "");
+    let dyn_transport: &dyn Transport = &transport;
+    dyn_transport.sync_directory(&table_dir, "table_a").await.unwrap();
+
+    // 第二次同步只上传新增的文件
+    fs::write(table_dir.join("table_a_2025-10-02.parquet"), b"day two").unwrap();
+    let uploaded = transport.upload_directory(&table_dir, "table_a").await.unwrap();
+    assert_eq!(uploaded, 1);
+}
+
+#[tokio::test]
+async fn test_upload_replaces_same_size_reexport() {
+    let temp_dir = tempdir().unwrap();
+    let table_dir = temp_dir.path().join("table_a");
+    fs::create_dir_all(&table_dir).unwrap();
+    let file = table_dir.join("table_a_2025-10-01.parquet");
+    fs::write(&file, b"day one").unwrap();
+
+    let store = Arc::new(InMemory::new());
+    let transport = S3Transport::with_store(store.clone(), "");
+    assert_eq!(transport.upload_directory(&table_dir, "table_a").await.unwrap(), 1);
+    assert_eq!(transport.upload_directory(&table_dir, "table_a").await.unwrap(), 0);
+
+    // 重新导出后大小不变但内容变了，必须重新上传
+    fs::write(&file, b"day 1!!").unwrap();
+    assert_eq!(transport.upload_directory(&table_dir, "table_a").await.unwrap(), 1);
+
+    let object = store
+        .get(&transport.object_path("table_a/table_a_2025-10-01.parquet"))
+        .await
+        .unwrap();
+    assert_eq!(object.bytes().await.unwrap().as_ref(), b"day 1!!");
+}
+
+#[tokio::test]
+async fn test_upload_nonexistent_directory() {
+    let transport = S3Transport::with_store(Arc::new(InMemory::new()), "");
+
+    let result = transport
+        .sync_directory(&PathBuf::from("/nonexistent/path"), "table_a")
+        .await;
+
+    assert!(result.unwrap_err().to_string().contains("does not exist"));
+}
+
+#[test]
+fn test_transport_requires_destination() {
+    let result = transport::from_config(&local_config(None));
+
+    let error = result.err().expect("config without remote_server or s3 should fail");
+    assert!(error.to_string().contains("[remote_server] or [s3]"));
+}
+
+#[test]
+fn test_transport_from_s3_config() {
+    let s3 = S3Config {
+        bucket: "exports".to_string(),
+        prefix: "heaven".to_string(),
+        region: "us-east-1".to_string(),
+        endpoint: Some("http://127.0.0.1:9000".to_string()),
+        access_key_id: Some("minio".to_string()),
+        secret_access_key: Some("minio123".to_string()),
+        allow_http: true,
+    };
+
+    assert!(transport::from_config(&local_config(Some(s3))).is_ok());
+}
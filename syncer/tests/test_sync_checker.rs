@@ -0,0 +1,242 @@
+use syncer::SyncStats;
+use syncer::sync_checker::{
+    CountDiff, FINE_BUCKET_SECS, InsertOutcome, bucket_counts_sql, deduplicate_columns,
+    diff_counts, fine_sync_windows, insert_outcome, optimize_deduplicate_sql, quote_ident,
+    quote_table, split_table,
+};
+
+#[test]
+fn test_merge_table_stats() {
+    let mut stats = SyncStats {
+        total_tables: 2,
+        ..Default::default()
+    };
+
+    stats.merge(SyncStats {
+        diff_hours: 2,
+        diff_minutes: 5,
+        synced_records: 100,
+        errors: vec!["a -> a: minute 60: timeout".to_string()],
+        ..Default::default()
+    });
+    stats.merge(SyncStats {
+        diff_hours: 1,
+        diff_minutes: 1,
+        synced_buckets: 3,
+        synced_records: 7,
+        errors: vec!["b -> b: connection refused".to_string()],
+        ..Default::default()
+    });
+
+    // total_tables 由调用方设置，合并时不累加
+    assert_eq!(stats.total_tables, 2);
+    assert_eq!(stats.diff_hours, 3);
+    assert_eq!(stats.diff_minutes, 6);
+    assert_eq!(stats.synced_buckets, 3);
+    assert_eq!(stats.synced_records, 107);
+    assert_eq!(stats.errors.len(), 2);
+}
+
+#[test]
+fn test_per_table_stats_and_json() {
+    let mut stats = SyncStats {
+        total_tables: 2,
+        ..Default::default()
+    };
+
+    // 并发完成顺序不固定，per_table 按本地表名排序
+    stats.record_table(
+        "trade",
+        "trade_remote",
+        SyncStats {
+            diff_hours: 1,
+            diff_minutes: 4,
+            synced_records: 40,
+            ..Default::default()
+        },
+    );
+    stats.record_table(
+        "create",
+        "create_remote",
+        SyncStats {
+            errors: vec!["create -> create_remote: timeout".to_string()],
+            ..Default::default()
+        },
+    );
+
+    assert_eq!(stats.per_table.len(), 2);
+    assert_eq!(stats.per_table[0].local_table, "create");
+    assert_eq!(stats.per_table[0].errors, 1);
+    assert_eq!(stats.per_table[1].remote_table, "trade_remote");
+    assert_eq!(stats.per_table[1].synced_records, 40);
+    assert_eq!(stats.diff_minutes, 4);
+    assert_eq!(stats.errors.len(), 1);
+
+    let json: serde_json::Value = serde_json::from_str(&stats.to_json()).unwrap();
+    assert_eq!(json["synced_records"], 40);
+    assert_eq!(json["per_table"][1]["local_table"], "trade");
+    assert_eq!(json["per_table"][1]["diff_minutes"], 4);
+}
+
+#[test]
+fn test_remote_only_minutes_are_not_counted_as_synced() {
+    let local = vec![(60, 5), (120, 3), (180, 1)];
+    let remote = vec![(60, 5), (120, 2), (240, 7), (300, 1)];
+
+    let diff = diff_counts(local, remote);
+
+    assert_eq!(
+        diff,
+        CountDiff {
+            // 只有本地有数据的分钟需要同步
+            differing: vec![(120, 3, 2), (180, 1, 0)],
+            remote_only: vec![(240, 7), (300, 1)],
+        }
+    );
+
+    // 同一分钟不会既算差异又算远程独有
+    let mut minutes: Vec<u32> = diff.differing.iter().map(|(m, _, _)| *m).collect();
+    minutes.extend(diff.remote_only.iter().map(|(m, _)| *m));
+    minutes.dedup();
+    assert_eq!(minutes.len(), 4);
+}
+
+#[test]
+fn test_remote_surplus_is_merged_separately() {
+    let mut stats = SyncStats::default();
+    stats.record_table(
+        "trade",
+        "trade",
+        SyncStats {
+            diff_minutes: 2,
+            remote_surplus: 3,
+            ..Default::default()
+        },
+    );
+
+    assert_eq!(stats.diff_minutes, 2);
+    assert_eq!(stats.remote_surplus, 3);
+    assert_eq!(stats.per_table[0].remote_surplus, 3);
+}
+
+#[test]
+fn test_quote_ident_reserved_word() {
+    assert_eq!(quote_ident("order"), "`order`");
+    assert_eq!(quote_ident("select"), "`select`");
+}
+
+#[test]
+fn test_quote_ident_escapes_backticks() {
+    assert_eq!(quote_ident("weird`name"), "`weird\\`name`");
+    assert_eq!(quote_ident("back\\slash"), "`back\\\\slash`");
+    // 注入尝试被整体引用成一个标识符
+    assert_eq!(
+        quote_ident("t` WHERE 1; DROP TABLE x; --"),
+        "`t\\` WHERE 1; DROP TABLE x; --`"
+    );
+}
+
+#[test]
+fn test_quote_database_qualified_table() {
+    assert_eq!(split_table("analytics.trades"), (Some("analytics"), "trades"));
+    assert_eq!(split_table("trades"), (None, "trades"));
+    assert_eq!(quote_table("analytics.trades"), "`analytics`.`trades`");
+    assert_eq!(quote_table("default.order"), "`default`.`order`");
+    assert_eq!(quote_table("pumpfun_trade_event_v2"), "`pumpfun_trade_event_v2`");
+}
+
+#[test]
+fn test_deduplicate_columns_include_table_keys() {
+    let key = vec!["signature".to_string(), "instruction_index".to_string()];
+    let table_keys = vec![
+        "slot".to_string(),
+        "transaction_index".to_string(),
+        "instruction_index".to_string(),
+    ];
+
+    assert_eq!(
+        deduplicate_columns(&key, &table_keys),
+        vec!["signature", "instruction_index", "slot", "transaction_index"]
+    );
+    assert_eq!(deduplicate_columns(&key, &[]), key);
+}
+
+#[test]
+fn test_optimize_deduplicate_sql() {
+    let columns = vec!["signature".to_string(), "instruction_index".to_string()];
+    assert_eq!(
+        optimize_deduplicate_sql("analytics.trade", &columns),
+        "OPTIMIZE TABLE `analytics`.`trade` FINAL DEDUPLICATE BY `signature`, `instruction_index`"
+    );
+}
+
+#[test]
+fn test_deduplicated_rows_are_merged() {
+    let mut stats = SyncStats::default();
+    stats.record_table(
+        "trade",
+        "trade",
+        SyncStats {
+            synced_records: 10,
+            deduplicated_rows: 4,
+            ..Default::default()
+        },
+    );
+
+    assert_eq!(stats.deduplicated_rows, 4);
+    assert_eq!(stats.per_table[0].deduplicated_rows, 4);
+}
+
+#[test]
+fn test_fine_grained_syncs_only_differing_buckets() {
+    // 2025-10-01 00:00:00 UTC 这一分钟
+    let minute = 1_759_276_800;
+    let local = [(minute, 3), (minute + 10, 2), (minute + 20, 5), (minute + 40, 1)];
+    // 10 秒桶少了一行，40 秒桶远程缺失，50 秒桶只在远程
+    let remote = [(minute, 3), (minute + 10, 1), (minute + 20, 5), (minute + 50, 4)];
+
+    let windows = fine_sync_windows(local, remote);
+
+    assert_eq!(windows, vec![(minute + 10, minute + 20), (minute + 40, minute + 50)]);
+    for (start, end) in windows {
+        assert_eq!(end - start, FINE_BUCKET_SECS);
+        assert!(start >= minute && end <= minute + 60);
+    }
+}
+
+#[test]
+fn test_fine_grained_matching_minute_has_no_windows() {
+    let minute = 1_759_276_800;
+    let counts = [(minute, 3), (minute + 30, 7)];
+    assert!(fine_sync_windows(counts, counts).is_empty());
+}
+
+#[test]
+fn test_bucket_counts_sql_groups_by_fine_bucket() {
+    let sql = bucket_counts_sql("db.trades", "(signature, instruction_index)", 1_759_276_800, 1_759_276_860);
+
+    assert!(sql.contains(&format!("INTERVAL {} SECOND", FINE_BUCKET_SECS)));
+    assert!(sql.contains("uniqExact((signature, instruction_index))"));
+    assert!(sql.contains("FROM `db`.`trades`"));
+    assert!(sql.contains(">= 1759276800 AND"));
+    assert!(sql.contains("< 1759276860"));
+}
+
+#[test]
+fn test_bucket_counts_sql_quotes_timestamp_column() {
+    let sql = bucket_counts_sql("trades", "signature", 0, 60);
+
+    assert!(sql.contains("toDateTime(`timestamp`)"));
+    assert!(sql.contains("WHERE `timestamp` >= 0 AND `timestamp` < 60"));
+}
+
+#[test]
+fn test_insert_retried_only_when_nothing_was_written() {
+    // 远程行数没变：写入没有生效，可以重试
+    assert_eq!(insert_outcome(100, 100, 30), InsertOutcome::NotApplied);
+    // 报错但行数正好增加了 30：写入已完成，不能再写一次
+    assert_eq!(insert_outcome(100, 130, 30), InsertOutcome::Applied);
+    // 只写入了一部分：重试会重复插入已写入的行
+    assert_eq!(insert_outcome(100, 110, 30), InsertOutcome::PartiallyApplied);
+    assert_eq!(insert_outcome(100, 160, 30), InsertOutcome::PartiallyApplied);
+}
@@ -1,6 +1,6 @@
 use std::path::PathBuf;
 use syncer::config::RemoteServerConfig;
-use syncer::transport::RsyncTransport;
+use syncer::transport::{shell_quote, RsyncError, RsyncOptions, RsyncTransport, STDERR_TAIL_LINES};
 use tempfile::tempdir;
 use std::fs;
 
@@ -33,6 +33,86 @@ async fn test_sync_nonexistent_directory() {
     }
 }
 
+#[test]
+fn test_default_rsync_args() {
+    let args = RsyncOptions::default().to_args();
+
+    assert_eq!(args[0], "-avz");
+    assert!(args.contains(&"--partial".to_string()));
+    // 默认不追加续传，重新导出的同名文件会完整重传
+    assert!(!args.contains(&"--append-verify".to_string()));
+    assert!(args.contains(&"--bwlimit=2048".to_string()));
+
+    let args = RsyncOptions {
+        append_verify: true,
+        ..Default::default()
+    }
+    .to_args();
+    assert!(args.contains(&"--partial".to_string()));
+    assert!(args.contains(&"--append-verify".to_string()));
+}
+
+#[test]
+fn test_custom_rsync_args() {
+    let options = RsyncOptions {
+        bwlimit_kbps: None,
+        partial: false,
+        append_verify: false,
+        compress: false,
+        extra_args: vec!["--checksum".to_string(), "--delay-updates".to_string()],
+    };
+    let args = options.to_args();
+
+    assert_eq!(args[0], "-av");
+    assert!(!args.iter().any(|arg| arg.starts_with("--bwlimit")));
+    assert!(!args.contains(&"--partial".to_string()));
+    // 额外参数按顺序追加在末尾
+    assert_eq!(&args[args.len() - 2..], ["--checksum", "--delay-updates"]);
+}
+
+#[tokio::test]
+async fn test_sync_nonexistent_directory_with_options() {
+    let transport = RsyncTransport::new().with_options(RsyncOptions {
+        bwlimit_kbps: Some(100),
+        ..Default::default()
+    });
+    let remote_config = RemoteServerConfig {
+        address: "example.com".to_string(),
+        port: 22,
+        username: "testuser".to_string(),
+        private_key_path: PathBuf::from("/tmp/test_key"),
+        remote_path: PathBuf::from("/tmp/remote"),
+    };
+
+    let result = transport
+        .sync_directory(&PathBuf::from("/nonexistent/path"), &remote_config)
+        .await;
+
+    assert!(result.unwrap_err().to_string().contains("does not exist"));
+}
+
+#[test]
+fn test_rsync_error_message() {
+    let stderr = "Permission denied (publickey).\r\n\nrsync: connection unexpectedly closed (0 bytes received so far) [sender]\n";
+    let error = RsyncError::new(Some(255), stderr);
+
+    assert_eq!(error.stderr_tail.len(), 2);
+    let message = error.to_string();
+    assert!(message.contains("exit code 255 (ssh connection or authentication failed)"));
+    assert!(message.contains("Permission denied (publickey)."));
+    assert!(!message.contains('\n'), "error should fit on one log line: {}", message);
+}
+
+#[test]
+fn test_rsync_error_keeps_stderr_tail() {
+    let stderr: String = (0..100).map(|i| format!("line {}\n", i)).collect();
+    let error = RsyncError::new(Some(11), &stderr);
+
+    assert_eq!(error.stderr_tail.len(), STDERR_TAIL_LINES);
+    assert_eq!(error.stderr_tail.last().unwrap(), "line 99");
+    assert_eq!(error.stderr_tail[0], format!("line {}", 100 - STDERR_TAIL_LINES));
+}
+
 #[tokio::test]
 async fn test_command_construction() {
     // 这个测试验证命令构建逻辑（不实际执行 rsync）
@@ -188,3 +268,9 @@ async fn test_directory_trailing_slash() {
     
     println!("✓ Trailing slash correctly added: {}", local_src);
 }
+
+#[test]
+fn test_shell_quote_escapes_single_quotes() {
+    assert_eq!(shell_quote("/data/table_a/a.parquet"), "'/data/table_a/a.parquet'");
+    assert_eq!(shell_quote("/data/it's; rm -rf ~"), "'/data/it'\\''s; rm -rf ~'");
+}
@@ -2,8 +2,8 @@ use criterion::{criterion_group, criterion_main, Criterion};
 use proto_lib::transaction::solana::{self, Transaction};
 use rand::{Rng, SeedableRng};
 use rand::rngs::StdRng;
-use utils::clickhouse_events;
-use utils::convert_transaction::TransactionConverter;
+use utils::base58::global_bs58;
+use utils::convert_transaction::{ConvertedEvents, TransactionConverter};
 
 const SEED: u64 = 42;
 
@@ -561,6 +561,27 @@ fn create_pumpfun_amm_withdraw_tx(rng: &mut StdRng) -> Transaction {
     tx
 }
 
+// 9. 不含 PumpFun 指令的普通交易（转账、ComputeBudget 等）
+fn create_unrelated_tx(rng: &mut StdRng) -> Transaction {
+    let mut tx = Transaction::default();
+    tx.slot = rng.random_range(100000..200000);
+    tx.index = rng.random_range(0..1000);
+    tx.signature = (0..64).map(|_| rng.random::<u8>()).collect();
+    tx.instructions = [
+        "ComputeBudgetSetComputeUnitLimit",
+        "ComputeBudgetSetComputeUnitPrice",
+        "SystemTransfer",
+        "TokenTransfer",
+    ]
+        .iter()
+        .map(|instr_type| solana::Instruction {
+            r#type: instr_type.to_string(),
+            parsed: None,
+        })
+        .collect();
+    tx
+}
+
 fn benchmark_individual_events(c: &mut Criterion) {
     let mut group = c.benchmark_group("individual_events");
     let mut rng = StdRng::seed_from_u64(SEED);
@@ -579,26 +600,9 @@ fn benchmark_individual_events(c: &mut Criterion) {
         ($name:expr, $tx:expr) => {
             group.bench_function($name, |b| {
                 b.iter(|| {
-                    let mut pumpfun_trade_event_rows: Vec<clickhouse_events::PumpfunTradeEventV2> = vec![];
-                    let mut pumpfun_create_event_rows: Vec<clickhouse_events::PumpfunCreateEventV2> = vec![];
-                    let mut pumpfun_migrate_event_rows: Vec<clickhouse_events::PumpfunMigrateEventV2> = vec![];
-                    let mut pumpfun_amm_buy_event_rows: Vec<clickhouse_events::PumpfunAmmBuyEventV2> = vec![];
-                    let mut pumpfun_amm_sell_event_rows: Vec<clickhouse_events::PumpfunAmmSellEventV2> = vec![];
-                    let mut pumpfun_amm_create_pool_event_rows: Vec<clickhouse_events::PumpfunAmmCreatePoolEventV2> = vec![];
-                    let mut pumpfun_amm_deposit_event_rows: Vec<clickhouse_events::PumpfunAmmDepositEventV2> = vec![];
-                    let mut pumpfun_amm_withdraw_event_rows: Vec<clickhouse_events::PumpfunAmmWithdrawEventV2> = vec![];
+                    let mut events = ConvertedEvents::default();
                     
-                    TransactionConverter::convert(
-                        std::hint::black_box(&$tx),
-                        &mut pumpfun_trade_event_rows,
-                        &mut pumpfun_create_event_rows,
-                        &mut pumpfun_migrate_event_rows,
-                        &mut pumpfun_amm_buy_event_rows,
-                        &mut pumpfun_amm_sell_event_rows,
-                        &mut pumpfun_amm_create_pool_event_rows,
-                        &mut pumpfun_amm_deposit_event_rows,
-                        &mut pumpfun_amm_withdraw_event_rows,
-                    );
+                    TransactionConverter::convert_into(std::hint::black_box(&$tx), &mut events);
                 });
             });
         };
@@ -620,9 +624,12 @@ fn benchmark_batch_conversion(c: &mut Criterion) {
     let mut group = c.benchmark_group("batch_conversion");
     let mut rng = StdRng::seed_from_u64(SEED);
     
-    // 混合交易：模拟真实场景
+    // 混合交易：模拟真实场景，一半交易不含 PumpFun 指令
     let mixed_txs: Vec<Transaction> = (0..100).map(|i| {
-        match i % 8 {
+        if i % 2 == 1 {
+            return create_unrelated_tx(&mut rng);
+        }
+        match (i / 2) % 8 {
             0 => create_pumpfun_trade_tx(&mut rng),
             1 => create_pumpfun_create_tx(&mut rng),
             2 => create_pumpfun_migrate_tx(&mut rng),
@@ -636,27 +643,10 @@ fn benchmark_batch_conversion(c: &mut Criterion) {
     
     group.bench_function("mixed_100_txs", |b| {
         b.iter(|| {
-            let mut pumpfun_trade_event_rows: Vec<clickhouse_events::PumpfunTradeEventV2> = vec![];
-            let mut pumpfun_create_event_rows: Vec<clickhouse_events::PumpfunCreateEventV2> = vec![];
-            let mut pumpfun_migrate_event_rows: Vec<clickhouse_events::PumpfunMigrateEventV2> = vec![];
-            let mut pumpfun_amm_buy_event_rows: Vec<clickhouse_events::PumpfunAmmBuyEventV2> = vec![];
-            let mut pumpfun_amm_sell_event_rows: Vec<clickhouse_events::PumpfunAmmSellEventV2> = vec![];
-            let mut pumpfun_amm_create_pool_event_rows: Vec<clickhouse_events::PumpfunAmmCreatePoolEventV2> = vec![];
-            let mut pumpfun_amm_deposit_event_rows: Vec<clickhouse_events::PumpfunAmmDepositEventV2> = vec![];
-            let mut pumpfun_amm_withdraw_event_rows: Vec<clickhouse_events::PumpfunAmmWithdrawEventV2> = vec![];
+            let mut events = ConvertedEvents::default();
             
             for tx in std::hint::black_box(&mixed_txs) {
-                TransactionConverter::convert(
-                    tx,
-                    &mut pumpfun_trade_event_rows,
-                    &mut pumpfun_create_event_rows,
-                    &mut pumpfun_migrate_event_rows,
-                    &mut pumpfun_amm_buy_event_rows,
-                    &mut pumpfun_amm_sell_event_rows,
-                    &mut pumpfun_amm_create_pool_event_rows,
-                    &mut pumpfun_amm_deposit_event_rows,
-                    &mut pumpfun_amm_withdraw_event_rows,
-                );
+                TransactionConverter::convert_into(tx, &mut events);
             }
         });
     });
@@ -664,5 +654,44 @@ fn benchmark_batch_conversion(c: &mut Criterion) {
     group.finish();
 }
 
-criterion_group!(benches, benchmark_individual_events, benchmark_batch_conversion);
+// 单个事件中 base58 编码的开销：AMM buy 事件有 1 个签名和 12 个账户 key，
+// 与 individual_events/pumpfun_amm_buy 对比可看出编码在转换中的占比
+fn benchmark_bs58_encoding(c: &mut Criterion) {
+    let mut group = c.benchmark_group("bs58_encoding");
+    let mut rng = StdRng::seed_from_u64(SEED);
+
+    let signature: [u8; 64] = std::array::from_fn(|_| rng.random::<u8>());
+    let keys: [[u8; 32]; 12] = std::array::from_fn(|_| rng.random::<[u8; 32]>());
+
+    group.bench_function("amm_buy_event_fields", |b| {
+        b.iter(|| {
+            let signature = global_bs58().encode_64(std::hint::black_box(&signature));
+            let keys: Vec<String> = std::hint::black_box(&keys)
+                .iter()
+                .map(|key| global_bs58().encode_32(key))
+                .collect();
+            (signature, keys)
+        });
+    });
+
+    // 复用同一组缓冲区，只剩编码本身的开销
+    let mut signature_out = String::new();
+    let mut key_outs: [String; 12] = Default::default();
+    group.bench_function("amm_buy_event_fields_into", |b| {
+        b.iter(|| {
+            let keys = std::hint::black_box(&keys);
+            global_bs58().encode_64_into(std::hint::black_box(&signature), &mut signature_out);
+            global_bs58().encode_32_batch_into(keys.each_ref(), key_outs.each_mut());
+        });
+    });
+
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    benchmark_individual_events,
+    benchmark_batch_conversion,
+    benchmark_bs58_encoding
+);
 criterion_main!(benches);
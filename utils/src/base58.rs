@@ -0,0 +1,133 @@
+//! base58 编解码：签名 / 公钥与 ClickHouse `String` 列之间的转换
+//!
+//! 编码在事件转换的热路径上（每个事件十几个 key），`global_bs58()` 的 `*_into`
+//! 方法写入调用方的缓冲区，批量处理时可以复用同一组 String
+
+use std::fmt;
+
+/// base58 解码失败
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Base58Error {
+    /// 含有 base58 字母表以外的字符，index 为字节位置
+    InvalidCharacter { character: char, index: usize },
+    /// 解码后的字节数与期望不符（例如把签名当作公钥解码）
+    WrongLength { expected: usize, actual: usize },
+    /// bs58 报告的其它错误
+    Malformed(String),
+}
+
+impl fmt::Display for Base58Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Base58Error::InvalidCharacter { character, index } => write!(
+                f,
+                "invalid base58 character {:?} at index {}",
+                character, index
+            ),
+            Base58Error::WrongLength { expected, actual } => write!(
+                f,
+                "base58 value decodes to {} bytes, expected {}",
+                actual, expected
+            ),
+            Base58Error::Malformed(message) => write!(f, "invalid base58 value: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for Base58Error {}
+
+/// 解码 32 字节的公钥
+pub fn decode_32(value: &str) -> Result<[u8; 32], Base58Error> {
+    decode_exact(value)
+}
+
+/// 解码 64 字节的签名
+pub fn decode_64(value: &str) -> Result<[u8; 64], Base58Error> {
+    decode_exact(value)
+}
+
+fn decode_exact<const N: usize>(value: &str) -> Result<[u8; N], Base58Error> {
+    let bytes = bs58::decode(value).into_vec().map_err(|e| match e {
+        bs58::decode::Error::InvalidCharacter { character, index } => {
+            Base58Error::InvalidCharacter { character, index }
+        }
+        bs58::decode::Error::NonAsciiCharacter { index } => Base58Error::InvalidCharacter {
+            character: value[index..].chars().next().unwrap_or_default(),
+            index,
+        },
+        other => Base58Error::Malformed(other.to_string()),
+    })?;
+
+    let actual = bytes.len();
+    bytes.try_into().map_err(|_| Base58Error::WrongLength {
+        expected: N,
+        actual,
+    })
+}
+
+/// 32 字节公钥编码后的最大长度
+pub const MAX_ENCODED_LEN_32: usize = 44;
+
+/// 64 字节签名编码后的最大长度
+pub const MAX_ENCODED_LEN_64: usize = 88;
+
+/// base58 编码器，通过 `global_bs58()` 获取
+///
+/// `encode_32` / `encode_64` 每次返回新的 String；`*_into` 写入调用方持有的 String，
+/// 反复使用同一批缓冲区时不再分配
+#[derive(Debug)]
+pub struct Bs58Encoder {
+    _private: (),
+}
+
+static GLOBAL_BS58: Bs58Encoder = Bs58Encoder { _private: () };
+
+/// 全局编码器
+pub fn global_bs58() -> &'static Bs58Encoder {
+    &GLOBAL_BS58
+}
+
+impl Bs58Encoder {
+    /// 编码 32 字节公钥（按最大长度分配一次）
+    pub fn encode_32(&self, key: &[u8]) -> String {
+        let mut encoded = String::with_capacity(MAX_ENCODED_LEN_32);
+        encode_into(key, &mut encoded);
+        encoded
+    }
+
+    /// 编码 64 字节签名（按最大长度分配一次）
+    pub fn encode_64(&self, signature: &[u8]) -> String {
+        let mut encoded = String::with_capacity(MAX_ENCODED_LEN_64);
+        encode_into(signature, &mut encoded);
+        encoded
+    }
+
+    /// 编码 32 字节公钥到 `out`（先清空，复用已有容量）
+    pub fn encode_32_into(&self, key: &[u8; 32], out: &mut String) {
+        encode_into(key, out);
+    }
+
+    /// 编码 64 字节签名到 `out`（先清空，复用已有容量）
+    pub fn encode_64_into(&self, signature: &[u8; 64], out: &mut String) {
+        encode_into(signature, out);
+    }
+
+    /// 一次编码多个公钥（如同一指令的全部账户），`keys[i]` 写入 `outs[i]`
+    pub fn encode_32_batch_into<const N: usize>(
+        &self,
+        keys: [&[u8; 32]; N],
+        outs: [&mut String; N],
+    ) {
+        for (key, out) in keys.into_iter().zip(outs) {
+            encode_into(key, out);
+        }
+    }
+}
+
+fn encode_into(bytes: &[u8], out: &mut String) {
+    out.clear();
+    // 输出到 String 时按需扩容，不会出现缓冲区不足
+    bs58::encode(bytes)
+        .onto(&mut *out)
+        .expect("encoding into a String cannot fail");
+}
@@ -1,27 +1,186 @@
 use clickhouse::Client;
-use std::sync::OnceLock;
+use hyper_timeout::TimeoutConnector;
+use hyper_util::client::legacy::Client as HttpClient;
+use hyper_util::client::legacy::connect::HttpConnector;
+use hyper_util::rt::TokioExecutor;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
 
+/// 默认建立 TCP 连接的超时（毫秒）
+pub const DEFAULT_CONNECT_TIMEOUT_MS: u64 = 5_000;
+/// 默认请求超时（毫秒）：连接上这么久没有读写任何数据即判定失败
+pub const DEFAULT_REQUEST_TIMEOUT_MS: u64 = 120_000;
+/// 默认每个地址保留的空闲连接数
+pub const DEFAULT_POOL_SIZE: usize = 16;
+
+/// 空闲连接的保留时间，需小于 ClickHouse 服务端的 keep_alive_timeout（默认 3 秒，23.11 起为 10 秒），
+/// 否则会复用已被服务端关闭的连接
+const POOL_IDLE_TIMEOUT: Duration = Duration::from_millis(2_500);
+const TCP_KEEPALIVE: Duration = Duration::from_secs(60);
+
+/// HTTP 接口的默认端口（TLS 为 8443），所有组件都通过 HTTP 接口连接
+pub const HTTP_PORTS: [u16; 2] = [8123, 8443];
+
+/// 原生 TCP 协议的默认端口（TLS 为 9440）
+///
+/// `clickhouse` crate 只实现了 HTTP 接口，URL 指向这些端口时构建客户端直接报错
+pub const NATIVE_PROTOCOL_PORTS: [u16; 2] = [9000, 9440];
+
+/// ClickHouse 连接配置（extractor / importer / subscriber 共用）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClickHouseConfig {
+    pub url: String,
+    pub user: String,
+    pub password: String,
+    pub database: String,
+    /// 建立连接的超时（毫秒），0 表示不限
+    #[serde(default = "default_connect_timeout_ms")]
+    pub connect_timeout_ms: u64,
+    /// 请求超时（毫秒），0 表示不限
+    ///
+    /// 按连接上的读写空闲时间计算：ClickHouse 卡住不返回数据时请求在该时间后失败，
+    /// 持续返回数据的长查询不受影响
+    #[serde(default = "default_request_timeout_ms")]
+    pub request_timeout_ms: u64,
+    /// 每个地址保留的空闲 HTTP 连接数，并发插入复用这些连接而不是每次新建
+    #[serde(default = "default_pool_size")]
+    pub pool_size: usize,
+}
+
+fn default_connect_timeout_ms() -> u64 {
+    DEFAULT_CONNECT_TIMEOUT_MS
+}
+
+fn default_request_timeout_ms() -> u64 {
+    DEFAULT_REQUEST_TIMEOUT_MS
+}
+
+fn default_pool_size() -> usize {
+    DEFAULT_POOL_SIZE
+}
+
+impl ClickHouseConfig {
+    /// 从环境变量读取连接配置
+    ///
+    /// CLICKHOUSE_CONNECT_TIMEOUT_MS、CLICKHOUSE_REQUEST_TIMEOUT_MS、CLICKHOUSE_POOL_SIZE
+    /// 可选，缺省为 DEFAULT_* 常量
+    pub fn from_env() -> Self {
+        Self::from_env_prefixed("CLICKHOUSE").unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// 读取命名客户端的连接配置，例如 `analytics` 对应 `CLICKHOUSE_ANALYTICS_URL` 等变量
+    pub fn from_env_named(name: &str) -> Result<Self, String> {
+        Self::from_env_prefixed(&format!("CLICKHOUSE_{}", env_key(name)))
+    }
+
+    fn from_env_prefixed(prefix: &str) -> Result<Self, String> {
+        let var = |key: &str| {
+            let name = format!("{}_{}", prefix, key);
+            std::env::var(&name).map_err(|_| format!("{} environment variable is required", name))
+        };
+
+        Ok(Self {
+            url: var("URL")?,
+            user: var("USER")?,
+            password: var("PASSWORD")?,
+            database: var("DATABASE")?,
+            connect_timeout_ms: optional_env(prefix, "CONNECT_TIMEOUT_MS", DEFAULT_CONNECT_TIMEOUT_MS)?,
+            request_timeout_ms: optional_env(prefix, "REQUEST_TIMEOUT_MS", DEFAULT_REQUEST_TIMEOUT_MS)?,
+            pool_size: optional_env(prefix, "POOL_SIZE", DEFAULT_POOL_SIZE)?,
+        })
+    }
+
+    /// 构建 HTTP 接口的客户端
+    ///
+    /// URL 指向原生协议的默认端口（9000/9440）时直接报错，这是最常见的配错方式，
+    /// 否则只会在第一次查询时得到难以理解的协议错误
+    pub fn build_client(&self) -> Result<Client, String> {
+        if let Some(port) = url_port(&self.url)
+            && NATIVE_PROTOCOL_PORTS.contains(&port)
+        {
+            return Err(format!(
+                "ClickHouse URL '{}' points at native protocol port {}; the HTTP interface listens on {} ({} for TLS)",
+                self.url, port, HTTP_PORTS[0], HTTP_PORTS[1]
+            ));
+        }
+
+        // 带连接池和超时的 HTTP 客户端（请求体类型由 with_http_client 推断）
+        let mut http = HttpConnector::new();
+        http.set_keepalive(Some(TCP_KEEPALIVE));
+        let request_timeout = timeout(self.request_timeout_ms);
+        let mut connector = TimeoutConnector::new(http);
+        connector.set_connect_timeout(timeout(self.connect_timeout_ms));
+        connector.set_read_timeout(request_timeout);
+        connector.set_write_timeout(request_timeout);
+        let http_client = HttpClient::builder(TokioExecutor::new())
+            .pool_idle_timeout(POOL_IDLE_TIMEOUT)
+            .pool_max_idle_per_host(self.pool_size)
+            .build(connector);
+
+        Ok(Client::with_http_client(http_client)
+            .with_url(&self.url)
+            .with_user(&self.user)
+            .with_password(&self.password)
+            .with_database(&self.database)
+            .with_option("enable_http_compression", "1"))
+    }
+}
+
+/// 毫秒数转换为超时，0 表示不限
+fn timeout(millis: u64) -> Option<Duration> {
+    (millis > 0).then(|| Duration::from_millis(millis))
+}
+
+/// 读取可选的数值环境变量，未设置时使用默认值
+fn optional_env<T: std::str::FromStr>(prefix: &str, key: &str, default: T) -> Result<T, String> {
+    let name = format!("{}_{}", prefix, key);
+    match std::env::var(&name) {
+        Ok(value) => value
+            .parse()
+            .map_err(|_| format!("{} must be a non-negative integer, got '{}'", name, value)),
+        Err(_) => Ok(default),
+    }
+}
+
+/// 客户端名转换为环境变量中的写法：大写，非字母数字替换为下划线
+fn env_key(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_uppercase() } else { '_' })
+        .collect()
+}
+
+/// 从 URL 中取出显式端口
+fn url_port(url: &str) -> Option<u16> {
+    let authority = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let authority = authority.split(['/', '?']).next()?;
+    let (_, port) = authority.rsplit_once(':')?;
+    port.parse().ok()
+}
+
+/// ClickHouse 写入客户端
+///
+/// `instance()` 是读取 `CLICKHOUSE_*` 的默认客户端；需要连接多个集群时用
+/// `register` / `named` 按名字管理，或直接用 `from_config` 创建独立的客户端
 pub struct ClickHouseClient {
     client: Client,
 }
 
 impl ClickHouseClient {
     fn new() -> Self {
-        let url = std::env::var("CLICKHOUSE_URL").expect("CLICKHOUSE_URL environment variable is required");
-        let user = std::env::var("CLICKHOUSE_USER").expect("CLICKHOUSE_USER environment variable is required");
-        let database = std::env::var("CLICKHOUSE_DATABASE").expect("CLICKHOUSE_DATABASE environment variable is required");
-        let password = std::env::var("CLICKHOUSE_PASSWORD").expect("CLICKHOUSE_PASSWORD environment variable is required");
-        
-        let client = Client::default()
-            .with_url(&url)
-            .with_user(&user)
-            .with_database(&database)
-            .with_password(&password)
+        Self::from_config(&ClickHouseConfig::from_env())
+            .unwrap_or_else(|e| panic!("Invalid ClickHouse config: {}", e))
+    }
+
+    /// 根据连接配置创建客户端（写入端，开启异步插入）
+    pub fn from_config(config: &ClickHouseConfig) -> Result<Self, String> {
+        let client = config
+            .build_client()?
             .with_option("async_insert", "1")
-            .with_option("wait_for_async_insert", "0")
-            .with_option("enable_http_compression", "1");
-        
-        Self { client }
+            .with_option("wait_for_async_insert", "0");
+
+        Ok(Self { client })
     }
 
     pub fn instance() -> &'static ClickHouseClient {
@@ -29,7 +188,40 @@ impl ClickHouseClient {
         INSTANCE.get_or_init(|| ClickHouseClient::new())
     }
 
+    /// 注册命名客户端，同名已注册时返回错误
+    pub fn register(name: &str, config: &ClickHouseConfig) -> Result<&'static ClickHouseClient, String> {
+        let mut clients = named_clients().lock().unwrap();
+        if clients.contains_key(name) {
+            return Err(format!("ClickHouse client '{}' is already registered", name));
+        }
+        let client = Self::from_config(config)
+            .map_err(|e| format!("Invalid ClickHouse config for '{}': {}", name, e))?;
+        // 命名客户端与 instance() 一样存活到进程结束
+        let client: &'static ClickHouseClient = Box::leak(Box::new(client));
+        clients.insert(name.to_string(), client);
+        Ok(client)
+    }
+
+    /// 取命名客户端，未注册时从 `CLICKHOUSE_<NAME>_*` 环境变量创建
+    pub fn named(name: &str) -> Result<&'static ClickHouseClient, String> {
+        let mut clients = named_clients().lock().unwrap();
+        if let Some(client) = clients.get(name) {
+            return Ok(client);
+        }
+        let config = ClickHouseConfig::from_env_named(name)?;
+        let client = Self::from_config(&config)
+            .map_err(|e| format!("Invalid ClickHouse config for '{}': {}", name, e))?;
+        let client: &'static ClickHouseClient = Box::leak(Box::new(client));
+        clients.insert(name.to_string(), client);
+        Ok(client)
+    }
+
     pub fn client(&self) -> &Client {
         &self.client
     }
 }
+
+fn named_clients() -> &'static Mutex<HashMap<String, &'static ClickHouseClient>> {
+    static CLIENTS: OnceLock<Mutex<HashMap<String, &'static ClickHouseClient>>> = OnceLock::new();
+    CLIENTS.get_or_init(|| Mutex::new(HashMap::new()))
+}
@@ -1,10 +1,13 @@
-use arrow::datatypes::FieldRef;
+use arrow::datatypes::{DataType, Field, FieldRef};
 use arrow::record_batch::RecordBatch;
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use serde_arrow::schema::SchemaLike;
 use serde_arrow::schema::TracingOptions;
 use serde_arrow::{from_record_batch, to_record_batch};
+use std::error::Error;
+use std::fmt;
+use std::sync::Arc;
 
 use clickhouse::Row;
 
@@ -36,6 +39,9 @@ pub struct PumpfunTradeEventV2 {
     pub total_claimed_tokens: u64,
     pub current_sol_volume: u64,
     pub last_update_timestamp: i64,
+    /// UInt128 列（见 `u128_le_bytes`）；旧文件缺少该列时为 0
+    #[serde(default, with = "u128_le_bytes")]
+    pub current_sol_volume_128: u128,
 }
 
 // pumpfun_create_event_v2
@@ -114,6 +120,9 @@ pub struct PumpfunAmmBuyEventV2 {
     pub current_sol_volume: u64,
     pub last_update_timestamp: i64,
     pub is_main_pool: u8,
+    /// UInt128 列（见 `u128_le_bytes`）；旧文件缺少该列时为 0
+    #[serde(default, with = "u128_le_bytes")]
+    pub current_sol_volume_128: u128,
 }
 
 // pumpfun_amm_sell_event_v2
@@ -237,12 +246,286 @@ pub struct PumpfunAmmWithdrawEventV2 {
     pub is_main_pool: u8,
 }
 
+// pumpfun_amm_collect_coin_creator_fee_event_v2
+#[derive(Debug, Row, Serialize, Deserialize, PartialEq)]
+pub struct PumpfunAmmCollectCoinCreatorFeeEventV2 {
+    pub signature: String,
+    pub slot: u64,
+    pub transaction_index: u32,
+    pub instruction_index: u32,
+    pub timestamp: u32,
+    pub coin_creator: String,
+    pub coin_creator_fee: u64,
+    pub coin_creator_vault_ata: String,
+    pub coin_creator_token_account: String,
+}
+
+// pumpfun_amm_disable_event_v2
+#[derive(Debug, Row, Serialize, Deserialize, PartialEq)]
+pub struct PumpfunAmmDisableEventV2 {
+    pub signature: String,
+    pub slot: u64,
+    pub transaction_index: u32,
+    pub instruction_index: u32,
+    pub timestamp: u32,
+    pub admin: String,
+    pub disable_create_pool: u8,
+    pub disable_deposit: u8,
+    pub disable_withdraw: u8,
+    pub disable_buy: u8,
+    pub disable_sell: u8,
+}
+
+// pumpfun_amm_set_params_event_v2
+#[derive(Debug, Row, Serialize, Deserialize, PartialEq)]
+pub struct PumpfunAmmSetParamsEventV2 {
+    pub signature: String,
+    pub slot: u64,
+    pub transaction_index: u32,
+    pub instruction_index: u32,
+    pub timestamp: u32,
+    pub admin: String,
+    pub lp_fee_basis_points: u64,
+    pub protocol_fee_basis_points: u64,
+    pub coin_creator_fee_basis_points: u64,
+}
+
+/// 行在 RowBinary 插入中的近似字节数，用于按数据量切分批次
+pub trait EstimatedSize {
+    fn estimated_size(&self) -> usize;
+}
+
+// 定长列按结构体内存大小近似，String 列按实际长度 + 长度前缀计
+macro_rules! impl_estimated_size {
+    ($row:ty; $($string_field:ident),+ $(,)?) => {
+        impl EstimatedSize for $row {
+            fn estimated_size(&self) -> usize {
+                let string_fields = [$(&self.$string_field),+];
+                std::mem::size_of::<Self>() - string_fields.len() * std::mem::size_of::<String>()
+                    + string_fields.iter().map(|field| field.len() + 1).sum::<usize>()
+            }
+        }
+    };
+}
+
+impl_estimated_size!(PumpfunTradeEventV2; signature, mint, user, fee_recipient, creator);
+impl_estimated_size!(
+    PumpfunCreateEventV2;
+    signature, name, symbol, uri, mint, bonding_curve, user, creator
+);
+impl_estimated_size!(PumpfunMigrateEventV2; signature, user, mint, bonding_curve, pool);
+impl_estimated_size!(
+    PumpfunAmmBuyEventV2;
+    signature, base_mint, quote_mint, pool, user, user_base_token_account,
+    user_quote_token_account, protocol_fee_recipient, protocol_fee_recipient_token_account,
+    coin_creator
+);
+impl_estimated_size!(
+    PumpfunAmmSellEventV2;
+    signature, base_mint, quote_mint, pool, user, user_base_token_account,
+    user_quote_token_account, protocol_fee_recipient, protocol_fee_recipient_token_account,
+    coin_creator
+);
+impl_estimated_size!(
+    PumpfunAmmCreatePoolEventV2;
+    signature, creator, base_mint, quote_mint, pool, lp_mint, user_base_token_account,
+    user_quote_token_account, coin_creator
+);
+impl_estimated_size!(
+    PumpfunAmmDepositEventV2;
+    signature, base_mint, quote_mint, pool, user, user_base_token_account,
+    user_quote_token_account, user_pool_token_account
+);
+impl_estimated_size!(
+    PumpfunAmmWithdrawEventV2;
+    signature, base_mint, quote_mint, pool, user, user_base_token_account,
+    user_quote_token_account, user_pool_token_account
+);
+impl_estimated_size!(
+    PumpfunAmmCollectCoinCreatorFeeEventV2;
+    signature, coin_creator, coin_creator_vault_ata, coin_creator_token_account
+);
+impl_estimated_size!(PumpfunAmmDisableEventV2; signature, admin);
+impl_estimated_size!(PumpfunAmmSetParamsEventV2; signature, admin);
+
+/// 行的去重键，与 syncer 默认的 `(signature, instruction_index)` 一致
+pub trait DedupKey {
+    fn dedup_key(&self) -> (&str, u32);
+}
+
+macro_rules! impl_dedup_key {
+    ($($row:ty),+ $(,)?) => {
+        $(
+            impl DedupKey for $row {
+                fn dedup_key(&self) -> (&str, u32) {
+                    (&self.signature, self.instruction_index)
+                }
+            }
+        )+
+    };
+}
+
+impl_dedup_key!(
+    PumpfunTradeEventV2,
+    PumpfunCreateEventV2,
+    PumpfunMigrateEventV2,
+    PumpfunAmmBuyEventV2,
+    PumpfunAmmSellEventV2,
+    PumpfunAmmCreatePoolEventV2,
+    PumpfunAmmDepositEventV2,
+    PumpfunAmmWithdrawEventV2,
+    PumpfunAmmCollectCoinCreatorFeeEventV2,
+    PumpfunAmmDisableEventV2,
+    PumpfunAmmSetParamsEventV2,
+);
+
+/// Vec<T> 与 Arrow RecordBatch 互转失败
+#[derive(Debug)]
+pub enum ArrowConvError {
+    /// 无法从类型推导 Arrow schema
+    Schema(serde_arrow::Error),
+    /// 行数据与 schema 不匹配
+    Convert(serde_arrow::Error),
+}
+
+impl fmt::Display for ArrowConvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ArrowConvError::Schema(source) => write!(f, "Arrow schema tracing failed: {}", source),
+            ArrowConvError::Convert(source) => write!(f, "Arrow conversion failed: {}", source),
+        }
+    }
+}
+
+impl Error for ArrowConvError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ArrowConvError::Schema(source) | ArrowConvError::Convert(source) => Some(source),
+        }
+    }
+}
+
+/// u128 列的序列化：16 字节小端序列
+///
+/// RowBinary 中即 UInt128 的原生编码；Arrow 中为 `FixedSizeBinary(16)`（见 `event_fields`），
+/// 与 ClickHouse 以 ArrowStream 输出 UInt128 时的类型一致，完整保留 u128 的取值范围
+pub mod u128_le_bytes {
+    use serde::de::{self, SeqAccess, Visitor};
+    use serde::ser::SerializeTuple;
+    use serde::{Deserializer, Serializer};
+    use std::fmt;
+
+    /// 编码后的字节数
+    pub const WIDTH: usize = 16;
+
+    pub fn serialize<S: Serializer>(value: &u128, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut tuple = serializer.serialize_tuple(WIDTH)?;
+        for byte in value.to_le_bytes() {
+            tuple.serialize_element(&byte)?;
+        }
+        tuple.end()
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u128, D::Error> {
+        deserializer.deserialize_tuple(WIDTH, LeBytesVisitor)
+    }
+
+    // RowBinary 按元组逐字节读取，Arrow 的 FixedSizeBinary 可能直接给出字节切片
+    struct LeBytesVisitor;
+
+    impl<'de> Visitor<'de> for LeBytesVisitor {
+        type Value = u128;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            write!(formatter, "{} little-endian bytes", WIDTH)
+        }
+
+        fn visit_bytes<E: de::Error>(self, bytes: &[u8]) -> Result<u128, E> {
+            let bytes: [u8; WIDTH] = bytes
+                .try_into()
+                .map_err(|_| E::invalid_length(bytes.len(), &self))?;
+            Ok(u128::from_le_bytes(bytes))
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<u128, A::Error> {
+            let mut bytes = [0u8; WIDTH];
+            for (index, byte) in bytes.iter_mut().enumerate() {
+                *byte = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(index, &self))?;
+            }
+            Ok(u128::from_le_bytes(bytes))
+        }
+    }
+}
+
+/// 以 `u128_le_bytes` 存储的列名
+pub const U128_COLUMNS: &[&str] = &["current_sol_volume_128"];
+
+/// 由事件结构体推导 Arrow 字段，`U128_COLUMNS` 中的列替换为 `FixedSizeBinary(16)`
+///
+/// serde_arrow 只能把字节元组推导为 Struct，这里按列名修正，避免各处手写 schema
+pub fn event_fields<T: DeserializeOwned>(
+    options: TracingOptions,
+) -> Result<Vec<FieldRef>, serde_arrow::Error> {
+    let fields = Vec::<FieldRef>::from_type::<T>(options)?;
+    Ok(fields
+        .into_iter()
+        .map(|field| {
+            if U128_COLUMNS.contains(&field.name().as_str()) {
+                Arc::new(Field::new(
+                    field.name(),
+                    DataType::FixedSizeBinary(u128_le_bytes::WIDTH as i32),
+                    field.is_nullable(),
+                ))
+            } else {
+                field
+            }
+        })
+        .collect())
+}
+
+/// vec_to_arrow_batch 使用的 schema 推导选项：允许可空字段（`Option<T>`）
+///
+/// 需要其它行为（如 `coerce_numbers` 统一整型宽度）时在此基础上修改后传给 `vec_to_arrow_batch_with`
+pub fn default_tracing_options() -> TracingOptions {
+    TracingOptions::default().allow_null_fields(true)
+}
+
+/// 转换为 Arrow RecordBatch，失败时 panic（测试用，服务代码请使用 `try_vec_to_arrow_batch`）
 pub fn vec_to_arrow_batch<T: Serialize + for<'de> Deserialize<'de>>(data: &Vec<T>) -> RecordBatch {
-    let fields = Vec::<FieldRef>::from_type::<T>(TracingOptions::default()).expect("schema tracing failed");
-    to_record_batch(&fields, data).expect("Failed to convert Vec<T> to Arrow RecordBatch")
+    try_vec_to_arrow_batch(data).expect("Failed to convert Vec<T> to Arrow RecordBatch")
 }
 
-/// 将 Arrow RecordBatch 转换为 Vec<T>
+/// 使用指定的 schema 推导选项转换为 Arrow RecordBatch，失败时 panic
+pub fn vec_to_arrow_batch_with<T: Serialize + for<'de> Deserialize<'de>>(
+    data: &[T],
+    options: TracingOptions,
+) -> RecordBatch {
+    try_vec_to_arrow_batch_with(data, options).expect("Failed to convert Vec<T> to Arrow RecordBatch")
+}
+
+pub fn try_vec_to_arrow_batch<T: Serialize + for<'de> Deserialize<'de>>(
+    data: &[T],
+) -> Result<RecordBatch, ArrowConvError> {
+    try_vec_to_arrow_batch_with(data, default_tracing_options())
+}
+
+pub fn try_vec_to_arrow_batch_with<T: Serialize + for<'de> Deserialize<'de>>(
+    data: &[T],
+    options: TracingOptions,
+) -> Result<RecordBatch, ArrowConvError> {
+    let fields = event_fields::<T>(options).map_err(ArrowConvError::Schema)?;
+    to_record_batch(&fields, data).map_err(ArrowConvError::Convert)
+}
+
+/// 将 Arrow RecordBatch 转换为 Vec<T>，失败时 panic（测试用，服务代码请使用 `try_arrow_batch_to_vec`）
 pub fn arrow_batch_to_vec<T: DeserializeOwned>(batch: &RecordBatch) -> Vec<T> {
-    from_record_batch(batch).expect("Failed to convert Arrow RecordBatch to Vec<T>")
+    try_arrow_batch_to_vec(batch).expect("Failed to convert Arrow RecordBatch to Vec<T>")
+}
+
+pub fn try_arrow_batch_to_vec<T: DeserializeOwned>(
+    batch: &RecordBatch,
+) -> Result<Vec<T>, ArrowConvError> {
+    from_record_batch(batch).map_err(ArrowConvError::Convert)
 }
@@ -1,13 +1,440 @@
 use super::clickhouse_events::{
-    PumpfunAmmBuyEventV2, PumpfunAmmCreatePoolEventV2, PumpfunAmmDepositEventV2,
-    PumpfunAmmSellEventV2, PumpfunAmmWithdrawEventV2, PumpfunCreateEventV2, PumpfunMigrateEventV2,
-    PumpfunTradeEventV2,
+    DedupKey, EstimatedSize, PumpfunAmmBuyEventV2, PumpfunAmmCollectCoinCreatorFeeEventV2, PumpfunAmmCreatePoolEventV2,
+    PumpfunAmmDepositEventV2, PumpfunAmmDisableEventV2, PumpfunAmmSellEventV2,
+    PumpfunAmmSetParamsEventV2, PumpfunAmmWithdrawEventV2, PumpfunCreateEventV2, PumpfunMigrateEventV2, PumpfunTradeEventV2,
 };
-use common::cached_bs58::global_bs58;
-use proto_lib::transaction::solana::Transaction;
+use super::pumpfun_decoder::PumpfunDecoder;
+use prost::Message;
+use serde::{Deserialize, Serialize};
+use proto_lib::transaction::solana::{Instruction, Transaction};
+use std::any::Any;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
+use std::sync::LazyLock;
 pub struct TransactionConverter;
 
+/// 默认的指令栈最大深度
+pub const DEFAULT_MAX_STACK_DEPTH: usize = 256;
+
+/// 转换选项
+#[derive(Debug, Clone)]
+pub struct ConverterOptions {
+    /// 记录每个匹配的 instruction+event 的 protobuf 编码字节数
+    /// 需要额外计算编码长度，热路径上默认关闭
+    pub record_byte_sizes: bool,
+    /// 待匹配指令栈的最大深度，超出时丢弃最早入栈的指令
+    /// （离 event 这么远的指令基本不可能再被匹配）
+    pub max_stack_depth: usize,
+}
+
+impl Default for ConverterOptions {
+    fn default() -> Self {
+        Self {
+            record_byte_sizes: false,
+            max_stack_depth: DEFAULT_MAX_STACK_DEPTH,
+        }
+    }
+}
+
+/// 单个匹配事件的编码字节数
+#[derive(Debug, Clone, PartialEq)]
+pub struct EventByteSize {
+    pub event_type: String,
+    pub instruction_index: u32,
+    pub instruction_bytes: usize,
+    pub event_bytes: usize,
+}
+
+impl EventByteSize {
+    /// instruction + event 的总字节数
+    pub fn total_bytes(&self) -> usize {
+        self.instruction_bytes + self.event_bytes
+    }
+}
+
+/// 转换过程中的辅助统计（按 ConverterOptions 开关填充）
+#[derive(Debug, Default)]
+pub struct ConversionMetrics {
+    pub event_byte_sizes: Vec<EventByteSize>,
+    /// 因超出 max_stack_depth 被丢弃的指令数
+    pub dropped_instructions: usize,
+    /// 转换过程中指令栈达到的最大深度
+    pub peak_stack_depth: usize,
+    /// 栈中找不到可匹配父指令而被跳过的 event 数
+    pub unmatched_events: usize,
+}
+
+/// 一笔或多笔交易转换得到的全部事件行
+///
+/// 各服务共用的事件集合；misaka_signal 以 msgpack（字段名作为 key）发送，
+/// 缺少的字段反序列化为空，外部 decoder 的行不参与序列化
+///
+/// 下游消费者只认识最初的 8 个 key：之后新增的表为空时不写入 map，
+/// 只有确实出现该类事件时 msgpack 中才会多出对应的 key
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ConvertedEvents {
+    pub pumpfun_trade_event: Vec<PumpfunTradeEventV2>,
+    pub pumpfun_create_event: Vec<PumpfunCreateEventV2>,
+    pub pumpfun_migrate_event: Vec<PumpfunMigrateEventV2>,
+    pub pumpfun_amm_buy_event: Vec<PumpfunAmmBuyEventV2>,
+    pub pumpfun_amm_sell_event: Vec<PumpfunAmmSellEventV2>,
+    pub pumpfun_amm_create_pool_event: Vec<PumpfunAmmCreatePoolEventV2>,
+    pub pumpfun_amm_deposit_event: Vec<PumpfunAmmDepositEventV2>,
+    pub pumpfun_amm_withdraw_event: Vec<PumpfunAmmWithdrawEventV2>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub pumpfun_amm_collect_coin_creator_fee_event: Vec<PumpfunAmmCollectCoinCreatorFeeEventV2>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub pumpfun_amm_disable_event: Vec<PumpfunAmmDisableEventV2>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub pumpfun_amm_set_params_event: Vec<PumpfunAmmSetParamsEventV2>,
+    /// 外部注册的 decoder 产出的行
+    #[serde(skip)]
+    pub custom: Vec<CustomRow>,
+}
+
+impl ConvertedEvents {
+    /// 追加另一组事件（按表依次 append）
+    pub fn extend(&mut self, mut other: ConvertedEvents) {
+        self.pumpfun_trade_event.append(&mut other.pumpfun_trade_event);
+        self.pumpfun_create_event.append(&mut other.pumpfun_create_event);
+        self.pumpfun_migrate_event.append(&mut other.pumpfun_migrate_event);
+        self.pumpfun_amm_buy_event.append(&mut other.pumpfun_amm_buy_event);
+        self.pumpfun_amm_sell_event.append(&mut other.pumpfun_amm_sell_event);
+        self.pumpfun_amm_create_pool_event
+            .append(&mut other.pumpfun_amm_create_pool_event);
+        self.pumpfun_amm_deposit_event
+            .append(&mut other.pumpfun_amm_deposit_event);
+        self.pumpfun_amm_withdraw_event
+            .append(&mut other.pumpfun_amm_withdraw_event);
+        self.pumpfun_amm_collect_coin_creator_fee_event
+            .append(&mut other.pumpfun_amm_collect_coin_creator_fee_event);
+        self.pumpfun_amm_disable_event
+            .append(&mut other.pumpfun_amm_disable_event);
+        self.pumpfun_amm_set_params_event
+            .append(&mut other.pumpfun_amm_set_params_event);
+        self.custom.append(&mut other.custom);
+    }
+
+    /// 按行类型追加到对应的 Vec
+    pub fn push(&mut self, row: DecodedRow) {
+        match row {
+            DecodedRow::PumpfunTrade(row) => self.pumpfun_trade_event.push(row),
+            DecodedRow::PumpfunCreate(row) => self.pumpfun_create_event.push(row),
+            DecodedRow::PumpfunMigrate(row) => self.pumpfun_migrate_event.push(row),
+            DecodedRow::PumpfunAmmBuy(row) => self.pumpfun_amm_buy_event.push(row),
+            DecodedRow::PumpfunAmmSell(row) => self.pumpfun_amm_sell_event.push(row),
+            DecodedRow::PumpfunAmmCreatePool(row) => self.pumpfun_amm_create_pool_event.push(row),
+            DecodedRow::PumpfunAmmDeposit(row) => self.pumpfun_amm_deposit_event.push(row),
+            DecodedRow::PumpfunAmmWithdraw(row) => self.pumpfun_amm_withdraw_event.push(row),
+            DecodedRow::PumpfunAmmCollectCoinCreatorFee(row) => {
+                self.pumpfun_amm_collect_coin_creator_fee_event.push(row)
+            }
+            DecodedRow::PumpfunAmmDisable(row) => self.pumpfun_amm_disable_event.push(row),
+            DecodedRow::PumpfunAmmSetParams(row) => self.pumpfun_amm_set_params_event.push(row),
+            DecodedRow::Custom(row) => self.custom.push(row),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// 事件集合中各表的名称（字段名，与 msgpack 中的 key 一致）
+    pub const TABLE_NAMES: [&'static str; 12] = [
+        "pumpfun_trade_event",
+        "pumpfun_create_event",
+        "pumpfun_migrate_event",
+        "pumpfun_amm_buy_event",
+        "pumpfun_amm_sell_event",
+        "pumpfun_amm_create_pool_event",
+        "pumpfun_amm_deposit_event",
+        "pumpfun_amm_withdraw_event",
+        "pumpfun_amm_collect_coin_creator_fee_event",
+        "pumpfun_amm_disable_event",
+        "pumpfun_amm_set_params_event",
+        "custom",
+    ];
+
+    /// 会被序列化发送给下游的表：`TABLE_NAMES` 去掉最后的 custom（`#[serde(skip)]`）
+    pub const SERIALIZED_TABLE_NAMES: &'static [&'static str] = match Self::TABLE_NAMES.split_last() {
+        Some((_, names)) => names,
+        None => &[],
+    };
+
+    /// 只保留 allowed 中列出的表（名称见 `TABLE_NAMES`），其余表清空
+    pub fn retain_tables<S: AsRef<str>>(&mut self, allowed: &[S]) {
+        let keep = |name: &str| allowed.iter().any(|table| table.as_ref() == name);
+
+        macro_rules! retain {
+            ($($field:ident),+ $(,)?) => {
+                $(
+                    if !keep(stringify!($field)) {
+                        self.$field.clear();
+                    }
+                )+
+            };
+        }
+
+        retain!(
+            pumpfun_trade_event,
+            pumpfun_create_event,
+            pumpfun_migrate_event,
+            pumpfun_amm_buy_event,
+            pumpfun_amm_sell_event,
+            pumpfun_amm_create_pool_event,
+            pumpfun_amm_deposit_event,
+            pumpfun_amm_withdraw_event,
+            pumpfun_amm_collect_coin_creator_fee_event,
+            pumpfun_amm_disable_event,
+            pumpfun_amm_set_params_event,
+            custom,
+        );
+    }
+
+    /// 对 tables 中列出的表（名称见 `TABLE_NAMES`）按 `(signature, instruction_index)`
+    /// 去重，保留第一次出现的行，返回删除的行数。custom 行没有统一的键，不参与去重
+    pub fn dedup_tables<S: AsRef<str>>(&mut self, tables: &[S]) -> usize {
+        fn dedup_rows<T: DedupKey>(rows: &mut Vec<T>) -> usize {
+            let before = rows.len();
+            let mut seen = HashSet::with_capacity(rows.len());
+            rows.retain(|row| {
+                let (signature, instruction_index) = row.dedup_key();
+                seen.insert((signature.to_string(), instruction_index))
+            });
+            before - rows.len()
+        }
+
+        let selected = |name: &str| tables.iter().any(|table| table.as_ref() == name);
+        let mut removed = 0;
+
+        macro_rules! dedup {
+            ($($field:ident),+ $(,)?) => {
+                $(
+                    if selected(stringify!($field)) {
+                        removed += dedup_rows(&mut self.$field);
+                    }
+                )+
+            };
+        }
+
+        dedup!(
+            pumpfun_trade_event,
+            pumpfun_create_event,
+            pumpfun_migrate_event,
+            pumpfun_amm_buy_event,
+            pumpfun_amm_sell_event,
+            pumpfun_amm_create_pool_event,
+            pumpfun_amm_deposit_event,
+            pumpfun_amm_withdraw_event,
+            pumpfun_amm_collect_coin_creator_fee_event,
+            pumpfun_amm_disable_event,
+            pumpfun_amm_set_params_event,
+        );
+        removed
+    }
+
+    /// 每种事件的行数（按字段名），外部 decoder 的行合计为 custom
+    pub fn len_per_table(&self) -> [(&'static str, usize); 12] {
+        [
+            ("pumpfun_trade_event", self.pumpfun_trade_event.len()),
+            ("pumpfun_create_event", self.pumpfun_create_event.len()),
+            ("pumpfun_migrate_event", self.pumpfun_migrate_event.len()),
+            ("pumpfun_amm_buy_event", self.pumpfun_amm_buy_event.len()),
+            ("pumpfun_amm_sell_event", self.pumpfun_amm_sell_event.len()),
+            ("pumpfun_amm_create_pool_event", self.pumpfun_amm_create_pool_event.len()),
+            ("pumpfun_amm_deposit_event", self.pumpfun_amm_deposit_event.len()),
+            ("pumpfun_amm_withdraw_event", self.pumpfun_amm_withdraw_event.len()),
+            (
+                "pumpfun_amm_collect_coin_creator_fee_event",
+                self.pumpfun_amm_collect_coin_creator_fee_event.len(),
+            ),
+            ("pumpfun_amm_disable_event", self.pumpfun_amm_disable_event.len()),
+            ("pumpfun_amm_set_params_event", self.pumpfun_amm_set_params_event.len()),
+            ("custom", self.custom.len()),
+        ]
+    }
+
+    /// 所有内置表的近似插入字节数（见 `EstimatedSize`），外部 decoder 的行不计入
+    pub fn estimated_bytes(&self) -> usize {
+        fn rows_bytes<T: EstimatedSize>(rows: &[T]) -> usize {
+            rows.iter().map(EstimatedSize::estimated_size).sum()
+        }
+
+        rows_bytes(&self.pumpfun_trade_event)
+            + rows_bytes(&self.pumpfun_create_event)
+            + rows_bytes(&self.pumpfun_migrate_event)
+            + rows_bytes(&self.pumpfun_amm_buy_event)
+            + rows_bytes(&self.pumpfun_amm_sell_event)
+            + rows_bytes(&self.pumpfun_amm_create_pool_event)
+            + rows_bytes(&self.pumpfun_amm_deposit_event)
+            + rows_bytes(&self.pumpfun_amm_withdraw_event)
+            + rows_bytes(&self.pumpfun_amm_collect_coin_creator_fee_event)
+            + rows_bytes(&self.pumpfun_amm_disable_event)
+            + rows_bytes(&self.pumpfun_amm_set_params_event)
+    }
+
+    /// 所有表的事件总行数
+    pub fn len(&self) -> usize {
+        self.pumpfun_trade_event.len()
+            + self.pumpfun_create_event.len()
+            + self.pumpfun_migrate_event.len()
+            + self.pumpfun_amm_buy_event.len()
+            + self.pumpfun_amm_sell_event.len()
+            + self.pumpfun_amm_create_pool_event.len()
+            + self.pumpfun_amm_deposit_event.len()
+            + self.pumpfun_amm_withdraw_event.len()
+            + self.pumpfun_amm_collect_coin_creator_fee_event.len()
+            + self.pumpfun_amm_disable_event.len()
+            + self.pumpfun_amm_set_params_event.len()
+            + self.custom.len()
+    }
+}
+
+/// decoder 解析出的一行
+// 行会立即移入 ConvertedEvents 的对应 Vec，装箱只会在热路径上多一次分配
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug)]
+pub enum DecodedRow {
+    PumpfunTrade(PumpfunTradeEventV2),
+    PumpfunCreate(PumpfunCreateEventV2),
+    PumpfunMigrate(PumpfunMigrateEventV2),
+    PumpfunAmmBuy(PumpfunAmmBuyEventV2),
+    PumpfunAmmSell(PumpfunAmmSellEventV2),
+    PumpfunAmmCreatePool(PumpfunAmmCreatePoolEventV2),
+    PumpfunAmmDeposit(PumpfunAmmDepositEventV2),
+    PumpfunAmmWithdraw(PumpfunAmmWithdrawEventV2),
+    PumpfunAmmCollectCoinCreatorFee(PumpfunAmmCollectCoinCreatorFeeEventV2),
+    PumpfunAmmDisable(PumpfunAmmDisableEventV2),
+    PumpfunAmmSetParams(PumpfunAmmSetParamsEventV2),
+    Custom(CustomRow),
+}
+
+/// 外部 decoder 的行，调用方按 event_type 自行 downcast
+pub struct CustomRow {
+    pub event_type: String,
+    pub row: Box<dyn Any + Send + Sync>,
+}
+
+impl CustomRow {
+    pub fn new<T: Any + Send + Sync>(event_type: &str, row: T) -> Self {
+        Self {
+            event_type: event_type.to_string(),
+            row: Box::new(row),
+        }
+    }
+
+    pub fn downcast_ref<T: Any>(&self) -> Option<&T> {
+        self.row.downcast_ref()
+    }
+}
+
+impl fmt::Debug for CustomRow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CustomRow")
+            .field("event_type", &self.event_type)
+            .finish_non_exhaustive()
+    }
+}
+
+/// 单个程序的事件解码器
+pub trait ProgramDecoder: Send + Sync {
+    /// 该 decoder 处理的 event 类型（Instruction.r#type）
+    fn event_types(&self) -> &[&str];
+
+    /// 能发出该 event 的父指令类型前缀，为空时取栈顶指令（紧邻匹配）
+    fn parent_prefixes(&self, _event_type: &str) -> &[&str] {
+        &[]
+    }
+
+    /// 由 event 和其父指令组装一行，无法解析时返回 None
+    fn decode(
+        &self,
+        event_instr: &Instruction,
+        parent_instr: &Instruction,
+        tx: &Transaction,
+        instruction_index: usize,
+    ) -> Option<DecodedRow>;
+}
+
+/// decoder 注册表：event 类型 -> decoder
+pub struct ConverterRegistry {
+    decoders: Vec<Box<dyn ProgramDecoder>>,
+    by_event_type: HashMap<String, usize>,
+}
+
+impl ConverterRegistry {
+    /// 空注册表
+    pub fn new() -> Self {
+        Self {
+            decoders: Vec::new(),
+            by_event_type: HashMap::new(),
+        }
+    }
+
+    /// 只包含内置的 PumpFun decoder
+    pub fn with_builtin() -> Self {
+        let mut registry = Self::new();
+        registry.register(PumpfunDecoder::new());
+        registry
+    }
+
+    /// 注册 decoder，与已注册 decoder 冲突的 event 类型由后注册者接管
+    pub fn register<D: ProgramDecoder + 'static>(&mut self, decoder: D) -> &mut Self {
+        let position = self.decoders.len();
+        for event_type in decoder.event_types() {
+            self.by_event_type.insert(event_type.to_string(), position);
+        }
+        self.decoders.push(Box::new(decoder));
+        self
+    }
+
+    pub fn decoder_for(&self, event_type: &str) -> Option<&dyn ProgramDecoder> {
+        self.by_event_type
+            .get(event_type)
+            .map(|&position| self.decoders[position].as_ref())
+    }
+
+    /// 交易中是否包含已注册的 event，只比较 r#type，不解析指令
+    pub fn has_known_events(&self, tx: &Transaction) -> bool {
+        tx.instructions.iter().any(|instr| self.is_event(&instr.r#type))
+    }
+
+    /// 是否为已注册的 event 类型
+    pub fn is_event(&self, event_type: &str) -> bool {
+        self.by_event_type.contains_key(event_type)
+    }
+}
+
+impl Default for ConverterRegistry {
+    fn default() -> Self {
+        Self::with_builtin()
+    }
+}
+
+static DEFAULT_REGISTRY: LazyLock<ConverterRegistry> = LazyLock::new(ConverterRegistry::with_builtin);
+
 impl TransactionConverter {
+    /// 交易中是否包含内置 decoder 能处理的 event
+    ///
+    /// 大部分交易不含 PumpFun 指令，热路径可先用它过滤，跳过完整转换
+    pub fn has_known_events(tx: &Transaction) -> bool {
+        DEFAULT_REGISTRY.has_known_events(tx)
+    }
+
+    /// 转换单笔交易，事件追加到 events
+    pub fn convert_into(tx: &Transaction, events: &mut ConvertedEvents) {
+        Self::convert_into_with_options(
+            tx,
+            &ConverterOptions::default(),
+            &mut ConversionMetrics::default(),
+            events,
+        );
+    }
+
+    /// 旧接口：八个输出 Vec 按固定顺序传入，新代码请使用 `convert_into`
+    ///
+    /// 只输出最初的八种事件，之后新增的事件类型会被丢弃
     pub fn convert(
         tx: &Transaction,
         pumpfun_trade_event_rows: &mut Vec<PumpfunTradeEventV2>,
@@ -19,388 +446,155 @@ impl TransactionConverter {
         pumpfun_amm_deposit_event_rows: &mut Vec<PumpfunAmmDepositEventV2>,
         pumpfun_amm_withdraw_event_rows: &mut Vec<PumpfunAmmWithdrawEventV2>,
     ) {
-        let mut stack: Vec<&proto_lib::transaction::solana::Instruction> = Vec::new();
+        Self::convert_with_options(
+            tx,
+            &ConverterOptions::default(),
+            &mut ConversionMetrics::default(),
+            pumpfun_trade_event_rows,
+            pumpfun_create_event_rows,
+            pumpfun_migrate_event_rows,
+            pumpfun_amm_buy_event_rows,
+            pumpfun_amm_sell_event_rows,
+            pumpfun_amm_create_pool_event_rows,
+            pumpfun_amm_deposit_event_rows,
+            pumpfun_amm_withdraw_event_rows,
+        );
+    }
+
+    /// 旧接口：带选项的转换，新代码请使用 `convert_into_with_options`
+    ///
+    /// 只输出最初的八种事件，之后新增的事件类型会被丢弃
+    pub fn convert_with_options(
+        tx: &Transaction,
+        options: &ConverterOptions,
+        metrics: &mut ConversionMetrics,
+        pumpfun_trade_event_rows: &mut Vec<PumpfunTradeEventV2>,
+        pumpfun_create_event_rows: &mut Vec<PumpfunCreateEventV2>,
+        pumpfun_migrate_event_rows: &mut Vec<PumpfunMigrateEventV2>,
+        pumpfun_amm_buy_event_rows: &mut Vec<PumpfunAmmBuyEventV2>,
+        pumpfun_amm_sell_event_rows: &mut Vec<PumpfunAmmSellEventV2>,
+        pumpfun_amm_create_pool_event_rows: &mut Vec<PumpfunAmmCreatePoolEventV2>,
+        pumpfun_amm_deposit_event_rows: &mut Vec<PumpfunAmmDepositEventV2>,
+        pumpfun_amm_withdraw_event_rows: &mut Vec<PumpfunAmmWithdrawEventV2>,
+    ) {
+        // 只移动 Vec 本身，不拷贝已有数据
+        let mut events = ConvertedEvents {
+            pumpfun_trade_event: std::mem::take(pumpfun_trade_event_rows),
+            pumpfun_create_event: std::mem::take(pumpfun_create_event_rows),
+            pumpfun_migrate_event: std::mem::take(pumpfun_migrate_event_rows),
+            pumpfun_amm_buy_event: std::mem::take(pumpfun_amm_buy_event_rows),
+            pumpfun_amm_sell_event: std::mem::take(pumpfun_amm_sell_event_rows),
+            pumpfun_amm_create_pool_event: std::mem::take(pumpfun_amm_create_pool_event_rows),
+            pumpfun_amm_deposit_event: std::mem::take(pumpfun_amm_deposit_event_rows),
+            pumpfun_amm_withdraw_event: std::mem::take(pumpfun_amm_withdraw_event_rows),
+            ..Default::default()
+        };
+
+        Self::convert_into_with_options(tx, options, metrics, &mut events);
+
+        *pumpfun_trade_event_rows = events.pumpfun_trade_event;
+        *pumpfun_create_event_rows = events.pumpfun_create_event;
+        *pumpfun_migrate_event_rows = events.pumpfun_migrate_event;
+        *pumpfun_amm_buy_event_rows = events.pumpfun_amm_buy_event;
+        *pumpfun_amm_sell_event_rows = events.pumpfun_amm_sell_event;
+        *pumpfun_amm_create_pool_event_rows = events.pumpfun_amm_create_pool_event;
+        *pumpfun_amm_deposit_event_rows = events.pumpfun_amm_deposit_event;
+        *pumpfun_amm_withdraw_event_rows = events.pumpfun_amm_withdraw_event;
+    }
+
+    /// 带选项的转换，事件追加到 events，统计结果写入 metrics
+    pub fn convert_into_with_options(
+        tx: &Transaction,
+        options: &ConverterOptions,
+        metrics: &mut ConversionMetrics,
+        events: &mut ConvertedEvents,
+    ) {
+        Self::convert_with_registry(tx, &DEFAULT_REGISTRY, options, metrics, events);
+    }
+
+    /// 使用自定义 decoder 注册表转换
+    pub fn convert_with_registry(
+        tx: &Transaction,
+        registry: &ConverterRegistry,
+        options: &ConverterOptions,
+        metrics: &mut ConversionMetrics,
+        events: &mut ConvertedEvents,
+    ) {
+        // 快速路径：没有任何 event 时不建栈；此时 metrics 中的栈统计也不会更新
+        if !registry.has_known_events(tx) {
+            return;
+        }
+
+        let mut stack: VecDeque<&Instruction> = VecDeque::new();
         let mut index = 0;
         for instr in &tx.instructions {
-            if is_event(instr) {
-                // 当前是event，出栈拿到前一个instruction
-                if let Some(prev_instr) = stack.pop() {
-                    // 这里根据event类型和prevInstr组装ClickHouseTable
-                    match instr.r#type.as_str() {
-                        "PumpFunTradeEvent" => {
-                            if let (Some(parsed_event), Some(_parsed_instr)) =
-                                (&instr.parsed, &prev_instr.parsed)
-                            {
-                                if let proto_lib::transaction::solana::instruction::Parsed::PumpfunTradeEvent(trade_event) = parsed_event {
-                                    // TradeEvent可以由Buy或BuyExactSolIn触发，但event本身已包含所有数据
-                                    // 我们不需要区分是哪个指令触发的，因为event数据是一致的
-                                    let event_v2 = PumpfunTradeEventV2 {
-                                        signature: global_bs58().encode_64(&tx.signature),
-                                        slot: tx.slot,
-                                        transaction_index: tx.index as u32,
-                                        instruction_index: index as u32,
-                                        mint: global_bs58().encode_32(&trade_event.mint),
-                                        sol_amount: trade_event.sol_amount,
-                                        token_amount: trade_event.token_amount,
-                                        is_buy: trade_event.is_buy as u8,
-                                        user: global_bs58().encode_32(&trade_event.user),
-                                        timestamp: trade_event.timestamp as u32,
-                                        virtual_sol_reserves: trade_event.virtual_sol_reserves,
-                                        virtual_token_reserves: trade_event.virtual_token_reserves,
-                                        real_sol_reserves: trade_event.real_sol_reserves,
-                                        real_token_reserves: trade_event.real_token_reserves,
-                                        fee_recipient: global_bs58().encode_32(&trade_event.fee_recipient),
-                                        fee_basis_points: trade_event.fee_basis_points,
-                                        fee: trade_event.fee,
-                                        creator: global_bs58().encode_32(&trade_event.creator),
-                                        creator_fee_basis_points: trade_event.creator_fee_basis_points,
-                                        creator_fee: trade_event.creator_fee,
-                                        track_volume: trade_event.track_volume as u8,
-                                        total_unclaimed_tokens: trade_event.total_unclaimed_tokens,
-                                        total_claimed_tokens: trade_event.total_claimed_tokens,
-                                        current_sol_volume: trade_event.current_sol_volume,
-                                        last_update_timestamp: trade_event.last_update_timestamp,
-                                    };
-                                    pumpfun_trade_event_rows.push(event_v2);
-                                }
-                            }
-                        }
-                        "PumpFunCreateEvent" => {
-                            if let (Some(parsed_event), Some(_parsed_instr)) =
-                                (&instr.parsed, &prev_instr.parsed)
-                            {
-                                if let proto_lib::transaction::solana::instruction::Parsed::PumpfunCreateEvent(create_event) = parsed_event {
-                                    let event_v2 = PumpfunCreateEventV2 {
-                                        signature: global_bs58().encode_64(&tx.signature),
-                                        slot: tx.slot,
-                                        transaction_index: tx.index as u32,
-                                        instruction_index: index as u32,
-                                        name: create_event.name.clone(),
-                                        symbol: create_event.symbol.clone(),
-                                        uri: create_event.uri.clone(),
-                                        mint: global_bs58().encode_32(&create_event.mint),
-                                        bonding_curve: global_bs58().encode_32(&create_event.bonding_curve),
-                                        user: global_bs58().encode_32(&create_event.user),
-                                        creator: global_bs58().encode_32(&create_event.creator),
-                                        timestamp: create_event.timestamp as u32,
-                                        virtual_token_reserves: create_event.virtual_token_reserves,
-                                        virtual_sol_reserves: create_event.virtual_sol_reserves,
-                                        real_token_reserves: create_event.real_token_reserves,
-                                        token_total_supply: create_event.token_total_supply,
-                                    };
-                                    pumpfun_create_event_rows.push(event_v2);
-                                }
-                            }
-                        }
-                        "PumpFunMigrateEvent" => {
-                            if let (Some(parsed_event), Some(_parsed_instr)) =
-                                (&instr.parsed, &prev_instr.parsed)
-                            {
-                                if let proto_lib::transaction::solana::instruction::Parsed::PumpfunMigrationEvent(migrate_event) = parsed_event {
-                                    let event_v2 = PumpfunMigrateEventV2 {
-                                        signature: global_bs58().encode_64(&tx.signature),
-                                        slot: tx.slot,
-                                        transaction_index: tx.index as u32,
-                                        instruction_index: index as u32,
-                                        user: global_bs58().encode_32(&migrate_event.user),
-                                        mint: global_bs58().encode_32(&migrate_event.mint),
-                                        mint_amount: migrate_event.mint_amount,
-                                        sol_amount: migrate_event.sol_amount,
-                                        pool_migration_fee: migrate_event.pool_migration_fee,
-                                        bonding_curve: global_bs58().encode_32(&migrate_event.bonding_curve),
-                                        timestamp: migrate_event.timestamp as u32,
-                                        pool: global_bs58().encode_32(&migrate_event.pool),
-                                    };
-                                    pumpfun_migrate_event_rows.push(event_v2);
-                                }
-                            }
-                        }
-                        "PumpFunAmmBuyEvent" => {
-                            if let (Some(parsed_event), Some(parsed_instr)) =
-                                (&instr.parsed, &prev_instr.parsed)
-                            {
-                                // 处理普通Buy指令
-                                if let (
-                                    proto_lib::transaction::solana::instruction::Parsed::PumpfunAmmBuyEvent(buy_event),
-                                    proto_lib::transaction::solana::instruction::Parsed::PumpfunAmmBuy(buy_instr)
-                                ) = (parsed_event, parsed_instr) {
-                                    if let Some(accounts) = &buy_instr.accounts {
-                                        let event_v2 = PumpfunAmmBuyEventV2 {
-                                            signature: global_bs58().encode_64(&tx.signature),
-                                            slot: tx.slot,
-                                            transaction_index: tx.index as u32,
-                                            instruction_index: index as u32,
-                                            base_mint: global_bs58().encode_32(&accounts.base_mint),
-                                            quote_mint: global_bs58().encode_32(&accounts.quote_mint),
-                                            timestamp: buy_event.timestamp as u32,
-                                            base_amount_out: buy_event.base_amount_out,
-                                            max_quote_amount_in: buy_event.max_quote_amount_in,
-                                            user_base_token_reserves: buy_event.user_base_token_reserves,
-                                            user_quote_token_reserves: buy_event.user_quote_token_reserves,
-                                            pool_base_token_reserves: buy_event.pool_base_token_reserves,
-                                            pool_quote_token_reserves: buy_event.pool_quote_token_reserves,
-                                            quote_amount_in: buy_event.quote_amount_in,
-                                            lp_fee_basis_points: buy_event.lp_fee_basis_points,
-                                            lp_fee: buy_event.lp_fee,
-                                            protocol_fee_basis_points: buy_event.protocol_fee_basis_points,
-                                            protocol_fee: buy_event.protocol_fee,
-                                            quote_amount_in_with_lp_fee: buy_event.quote_amount_in_with_lp_fee,
-                                            user_quote_amount_in: buy_event.user_quote_amount_in,
-                                            pool: global_bs58().encode_32(&accounts.pool),
-                                            user: global_bs58().encode_32(&accounts.user),
-                                            user_base_token_account: global_bs58().encode_32(&accounts.user_base_token_account),
-                                            user_quote_token_account: global_bs58().encode_32(&accounts.user_quote_token_account),
-                                            protocol_fee_recipient: global_bs58().encode_32(&accounts.protocol_fee_recipient),
-                                            protocol_fee_recipient_token_account: global_bs58().encode_32(&accounts.protocol_fee_recipient_token_account),
-                                            coin_creator: global_bs58().encode_32(&buy_event.coin_creator),
-                                            coin_creator_fee_basis_points: buy_event.coin_creator_fee_basis_points,
-                                            coin_creator_fee: buy_event.coin_creator_fee,
-                                            track_volume: buy_event.track_volume as u8,
-                                            total_unclaimed_tokens: buy_event.total_unclaimed_tokens,
-                                            total_claimed_tokens: buy_event.total_claimed_tokens,
-                                            current_sol_volume: buy_event.current_sol_volume,
-                                            last_update_timestamp: buy_event.last_update_timestamp,
-                                            is_main_pool: buy_instr.is_main_pool as u8,
-                                        };
-                                        pumpfun_amm_buy_event_rows.push(event_v2);
-                                    }
-                                // 处理BuyExactQuoteIn指令
-                                } else if let (
-                                    proto_lib::transaction::solana::instruction::Parsed::PumpfunAmmBuyEvent(buy_event),
-                                    proto_lib::transaction::solana::instruction::Parsed::PumpfunAmmBuyExactQuoteIn(buy_exact_instr)
-                                ) = (parsed_event, parsed_instr) {
-                                    if let Some(accounts) = &buy_exact_instr.accounts {
-                                        let event_v2 = PumpfunAmmBuyEventV2 {
-                                            signature: global_bs58().encode_64(&tx.signature),
-                                            slot: tx.slot,
-                                            transaction_index: tx.index as u32,
-                                            instruction_index: index as u32,
-                                            base_mint: global_bs58().encode_32(&accounts.base_mint),
-                                            quote_mint: global_bs58().encode_32(&accounts.quote_mint),
-                                            timestamp: buy_event.timestamp as u32,
-                                            base_amount_out: buy_event.base_amount_out,
-                                            max_quote_amount_in: buy_event.max_quote_amount_in,
-                                            user_base_token_reserves: buy_event.user_base_token_reserves,
-                                            user_quote_token_reserves: buy_event.user_quote_token_reserves,
-                                            pool_base_token_reserves: buy_event.pool_base_token_reserves,
-                                            pool_quote_token_reserves: buy_event.pool_quote_token_reserves,
-                                            quote_amount_in: buy_event.quote_amount_in,
-                                            lp_fee_basis_points: buy_event.lp_fee_basis_points,
-                                            lp_fee: buy_event.lp_fee,
-                                            protocol_fee_basis_points: buy_event.protocol_fee_basis_points,
-                                            protocol_fee: buy_event.protocol_fee,
-                                            quote_amount_in_with_lp_fee: buy_event.quote_amount_in_with_lp_fee,
-                                            user_quote_amount_in: buy_event.user_quote_amount_in,
-                                            pool: global_bs58().encode_32(&accounts.pool),
-                                            user: global_bs58().encode_32(&accounts.user),
-                                            user_base_token_account: global_bs58().encode_32(&accounts.user_base_token_account),
-                                            user_quote_token_account: global_bs58().encode_32(&accounts.user_quote_token_account),
-                                            protocol_fee_recipient: global_bs58().encode_32(&accounts.protocol_fee_recipient),
-                                            protocol_fee_recipient_token_account: global_bs58().encode_32(&accounts.protocol_fee_recipient_token_account),
-                                            coin_creator: global_bs58().encode_32(&buy_event.coin_creator),
-                                            coin_creator_fee_basis_points: buy_event.coin_creator_fee_basis_points,
-                                            coin_creator_fee: buy_event.coin_creator_fee,
-                                            track_volume: buy_event.track_volume as u8,
-                                            total_unclaimed_tokens: buy_event.total_unclaimed_tokens,
-                                            total_claimed_tokens: buy_event.total_claimed_tokens,
-                                            current_sol_volume: buy_event.current_sol_volume,
-                                            last_update_timestamp: buy_event.last_update_timestamp,
-                                            is_main_pool: buy_exact_instr.is_main_pool as u8,
-                                        };
-                                        pumpfun_amm_buy_event_rows.push(event_v2);
-                                    }
-                                }
-                            }
-                        }
-                        "PumpFunAmmSellEvent" => {
-                            if let (Some(parsed_event), Some(parsed_instr)) =
-                                (&instr.parsed, &prev_instr.parsed)
-                            {
-                                if let (
-                                    proto_lib::transaction::solana::instruction::Parsed::PumpfunAmmSellEvent(sell_event),
-                                    proto_lib::transaction::solana::instruction::Parsed::PumpfunAmmSell(sell_instr)
-                                ) = (parsed_event, parsed_instr) {
-                                    if let Some(accounts) = &sell_instr.accounts {
-                                        let event_v2 = PumpfunAmmSellEventV2 {
-                                            signature: global_bs58().encode_64(&tx.signature),
-                                            slot: tx.slot,
-                                            transaction_index: tx.index as u32,
-                                            instruction_index: index as u32,
-                                            base_mint: global_bs58().encode_32(&accounts.base_mint),
-                                            quote_mint: global_bs58().encode_32(&accounts.quote_mint),
-                                            timestamp: sell_event.timestamp as u32,
-                                            base_amount_in: sell_event.base_amount_in,
-                                            min_quote_amount_out: sell_event.min_quote_amount_out,
-                                            user_base_token_reserves: sell_event.user_base_token_reserves,
-                                            user_quote_token_reserves: sell_event.user_quote_token_reserves,
-                                            pool_base_token_reserves: sell_event.pool_base_token_reserves,
-                                            pool_quote_token_reserves: sell_event.pool_quote_token_reserves,
-                                            quote_amount_out: sell_event.quote_amount_out,
-                                            lp_fee_basis_points: sell_event.lp_fee_basis_points,
-                                            lp_fee: sell_event.lp_fee,
-                                            protocol_fee_basis_points: sell_event.protocol_fee_basis_points,
-                                            protocol_fee: sell_event.protocol_fee,
-                                            quote_amount_out_without_lp_fee: sell_event.quote_amount_out_without_lp_fee,
-                                            user_quote_amount_out: sell_event.user_quote_amount_out,
-                                            pool: global_bs58().encode_32(&accounts.pool),
-                                            user: global_bs58().encode_32(&accounts.user),
-                                            user_base_token_account: global_bs58().encode_32(&accounts.user_base_token_account),
-                                            user_quote_token_account: global_bs58().encode_32(&accounts.user_quote_token_account),
-                                            protocol_fee_recipient: global_bs58().encode_32(&accounts.protocol_fee_recipient),
-                                            protocol_fee_recipient_token_account: global_bs58().encode_32(&accounts.protocol_fee_recipient_token_account),
-                                            coin_creator: global_bs58().encode_32(&sell_event.coin_creator),
-                                            coin_creator_fee_basis_points: sell_event.coin_creator_fee_basis_points,
-                                            coin_creator_fee: sell_event.coin_creator_fee,
-                                            is_main_pool: sell_instr.is_main_pool as u8,
-                                        };
-                                        pumpfun_amm_sell_event_rows.push(event_v2);
-                                    }
-                                }
-                            }
-                        }
-                        "PumpFunAmmDepositEvent" => {
-                            if let (Some(parsed_event), Some(parsed_instr)) =
-                                (&instr.parsed, &prev_instr.parsed)
-                            {
-                                if let (
-                                    proto_lib::transaction::solana::instruction::Parsed::PumpfunAmmDepositEvent(deposit_event),
-                                    proto_lib::transaction::solana::instruction::Parsed::PumpfunAmmDeposit(deposit_instr)
-                                ) = (parsed_event, parsed_instr) {
-                                    if let Some(accounts) = &deposit_instr.accounts {
-                                        let event_v2 = PumpfunAmmDepositEventV2 {
-                                            signature: global_bs58().encode_64(&tx.signature),
-                                            slot: tx.slot,
-                                            transaction_index: tx.index as u32,
-                                            instruction_index: index as u32,
-                                            base_mint: global_bs58().encode_32(&accounts.base_mint),
-                                            quote_mint: global_bs58().encode_32(&accounts.quote_mint),
-                                            timestamp: deposit_event.timestamp as u32,
-                                            lp_token_amount_out: deposit_event.lp_token_amount_out,
-                                            max_base_amount_in: deposit_event.max_base_amount_in,
-                                            max_quote_amount_in: deposit_event.max_quote_amount_in,
-                                            user_base_token_reserves: deposit_event.user_base_token_reserves,
-                                            user_quote_token_reserves: deposit_event.user_quote_token_reserves,
-                                            pool_base_token_reserves: deposit_event.pool_base_token_reserves,
-                                            pool_quote_token_reserves: deposit_event.pool_quote_token_reserves,
-                                            base_amount_in: deposit_event.base_amount_in,
-                                            quote_amount_in: deposit_event.quote_amount_in,
-                                            lp_mint_supply: deposit_event.lp_mint_supply,
-                                            pool: global_bs58().encode_32(&accounts.pool),
-                                            user: global_bs58().encode_32(&accounts.user),
-                                            user_base_token_account: global_bs58().encode_32(&accounts.user_base_token_account),
-                                            user_quote_token_account: global_bs58().encode_32(&accounts.user_quote_token_account),
-                                            user_pool_token_account: global_bs58().encode_32(&accounts.user_pool_token_account),
-                                            is_main_pool: deposit_instr.is_main_pool as u8,
-                                        };
-                                        pumpfun_amm_deposit_event_rows.push(event_v2);
-                                    }
-                                }
-                            }
-                        }
-                        "PumpFunAmmWithdrawEvent" => {
-                            if let (Some(parsed_event), Some(parsed_instr)) =
-                                (&instr.parsed, &prev_instr.parsed)
-                            {
-                                if let (
-                                    proto_lib::transaction::solana::instruction::Parsed::PumpfunAmmWithdrawEvent(withdraw_event),
-                                    proto_lib::transaction::solana::instruction::Parsed::PumpfunAmmWithdraw(withdraw_instr)
-                                ) = (parsed_event, parsed_instr) {
-                                    if let Some(accounts) = &withdraw_instr.accounts {
-                                        let event_v2 = PumpfunAmmWithdrawEventV2 {
-                                            signature: global_bs58().encode_64(&tx.signature),
-                                            slot: tx.slot,
-                                            transaction_index: tx.index as u32,
-                                            instruction_index: index as u32,
-                                            base_mint: global_bs58().encode_32(&accounts.base_mint),
-                                            quote_mint: global_bs58().encode_32(&accounts.quote_mint),
-                                            timestamp: withdraw_event.timestamp as u32,
-                                            lp_token_amount_in: withdraw_event.lp_token_amount_in,
-                                            min_base_amount_out: withdraw_event.min_base_amount_out,
-                                            min_quote_amount_out: withdraw_event.min_quote_amount_out,
-                                            user_base_token_reserves: withdraw_event.user_base_token_reserves,
-                                            user_quote_token_reserves: withdraw_event.user_quote_token_reserves,
-                                            pool_base_token_reserves: withdraw_event.pool_base_token_reserves,
-                                            pool_quote_token_reserves: withdraw_event.pool_quote_token_reserves,
-                                            base_amount_out: withdraw_event.base_amount_out,
-                                            quote_amount_out: withdraw_event.quote_amount_out,
-                                            lp_mint_supply: withdraw_event.lp_mint_supply,
-                                            pool: global_bs58().encode_32(&accounts.pool),
-                                            user: global_bs58().encode_32(&accounts.user),
-                                            user_base_token_account: global_bs58().encode_32(&accounts.user_base_token_account),
-                                            user_quote_token_account: global_bs58().encode_32(&accounts.user_quote_token_account),
-                                            user_pool_token_account: global_bs58().encode_32(&accounts.user_pool_token_account),
-                                            is_main_pool: withdraw_instr.is_main_pool as u8,
-                                        };
-                                        pumpfun_amm_withdraw_event_rows.push(event_v2);
-                                    }
-                                }
-                            }
+            if let Some(decoder) = registry.decoder_for(&instr.r#type) {
+                // 当前是event，按类型在栈中查找其父指令（见 pop_parent 的匹配规则）
+                match pop_parent(&mut stack, decoder.parent_prefixes(&instr.r#type)) {
+                    Some(prev_instr) => {
+                        if let Some(row) = decoder.decode(instr, prev_instr, tx, index) {
+                            events.push(row);
+                            record_byte_size(options, metrics, prev_instr, instr, index);
                         }
-                        "PumpFunAmmCreatePoolEvent" => {
-                            if let (Some(parsed_event), Some(parsed_instr)) =
-                                (&instr.parsed, &prev_instr.parsed)
-                            {
-                                if let (
-                                    proto_lib::transaction::solana::instruction::Parsed::PumpfunAmmCreatePoolEvent(create_event),
-                                    proto_lib::transaction::solana::instruction::Parsed::PumpfunAmmCreatePool(create_instr)
-                                ) = (parsed_event, parsed_instr) {
-                                    if let Some(accounts) = &create_instr.accounts {
-                                        let event_v2 = PumpfunAmmCreatePoolEventV2 {
-                                            signature: global_bs58().encode_64(&tx.signature),
-                                            slot: tx.slot,
-                                            transaction_index: tx.index as u32,
-                                            instruction_index: index as u32,
-                                            timestamp: create_event.timestamp as u32,
-                                            index: create_event.index,
-                                            creator: global_bs58().encode_32(&accounts.creator),
-                                            base_mint: global_bs58().encode_32(&accounts.base_mint),
-                                            quote_mint: global_bs58().encode_32(&accounts.quote_mint),
-                                            base_mint_decimals: create_event.base_mint_decimals,
-                                            quote_mint_decimals: create_event.quote_mint_decimals,
-                                            base_amount_in: create_event.base_amount_in,
-                                            quote_amount_in: create_event.quote_amount_in,
-                                            pool_base_amount: create_event.pool_base_amount,
-                                            pool_quote_amount: create_event.pool_quote_amount,
-                                            minimum_liquidity: create_event.minimum_liquidity,
-                                            initial_liquidity: create_event.initial_liquidity,
-                                            lp_token_amount_out: create_event.lp_token_amount_out,
-                                            pool_bump: create_event.pool_bump,
-                                            pool: global_bs58().encode_32(&accounts.pool),
-                                            lp_mint: global_bs58().encode_32(&accounts.lp_mint),
-                                            user_base_token_account: global_bs58().encode_32(&accounts.user_base_token_account),
-                                            user_quote_token_account: global_bs58().encode_32(&accounts.user_quote_token_account),
-                                            coin_creator: global_bs58().encode_32(&create_event.coin_creator),
-                                            is_main_pool: create_instr.is_main_pool as u8,
-                                        };
-                                        pumpfun_amm_create_pool_event_rows.push(event_v2);
-                                    }
-                                }
-                            }
-                        }
-                        // 其它 PumpFunAmmXXXEvent 可用同样方式补全
-                        _ => {}
                     }
+                    None => metrics.unmatched_events += 1,
                 }
             } else {
-                // 不是event，入栈
-                stack.push(instr);
+                // 不是event，入栈；超出深度上限时丢弃最早的指令
+                if stack.len() >= options.max_stack_depth.max(1) {
+                    stack.pop_front();
+                    metrics.dropped_instructions += 1;
+                }
+                stack.push_back(instr);
+                metrics.peak_stack_depth = metrics.peak_stack_depth.max(stack.len());
             }
             index += 1;
         }
     }
 }
 
-// 判断是否为event类型
-fn is_event(instr: &proto_lib::transaction::solana::Instruction) -> bool {
-    matches!(
-        instr.r#type.as_str(),
-        "PumpFunTradeEvent"
-            | "PumpFunCreateEvent"
-            | "PumpFunMigrateEvent"
-            | "PumpFunAmmBuyEvent"
-            | "PumpFunAmmSellEvent"
-            | "PumpFunAmmDepositEvent"
-            | "PumpFunAmmWithdrawEvent"
-            | "PumpFunAmmCreatePoolEvent"
-    )
+/// 为 event 查找父指令
+///
+/// 匹配规则：event 由其父指令在执行末尾通过 self-CPI 发出，展开后的指令序列中
+/// 父指令与 event 之间可能夹着父指令自己的 CPI 内部指令（转账、嵌套调用等），
+/// 嵌套调用的 event 总是先于外层 event 出现。因此从栈顶向下找最近的、
+/// 类型能发出该 event 的未匹配指令作为父指令，并把它连同其上方的内部指令一起出栈。
+/// 找不到时栈保持不变，event 被跳过。前缀为空时直接取栈顶指令。
+fn pop_parent<'a>(
+    stack: &mut VecDeque<&'a Instruction>,
+    prefixes: &[&str],
+) -> Option<&'a Instruction> {
+    if prefixes.is_empty() {
+        return stack.pop_back();
+    }
+    let position = stack
+        .iter()
+        .rposition(|instr| prefixes.iter().any(|prefix| instr.r#type.starts_with(prefix)))?;
+    let parent = stack[position];
+    stack.truncate(position);
+    Some(parent)
+}
+
+// 记录匹配的 instruction+event 的编码字节数
+fn record_byte_size(
+    options: &ConverterOptions,
+    metrics: &mut ConversionMetrics,
+    prev_instr: &Instruction,
+    instr: &Instruction,
+    index: usize,
+) {
+    if !options.record_byte_sizes {
+        return;
+    }
+    metrics.event_byte_sizes.push(EventByteSize {
+        event_type: instr.r#type.clone(),
+        instruction_index: index as u32,
+        instruction_bytes: prev_instr.encoded_len(),
+        event_bytes: instr.encoded_len(),
+    });
+}
+
+/// 判断是否为内置 decoder 登记的 event 类型
+pub fn is_event(instr: &Instruction) -> bool {
+    DEFAULT_REGISTRY.is_event(&instr.r#type)
 }
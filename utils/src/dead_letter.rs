@@ -0,0 +1,98 @@
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// 无法解码的 NATS 消息的死信处理
+///
+/// 原始字节写入死信目录（配置时）后继续消费；一个统计窗口内的解码失败超过
+/// `max_errors` 时 `record` 返回 true，由调用方停止订阅并正常关闭
+pub struct DeadLetter {
+    dir: Option<PathBuf>,
+    max_errors: u64, // 0 表示不限
+    window: Duration,
+    state: Mutex<WindowState>,
+    total: AtomicU64,
+}
+
+struct WindowState {
+    started_at: Instant,
+    errors: u64,
+}
+
+impl DeadLetter {
+    pub fn new(dir: Option<PathBuf>, max_errors: u64, window: Duration) -> Self {
+        Self::starting_at(dir, max_errors, window, Instant::now())
+    }
+
+    /// 指定第一个窗口的起始时间（测试用）
+    pub fn starting_at(dir: Option<PathBuf>, max_errors: u64, window: Duration, now: Instant) -> Self {
+        Self {
+            dir,
+            max_errors,
+            window,
+            state: Mutex::new(WindowState {
+                started_at: now,
+                errors: 0,
+            }),
+            total: AtomicU64::new(0),
+        }
+    }
+
+    /// 记录一条无法解码的消息，返回当前窗口内的失败是否已超过上限
+    pub fn record(&self, payload: &[u8], error: &str) -> bool {
+        self.record_at(payload, error, Instant::now())
+    }
+
+    pub fn record_at(&self, payload: &[u8], error: &str, now: Instant) -> bool {
+        let seq = self.total.fetch_add(1, Ordering::Relaxed) + 1;
+        match self.write(payload, seq) {
+            Ok(Some(path)) => tracing::warn!(
+                payload_len = payload.len(),
+                path = %path.display(),
+                "⚠️  Failed to decode message, saved to dead-letter dir: {}",
+                error
+            ),
+            Ok(None) => tracing::warn!(
+                payload_len = payload.len(),
+                "⚠️  Failed to decode message, dropped: {}",
+                error
+            ),
+            Err(e) => tracing::error!(
+                payload_len = payload.len(),
+                "❌ Failed to decode message ({}) and to write it to the dead-letter dir: {}",
+                error,
+                e
+            ),
+        }
+
+        let mut state = self.state.lock().unwrap();
+        if now.duration_since(state.started_at) >= self.window {
+            state.started_at = now;
+            state.errors = 0;
+        }
+        state.errors += 1;
+        self.max_errors > 0 && state.errors > self.max_errors
+    }
+
+    /// 启动以来解码失败的消息总数
+    pub fn total(&self) -> u64 {
+        self.total.load(Ordering::Relaxed)
+    }
+
+    /// 写入 `{dir}/{unix毫秒}_{序号}.bin`，未配置目录时返回 None
+    fn write(&self, payload: &[u8], seq: u64) -> std::io::Result<Option<PathBuf>> {
+        let Some(dir) = &self.dir else {
+            return Ok(None);
+        };
+        std::fs::create_dir_all(dir)?;
+
+        let millis = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis();
+        let path = dir.join(format!("{}_{}.bin", millis, seq));
+        std::fs::write(&path, payload)?;
+        Ok(Some(path))
+    }
+}
@@ -0,0 +1,80 @@
+use serde::de::DeserializeOwned;
+
+/// 按环境变量展开字符串中的 `${NAME}`
+///
+/// 密码、密钥路径等可以放在环境变量里，不必写进提交的配置文件。`$${` 表示字面的 `${`，
+/// 其他 `$` 原样保留；引用的变量未设置时报错，而不是展开成空字符串
+pub fn expand_env_vars(input: &str) -> Result<String, String> {
+    expand_with(input, |name| std::env::var(name).ok())
+}
+
+/// 用给定的查找函数展开 `${NAME}`，`lookup` 返回 None 表示变量未设置
+pub fn expand_with(input: &str, lookup: impl Fn(&str) -> Option<String>) -> Result<String, String> {
+    let mut output = String::with_capacity(input.len());
+    let mut rest = input;
+    while let Some(start) = rest.find('$') {
+        output.push_str(&rest[..start]);
+        let after = &rest[start..];
+        if let Some(escaped) = after.strip_prefix("$${") {
+            output.push_str("${");
+            rest = escaped;
+        } else if let Some(reference) = after.strip_prefix("${") {
+            let end = reference
+                .find('}')
+                .ok_or_else(|| format!("Unterminated '${{' in '{}'", input))?;
+            let name = &reference[..end];
+            if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+                return Err(format!("Invalid environment variable name '{}' in '{}'", name, input));
+            }
+            let value = lookup(name).ok_or_else(|| format!("Environment variable '{}' is not set", name))?;
+            output.push_str(&value);
+            rest = &reference[end + 1..];
+        } else {
+            output.push('$');
+            rest = &after[1..];
+        }
+    }
+    output.push_str(rest);
+    Ok(output)
+}
+
+/// 递归展开 TOML 中所有字符串值（不含键名），出错时带上值所在的路径（如 `remote_server.private_key_path`）
+pub fn expand_toml_value(value: &mut toml::Value) -> Result<(), String> {
+    expand_toml_at(value, "")
+}
+
+fn expand_toml_at(value: &mut toml::Value, path: &str) -> Result<(), String> {
+    match value {
+        toml::Value::String(text) => {
+            *text = expand_env_vars(text).map_err(|error| format!("Config value '{}': {}", path, error))?;
+        }
+        toml::Value::Array(items) => {
+            for (index, item) in items.iter_mut().enumerate() {
+                expand_toml_at(item, &format!("{}[{}]", path, index))?;
+            }
+        }
+        toml::Value::Table(table) => {
+            for (key, item) in table.iter_mut() {
+                let path = if path.is_empty() { key.clone() } else { format!("{}.{}", path, key) };
+                expand_toml_at(item, &path)?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// 解析 TOML 文本、展开环境变量并反序列化为配置结构体
+pub fn parse_toml<T: DeserializeOwned>(content: &str) -> Result<T, Box<dyn std::error::Error>> {
+    let mut value: toml::Value = toml::from_str(content)?;
+    expand_toml_value(&mut value)?;
+    Ok(value.try_into()?)
+}
+
+/// 读取 TOML 配置文件并展开环境变量
+pub fn read_toml_value(path: &str) -> Result<toml::Value, Box<dyn std::error::Error>> {
+    let content = std::fs::read_to_string(path)?;
+    let mut value: toml::Value = toml::from_str(&content)?;
+    expand_toml_value(&mut value)?;
+    Ok(value)
+}
@@ -0,0 +1,355 @@
+use arrow::datatypes::DataType;
+use clickhouse::{Client, Row};
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use serde_arrow::schema::TracingOptions;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+
+use crate::clickhouse_events::*;
+use crate::schema::split_table;
+
+/// 期望的列定义（由事件结构体推导）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColumnSpec {
+    pub name: String,
+    pub clickhouse_type: String,
+}
+
+/// 单个事件类型与目标表的对应关系
+#[derive(Debug, Clone)]
+pub struct EventSchema {
+    pub event_type: String,
+    pub table: String,
+    pub columns: Vec<ColumnSpec>,
+}
+
+impl EventSchema {
+    /// 从事件结构体推导期望的表结构（字段顺序即列顺序）
+    pub fn derive<T: DeserializeOwned>(event_type: &str, table: &str) -> Result<Self, String> {
+        let fields = event_fields::<T>(TracingOptions::default())
+            .map_err(|e| format!("Failed to trace schema of {}: {}", event_type, e))?;
+
+        let columns = fields
+            .iter()
+            .map(|field| {
+                Ok(ColumnSpec {
+                    name: field.name().clone(),
+                    clickhouse_type: clickhouse_type(field.data_type())
+                        .ok_or_else(|| {
+                            format!(
+                                "Unsupported type {:?} for column {}.{}",
+                                field.data_type(),
+                                event_type,
+                                field.name()
+                            )
+                        })?
+                        .to_string(),
+                })
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+
+        Ok(Self {
+            event_type: event_type.to_string(),
+            table: table.to_string(),
+            columns,
+        })
+    }
+}
+
+/// Arrow 类型 -> ClickHouse 类型
+pub fn clickhouse_type(data_type: &DataType) -> Option<&'static str> {
+    match data_type {
+        DataType::UInt8 => Some("UInt8"),
+        DataType::UInt16 => Some("UInt16"),
+        DataType::UInt32 => Some("UInt32"),
+        DataType::UInt64 => Some("UInt64"),
+        DataType::Int8 => Some("Int8"),
+        DataType::Int16 => Some("Int16"),
+        DataType::Int32 => Some("Int32"),
+        DataType::Int64 => Some("Int64"),
+        DataType::Float32 => Some("Float32"),
+        DataType::Float64 => Some("Float64"),
+        DataType::Boolean => Some("Bool"),
+        DataType::Utf8 | DataType::LargeUtf8 => Some("String"),
+        // u128 列（见 `clickhouse_events::u128_le_bytes`）
+        DataType::FixedSizeBinary(16) => Some("UInt128"),
+        _ => None,
+    }
+}
+
+/// 事件注册表：事件类型 -> 目标表及期望结构
+#[derive(Debug, Clone, Default)]
+pub struct EventRegistry {
+    schemas: Vec<EventSchema>,
+}
+
+impl EventRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 注册一个事件类型
+    pub fn register<T: DeserializeOwned>(
+        &mut self,
+        event_type: &str,
+        table: &str,
+    ) -> Result<&mut Self, String> {
+        self.schemas.push(EventSchema::derive::<T>(event_type, table)?);
+        Ok(self)
+    }
+
+    /// 所有 V2 事件，使用默认表名
+    pub fn v2() -> Result<Self, String> {
+        let mut registry = Self::new();
+        registry
+            .register::<PumpfunTradeEventV2>("PumpfunTradeEventV2", "pumpfun_trade_event_v2")?
+            .register::<PumpfunCreateEventV2>("PumpfunCreateEventV2", "pumpfun_create_event_v2")?
+            .register::<PumpfunMigrateEventV2>("PumpfunMigrateEventV2", "pumpfun_migrate_event_v2")?
+            .register::<PumpfunAmmBuyEventV2>("PumpfunAmmBuyEventV2", "pumpfun_amm_buy_event_v2")?
+            .register::<PumpfunAmmSellEventV2>("PumpfunAmmSellEventV2", "pumpfun_amm_sell_event_v2")?
+            .register::<PumpfunAmmCreatePoolEventV2>(
+                "PumpfunAmmCreatePoolEventV2",
+                "pumpfun_amm_create_pool_event_v2",
+            )?
+            .register::<PumpfunAmmDepositEventV2>(
+                "PumpfunAmmDepositEventV2",
+                "pumpfun_amm_deposit_event_v2",
+            )?
+            .register::<PumpfunAmmWithdrawEventV2>(
+                "PumpfunAmmWithdrawEventV2",
+                "pumpfun_amm_withdraw_event_v2",
+            )?
+            .register::<PumpfunAmmCollectCoinCreatorFeeEventV2>(
+                "PumpfunAmmCollectCoinCreatorFeeEventV2",
+                "pumpfun_amm_collect_coin_creator_fee_event_v2",
+            )?
+            .register::<PumpfunAmmDisableEventV2>(
+                "PumpfunAmmDisableEventV2",
+                "pumpfun_amm_disable_event_v2",
+            )?
+            .register::<PumpfunAmmSetParamsEventV2>(
+                "PumpfunAmmSetParamsEventV2",
+                "pumpfun_amm_set_params_event_v2",
+            )?;
+        Ok(registry)
+    }
+
+    pub fn schemas(&self) -> &[EventSchema] {
+        &self.schemas
+    }
+}
+
+/// system.columns 中的一列
+#[derive(Debug, Clone, Row, Deserialize)]
+pub struct LiveColumn {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub column_type: String,
+    /// DEFAULT / MATERIALIZED / ALIAS，普通列为空
+    pub default_kind: String,
+}
+
+/// 单个不一致项
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SchemaMismatch {
+    MissingTable {
+        table: String,
+    },
+    MissingColumn {
+        table: String,
+        column: String,
+    },
+    TypeMismatch {
+        table: String,
+        column: String,
+        expected: String,
+        found: String,
+    },
+    OrderMismatch {
+        table: String,
+        column: String,
+        expected_position: usize,
+        found_position: usize,
+    },
+    UnexpectedColumn {
+        table: String,
+        column: String,
+        column_type: String,
+    },
+}
+
+impl fmt::Display for SchemaMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SchemaMismatch::MissingTable { table } => write!(f, "{}: table not found", table),
+            SchemaMismatch::MissingColumn { table, column } => {
+                write!(f, "{}.{}: column missing in ClickHouse", table, column)
+            }
+            SchemaMismatch::TypeMismatch {
+                table,
+                column,
+                expected,
+                found,
+            } => write!(f, "{}.{}: expected type {}, found {}", table, column, expected, found),
+            SchemaMismatch::OrderMismatch {
+                table,
+                column,
+                expected_position,
+                found_position,
+            } => write!(
+                f,
+                "{}.{}: expected at position {}, found at {}",
+                table, column, expected_position, found_position
+            ),
+            SchemaMismatch::UnexpectedColumn {
+                table,
+                column,
+                column_type,
+            } => write!(
+                f,
+                "{}.{}: unexpected column {} without default",
+                table, column, column_type
+            ),
+        }
+    }
+}
+
+/// 校验报告，包含全部不一致项
+#[derive(Debug, Clone)]
+pub struct SchemaReport {
+    pub mismatches: Vec<SchemaMismatch>,
+}
+
+impl fmt::Display for SchemaReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ClickHouse schema mismatch ({} issues)", self.mismatches.len())?;
+        for mismatch in &self.mismatches {
+            write!(f, "\n  - {}", mismatch)?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for SchemaReport {}
+
+/// 归一化线上类型：LowCardinality 对写入透明，DateTime/Bool 在 RowBinary 中分别是 UInt32/UInt8
+pub fn normalize_type(column_type: &str) -> &str {
+    let inner = column_type
+        .strip_prefix("LowCardinality(")
+        .and_then(|t| t.strip_suffix(')'))
+        .unwrap_or(column_type);
+    match inner {
+        "DateTime" => "UInt32",
+        "Bool" => "UInt8",
+        other => other,
+    }
+}
+
+/// 对比注册表与给定的线上列（按表名索引，列按 position 排序）
+pub fn verify_against(
+    registry: &EventRegistry,
+    live: &HashMap<String, Vec<LiveColumn>>,
+) -> Result<(), SchemaReport> {
+    let mut mismatches = Vec::new();
+
+    for schema in registry.schemas() {
+        let table = &schema.table;
+        let live_columns = match live.get(table) {
+            Some(columns) if !columns.is_empty() => columns,
+            _ => {
+                mismatches.push(SchemaMismatch::MissingTable {
+                    table: table.clone(),
+                });
+                continue;
+            }
+        };
+
+        // 1. 列是否存在、类型是否一致
+        for expected in &schema.columns {
+            match live_columns.iter().find(|c| c.name == expected.name) {
+                None => mismatches.push(SchemaMismatch::MissingColumn {
+                    table: table.clone(),
+                    column: expected.name.clone(),
+                }),
+                Some(column) => {
+                    if normalize_type(&column.column_type) != normalize_type(&expected.clickhouse_type) {
+                        mismatches.push(SchemaMismatch::TypeMismatch {
+                            table: table.clone(),
+                            column: expected.name.clone(),
+                            expected: expected.clickhouse_type.clone(),
+                            found: column.column_type.clone(),
+                        });
+                    }
+                }
+            }
+        }
+
+        // 2. 多出来的列（有默认值的列写入时可以省略，不算错误）
+        for column in live_columns {
+            if column.default_kind.is_empty()
+                && !schema.columns.iter().any(|c| c.name == column.name)
+            {
+                mismatches.push(SchemaMismatch::UnexpectedColumn {
+                    table: table.clone(),
+                    column: column.name.clone(),
+                    column_type: column.column_type.clone(),
+                });
+            }
+        }
+
+        // 3. 共有列的相对顺序
+        let expected_order: Vec<&str> = schema
+            .columns
+            .iter()
+            .map(|c| c.name.as_str())
+            .filter(|name| live_columns.iter().any(|c| c.name == *name))
+            .collect();
+        let live_order: Vec<&str> = live_columns
+            .iter()
+            .map(|c| c.name.as_str())
+            .filter(|name| expected_order.contains(name))
+            .collect();
+        for (position, name) in expected_order.iter().enumerate() {
+            if live_order[position] != *name {
+                let found_position = live_order.iter().position(|n| n == name).unwrap_or(position);
+                mismatches.push(SchemaMismatch::OrderMismatch {
+                    table: table.clone(),
+                    column: name.to_string(),
+                    expected_position: position,
+                    found_position,
+                });
+            }
+        }
+    }
+
+    if mismatches.is_empty() {
+        Ok(())
+    } else {
+        Err(SchemaReport { mismatches })
+    }
+}
+
+/// 启动前校验：读取每个注册表的 system.columns 并与期望结构对比
+///
+/// 不一致时返回 `SchemaReport`（可 downcast），查询失败返回 ClickHouse 错误
+pub async fn verify_schema(client: &Client, registry: &EventRegistry) -> Result<(), Box<dyn Error>> {
+    let mut live = HashMap::new();
+    for schema in registry.schemas() {
+        let (database, name) = split_table(&schema.table);
+        let columns = client
+            .query(
+                "SELECT name, type, default_kind FROM system.columns \
+                 WHERE database = coalesce(?, currentDatabase()) AND table = ? ORDER BY position",
+            )
+            .bind(database)
+            .bind(name)
+            .fetch_all::<LiveColumn>()
+            .await?;
+        live.insert(schema.table.clone(), columns);
+    }
+
+    verify_against(registry, &live)?;
+    Ok(())
+}
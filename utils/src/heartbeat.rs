@@ -0,0 +1,63 @@
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tokio::time::{sleep_until, Instant};
+
+/// 心跳监视器
+///
+/// 记录最近一次真实信号的发送时间，空闲超过 `interval` 时调用 emit 发送心跳，
+/// 让下游能区分“转发器正常但没有流量”和“转发器已挂”
+#[derive(Clone)]
+pub struct Heartbeat {
+    interval: Duration,
+    last_activity: Arc<Mutex<Instant>>,
+}
+
+impl Heartbeat {
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            last_activity: Arc::new(Mutex::new(Instant::now())),
+        }
+    }
+
+    /// 真实信号发送成功后调用，重置空闲计时
+    pub fn mark_activity(&self) {
+        *self.last_activity.lock().unwrap() = Instant::now();
+    }
+
+    fn last_activity(&self) -> Instant {
+        *self.last_activity.lock().unwrap()
+    }
+
+    /// 启动心跳任务，emit 失败只打印错误，不影响主流程
+    pub fn spawn<F, Fut>(&self, emit: F) -> JoinHandle<()>
+    where
+        F: Fn() -> Fut + Send + 'static,
+        Fut: Future<Output = Result<(), String>> + Send,
+    {
+        let heartbeat = self.clone();
+
+        tokio::spawn(async move {
+            loop {
+                let deadline = heartbeat.last_activity() + heartbeat.interval;
+                sleep_until(deadline).await;
+
+                // 等待期间有真实信号，重新计时
+                if heartbeat.last_activity() + heartbeat.interval > Instant::now() {
+                    continue;
+                }
+
+                match emit().await {
+                    Ok(()) => heartbeat.mark_activity(),
+                    Err(e) => {
+                        eprintln!("⚠️  Failed to emit heartbeat: {}", e);
+                        // 失败后同样等待一个间隔再重试，避免刷屏
+                        heartbeat.mark_activity();
+                    }
+                }
+            }
+        })
+    }
+}
@@ -1,4 +1,18 @@
+pub mod base58;
 pub mod clickhouse_client;
 pub mod clickhouse_events;
 pub mod convert_transaction;
-pub mod slot_meta;
\ No newline at end of file
+pub mod dead_letter;
+pub mod env_expand;
+pub mod event_registry;
+pub mod heartbeat;
+#[cfg(feature = "prometheus")]
+pub mod metrics_server;
+pub mod pumpfun_decoder;
+pub mod reconnect;
+pub mod schema;
+pub mod slot_meta;
+pub mod task_pool;
+pub mod timestamp;
+pub mod trace;
+pub mod url;
@@ -0,0 +1,60 @@
+use prometheus::{Encoder, TextEncoder};
+use std::io;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinHandle;
+
+/// 启动 Prometheus 指标端点，任意路径的 GET 都返回默认 registry 中的全部指标
+///
+/// 监听失败（地址非法、端口被占用）时直接返回错误，之后的连接错误只记录日志
+pub async fn serve_metrics(addr: &str) -> io::Result<JoinHandle<()>> {
+    let listener = TcpListener::bind(addr).await?;
+    println!("📊 Prometheus metrics listening on http://{}/metrics", listener.local_addr()?);
+
+    Ok(tokio::spawn(async move {
+        loop {
+            match listener.accept().await {
+                Ok((stream, _)) => {
+                    tokio::spawn(async move {
+                        if let Err(e) = handle_connection(stream).await {
+                            tracing::warn!("Metrics connection error: {}", e);
+                        }
+                    });
+                }
+                Err(e) => tracing::warn!("Metrics listener accept failed: {}", e),
+            }
+        }
+    }))
+}
+
+/// 当前所有指标的文本格式（exposition format）
+pub fn render_metrics() -> String {
+    let mut buffer = Vec::new();
+    if let Err(e) = TextEncoder::new().encode(&prometheus::gather(), &mut buffer) {
+        tracing::warn!("Failed to encode metrics: {}", e);
+    }
+    String::from_utf8(buffer).unwrap_or_default()
+}
+
+async fn handle_connection(mut stream: TcpStream) -> io::Result<()> {
+    // 只需读完请求头，内容不关心
+    let mut request = Vec::new();
+    let mut chunk = [0u8; 1024];
+    while !request.windows(4).any(|window| window == b"\r\n\r\n") && request.len() < 8192 {
+        let read = stream.read(&mut chunk).await?;
+        if read == 0 {
+            break;
+        }
+        request.extend_from_slice(&chunk[..read]);
+    }
+
+    let body = render_metrics();
+    let response = format!(
+        "HTTP/1.1 200 OK\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        TextEncoder::new().format_type(),
+        body.len(),
+        body
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await
+}
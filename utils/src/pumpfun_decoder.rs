@@ -0,0 +1,634 @@
+use crate::base58::global_bs58;
+use proto_lib::transaction::solana::{Instruction, Transaction};
+
+use crate::clickhouse_events::{
+    PumpfunAmmBuyEventV2, PumpfunAmmCollectCoinCreatorFeeEventV2, PumpfunAmmCreatePoolEventV2,
+    PumpfunAmmDepositEventV2, PumpfunAmmDisableEventV2, PumpfunAmmSellEventV2,
+    PumpfunAmmSetParamsEventV2, PumpfunAmmWithdrawEventV2, PumpfunCreateEventV2,
+    PumpfunMigrateEventV2, PumpfunTradeEventV2,
+};
+use crate::convert_transaction::{DecodedRow, ProgramDecoder};
+use crate::timestamp::{clamp_unix_u32, to_unix_u32};
+
+/// 内置的 PumpFun + PumpFun AMM 解码器
+pub struct PumpfunDecoder {
+    event_types: Vec<&'static str>,
+}
+
+impl PumpfunDecoder {
+    pub fn new() -> Self {
+        Self {
+            event_types: EVENT_SPECS.iter().map(|spec| spec.event_type).collect(),
+        }
+    }
+}
+
+impl Default for PumpfunDecoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ProgramDecoder for PumpfunDecoder {
+    fn event_types(&self) -> &[&str] {
+        &self.event_types
+    }
+
+    fn parent_prefixes(&self, event_type: &str) -> &[&str] {
+        event_spec(event_type)
+            .map(|spec| spec.parent_prefixes)
+            .unwrap_or(&[])
+    }
+
+    fn decode(
+        &self,
+        event_instr: &Instruction,
+        parent_instr: &Instruction,
+        tx: &Transaction,
+        instruction_index: usize,
+    ) -> Option<DecodedRow> {
+        let spec = event_spec(&event_instr.r#type)?;
+        (spec.handler)(tx, event_instr, parent_instr, instruction_index)
+    }
+}
+
+/// 事件解析函数：从 event 与其父指令组装一行，字段不匹配时返回 None
+type EventHandler = fn(&Transaction, &Instruction, &Instruction, usize) -> Option<DecodedRow>;
+
+/// 单个 event 类型的转换规则
+struct EventSpec {
+    /// Instruction.r#type 中的 event 类型
+    event_type: &'static str,
+    /// 能发出该 event 的父指令类型前缀（如 PumpFunAmmBuy 同时覆盖 PumpFunAmmBuyExactQuoteIn）
+    parent_prefixes: &'static [&'static str],
+    handler: EventHandler,
+}
+
+/// PumpFun / PumpFun AMM 支持的 event；新增事件只需实现解析函数并在此登记
+static EVENT_SPECS: &[EventSpec] = &[
+    EventSpec {
+        event_type: "PumpFunTradeEvent",
+        parent_prefixes: &["PumpFunBuy", "PumpFunSell"],
+        handler: convert_trade_event,
+    },
+    EventSpec {
+        event_type: "PumpFunCreateEvent",
+        parent_prefixes: &["PumpFunCreate"],
+        handler: convert_create_event,
+    },
+    EventSpec {
+        event_type: "PumpFunMigrateEvent",
+        parent_prefixes: &["PumpFunMigrate"],
+        handler: convert_migrate_event,
+    },
+    EventSpec {
+        event_type: "PumpFunAmmBuyEvent",
+        parent_prefixes: &["PumpFunAmmBuy"],
+        handler: convert_amm_buy_event,
+    },
+    EventSpec {
+        event_type: "PumpFunAmmSellEvent",
+        parent_prefixes: &["PumpFunAmmSell"],
+        handler: convert_amm_sell_event,
+    },
+    EventSpec {
+        event_type: "PumpFunAmmDepositEvent",
+        parent_prefixes: &["PumpFunAmmDeposit"],
+        handler: convert_amm_deposit_event,
+    },
+    EventSpec {
+        event_type: "PumpFunAmmWithdrawEvent",
+        parent_prefixes: &["PumpFunAmmWithdraw"],
+        handler: convert_amm_withdraw_event,
+    },
+    EventSpec {
+        event_type: "PumpFunAmmCreatePoolEvent",
+        parent_prefixes: &["PumpFunAmmCreatePool"],
+        handler: convert_amm_create_pool_event,
+    },
+    EventSpec {
+        event_type: "PumpFunAmmCollectCoinCreatorFeeEvent",
+        parent_prefixes: &["PumpFunAmmCollectCoinCreatorFee"],
+        handler: convert_amm_collect_coin_creator_fee_event,
+    },
+    EventSpec {
+        event_type: "PumpFunAmmDisableEvent",
+        parent_prefixes: &["PumpFunAmmDisable"],
+        handler: convert_amm_disable_event,
+    },
+    EventSpec {
+        event_type: "PumpFunAmmSetParamsEvent",
+        parent_prefixes: &["PumpFunAmmSetParams"],
+        handler: convert_amm_set_params_event,
+    },
+];
+
+fn event_spec(event_type: &str) -> Option<&'static EventSpec> {
+    EVENT_SPECS.iter().find(|spec| spec.event_type == event_type)
+}
+
+// event 时间戳转换为 u32，无效时截断到边界并告警，避免 `as u32` 静默回绕
+fn event_timestamp(raw: impl Into<i128>, instr: &Instruction, tx: &Transaction) -> u32 {
+    let raw = raw.into();
+    to_unix_u32(raw).unwrap_or_else(|| {
+        tracing::warn!(
+            slot = tx.slot,
+            event_type = %instr.r#type,
+            raw_timestamp = %raw,
+            "⚠️  Invalid event timestamp, clamped to u32 range"
+        );
+        clamp_unix_u32(raw)
+    })
+}
+
+fn convert_trade_event(
+    tx: &Transaction,
+    instr: &Instruction,
+    prev_instr: &Instruction,
+    index: usize,
+) -> Option<DecodedRow> {
+    if let (Some(parsed_event), Some(_parsed_instr)) =
+        (&instr.parsed, &prev_instr.parsed)
+    {
+        if let proto_lib::transaction::solana::instruction::Parsed::PumpfunTradeEvent(trade_event) = parsed_event {
+            // TradeEvent可以由Buy或BuyExactSolIn触发，但event本身已包含所有数据
+            // 我们不需要区分是哪个指令触发的，因为event数据是一致的
+            let event_v2 = PumpfunTradeEventV2 {
+                signature: global_bs58().encode_64(&tx.signature),
+                slot: tx.slot,
+                transaction_index: tx.index as u32,
+                instruction_index: index as u32,
+                mint: global_bs58().encode_32(&trade_event.mint),
+                sol_amount: trade_event.sol_amount,
+                token_amount: trade_event.token_amount,
+                is_buy: trade_event.is_buy as u8,
+                user: global_bs58().encode_32(&trade_event.user),
+                timestamp: event_timestamp(trade_event.timestamp, instr, tx),
+                virtual_sol_reserves: trade_event.virtual_sol_reserves,
+                virtual_token_reserves: trade_event.virtual_token_reserves,
+                real_sol_reserves: trade_event.real_sol_reserves,
+                real_token_reserves: trade_event.real_token_reserves,
+                fee_recipient: global_bs58().encode_32(&trade_event.fee_recipient),
+                fee_basis_points: trade_event.fee_basis_points,
+                fee: trade_event.fee,
+                creator: global_bs58().encode_32(&trade_event.creator),
+                creator_fee_basis_points: trade_event.creator_fee_basis_points,
+                creator_fee: trade_event.creator_fee,
+                track_volume: trade_event.track_volume as u8,
+                total_unclaimed_tokens: trade_event.total_unclaimed_tokens,
+                total_claimed_tokens: trade_event.total_claimed_tokens,
+                current_sol_volume: trade_event.current_sol_volume,
+                last_update_timestamp: trade_event.last_update_timestamp,
+                current_sol_volume_128: trade_event.current_sol_volume as u128,
+            };
+            return Some(DecodedRow::PumpfunTrade(event_v2));
+        }
+    }
+    None
+}
+
+fn convert_create_event(
+    tx: &Transaction,
+    instr: &Instruction,
+    prev_instr: &Instruction,
+    index: usize,
+) -> Option<DecodedRow> {
+    if let (Some(parsed_event), Some(_parsed_instr)) =
+        (&instr.parsed, &prev_instr.parsed)
+    {
+        if let proto_lib::transaction::solana::instruction::Parsed::PumpfunCreateEvent(create_event) = parsed_event {
+            let event_v2 = PumpfunCreateEventV2 {
+                signature: global_bs58().encode_64(&tx.signature),
+                slot: tx.slot,
+                transaction_index: tx.index as u32,
+                instruction_index: index as u32,
+                name: create_event.name.clone(),
+                symbol: create_event.symbol.clone(),
+                uri: create_event.uri.clone(),
+                mint: global_bs58().encode_32(&create_event.mint),
+                bonding_curve: global_bs58().encode_32(&create_event.bonding_curve),
+                user: global_bs58().encode_32(&create_event.user),
+                creator: global_bs58().encode_32(&create_event.creator),
+                timestamp: event_timestamp(create_event.timestamp, instr, tx),
+                virtual_token_reserves: create_event.virtual_token_reserves,
+                virtual_sol_reserves: create_event.virtual_sol_reserves,
+                real_token_reserves: create_event.real_token_reserves,
+                token_total_supply: create_event.token_total_supply,
+            };
+            return Some(DecodedRow::PumpfunCreate(event_v2));
+        }
+    }
+    None
+}
+
+fn convert_migrate_event(
+    tx: &Transaction,
+    instr: &Instruction,
+    prev_instr: &Instruction,
+    index: usize,
+) -> Option<DecodedRow> {
+    if let (Some(parsed_event), Some(_parsed_instr)) =
+        (&instr.parsed, &prev_instr.parsed)
+    {
+        if let proto_lib::transaction::solana::instruction::Parsed::PumpfunMigrationEvent(migrate_event) = parsed_event {
+            let event_v2 = PumpfunMigrateEventV2 {
+                signature: global_bs58().encode_64(&tx.signature),
+                slot: tx.slot,
+                transaction_index: tx.index as u32,
+                instruction_index: index as u32,
+                user: global_bs58().encode_32(&migrate_event.user),
+                mint: global_bs58().encode_32(&migrate_event.mint),
+                mint_amount: migrate_event.mint_amount,
+                sol_amount: migrate_event.sol_amount,
+                pool_migration_fee: migrate_event.pool_migration_fee,
+                bonding_curve: global_bs58().encode_32(&migrate_event.bonding_curve),
+                timestamp: event_timestamp(migrate_event.timestamp, instr, tx),
+                pool: global_bs58().encode_32(&migrate_event.pool),
+            };
+            return Some(DecodedRow::PumpfunMigrate(event_v2));
+        }
+    }
+    None
+}
+
+fn convert_amm_buy_event(
+    tx: &Transaction,
+    instr: &Instruction,
+    prev_instr: &Instruction,
+    index: usize,
+) -> Option<DecodedRow> {
+    if let (Some(parsed_event), Some(parsed_instr)) =
+        (&instr.parsed, &prev_instr.parsed)
+    {
+        // 处理普通Buy指令
+        if let (
+            proto_lib::transaction::solana::instruction::Parsed::PumpfunAmmBuyEvent(buy_event),
+            proto_lib::transaction::solana::instruction::Parsed::PumpfunAmmBuy(buy_instr)
+        ) = (parsed_event, parsed_instr) {
+            if let Some(accounts) = &buy_instr.accounts {
+                let event_v2 = PumpfunAmmBuyEventV2 {
+                    signature: global_bs58().encode_64(&tx.signature),
+                    slot: tx.slot,
+                    transaction_index: tx.index as u32,
+                    instruction_index: index as u32,
+                    base_mint: global_bs58().encode_32(&accounts.base_mint),
+                    quote_mint: global_bs58().encode_32(&accounts.quote_mint),
+                    timestamp: event_timestamp(buy_event.timestamp, instr, tx),
+                    base_amount_out: buy_event.base_amount_out,
+                    max_quote_amount_in: buy_event.max_quote_amount_in,
+                    user_base_token_reserves: buy_event.user_base_token_reserves,
+                    user_quote_token_reserves: buy_event.user_quote_token_reserves,
+                    pool_base_token_reserves: buy_event.pool_base_token_reserves,
+                    pool_quote_token_reserves: buy_event.pool_quote_token_reserves,
+                    quote_amount_in: buy_event.quote_amount_in,
+                    lp_fee_basis_points: buy_event.lp_fee_basis_points,
+                    lp_fee: buy_event.lp_fee,
+                    protocol_fee_basis_points: buy_event.protocol_fee_basis_points,
+                    protocol_fee: buy_event.protocol_fee,
+                    quote_amount_in_with_lp_fee: buy_event.quote_amount_in_with_lp_fee,
+                    user_quote_amount_in: buy_event.user_quote_amount_in,
+                    pool: global_bs58().encode_32(&accounts.pool),
+                    user: global_bs58().encode_32(&accounts.user),
+                    user_base_token_account: global_bs58().encode_32(&accounts.user_base_token_account),
+                    user_quote_token_account: global_bs58().encode_32(&accounts.user_quote_token_account),
+                    protocol_fee_recipient: global_bs58().encode_32(&accounts.protocol_fee_recipient),
+                    protocol_fee_recipient_token_account: global_bs58().encode_32(&accounts.protocol_fee_recipient_token_account),
+                    coin_creator: global_bs58().encode_32(&buy_event.coin_creator),
+                    coin_creator_fee_basis_points: buy_event.coin_creator_fee_basis_points,
+                    coin_creator_fee: buy_event.coin_creator_fee,
+                    track_volume: buy_event.track_volume as u8,
+                    total_unclaimed_tokens: buy_event.total_unclaimed_tokens,
+                    total_claimed_tokens: buy_event.total_claimed_tokens,
+                    current_sol_volume: buy_event.current_sol_volume,
+                    last_update_timestamp: buy_event.last_update_timestamp,
+                    is_main_pool: buy_instr.is_main_pool as u8,
+                    current_sol_volume_128: buy_event.current_sol_volume as u128,
+                };
+                return Some(DecodedRow::PumpfunAmmBuy(event_v2));
+            }
+        // 处理BuyExactQuoteIn指令
+        } else if let (
+            proto_lib::transaction::solana::instruction::Parsed::PumpfunAmmBuyEvent(buy_event),
+            proto_lib::transaction::solana::instruction::Parsed::PumpfunAmmBuyExactQuoteIn(buy_exact_instr)
+        ) = (parsed_event, parsed_instr) {
+            if let Some(accounts) = &buy_exact_instr.accounts {
+                let event_v2 = PumpfunAmmBuyEventV2 {
+                    signature: global_bs58().encode_64(&tx.signature),
+                    slot: tx.slot,
+                    transaction_index: tx.index as u32,
+                    instruction_index: index as u32,
+                    base_mint: global_bs58().encode_32(&accounts.base_mint),
+                    quote_mint: global_bs58().encode_32(&accounts.quote_mint),
+                    timestamp: event_timestamp(buy_event.timestamp, instr, tx),
+                    base_amount_out: buy_event.base_amount_out,
+                    max_quote_amount_in: buy_event.max_quote_amount_in,
+                    user_base_token_reserves: buy_event.user_base_token_reserves,
+                    user_quote_token_reserves: buy_event.user_quote_token_reserves,
+                    pool_base_token_reserves: buy_event.pool_base_token_reserves,
+                    pool_quote_token_reserves: buy_event.pool_quote_token_reserves,
+                    quote_amount_in: buy_event.quote_amount_in,
+                    lp_fee_basis_points: buy_event.lp_fee_basis_points,
+                    lp_fee: buy_event.lp_fee,
+                    protocol_fee_basis_points: buy_event.protocol_fee_basis_points,
+                    protocol_fee: buy_event.protocol_fee,
+                    quote_amount_in_with_lp_fee: buy_event.quote_amount_in_with_lp_fee,
+                    user_quote_amount_in: buy_event.user_quote_amount_in,
+                    pool: global_bs58().encode_32(&accounts.pool),
+                    user: global_bs58().encode_32(&accounts.user),
+                    user_base_token_account: global_bs58().encode_32(&accounts.user_base_token_account),
+                    user_quote_token_account: global_bs58().encode_32(&accounts.user_quote_token_account),
+                    protocol_fee_recipient: global_bs58().encode_32(&accounts.protocol_fee_recipient),
+                    protocol_fee_recipient_token_account: global_bs58().encode_32(&accounts.protocol_fee_recipient_token_account),
+                    coin_creator: global_bs58().encode_32(&buy_event.coin_creator),
+                    coin_creator_fee_basis_points: buy_event.coin_creator_fee_basis_points,
+                    coin_creator_fee: buy_event.coin_creator_fee,
+                    track_volume: buy_event.track_volume as u8,
+                    total_unclaimed_tokens: buy_event.total_unclaimed_tokens,
+                    total_claimed_tokens: buy_event.total_claimed_tokens,
+                    current_sol_volume: buy_event.current_sol_volume,
+                    last_update_timestamp: buy_event.last_update_timestamp,
+                    is_main_pool: buy_exact_instr.is_main_pool as u8,
+                    current_sol_volume_128: buy_event.current_sol_volume as u128,
+                };
+                return Some(DecodedRow::PumpfunAmmBuy(event_v2));
+            }
+        }
+    }
+    None
+}
+
+fn convert_amm_sell_event(
+    tx: &Transaction,
+    instr: &Instruction,
+    prev_instr: &Instruction,
+    index: usize,
+) -> Option<DecodedRow> {
+    if let (Some(parsed_event), Some(parsed_instr)) =
+        (&instr.parsed, &prev_instr.parsed)
+    {
+        if let (
+            proto_lib::transaction::solana::instruction::Parsed::PumpfunAmmSellEvent(sell_event),
+            proto_lib::transaction::solana::instruction::Parsed::PumpfunAmmSell(sell_instr)
+        ) = (parsed_event, parsed_instr) {
+            if let Some(accounts) = &sell_instr.accounts {
+                let event_v2 = PumpfunAmmSellEventV2 {
+                    signature: global_bs58().encode_64(&tx.signature),
+                    slot: tx.slot,
+                    transaction_index: tx.index as u32,
+                    instruction_index: index as u32,
+                    base_mint: global_bs58().encode_32(&accounts.base_mint),
+                    quote_mint: global_bs58().encode_32(&accounts.quote_mint),
+                    timestamp: event_timestamp(sell_event.timestamp, instr, tx),
+                    base_amount_in: sell_event.base_amount_in,
+                    min_quote_amount_out: sell_event.min_quote_amount_out,
+                    user_base_token_reserves: sell_event.user_base_token_reserves,
+                    user_quote_token_reserves: sell_event.user_quote_token_reserves,
+                    pool_base_token_reserves: sell_event.pool_base_token_reserves,
+                    pool_quote_token_reserves: sell_event.pool_quote_token_reserves,
+                    quote_amount_out: sell_event.quote_amount_out,
+                    lp_fee_basis_points: sell_event.lp_fee_basis_points,
+                    lp_fee: sell_event.lp_fee,
+                    protocol_fee_basis_points: sell_event.protocol_fee_basis_points,
+                    protocol_fee: sell_event.protocol_fee,
+                    quote_amount_out_without_lp_fee: sell_event.quote_amount_out_without_lp_fee,
+                    user_quote_amount_out: sell_event.user_quote_amount_out,
+                    pool: global_bs58().encode_32(&accounts.pool),
+                    user: global_bs58().encode_32(&accounts.user),
+                    user_base_token_account: global_bs58().encode_32(&accounts.user_base_token_account),
+                    user_quote_token_account: global_bs58().encode_32(&accounts.user_quote_token_account),
+                    protocol_fee_recipient: global_bs58().encode_32(&accounts.protocol_fee_recipient),
+                    protocol_fee_recipient_token_account: global_bs58().encode_32(&accounts.protocol_fee_recipient_token_account),
+                    coin_creator: global_bs58().encode_32(&sell_event.coin_creator),
+                    coin_creator_fee_basis_points: sell_event.coin_creator_fee_basis_points,
+                    coin_creator_fee: sell_event.coin_creator_fee,
+                    is_main_pool: sell_instr.is_main_pool as u8,
+                };
+                return Some(DecodedRow::PumpfunAmmSell(event_v2));
+            }
+        }
+    }
+    None
+}
+
+fn convert_amm_deposit_event(
+    tx: &Transaction,
+    instr: &Instruction,
+    prev_instr: &Instruction,
+    index: usize,
+) -> Option<DecodedRow> {
+    if let (Some(parsed_event), Some(parsed_instr)) =
+        (&instr.parsed, &prev_instr.parsed)
+    {
+        if let (
+            proto_lib::transaction::solana::instruction::Parsed::PumpfunAmmDepositEvent(deposit_event),
+            proto_lib::transaction::solana::instruction::Parsed::PumpfunAmmDeposit(deposit_instr)
+        ) = (parsed_event, parsed_instr) {
+            if let Some(accounts) = &deposit_instr.accounts {
+                let event_v2 = PumpfunAmmDepositEventV2 {
+                    signature: global_bs58().encode_64(&tx.signature),
+                    slot: tx.slot,
+                    transaction_index: tx.index as u32,
+                    instruction_index: index as u32,
+                    base_mint: global_bs58().encode_32(&accounts.base_mint),
+                    quote_mint: global_bs58().encode_32(&accounts.quote_mint),
+                    timestamp: event_timestamp(deposit_event.timestamp, instr, tx),
+                    lp_token_amount_out: deposit_event.lp_token_amount_out,
+                    max_base_amount_in: deposit_event.max_base_amount_in,
+                    max_quote_amount_in: deposit_event.max_quote_amount_in,
+                    user_base_token_reserves: deposit_event.user_base_token_reserves,
+                    user_quote_token_reserves: deposit_event.user_quote_token_reserves,
+                    pool_base_token_reserves: deposit_event.pool_base_token_reserves,
+                    pool_quote_token_reserves: deposit_event.pool_quote_token_reserves,
+                    base_amount_in: deposit_event.base_amount_in,
+                    quote_amount_in: deposit_event.quote_amount_in,
+                    lp_mint_supply: deposit_event.lp_mint_supply,
+                    pool: global_bs58().encode_32(&accounts.pool),
+                    user: global_bs58().encode_32(&accounts.user),
+                    user_base_token_account: global_bs58().encode_32(&accounts.user_base_token_account),
+                    user_quote_token_account: global_bs58().encode_32(&accounts.user_quote_token_account),
+                    user_pool_token_account: global_bs58().encode_32(&accounts.user_pool_token_account),
+                    is_main_pool: deposit_instr.is_main_pool as u8,
+                };
+                return Some(DecodedRow::PumpfunAmmDeposit(event_v2));
+            }
+        }
+    }
+    None
+}
+
+fn convert_amm_withdraw_event(
+    tx: &Transaction,
+    instr: &Instruction,
+    prev_instr: &Instruction,
+    index: usize,
+) -> Option<DecodedRow> {
+    if let (Some(parsed_event), Some(parsed_instr)) =
+        (&instr.parsed, &prev_instr.parsed)
+    {
+        if let (
+            proto_lib::transaction::solana::instruction::Parsed::PumpfunAmmWithdrawEvent(withdraw_event),
+            proto_lib::transaction::solana::instruction::Parsed::PumpfunAmmWithdraw(withdraw_instr)
+        ) = (parsed_event, parsed_instr) {
+            if let Some(accounts) = &withdraw_instr.accounts {
+                let event_v2 = PumpfunAmmWithdrawEventV2 {
+                    signature: global_bs58().encode_64(&tx.signature),
+                    slot: tx.slot,
+                    transaction_index: tx.index as u32,
+                    instruction_index: index as u32,
+                    base_mint: global_bs58().encode_32(&accounts.base_mint),
+                    quote_mint: global_bs58().encode_32(&accounts.quote_mint),
+                    timestamp: event_timestamp(withdraw_event.timestamp, instr, tx),
+                    lp_token_amount_in: withdraw_event.lp_token_amount_in,
+                    min_base_amount_out: withdraw_event.min_base_amount_out,
+                    min_quote_amount_out: withdraw_event.min_quote_amount_out,
+                    user_base_token_reserves: withdraw_event.user_base_token_reserves,
+                    user_quote_token_reserves: withdraw_event.user_quote_token_reserves,
+                    pool_base_token_reserves: withdraw_event.pool_base_token_reserves,
+                    pool_quote_token_reserves: withdraw_event.pool_quote_token_reserves,
+                    base_amount_out: withdraw_event.base_amount_out,
+                    quote_amount_out: withdraw_event.quote_amount_out,
+                    lp_mint_supply: withdraw_event.lp_mint_supply,
+                    pool: global_bs58().encode_32(&accounts.pool),
+                    user: global_bs58().encode_32(&accounts.user),
+                    user_base_token_account: global_bs58().encode_32(&accounts.user_base_token_account),
+                    user_quote_token_account: global_bs58().encode_32(&accounts.user_quote_token_account),
+                    user_pool_token_account: global_bs58().encode_32(&accounts.user_pool_token_account),
+                    is_main_pool: withdraw_instr.is_main_pool as u8,
+                };
+                return Some(DecodedRow::PumpfunAmmWithdraw(event_v2));
+            }
+        }
+    }
+    None
+}
+
+fn convert_amm_create_pool_event(
+    tx: &Transaction,
+    instr: &Instruction,
+    prev_instr: &Instruction,
+    index: usize,
+) -> Option<DecodedRow> {
+    if let (Some(parsed_event), Some(parsed_instr)) =
+        (&instr.parsed, &prev_instr.parsed)
+    {
+        if let (
+            proto_lib::transaction::solana::instruction::Parsed::PumpfunAmmCreatePoolEvent(create_event),
+            proto_lib::transaction::solana::instruction::Parsed::PumpfunAmmCreatePool(create_instr)
+        ) = (parsed_event, parsed_instr) {
+            if let Some(accounts) = &create_instr.accounts {
+                let event_v2 = PumpfunAmmCreatePoolEventV2 {
+                    signature: global_bs58().encode_64(&tx.signature),
+                    slot: tx.slot,
+                    transaction_index: tx.index as u32,
+                    instruction_index: index as u32,
+                    timestamp: event_timestamp(create_event.timestamp, instr, tx),
+                    index: create_event.index,
+                    creator: global_bs58().encode_32(&accounts.creator),
+                    base_mint: global_bs58().encode_32(&accounts.base_mint),
+                    quote_mint: global_bs58().encode_32(&accounts.quote_mint),
+                    base_mint_decimals: create_event.base_mint_decimals,
+                    quote_mint_decimals: create_event.quote_mint_decimals,
+                    base_amount_in: create_event.base_amount_in,
+                    quote_amount_in: create_event.quote_amount_in,
+                    pool_base_amount: create_event.pool_base_amount,
+                    pool_quote_amount: create_event.pool_quote_amount,
+                    minimum_liquidity: create_event.minimum_liquidity,
+                    initial_liquidity: create_event.initial_liquidity,
+                    lp_token_amount_out: create_event.lp_token_amount_out,
+                    pool_bump: create_event.pool_bump,
+                    pool: global_bs58().encode_32(&accounts.pool),
+                    lp_mint: global_bs58().encode_32(&accounts.lp_mint),
+                    user_base_token_account: global_bs58().encode_32(&accounts.user_base_token_account),
+                    user_quote_token_account: global_bs58().encode_32(&accounts.user_quote_token_account),
+                    coin_creator: global_bs58().encode_32(&create_event.coin_creator),
+                    is_main_pool: create_instr.is_main_pool as u8,
+                };
+                return Some(DecodedRow::PumpfunAmmCreatePool(event_v2));
+            }
+        }
+    }
+    None
+}
+
+fn convert_amm_collect_coin_creator_fee_event(
+    tx: &Transaction,
+    instr: &Instruction,
+    prev_instr: &Instruction,
+    index: usize,
+) -> Option<DecodedRow> {
+    if let (Some(parsed_event), Some(_parsed_instr)) =
+        (&instr.parsed, &prev_instr.parsed)
+    {
+        if let proto_lib::transaction::solana::instruction::Parsed::PumpfunAmmCollectCoinCreatorFeeEvent(fee_event) = parsed_event {
+            let event_v2 = PumpfunAmmCollectCoinCreatorFeeEventV2 {
+                signature: global_bs58().encode_64(&tx.signature),
+                slot: tx.slot,
+                transaction_index: tx.index as u32,
+                instruction_index: index as u32,
+                timestamp: event_timestamp(fee_event.timestamp, instr, tx),
+                coin_creator: global_bs58().encode_32(&fee_event.coin_creator),
+                coin_creator_fee: fee_event.coin_creator_fee,
+                coin_creator_vault_ata: global_bs58().encode_32(&fee_event.coin_creator_vault_ata),
+                coin_creator_token_account: global_bs58().encode_32(&fee_event.coin_creator_token_account),
+            };
+            return Some(DecodedRow::PumpfunAmmCollectCoinCreatorFee(event_v2));
+        }
+    }
+    None
+}
+
+fn convert_amm_disable_event(
+    tx: &Transaction,
+    instr: &Instruction,
+    prev_instr: &Instruction,
+    index: usize,
+) -> Option<DecodedRow> {
+    if let (Some(parsed_event), Some(_parsed_instr)) =
+        (&instr.parsed, &prev_instr.parsed)
+    {
+        if let proto_lib::transaction::solana::instruction::Parsed::PumpfunAmmDisableEvent(disable_event) = parsed_event {
+            let event_v2 = PumpfunAmmDisableEventV2 {
+                signature: global_bs58().encode_64(&tx.signature),
+                slot: tx.slot,
+                transaction_index: tx.index as u32,
+                instruction_index: index as u32,
+                timestamp: event_timestamp(disable_event.timestamp, instr, tx),
+                admin: global_bs58().encode_32(&disable_event.admin),
+                disable_create_pool: disable_event.disable_create_pool as u8,
+                disable_deposit: disable_event.disable_deposit as u8,
+                disable_withdraw: disable_event.disable_withdraw as u8,
+                disable_buy: disable_event.disable_buy as u8,
+                disable_sell: disable_event.disable_sell as u8,
+            };
+            return Some(DecodedRow::PumpfunAmmDisable(event_v2));
+        }
+    }
+    None
+}
+
+fn convert_amm_set_params_event(
+    tx: &Transaction,
+    instr: &Instruction,
+    prev_instr: &Instruction,
+    index: usize,
+) -> Option<DecodedRow> {
+    if let (Some(parsed_event), Some(_parsed_instr)) =
+        (&instr.parsed, &prev_instr.parsed)
+    {
+        if let proto_lib::transaction::solana::instruction::Parsed::PumpfunAmmSetParamsEvent(params_event) = parsed_event {
+            let event_v2 = PumpfunAmmSetParamsEventV2 {
+                signature: global_bs58().encode_64(&tx.signature),
+                slot: tx.slot,
+                transaction_index: tx.index as u32,
+                instruction_index: index as u32,
+                timestamp: event_timestamp(params_event.timestamp, instr, tx),
+                admin: global_bs58().encode_32(&params_event.admin),
+                lp_fee_basis_points: params_event.lp_fee_basis_points,
+                protocol_fee_basis_points: params_event.protocol_fee_basis_points,
+                coin_creator_fee_basis_points: params_event.coin_creator_fee_basis_points,
+            };
+            return Some(DecodedRow::PumpfunAmmSetParams(event_v2));
+        }
+    }
+    None
+}
@@ -0,0 +1,89 @@
+use std::fmt::Display;
+use std::future::Future;
+use std::time::Duration;
+
+/// NATS 断开后重新订阅 / 重连的退避策略
+///
+/// 等待时间从 base_delay 开始每次翻倍，不超过 max_delay；
+/// max_attempts 为连续重连次数上限，0 表示无限重试
+#[derive(Debug, Clone)]
+pub struct ReconnectBackoff {
+    max_attempts: u32,
+    base_delay: Duration,
+    max_delay: Duration,
+}
+
+impl ReconnectBackoff {
+    pub fn new(max_attempts: u32) -> Self {
+        Self::with_delays(max_attempts, Duration::from_secs(1), Duration::from_secs(30))
+    }
+
+    pub fn with_delays(max_attempts: u32, base_delay: Duration, max_delay: Duration) -> Self {
+        Self {
+            max_attempts,
+            base_delay,
+            max_delay,
+        }
+    }
+
+    /// 第 attempt 次（从 1 开始）重连前的等待时间，超过次数上限返回 None
+    pub fn delay(&self, attempt: u32) -> Option<Duration> {
+        if self.max_attempts > 0 && attempt > self.max_attempts {
+            return None;
+        }
+        let factor = 1u32 << attempt.saturating_sub(1).min(16);
+        Some(self.base_delay.saturating_mul(factor).min(self.max_delay))
+    }
+
+    /// 记录日志并等待第 attempt 次重连的退避时间，次数用尽时返回错误
+    pub async fn wait(&self, attempt: u32) -> Result<(), String> {
+        let delay = self.delay(attempt).ok_or_else(|| {
+            format!(
+                "NATS subscription lost, giving up after {} reconnect attempts",
+                attempt - 1
+            )
+        })?;
+        tracing::info!(
+            attempt,
+            delay_ms = delay.as_millis() as u64,
+            "resubscribing to NATS"
+        );
+        tokio::time::sleep(delay).await;
+        Ok(())
+    }
+
+    /// 按退避策略重复执行 `subscribe` 直到成功，失败同样计入连续重连次数
+    ///
+    /// `attempts` 为调用方维护的连续重连次数，收到消息后由调用方清零；
+    /// 次数用尽时返回 `wait` 的错误
+    pub async fn resubscribe<T, E, F, Fut>(
+        &self,
+        attempts: &mut u32,
+        mut subscribe: F,
+    ) -> Result<T, String>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+        E: Display,
+    {
+        loop {
+            *attempts += 1;
+            self.wait(*attempts).await?;
+            match subscribe().await {
+                Ok(subscriber) => return Ok(subscriber),
+                Err(e) => tracing::warn!(
+                    attempt = *attempts,
+                    error = %e,
+                    "failed to resubscribe to NATS"
+                ),
+            }
+        }
+    }
+
+    /// async-nats `reconnect_delay_callback` 使用的等待时间（`attempts` 从 0 开始），
+    /// 客户端自己控制重连次数，这里不设上限
+    pub fn client_delay(&self, attempts: usize) -> Duration {
+        let attempt = u32::try_from(attempts).unwrap_or(u32::MAX).saturating_add(1);
+        self.delay(attempt).unwrap_or(self.max_delay)
+    }
+}
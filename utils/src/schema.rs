@@ -0,0 +1,92 @@
+use clickhouse::Client;
+use serde::de::DeserializeOwned;
+
+use crate::event_registry::{EventRegistry, EventSchema};
+
+/// 建表使用的排序键（与 syncer 提取时的 ORDER BY 一致），结构体缺少其中的列时退化为 `tuple()`
+pub const ORDER_BY_COLUMNS: &[&str] = &["slot", "transaction_index", "instruction_index"];
+
+/// 由事件结构体生成 `CREATE TABLE IF NOT EXISTS` 语句
+///
+/// 列名、类型和顺序与 `EventSchema::derive` 一致，因此建出的表能通过 `verify_schema`
+pub fn create_table_sql<T: DeserializeOwned>(table_name: &str) -> Result<String, String> {
+    let event_type = std::any::type_name::<T>().rsplit("::").next().unwrap_or_default();
+    Ok(create_table_sql_for(&EventSchema::derive::<T>(event_type, table_name)?))
+}
+
+/// 按已推导的表结构生成建表语句
+pub fn create_table_sql_for(schema: &EventSchema) -> String {
+    let columns = schema
+        .columns
+        .iter()
+        .map(|column| format!("    {} {}", quote_identifier(&column.name), column.clickhouse_type))
+        .collect::<Vec<_>>()
+        .join(",\n");
+
+    let has_order_by = ORDER_BY_COLUMNS
+        .iter()
+        .all(|name| schema.columns.iter().any(|column| column.name == *name));
+    let order_by = if has_order_by {
+        format!("({})", ORDER_BY_COLUMNS.join(", "))
+    } else {
+        "tuple()".to_string()
+    };
+
+    format!(
+        "CREATE TABLE IF NOT EXISTS {} (\n{}\n)\nENGINE = MergeTree\nORDER BY {}",
+        quote_table(&schema.table),
+        columns,
+        order_by
+    )
+}
+
+/// 表不存在时按事件结构体建表，返回是否新建
+///
+/// 已存在的表不做修改，结构是否一致由 `verify_schema` 检查
+pub async fn ensure_table(client: &Client, schema: &EventSchema) -> Result<bool, clickhouse::error::Error> {
+    let (database, name) = split_table(&schema.table);
+    let exists = client
+        .query("SELECT count() FROM system.tables WHERE database = coalesce(?, currentDatabase()) AND name = ?")
+        .bind(database)
+        .bind(name)
+        .fetch_one::<u64>()
+        .await?
+        > 0;
+    if exists {
+        return Ok(false);
+    }
+
+    client.query(&create_table_sql_for(schema)).execute().await?;
+    Ok(true)
+}
+
+/// 为注册表中的所有事件建表，返回新建的表名
+pub async fn ensure_tables(
+    client: &Client,
+    registry: &EventRegistry,
+) -> Result<Vec<String>, clickhouse::error::Error> {
+    let mut created = Vec::new();
+    for schema in registry.schemas() {
+        if ensure_table(client, schema).await? {
+            created.push(schema.table.clone());
+        }
+    }
+    Ok(created)
+}
+
+/// 拆分可带数据库前缀的表名 "db.table" -> (Some("db"), "table")，不带前缀时为当前数据库
+pub fn split_table(table: &str) -> (Option<&str>, &str) {
+    match table.split_once('.') {
+        Some((database, table)) => (Some(database), table),
+        None => (None, table),
+    }
+}
+
+/// `db.table` 形式的表名逐段加反引号
+fn quote_table(table: &str) -> String {
+    table.split('.').map(quote_identifier).collect::<Vec<_>>().join(".")
+}
+
+fn quote_identifier(name: &str) -> String {
+    format!("`{}`", name.replace('\\', "\\\\").replace('`', "\\`"))
+}
@@ -0,0 +1,194 @@
+use common::async_pool::AsyncPool;
+use std::any::Any;
+use std::fmt;
+use std::future::Future;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::sync::{Notify, Semaphore};
+
+/// 任务 panic（或被取消）时记录到池中的错误
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaskPanic {
+    pub message: String,
+}
+
+impl fmt::Display for TaskPanic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "task panicked: {}", self.message)
+    }
+}
+
+impl std::error::Error for TaskPanic {}
+
+impl From<TaskPanic> for String {
+    fn from(panic: TaskPanic) -> Self {
+        panic.to_string()
+    }
+}
+
+/// 任务结束（包括 panic）时减少未完成计数并释放许可
+struct PendingGuard {
+    pending: Arc<AtomicUsize>,
+    idle: Arc<Notify>,
+    _permit: Option<tokio::sync::OwnedSemaphorePermit>,
+}
+
+impl Drop for PendingGuard {
+    fn drop(&mut self) {
+        if self.pending.fetch_sub(1, Ordering::SeqCst) == 1 {
+            self.idle.notify_waiters();
+        }
+    }
+}
+
+/// 收集任务错误的协程池
+///
+/// 包装 `common::async_pool::AsyncPool`：任务返回 `Result<(), E>`，失败不再只能靠
+/// `process::exit` 暴露，而是记录下来由调用方在 `wait_all_tasks` 后统一处理。
+///
+/// `submit_blocking` 在未完成任务数达到 `max_concurrent` 时等待，生产者因此被
+/// ClickHouse 的消化速度限速，不会无限堆积待插入的批次
+///
+/// `scoped` 得到共享底层协程池和并发上限、但单独记录错误和未完成任务的子池，
+/// 多个文件并行处理时各自等待、各自汇总失败
+///
+/// 任务 panic 时转换为 `E::from(TaskPanic)` 记录，`wait_all_tasks` 不会因此卡住
+pub struct TaskPool<E> {
+    pool: Arc<AsyncPool>,
+    errors: Arc<Mutex<Vec<E>>>,
+    permits: Arc<Semaphore>,
+    pending: Arc<AtomicUsize>,
+    idle: Arc<Notify>,
+}
+
+impl<E: From<TaskPanic> + Send + 'static> TaskPool<E> {
+    pub fn new(max_concurrent: usize) -> Self {
+        Self {
+            pool: Arc::new(AsyncPool::new(max_concurrent)),
+            errors: Arc::new(Mutex::new(Vec::new())),
+            permits: Arc::new(Semaphore::new(max_concurrent.max(1))),
+            pending: Arc::new(AtomicUsize::new(0)),
+            idle: Arc::new(Notify::new()),
+        }
+    }
+
+    /// 共享协程池和并发上限的子池，错误和未完成任务单独统计
+    pub fn scoped(&self) -> Self {
+        Self {
+            pool: self.pool.clone(),
+            errors: Arc::new(Mutex::new(Vec::new())),
+            permits: self.permits.clone(),
+            pending: Arc::new(AtomicUsize::new(0)),
+            idle: Arc::new(Notify::new()),
+        }
+    }
+
+    /// 立即提交可能失败的任务，失败时错误记录到池中
+    ///
+    /// 不受背压限制，待执行任务可以无限堆积；生产速度可能超过消费速度时用 `submit_blocking`
+    pub fn submit_fallible<F, Fut>(&self, f: F)
+    where
+        F: FnOnce() -> Fut + Send + 'static,
+        Fut: Future<Output = Result<(), E>> + Send + 'static,
+    {
+        self.submit_inner(f, None);
+    }
+
+    /// 等到未完成任务数低于 `max_concurrent` 再提交，失败时错误记录到池中
+    ///
+    /// 与 `submit_fallible` 不同，调用方会在池满时挂起，直到有任务完成
+    pub async fn submit_blocking<F, Fut>(&self, f: F)
+    where
+        F: FnOnce() -> Fut + Send + 'static,
+        Fut: Future<Output = Result<(), E>> + Send + 'static,
+    {
+        let permit = self
+            .permits
+            .clone()
+            .acquire_owned()
+            .await
+            .expect("task pool semaphore is never closed");
+        self.submit_inner(f, Some(permit));
+    }
+
+    fn submit_inner<F, Fut>(&self, f: F, permit: Option<tokio::sync::OwnedSemaphorePermit>)
+    where
+        F: FnOnce() -> Fut + Send + 'static,
+        Fut: Future<Output = Result<(), E>> + Send + 'static,
+    {
+        let errors = self.errors.clone();
+        self.pending.fetch_add(1, Ordering::SeqCst);
+        // 任务结束后才释放许可，唤醒等待中的 submit_blocking
+        let guard = PendingGuard {
+            pending: self.pending.clone(),
+            idle: self.idle.clone(),
+            _permit: permit,
+        };
+        self.pool.submit(move || async move {
+            let _guard = guard;
+            // 在单独的 tokio 任务中执行，panic 由 JoinHandle 捕获后记为错误
+            let error = match tokio::spawn(async move { f().await }).await {
+                Ok(Ok(())) => return,
+                Ok(Err(e)) => e,
+                Err(join_error) => E::from(TaskPanic {
+                    message: if join_error.is_panic() {
+                        panic_message(join_error.into_panic())
+                    } else {
+                        "task was cancelled".to_string()
+                    },
+                }),
+            };
+            errors.lock().unwrap().push(error);
+        });
+    }
+
+    /// 已提交但尚未完成的任务数（排队 + 执行中）
+    pub fn pending_len(&self) -> usize {
+        self.pending.load(Ordering::SeqCst)
+    }
+
+    /// 是否已有任务失败（不等待未完成的任务）
+    pub fn has_errors(&self) -> bool {
+        !self.errors.lock().unwrap().is_empty()
+    }
+
+    /// 取出并清空已收集的错误
+    pub fn take_errors(&self) -> Vec<E> {
+        std::mem::take(&mut *self.errors.lock().unwrap())
+    }
+
+    /// 等待本池提交的所有任务完成，返回期间收集到的全部错误
+    ///
+    /// 只等待通过本池（而非共享同一协程池的其他子池）提交的任务
+    pub async fn wait_all_tasks(&self) -> Vec<E> {
+        loop {
+            // 先注册通知再检查计数，避免错过最后一个任务的唤醒
+            let notified = self.idle.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+            if self.pending.load(Ordering::SeqCst) == 0 {
+                break;
+            }
+            notified.await;
+        }
+        self.take_errors()
+    }
+
+    /// 关闭底层协程池（仍有子池持有时由最后一个持有者关闭）
+    pub fn join(self) {
+        if let Ok(pool) = Arc::try_unwrap(self.pool) {
+            pool.join();
+        }
+    }
+}
+
+/// panic 负载中的消息（`panic!` 的参数为字符串时）
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    match payload.downcast::<String>() {
+        Ok(message) => *message,
+        Err(payload) => payload
+            .downcast_ref::<&str>()
+            .map(|message| message.to_string())
+            .unwrap_or_else(|| "unknown panic payload".to_string()),
+    }
+}
@@ -0,0 +1,15 @@
+/// 链上 unix 秒级时间戳 -> ClickHouse `DateTime`(u32)
+///
+/// 0、负数以及超出 u32 的值（2106 年之后）视为无效，返回 None
+pub fn to_unix_u32(raw: impl Into<i128>) -> Option<u32> {
+    let raw = raw.into();
+    if raw <= 0 {
+        return None;
+    }
+    u32::try_from(raw).ok()
+}
+
+/// 截断到 u32 范围：负数为 0，超出上限为 u32::MAX
+pub fn clamp_unix_u32(raw: impl Into<i128>) -> u32 {
+    raw.into().clamp(0, u32::MAX as i128) as u32
+}
@@ -0,0 +1,17 @@
+use tracing::Span;
+
+/// 携带链路 id 的 NATS header 名
+pub const TRACE_ID_HEADER: &str = "trace_id";
+
+/// 取 header 中的 trace id，缺失或为空时生成一个新的
+pub fn resolve_trace_id(header_value: Option<&str>) -> String {
+    match header_value.map(str::trim) {
+        Some(value) if !value.is_empty() => value.to_string(),
+        _ => uuid::Uuid::new_v4().simple().to_string(),
+    }
+}
+
+/// 单条消息的处理 span，范围内的日志事件都会带上 trace_id 字段
+pub fn message_span(trace_id: &str) -> Span {
+    tracing::info_span!("message", trace_id = %trace_id)
+}
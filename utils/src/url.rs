@@ -0,0 +1,87 @@
+//! 服务配置中 URL 的补全和校验
+//!
+//! 配置里的地址常常只写主机名或 `host:port`，连接前统一补全 scheme 和端口，
+//! 写错时给出具体是哪个字段、哪里不对
+
+/// NATS 客户端支持的 scheme，第一个为缺省值
+pub const NATS_SCHEMES: [&str; 4] = ["nats", "tls", "ws", "wss"];
+
+/// 逐个补全逗号分隔的多个地址（如 NATS 集群的多个服务器），结果仍以逗号连接
+pub fn normalize_url_list(field: &str, urls: &str, schemes: &[&str]) -> Result<String, String> {
+    Ok(urls
+        .split(',')
+        .map(|url| normalize_url(field, url, schemes))
+        .collect::<Result<Vec<_>, _>>()?
+        .join(","))
+}
+
+/// 补全并校验 `scheme://[user@]host:port[/path]`
+///
+/// 没写 scheme 时使用 `schemes[0]`，没写端口时使用该 scheme 的默认端口
+pub fn normalize_url(field: &str, url: &str, schemes: &[&str]) -> Result<String, String> {
+    let invalid = |reason: String| format!("Invalid {} '{}': {}", field, url, reason);
+
+    let url = url.trim();
+    if url.is_empty() {
+        return Err(invalid("empty URL".to_string()));
+    }
+    let (scheme, rest) = match url.split_once("://") {
+        Some((scheme, rest)) => (scheme.to_ascii_lowercase(), rest),
+        // 没有 "://" 却带路径，多半是 scheme 写错了（如 "nats:/host"）
+        None if url.contains('/') => {
+            return Err(invalid(format!("malformed scheme, expected e.g. {}://host:port", schemes[0])));
+        }
+        None => (schemes[0].to_string(), url),
+    };
+    if !schemes.contains(&scheme.as_str()) {
+        return Err(invalid(format!(
+            "unsupported scheme '{}', expected one of {:?}",
+            scheme, schemes
+        )));
+    }
+
+    let (authority, path) = match rest.find('/') {
+        // 只有结尾的 "/" 时不保留路径
+        Some(index) if &rest[index..] == "/" => (&rest[..index], ""),
+        Some(index) => rest.split_at(index),
+        None => (rest, ""),
+    };
+    let (userinfo, host_port) = match authority.rsplit_once('@') {
+        Some((userinfo, host_port)) => (Some(userinfo), host_port),
+        None => (None, authority),
+    };
+
+    // IPv6 地址写作 [::1]:4222
+    let port_separator = match host_port.rfind(']') {
+        Some(bracket) => host_port[bracket..].find(':').map(|i| bracket + i),
+        None => host_port.rfind(':'),
+    };
+    let (host, port) = match port_separator {
+        Some(index) => (&host_port[..index], Some(&host_port[index + 1..])),
+        None => (host_port, None),
+    };
+    let bad_host = host.is_empty()
+        || host.contains(char::is_whitespace)
+        || (!host.starts_with('[') && host.contains(':'));
+    if bad_host {
+        return Err(invalid("missing or invalid host".to_string()));
+    }
+    let port = match port {
+        Some(port) => port
+            .parse::<u16>()
+            .map_err(|_| invalid(format!("invalid port '{}'", port)))?,
+        None => default_port(&scheme),
+    };
+
+    let userinfo = userinfo.map(|u| format!("{}@", u)).unwrap_or_default();
+    Ok(format!("{}://{}{}:{}{}", scheme, userinfo, host, port, path))
+}
+
+/// scheme 的默认端口：nats / tls 为 4222，https / wss 为 443，其余为 80
+pub fn default_port(scheme: &str) -> u16 {
+    match scheme {
+        "nats" | "tls" => 4222,
+        "https" | "wss" => 443,
+        _ => 80,
+    }
+}
@@ -1,3 +1,5 @@
+use arrow::array::Array;
+use serde::{Deserialize, Serialize};
 use utils::clickhouse_events::*;
 
 #[test]
@@ -29,6 +31,7 @@ fn test_vec_to_arrow_and_back() {
             total_claimed_tokens: 10,
             current_sol_volume: 11,
             last_update_timestamp: 123456789,
+            current_sol_volume_128: 11,
         },
     ];
     let batch = vec_to_arrow_batch(&events);
@@ -120,12 +123,78 @@ fn test_vec_to_arrow_and_back_buy() {
         current_sol_volume: 58,
         last_update_timestamp: 44444444,
         is_main_pool: 1,
+        current_sol_volume_128: 58,
     }];
     let batch = vec_to_arrow_batch(&events);
     let restored: Vec<PumpfunAmmBuyEventV2> = arrow_batch_to_vec(&batch);
     assert_eq!(events, restored);
 }
 
+fn trade_event_with_volume(signature: &str, current_sol_volume_128: u128) -> PumpfunTradeEventV2 {
+    PumpfunTradeEventV2 {
+        signature: signature.to_string(),
+        slot: 6,
+        transaction_index: 0,
+        instruction_index: 0,
+        mint: "mint6".to_string(),
+        sol_amount: 100,
+        token_amount: 200,
+        is_buy: 1,
+        user: "user6".to_string(),
+        timestamp: 666666,
+        virtual_sol_reserves: 10,
+        virtual_token_reserves: 20,
+        real_sol_reserves: 30,
+        real_token_reserves: 40,
+        fee_recipient: "fee6".to_string(),
+        fee_basis_points: 5,
+        fee: 6,
+        creator: "creator6".to_string(),
+        creator_fee_basis_points: 7,
+        creator_fee: 8,
+        track_volume: 1,
+        total_unclaimed_tokens: 9,
+        total_claimed_tokens: 10,
+        current_sol_volume: u64::MAX,
+        last_update_timestamp: 66666666,
+        current_sol_volume_128,
+    }
+}
+
+#[test]
+fn test_vec_to_arrow_and_back_u128_above_u64_max() {
+    let large = u64::MAX as u128 + 1;
+    let events = vec![
+        trade_event_with_volume("sig6", large),
+        trade_event_with_volume("sig7", u128::MAX),
+    ];
+    let batch = vec_to_arrow_batch(&events);
+
+    let column = batch.column_by_name("current_sol_volume_128").unwrap();
+    assert_eq!(column.data_type(), &arrow::datatypes::DataType::FixedSizeBinary(16));
+
+    let restored: Vec<PumpfunTradeEventV2> = arrow_batch_to_vec(&batch);
+    assert_eq!(events, restored);
+    assert_eq!(restored[0].current_sol_volume_128, large);
+    assert_eq!(restored[1].current_sol_volume_128, u128::MAX);
+}
+
+#[test]
+fn test_u128_column_is_little_endian_like_clickhouse() {
+    let batch = vec_to_arrow_batch(&vec![trade_event_with_volume("sig8", 1u128 << 64)]);
+
+    let column = batch
+        .column_by_name("current_sol_volume_128")
+        .unwrap()
+        .as_any()
+        .downcast_ref::<arrow::array::FixedSizeBinaryArray>()
+        .unwrap();
+    // ClickHouse 以 ArrowStream 输出 UInt128 时同样是 16 字节小端
+    let mut expected = [0u8; 16];
+    expected[8] = 1;
+    assert_eq!(column.value(0), expected);
+}
+
 #[test]
 fn test_vec_to_arrow_and_back_sell() {
     let events = vec![PumpfunAmmSellEventV2 {
@@ -262,3 +331,121 @@ fn test_vec_to_arrow_and_back_withdraw() {
     let restored: Vec<PumpfunAmmWithdrawEventV2> = arrow_batch_to_vec(&batch);
     assert_eq!(events, restored);
 }
+
+#[test]
+fn test_vec_to_arrow_and_back_collect_coin_creator_fee() {
+    let events = vec![PumpfunAmmCollectCoinCreatorFeeEventV2 {
+        signature: "sig9".to_string(),
+        slot: 9,
+        transaction_index: 8,
+        instruction_index: 8,
+        timestamp: 999999,
+        coin_creator: "creator9".to_string(),
+        coin_creator_fee: 121,
+        coin_creator_vault_ata: "vault9".to_string(),
+        coin_creator_token_account: "cta9".to_string(),
+    }];
+    let batch = vec_to_arrow_batch(&events);
+    let restored: Vec<PumpfunAmmCollectCoinCreatorFeeEventV2> = arrow_batch_to_vec(&batch);
+    assert_eq!(events, restored);
+}
+
+#[test]
+fn test_vec_to_arrow_and_back_disable() {
+    let events = vec![PumpfunAmmDisableEventV2 {
+        signature: "sig10".to_string(),
+        slot: 10,
+        transaction_index: 9,
+        instruction_index: 9,
+        timestamp: 101010,
+        admin: "admin10".to_string(),
+        disable_create_pool: 1,
+        disable_deposit: 0,
+        disable_withdraw: 0,
+        disable_buy: 1,
+        disable_sell: 1,
+    }];
+    let batch = vec_to_arrow_batch(&events);
+    let restored: Vec<PumpfunAmmDisableEventV2> = arrow_batch_to_vec(&batch);
+    assert_eq!(events, restored);
+}
+
+#[test]
+fn test_vec_to_arrow_and_back_set_params() {
+    let events = vec![PumpfunAmmSetParamsEventV2 {
+        signature: "sig11".to_string(),
+        slot: 11,
+        transaction_index: 10,
+        instruction_index: 10,
+        timestamp: 111111,
+        admin: "admin11".to_string(),
+        lp_fee_basis_points: 20,
+        protocol_fee_basis_points: 5,
+        coin_creator_fee_basis_points: 5,
+    }];
+    let batch = vec_to_arrow_batch(&events);
+    let restored: Vec<PumpfunAmmSetParamsEventV2> = arrow_batch_to_vec(&batch);
+    assert_eq!(events, restored);
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct OptionalFieldRow {
+    signature: String,
+    slot: u64,
+    current_sol_volume: Option<u64>,
+    memo: Option<String>,
+}
+
+#[test]
+fn test_vec_to_arrow_and_back_optional_fields() {
+    let events = vec![
+        OptionalFieldRow {
+            signature: "sig11".to_string(),
+            slot: 11,
+            current_sol_volume: Some(1_000),
+            memo: None,
+        },
+        OptionalFieldRow {
+            signature: "sig12".to_string(),
+            slot: 12,
+            current_sol_volume: None,
+            memo: Some("memo12".to_string()),
+        },
+    ];
+    let batch = vec_to_arrow_batch(&events);
+    assert!(batch.schema().field_with_name("current_sol_volume").unwrap().is_nullable());
+    assert_eq!(batch.column_by_name("current_sol_volume").unwrap().null_count(), 1);
+
+    let restored: Vec<OptionalFieldRow> = arrow_batch_to_vec(&batch);
+    assert_eq!(events, restored);
+}
+
+#[test]
+fn test_vec_to_arrow_batch_with_custom_options() {
+    let events = vec![OptionalFieldRow {
+        signature: "sig13".to_string(),
+        slot: 13,
+        current_sol_volume: None,
+        memo: None,
+    }];
+    let options = default_tracing_options().coerce_numbers(true);
+    let batch = vec_to_arrow_batch_with(&events, options);
+    let restored: Vec<OptionalFieldRow> = arrow_batch_to_vec(&batch);
+    assert_eq!(events, restored);
+}
+
+#[test]
+fn test_try_arrow_batch_to_vec_schema_mismatch() {
+    let events = vec![OptionalFieldRow {
+        signature: "sig14".to_string(),
+        slot: 14,
+        current_sol_volume: Some(1),
+        memo: None,
+    }];
+    let batch = try_vec_to_arrow_batch(&events).unwrap();
+
+    // 列不匹配时返回错误而不是 panic
+    let result = try_arrow_batch_to_vec::<PumpfunMigrateEventV2>(&batch);
+    assert!(matches!(result, Err(ArrowConvError::Convert(_))));
+    assert!(result.unwrap_err().to_string().contains("Arrow conversion failed"));
+}
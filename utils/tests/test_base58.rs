@@ -0,0 +1,84 @@
+use utils::base58::{
+    Base58Error, MAX_ENCODED_LEN_32, MAX_ENCODED_LEN_64, decode_32, decode_64, global_bs58,
+};
+
+#[test]
+fn test_decode_valid_pubkey_and_signature() {
+    // System Program
+    assert_eq!(decode_32("11111111111111111111111111111111").unwrap(), [0u8; 32]);
+
+    let key: [u8; 32] = std::array::from_fn(|i| i as u8 + 1);
+    assert_eq!(decode_32(&bs58::encode(key).into_string()).unwrap(), key);
+
+    let signature: [u8; 64] = std::array::from_fn(|i| 255 - i as u8);
+    assert_eq!(decode_64(&bs58::encode(signature).into_string()).unwrap(), signature);
+}
+
+#[test]
+fn test_decode_wrong_length() {
+    let error = decode_32("1111").unwrap_err();
+    assert_eq!(error, Base58Error::WrongLength { expected: 32, actual: 4 });
+
+    // 公钥当作签名解码
+    let key = bs58::encode([7u8; 32]).into_string();
+    let error = decode_64(&key).unwrap_err();
+    assert_eq!(error, Base58Error::WrongLength { expected: 64, actual: 32 });
+    assert!(error.to_string().contains("expected 64"), "{}", error);
+
+    assert!(matches!(decode_32(""), Err(Base58Error::WrongLength { actual: 0, .. })));
+}
+
+#[test]
+fn test_decode_invalid_alphabet() {
+    // 0、O、I、l 不在 base58 字母表中
+    let error = decode_32("0OIl").unwrap_err();
+    assert_eq!(error, Base58Error::InvalidCharacter { character: '0', index: 0 });
+
+    let error = decode_32("abc€").unwrap_err();
+    assert_eq!(error, Base58Error::InvalidCharacter { character: '€', index: 3 });
+    assert!(error.to_string().contains("index 3"), "{}", error);
+}
+
+#[test]
+fn test_encode_matches_bs58_and_round_trips() {
+    let encoder = global_bs58();
+    assert_eq!(encoder.encode_32(&[0u8; 32]), "11111111111111111111111111111111");
+
+    let key: [u8; 32] = std::array::from_fn(|i| i as u8 + 1);
+    assert_eq!(encoder.encode_32(&key), bs58::encode(key).into_string());
+    assert_eq!(decode_32(&encoder.encode_32(&key)).unwrap(), key);
+
+    let signature: [u8; 64] = std::array::from_fn(|i| 255 - i as u8);
+    assert_eq!(encoder.encode_64(&signature), bs58::encode(signature).into_string());
+    assert_eq!(decode_64(&encoder.encode_64(&signature)).unwrap(), signature);
+
+    // 全 0xff 是最长的编码
+    assert_eq!(encoder.encode_32(&[0xff; 32]).len(), MAX_ENCODED_LEN_32);
+    assert_eq!(encoder.encode_64(&[0xff; 64]).len(), MAX_ENCODED_LEN_64);
+}
+
+#[test]
+fn test_encode_into_replaces_buffer_contents() {
+    let mut buffer = String::from("previous value that is longer than any key");
+    let capacity = buffer.capacity();
+
+    global_bs58().encode_32_into(&[1u8; 32], &mut buffer);
+    assert_eq!(buffer, global_bs58().encode_32(&[1u8; 32]));
+    assert_eq!(buffer.capacity(), capacity);
+
+    global_bs58().encode_64_into(&[2u8; 64], &mut buffer);
+    assert_eq!(buffer, global_bs58().encode_64(&[2u8; 64]));
+}
+
+#[test]
+fn test_encode_batch_into_reuses_caller_buffers() {
+    let keys: [[u8; 32]; 4] = std::array::from_fn(|i| [i as u8; 32]);
+    let mut outs: [String; 4] = std::array::from_fn(|_| String::with_capacity(MAX_ENCODED_LEN_32));
+    let pointers = outs.each_ref().map(|out| out.as_ptr());
+
+    global_bs58().encode_32_batch_into(keys.each_ref(), outs.each_mut());
+
+    assert_eq!(outs, keys.map(|key| global_bs58().encode_32(&key)));
+    // 写入原有缓冲区，没有重新分配
+    assert_eq!(outs.each_ref().map(|out| out.as_ptr()), pointers);
+}
@@ -0,0 +1,114 @@
+use std::time::{Duration, Instant};
+use utils::clickhouse_client::{
+    ClickHouseClient, ClickHouseConfig, DEFAULT_CONNECT_TIMEOUT_MS, DEFAULT_POOL_SIZE,
+    DEFAULT_REQUEST_TIMEOUT_MS,
+};
+
+fn config(url: &str) -> ClickHouseConfig {
+    ClickHouseConfig {
+        url: url.to_string(),
+        user: "default".to_string(),
+        password: String::new(),
+        database: "default".to_string(),
+        connect_timeout_ms: DEFAULT_CONNECT_TIMEOUT_MS,
+        request_timeout_ms: DEFAULT_REQUEST_TIMEOUT_MS,
+        pool_size: DEFAULT_POOL_SIZE,
+    }
+}
+
+#[test]
+fn test_http_config_builds_client() {
+    assert!(ClickHouseClient::from_config(&config("http://localhost:8123")).is_ok());
+}
+
+#[test]
+fn test_http_protocol_rejects_native_port() {
+    let error_msg = config("http://localhost:9000")
+        .build_client()
+        .err()
+        .expect("HTTP client pointed at port 9000 should be rejected");
+
+    assert!(error_msg.contains("9000"), "{}", error_msg);
+    assert!(error_msg.contains("8123"), "{}", error_msg);
+}
+
+#[test]
+fn test_named_clients_are_registered_once() {
+    let registered = ClickHouseClient::register(
+        "warehouse",
+        &config("http://warehouse:8123"),
+    )
+    .unwrap();
+    let named = ClickHouseClient::named("warehouse").unwrap();
+    assert!(std::ptr::eq(registered, named));
+
+    let error = ClickHouseClient::register(
+        "warehouse",
+        &config("http://other:8123"),
+    )
+    .err()
+    .unwrap();
+    assert!(error.contains("already registered"), "{}", error);
+}
+
+#[test]
+fn test_named_client_reads_prefixed_env() {
+    let error = ClickHouseClient::named("missing-cluster").err().unwrap();
+    assert!(error.contains("CLICKHOUSE_MISSING_CLUSTER_URL"), "{}", error);
+
+    // 测试进程内只有这里设置这些变量
+    unsafe {
+        std::env::set_var("CLICKHOUSE_ANALYTICS_URL", "http://analytics:8123");
+        std::env::set_var("CLICKHOUSE_ANALYTICS_USER", "default");
+        std::env::set_var("CLICKHOUSE_ANALYTICS_PASSWORD", "");
+        std::env::set_var("CLICKHOUSE_ANALYTICS_DATABASE", "analytics");
+    }
+    let config = ClickHouseConfig::from_env_named("analytics").unwrap();
+    assert_eq!(config.url, "http://analytics:8123");
+    assert_eq!(config.database, "analytics");
+    assert!(ClickHouseClient::named("analytics").is_ok());
+}
+
+/// 查询必须在超时附近失败，外层超时用于判定“挂住”
+async fn assert_fails_within(config: &ClickHouseConfig, limit: Duration) {
+    let client = config.build_client().unwrap();
+    let start = Instant::now();
+
+    let result = tokio::time::timeout(
+        Duration::from_secs(10),
+        client.query("SELECT 1").fetch_one::<u8>(),
+    )
+    .await
+    .expect("query hung instead of failing within the configured timeout");
+
+    assert!(result.is_err(), "query against a dead server should fail");
+    assert!(start.elapsed() < limit, "took {:?}", start.elapsed());
+}
+
+#[tokio::test]
+async fn test_connect_timeout_on_blackhole_address() {
+    // 不可路由地址：SYN 没有回应，只能靠连接超时结束
+    let mut config = config("http://10.255.255.1:8123");
+    config.connect_timeout_ms = 200;
+
+    assert_fails_within(&config, Duration::from_secs(3)).await;
+}
+
+#[tokio::test]
+async fn test_request_timeout_on_hung_server() {
+    // 接受连接但从不响应的服务端
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        let mut connections = Vec::new();
+        while let Ok((socket, _)) = listener.accept().await {
+            connections.push(socket);
+        }
+    });
+
+    let mut config = config(&format!("http://{}", addr));
+    config.request_timeout_ms = 200;
+
+    assert_fails_within(&config, Duration::from_secs(3)).await;
+}
+
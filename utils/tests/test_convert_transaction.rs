@@ -0,0 +1,563 @@
+use proto_lib::transaction::solana::{self, Transaction};
+use utils::clickhouse_events::*;
+use utils::convert_transaction::{
+    ConversionMetrics, ConvertedEvents, ConverterOptions, ConverterRegistry, CustomRow,
+    DecodedRow, ProgramDecoder, TransactionConverter, is_event,
+};
+
+fn bytes_32(seed: u8) -> Vec<u8> {
+    vec![seed; 32]
+}
+
+// 构造一个 PumpFun AMM Buy 指令 + BuyEvent 的交易
+fn create_amm_buy_tx() -> Transaction {
+    let mut tx = Transaction::default();
+    tx.slot = 123456;
+    tx.index = 7;
+    tx.signature = vec![9u8; 64];
+
+    let instr = solana::Instruction {
+        r#type: "PumpFunAmmBuy".to_string(),
+        parsed: Some(solana::instruction::Parsed::PumpfunAmmBuy(
+            proto_lib::transaction::pumpfun_amm::instructions::Buy {
+                base_amount_out: 1_000,
+                max_quote_amount_in: 2_000,
+                track_volume: Some(true),
+                is_main_pool: true,
+                accounts: Some(proto_lib::transaction::pumpfun_amm::instructions::BuyAccounts {
+                    pool: bytes_32(1),
+                    user: bytes_32(2),
+                    global_config: bytes_32(3),
+                    base_mint: bytes_32(4),
+                    quote_mint: bytes_32(5),
+                    user_base_token_account: bytes_32(6),
+                    user_quote_token_account: bytes_32(7),
+                    pool_base_token_account: bytes_32(8),
+                    pool_quote_token_account: bytes_32(9),
+                    protocol_fee_recipient: bytes_32(10),
+                    protocol_fee_recipient_token_account: bytes_32(11),
+                    base_token_program: bytes_32(12),
+                    quote_token_program: bytes_32(13),
+                    system_program: bytes_32(14),
+                    associated_token_program: bytes_32(15),
+                    event_authority: bytes_32(16),
+                    program: bytes_32(17),
+                    coin_creator_vault_ata: bytes_32(18),
+                    coin_creator_vault_authority: bytes_32(19),
+                    global_volume_accumulator: bytes_32(20),
+                    user_volume_accumulator: bytes_32(21),
+                    fee_config: bytes_32(22),
+                    fee_program: bytes_32(23),
+                }),
+            },
+        )),
+    };
+
+    let event = solana::Instruction {
+        r#type: "PumpFunAmmBuyEvent".to_string(),
+        parsed: Some(solana::instruction::Parsed::PumpfunAmmBuyEvent(
+            proto_lib::transaction::pumpfun_amm::events::BuyEvent {
+                timestamp: 1_700_000_000,
+                base_amount_out: 1_000,
+                max_quote_amount_in: 2_000,
+                user_base_token_reserves: 3_000,
+                user_quote_token_reserves: 4_000,
+                pool_base_token_reserves: 5_000,
+                pool_quote_token_reserves: 6_000,
+                quote_amount_in: 1_900,
+                lp_fee_basis_points: 20,
+                lp_fee: 4,
+                protocol_fee_basis_points: 5,
+                protocol_fee: 1,
+                quote_amount_in_with_lp_fee: 1_904,
+                user_quote_amount_in: 1_905,
+                pool: bytes_32(1),
+                user: bytes_32(2),
+                user_base_token_account: bytes_32(6),
+                user_quote_token_account: bytes_32(7),
+                protocol_fee_recipient: bytes_32(10),
+                protocol_fee_recipient_token_account: bytes_32(11),
+                coin_creator: bytes_32(24),
+                coin_creator_fee_basis_points: 5,
+                coin_creator_fee: 1,
+                track_volume: true,
+                total_unclaimed_tokens: 0,
+                total_claimed_tokens: 0,
+                current_sol_volume: 0,
+                last_update_timestamp: 1_700_000_000,
+            },
+        )),
+    };
+
+    tx.instructions = vec![instr, event];
+    tx
+}
+
+#[test]
+fn test_record_byte_sizes_for_amm_buy() {
+    let tx = create_amm_buy_tx();
+    let options = ConverterOptions {
+        record_byte_sizes: true,
+        ..Default::default()
+    };
+    let mut metrics = ConversionMetrics::default();
+
+    let mut trade_rows: Vec<PumpfunTradeEventV2> = vec![];
+    let mut create_rows: Vec<PumpfunCreateEventV2> = vec![];
+    let mut migrate_rows: Vec<PumpfunMigrateEventV2> = vec![];
+    let mut buy_rows: Vec<PumpfunAmmBuyEventV2> = vec![];
+    let mut sell_rows: Vec<PumpfunAmmSellEventV2> = vec![];
+    let mut create_pool_rows: Vec<PumpfunAmmCreatePoolEventV2> = vec![];
+    let mut deposit_rows: Vec<PumpfunAmmDepositEventV2> = vec![];
+    let mut withdraw_rows: Vec<PumpfunAmmWithdrawEventV2> = vec![];
+
+    TransactionConverter::convert_with_options(
+        &tx,
+        &options,
+        &mut metrics,
+        &mut trade_rows,
+        &mut create_rows,
+        &mut migrate_rows,
+        &mut buy_rows,
+        &mut sell_rows,
+        &mut create_pool_rows,
+        &mut deposit_rows,
+        &mut withdraw_rows,
+    );
+
+    assert_eq!(buy_rows.len(), 1);
+    assert_eq!(metrics.event_byte_sizes.len(), 1);
+
+    let size = &metrics.event_byte_sizes[0];
+    assert_eq!(size.event_type, "PumpFunAmmBuyEvent");
+    assert_eq!(size.instruction_index, 1);
+    // 指令包含 23 个 32 字节账户，事件包含 7 个 32 字节 pubkey
+    assert!(size.instruction_bytes > 23 * 32, "instruction bytes: {}", size.instruction_bytes);
+    assert!(size.event_bytes > 7 * 32, "event bytes: {}", size.event_bytes);
+    assert!(size.total_bytes() < 4096, "total bytes: {}", size.total_bytes());
+}
+
+#[test]
+fn test_byte_sizes_not_recorded_by_default() {
+    let tx = create_amm_buy_tx();
+    let mut metrics = ConversionMetrics::default();
+
+    let mut trade_rows: Vec<PumpfunTradeEventV2> = vec![];
+    let mut create_rows: Vec<PumpfunCreateEventV2> = vec![];
+    let mut migrate_rows: Vec<PumpfunMigrateEventV2> = vec![];
+    let mut buy_rows: Vec<PumpfunAmmBuyEventV2> = vec![];
+    let mut sell_rows: Vec<PumpfunAmmSellEventV2> = vec![];
+    let mut create_pool_rows: Vec<PumpfunAmmCreatePoolEventV2> = vec![];
+    let mut deposit_rows: Vec<PumpfunAmmDepositEventV2> = vec![];
+    let mut withdraw_rows: Vec<PumpfunAmmWithdrawEventV2> = vec![];
+
+    TransactionConverter::convert_with_options(
+        &tx,
+        &ConverterOptions::default(),
+        &mut metrics,
+        &mut trade_rows,
+        &mut create_rows,
+        &mut migrate_rows,
+        &mut buy_rows,
+        &mut sell_rows,
+        &mut create_pool_rows,
+        &mut deposit_rows,
+        &mut withdraw_rows,
+    );
+
+    assert_eq!(buy_rows.len(), 1);
+    assert!(metrics.event_byte_sizes.is_empty());
+}
+
+// 构造一条只有普通指令、没有任何 event 的长交易
+fn create_long_instruction_run_tx(count: usize) -> Transaction {
+    let mut tx = Transaction::default();
+    tx.slot = 654321;
+    tx.signature = vec![3u8; 64];
+    tx.instructions = (0..count)
+        .map(|_| solana::Instruction {
+            r#type: "SystemTransfer".to_string(),
+            parsed: None,
+        })
+        .collect();
+    tx
+}
+
+#[test]
+fn test_instruction_stack_is_bounded() {
+    let mut tx = create_long_instruction_run_tx(5_000);
+    // 末尾追加一个 AMM Buy 指令 + event，验证丢弃旧指令后仍能正常匹配
+    tx.instructions.extend(create_amm_buy_tx().instructions);
+
+    let options = ConverterOptions {
+        max_stack_depth: 64,
+        ..Default::default()
+    };
+    let mut metrics = ConversionMetrics::default();
+
+    let mut trade_rows: Vec<PumpfunTradeEventV2> = vec![];
+    let mut create_rows: Vec<PumpfunCreateEventV2> = vec![];
+    let mut migrate_rows: Vec<PumpfunMigrateEventV2> = vec![];
+    let mut buy_rows: Vec<PumpfunAmmBuyEventV2> = vec![];
+    let mut sell_rows: Vec<PumpfunAmmSellEventV2> = vec![];
+    let mut create_pool_rows: Vec<PumpfunAmmCreatePoolEventV2> = vec![];
+    let mut deposit_rows: Vec<PumpfunAmmDepositEventV2> = vec![];
+    let mut withdraw_rows: Vec<PumpfunAmmWithdrawEventV2> = vec![];
+
+    TransactionConverter::convert_with_options(
+        &tx,
+        &options,
+        &mut metrics,
+        &mut trade_rows,
+        &mut create_rows,
+        &mut migrate_rows,
+        &mut buy_rows,
+        &mut sell_rows,
+        &mut create_pool_rows,
+        &mut deposit_rows,
+        &mut withdraw_rows,
+    );
+
+    assert_eq!(metrics.peak_stack_depth, 64);
+    // 5000 条普通指令 + 1 条 Buy 指令入栈，只保留 64 条
+    assert_eq!(metrics.dropped_instructions, 5_001 - 64);
+    assert_eq!(buy_rows.len(), 1);
+    assert_eq!(buy_rows[0].instruction_index, 5_001);
+}
+
+#[test]
+fn test_fast_path_skips_transactions_without_events() {
+    let plain = create_long_instruction_run_tx(100);
+    assert!(!TransactionConverter::has_known_events(&plain));
+
+    let mut metrics = ConversionMetrics::default();
+    let mut events = ConvertedEvents::default();
+    TransactionConverter::convert_into_with_options(
+        &plain,
+        &ConverterOptions::default(),
+        &mut metrics,
+        &mut events,
+    );
+    assert!(events.is_empty());
+    assert_eq!(metrics.peak_stack_depth, 0);
+
+    // 含 event 的交易仍走完整转换
+    let mut tx = create_long_instruction_run_tx(3);
+    tx.instructions.extend(create_amm_buy_tx().instructions);
+    assert!(TransactionConverter::has_known_events(&tx));
+
+    let mut events = ConvertedEvents::default();
+    TransactionConverter::convert_into(&tx, &mut events);
+    assert_eq!(events.pumpfun_amm_buy_event.len(), 1);
+    assert_eq!(events.pumpfun_amm_buy_event[0].instruction_index, 4);
+}
+
+#[test]
+fn test_estimated_bytes_tracks_row_contents() {
+    let mut events = ConvertedEvents::default();
+    assert_eq!(events.estimated_bytes(), 0);
+
+    TransactionConverter::convert_into(&create_amm_buy_tx(), &mut events);
+    let one_row = events.estimated_bytes();
+    let row = &events.pumpfun_amm_buy_event[0];
+    assert_eq!(one_row, row.estimated_size());
+    // 至少包含 signature 的字节数
+    assert!(one_row > row.signature.len());
+
+    TransactionConverter::convert_into(&create_amm_buy_tx(), &mut events);
+    assert_eq!(events.estimated_bytes(), one_row * 2);
+}
+
+#[test]
+fn test_retain_tables_filters_event_types() {
+    let mut events = ConvertedEvents::default();
+    TransactionConverter::convert_into(&create_amm_buy_tx(), &mut events);
+
+    events.retain_tables(&["pumpfun_amm_buy_event"]);
+    assert_eq!(events.pumpfun_amm_buy_event.len(), 1);
+
+    // 只允许 create/migrate 时 AMM buy 被全部过滤，bundle 为空
+    events.retain_tables(&["pumpfun_create_event", "pumpfun_migrate_event"]);
+    assert!(events.is_empty());
+}
+
+#[test]
+fn test_dedup_tables_removes_repeated_events() {
+    let mut events = ConvertedEvents::default();
+    // 同一笔交易转换两次（模拟重叠的 block 文件）
+    TransactionConverter::convert_into(&create_amm_buy_tx(), &mut events);
+    TransactionConverter::convert_into(&create_amm_buy_tx(), &mut events);
+    assert_eq!(events.pumpfun_amm_buy_event.len(), 2);
+
+    // 未选中的表不去重
+    assert_eq!(events.dedup_tables(&["pumpfun_trade_event"]), 0);
+    assert_eq!(events.pumpfun_amm_buy_event.len(), 2);
+
+    assert_eq!(events.dedup_tables(&["pumpfun_amm_buy_event"]), 1);
+    assert_eq!(events.pumpfun_amm_buy_event.len(), 1);
+}
+
+#[test]
+fn test_convert_into_matches_legacy_convert() {
+    let tx = create_amm_buy_tx();
+
+    let mut events = ConvertedEvents::default();
+    assert!(events.is_empty());
+    TransactionConverter::convert_into(&tx, &mut events);
+
+    let mut trade_rows: Vec<PumpfunTradeEventV2> = vec![];
+    let mut create_rows: Vec<PumpfunCreateEventV2> = vec![];
+    let mut migrate_rows: Vec<PumpfunMigrateEventV2> = vec![];
+    let mut buy_rows: Vec<PumpfunAmmBuyEventV2> = vec![];
+    let mut sell_rows: Vec<PumpfunAmmSellEventV2> = vec![];
+    let mut create_pool_rows: Vec<PumpfunAmmCreatePoolEventV2> = vec![];
+    let mut deposit_rows: Vec<PumpfunAmmDepositEventV2> = vec![];
+    let mut withdraw_rows: Vec<PumpfunAmmWithdrawEventV2> = vec![];
+
+    TransactionConverter::convert(
+        &tx,
+        &mut trade_rows,
+        &mut create_rows,
+        &mut migrate_rows,
+        &mut buy_rows,
+        &mut sell_rows,
+        &mut create_pool_rows,
+        &mut deposit_rows,
+        &mut withdraw_rows,
+    );
+
+    assert_eq!(events.len(), 1);
+    assert_eq!(events.pumpfun_amm_buy_event.len(), buy_rows.len());
+    assert_eq!(
+        events.pumpfun_amm_buy_event[0].base_mint,
+        buy_rows[0].base_mint
+    );
+}
+
+#[test]
+fn test_converted_events_extend() {
+    let tx = create_amm_buy_tx();
+
+    let mut total = ConvertedEvents::default();
+    for _ in 0..3 {
+        let mut events = ConvertedEvents::default();
+        TransactionConverter::convert_into(&tx, &mut events);
+        total.extend(events);
+    }
+
+    assert!(!total.is_empty());
+    assert_eq!(total.len(), 3);
+    assert_eq!(total.pumpfun_amm_buy_event.len(), 3);
+    assert!(total.pumpfun_trade_event.is_empty());
+}
+
+fn inner_transfer() -> solana::Instruction {
+    solana::Instruction {
+        r#type: "SplTokenTransfer".to_string(),
+        parsed: None,
+    }
+}
+
+#[test]
+fn test_nested_cpi_events_match_their_own_parent() {
+    let buy = create_amm_buy_tx();
+    let outer_buy = buy.instructions[0].clone();
+    let buy_event = buy.instructions[1].clone();
+
+    // 第二个 Buy 使用不同的 base_mint
+    let mut nested_buy = outer_buy.clone();
+    if let Some(solana::instruction::Parsed::PumpfunAmmBuy(instr)) = &mut nested_buy.parsed {
+        instr.accounts.as_mut().unwrap().base_mint = bytes_32(40);
+    }
+
+    // 聚合器外层指令 -> Buy A -> (转账, 嵌套 Buy B -> 转账, EventB) -> 转账 -> EventA
+    let mut tx = buy.clone();
+    tx.instructions = vec![
+        solana::Instruction {
+            r#type: "JupiterRoute".to_string(),
+            parsed: None,
+        },
+        outer_buy,
+        inner_transfer(),
+        nested_buy,
+        inner_transfer(),
+        buy_event.clone(),
+        inner_transfer(),
+        buy_event,
+    ];
+
+    let mut metrics = ConversionMetrics::default();
+    let mut events = ConvertedEvents::default();
+    TransactionConverter::convert_into_with_options(
+        &tx,
+        &ConverterOptions::default(),
+        &mut metrics,
+        &mut events,
+    );
+
+    let mut expected = ConvertedEvents::default();
+    TransactionConverter::convert_into(&buy, &mut expected);
+    let outer_mint = &expected.pumpfun_amm_buy_event[0].base_mint;
+
+    assert_eq!(metrics.unmatched_events, 0);
+    assert_eq!(events.pumpfun_amm_buy_event.len(), 2);
+    // EventB 匹配嵌套的 Buy B，EventA 匹配外层 Buy A
+    assert_eq!(events.pumpfun_amm_buy_event[0].instruction_index, 5);
+    assert_ne!(&events.pumpfun_amm_buy_event[0].base_mint, outer_mint);
+    assert_eq!(events.pumpfun_amm_buy_event[1].instruction_index, 7);
+    assert_eq!(&events.pumpfun_amm_buy_event[1].base_mint, outer_mint);
+}
+
+#[test]
+fn test_event_without_parent_is_skipped() {
+    let buy = create_amm_buy_tx();
+    let mut tx = buy.clone();
+    tx.instructions = vec![inner_transfer(), buy.instructions[1].clone()];
+
+    let mut metrics = ConversionMetrics::default();
+    let mut events = ConvertedEvents::default();
+    TransactionConverter::convert_into_with_options(
+        &tx,
+        &ConverterOptions::default(),
+        &mut metrics,
+        &mut events,
+    );
+
+    assert!(events.is_empty());
+    assert_eq!(metrics.unmatched_events, 1);
+}
+
+#[test]
+fn test_is_event_uses_registered_event_types() {
+    let buy = create_amm_buy_tx();
+
+    assert!(!is_event(&buy.instructions[0]));
+    assert!(is_event(&buy.instructions[1]));
+    assert!(!is_event(&inner_transfer()));
+}
+
+#[derive(Debug, PartialEq)]
+struct RaydiumSwapRow {
+    instruction_index: usize,
+    slot: u64,
+}
+
+struct RaydiumDecoder;
+
+impl ProgramDecoder for RaydiumDecoder {
+    fn event_types(&self) -> &[&str] {
+        &["RaydiumSwapEvent"]
+    }
+
+    fn decode(
+        &self,
+        event_instr: &solana::Instruction,
+        _parent_instr: &solana::Instruction,
+        tx: &Transaction,
+        instruction_index: usize,
+    ) -> Option<DecodedRow> {
+        let row = RaydiumSwapRow {
+            instruction_index,
+            slot: tx.slot,
+        };
+        Some(DecodedRow::Custom(CustomRow::new(&event_instr.r#type, row)))
+    }
+}
+
+#[test]
+fn test_registry_with_custom_decoder() {
+    let buy = create_amm_buy_tx();
+    let mut tx = buy.clone();
+    tx.instructions.push(solana::Instruction {
+        r#type: "RaydiumSwap".to_string(),
+        parsed: None,
+    });
+    tx.instructions.push(solana::Instruction {
+        r#type: "RaydiumSwapEvent".to_string(),
+        parsed: None,
+    });
+
+    let mut registry = ConverterRegistry::with_builtin();
+    registry.register(RaydiumDecoder);
+    assert!(registry.is_event("RaydiumSwapEvent"));
+    assert!(registry.is_event("PumpFunAmmBuyEvent"));
+
+    let mut metrics = ConversionMetrics::default();
+    let mut events = ConvertedEvents::default();
+    TransactionConverter::convert_with_registry(
+        &tx,
+        &registry,
+        &ConverterOptions::default(),
+        &mut metrics,
+        &mut events,
+    );
+
+    // 内置 decoder 仍然生效
+    assert_eq!(events.pumpfun_amm_buy_event.len(), 1);
+    assert_eq!(events.custom.len(), 1);
+    assert_eq!(events.custom[0].event_type, "RaydiumSwapEvent");
+    assert_eq!(
+        events.custom[0].downcast_ref::<RaydiumSwapRow>(),
+        Some(&RaydiumSwapRow {
+            instruction_index: 3,
+            slot: tx.slot,
+        })
+    );
+
+    // 默认注册表不认识 Raydium 事件，当作普通指令处理
+    let mut default_events = ConvertedEvents::default();
+    TransactionConverter::convert_into(&tx, &mut default_events);
+    assert!(default_events.custom.is_empty());
+    assert_eq!(default_events.len(), 1);
+}
+
+#[test]
+fn test_converted_events_msgpack_round_trip() {
+    let mut events = ConvertedEvents::default();
+    TransactionConverter::convert_into(&create_amm_buy_tx(), &mut events);
+
+    let counts = events.len_per_table();
+    assert!(counts.contains(&("pumpfun_amm_buy_event", 1)));
+    assert!(counts.contains(&("pumpfun_trade_event", 0)));
+
+    // misaka_signal 使用 to_vec_named 发送
+    let bytes = rmp_serde::to_vec_named(&events).unwrap();
+    let restored: ConvertedEvents = rmp_serde::from_slice(&bytes).unwrap();
+    assert_eq!(restored.pumpfun_amm_buy_event, events.pumpfun_amm_buy_event);
+    assert_eq!(restored.len(), 1);
+}
+
+#[test]
+fn test_out_of_range_timestamp_is_clamped() {
+    let mut tx = create_amm_buy_tx();
+    if let Some(solana::instruction::Parsed::PumpfunAmmBuyEvent(event)) =
+        &mut tx.instructions[1].parsed
+    {
+        event.timestamp = 1 << 32;
+    }
+
+    let mut events = ConvertedEvents::default();
+    TransactionConverter::convert_into(&tx, &mut events);
+
+    // 不再回绕成 0，而是截断到 u32::MAX
+    assert_eq!(events.pumpfun_amm_buy_event[0].timestamp, u32::MAX);
+}
+
+#[test]
+fn test_converted_events_msgpack_keeps_original_keys() {
+    use serde::de::IgnoredAny;
+    use std::collections::BTreeMap;
+
+    let mut events = ConvertedEvents::default();
+    TransactionConverter::convert_into(&create_amm_buy_tx(), &mut events);
+
+    // 新增的表为空时不出现在 msgpack map 中，下游看到的 key 与原来一致
+    let bytes = rmp_serde::to_vec_named(&events).unwrap();
+    let keys: Vec<String> = rmp_serde::from_slice::<BTreeMap<String, IgnoredAny>>(&bytes)
+        .unwrap()
+        .into_keys()
+        .collect();
+    let mut expected: Vec<&str> = ConvertedEvents::TABLE_NAMES[..8].to_vec();
+    expected.sort_unstable();
+    assert_eq!(keys, expected);
+}
@@ -0,0 +1,33 @@
+use std::time::{Duration, Instant};
+use utils::dead_letter::DeadLetter;
+
+const WINDOW: Duration = Duration::from_secs(60);
+
+#[test]
+fn test_payload_written_to_dead_letter_dir() {
+    let dir = std::env::temp_dir().join(format!("dead_letter_{}", uuid::Uuid::new_v4().simple()));
+    let dead_letter = DeadLetter::new(Some(dir.clone()), 0, WINDOW);
+
+    assert!(!dead_letter.record(b"\xff\xfe garbage", "invalid wire type"));
+    assert_eq!(dead_letter.total(), 1);
+
+    let files: Vec<_> = std::fs::read_dir(&dir).unwrap().map(|e| e.unwrap().path()).collect();
+    assert_eq!(files.len(), 1);
+    assert_eq!(std::fs::read(&files[0]).unwrap(), b"\xff\xfe garbage");
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_threshold_counts_errors_within_window() {
+    let start = Instant::now();
+    let dead_letter = DeadLetter::starting_at(None, 2, WINDOW, start);
+
+    assert!(!dead_letter.record_at(b"a", "bad", start));
+    assert!(!dead_letter.record_at(b"b", "bad", start + Duration::from_secs(1)));
+    // 新窗口重新计数
+    assert!(!dead_letter.record_at(b"c", "bad", start + WINDOW));
+    assert!(!dead_letter.record_at(b"d", "bad", start + WINDOW + Duration::from_secs(1)));
+    assert!(dead_letter.record_at(b"e", "bad", start + WINDOW + Duration::from_secs(2)));
+    assert_eq!(dead_letter.total(), 5);
+}
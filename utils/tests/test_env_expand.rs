@@ -0,0 +1,79 @@
+use std::collections::HashMap;
+use utils::env_expand::{expand_env_vars, expand_toml_value, expand_with, parse_toml};
+
+fn lookup(name: &str) -> Option<String> {
+    let vars = HashMap::from([("DB_PASSWORD", "s3cret"), ("KEY_DIR", "/home/syncer/.ssh")]);
+    vars.get(name).map(|value| value.to_string())
+}
+
+#[test]
+fn test_expand_references() {
+    assert_eq!(expand_with("${DB_PASSWORD}", lookup).unwrap(), "s3cret");
+    assert_eq!(expand_with("${KEY_DIR}/id_rsa", lookup).unwrap(), "/home/syncer/.ssh/id_rsa");
+    assert_eq!(expand_with("${DB_PASSWORD}-${DB_PASSWORD}", lookup).unwrap(), "s3cret-s3cret");
+    // 没有引用的字符串和单独的 $ 原样保留
+    assert_eq!(expand_with("plain", lookup).unwrap(), "plain");
+    assert_eq!(expand_with("pa$$word$", lookup).unwrap(), "pa$$word$");
+    // $${ 转义为字面的 ${
+    assert_eq!(expand_with("$${DB_PASSWORD}", lookup).unwrap(), "${DB_PASSWORD}");
+}
+
+#[test]
+fn test_expand_errors() {
+    let error = expand_with("${MISSING_VAR}", lookup).unwrap_err();
+    assert!(error.contains("MISSING_VAR") && error.contains("not set"), "{}", error);
+
+    assert!(expand_with("${DB_PASSWORD", lookup).unwrap_err().contains("Unterminated"));
+    assert!(expand_with("${}", lookup).unwrap_err().contains("Invalid"));
+    assert!(expand_with("${DB-PASSWORD}", lookup).unwrap_err().contains("Invalid"));
+}
+
+#[test]
+fn test_expand_from_process_environment() {
+    // cargo 运行测试时会设置 CARGO_MANIFEST_DIR
+    assert_eq!(
+        expand_env_vars("${CARGO_MANIFEST_DIR}/config").unwrap(),
+        format!("{}/config", env!("CARGO_MANIFEST_DIR"))
+    );
+}
+
+#[test]
+fn test_expand_toml_value_reports_path() {
+    let mut value: toml::Value = toml::from_str(
+        r#"
+password = "plain"
+
+[remote_server]
+private_key_path = "${HEAVEN_CANCELLER_UNSET_TEST_VAR}"
+"#,
+    )
+    .unwrap();
+
+    let error = expand_toml_value(&mut value).unwrap_err();
+    assert!(error.contains("remote_server.private_key_path"), "{}", error);
+    assert!(error.contains("HEAVEN_CANCELLER_UNSET_TEST_VAR"), "{}", error);
+}
+
+#[test]
+fn test_parse_toml_expands_string_values() {
+    #[derive(serde::Deserialize)]
+    struct Config {
+        root: String,
+        paths: Vec<String>,
+        port: u16,
+    }
+
+    let config: Config = parse_toml(
+        r#"
+# 注释中的 ${NOT_EXPANDED} 不会展开
+root = "${CARGO_MANIFEST_DIR}"
+paths = ["${CARGO_MANIFEST_DIR}/a", "b"]
+port = 22
+"#,
+    )
+    .unwrap();
+
+    assert_eq!(config.root, env!("CARGO_MANIFEST_DIR"));
+    assert_eq!(config.paths, vec![format!("{}/a", env!("CARGO_MANIFEST_DIR")), "b".to_string()]);
+    assert_eq!(config.port, 22);
+}
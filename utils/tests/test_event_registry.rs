@@ -0,0 +1,126 @@
+use std::collections::HashMap;
+use utils::clickhouse_events::PumpfunMigrateEventV2;
+use utils::event_registry::{EventRegistry, LiveColumn, SchemaMismatch, verify_against};
+
+fn live_column(name: &str, column_type: &str) -> LiveColumn {
+    LiveColumn {
+        name: name.to_string(),
+        column_type: column_type.to_string(),
+        default_kind: String::new(),
+    }
+}
+
+fn migrate_registry() -> EventRegistry {
+    let mut registry = EventRegistry::new();
+    registry
+        .register::<PumpfunMigrateEventV2>("PumpfunMigrateEventV2", "pumpfun_migrate_event_v2")
+        .unwrap();
+    registry
+}
+
+// 与 PumpfunMigrateEventV2 一致的线上列
+fn matching_columns() -> Vec<LiveColumn> {
+    vec![
+        live_column("signature", "String"),
+        live_column("slot", "UInt64"),
+        live_column("transaction_index", "UInt32"),
+        live_column("instruction_index", "UInt32"),
+        live_column("user", "String"),
+        live_column("mint", "LowCardinality(String)"),
+        live_column("mint_amount", "UInt64"),
+        live_column("sol_amount", "UInt64"),
+        live_column("pool_migration_fee", "UInt64"),
+        live_column("bonding_curve", "String"),
+        live_column("timestamp", "DateTime"),
+        live_column("pool", "String"),
+    ]
+}
+
+#[test]
+fn test_derived_schema_follows_struct_fields() {
+    let registry = EventRegistry::v2().unwrap();
+    assert_eq!(registry.schemas().len(), 11);
+
+    let migrate = &migrate_registry().schemas()[0];
+    assert_eq!(migrate.columns[0].name, "signature");
+    assert_eq!(migrate.columns[0].clickhouse_type, "String");
+    assert_eq!(migrate.columns[1].clickhouse_type, "UInt64");
+    assert_eq!(migrate.columns.len(), 12);
+}
+
+#[test]
+fn test_u128_column_maps_to_uint128() {
+    let registry = EventRegistry::v2().unwrap();
+    let trade = registry
+        .schemas()
+        .iter()
+        .find(|schema| schema.event_type == "PumpfunTradeEventV2")
+        .unwrap();
+
+    let column = trade.columns.last().unwrap();
+    assert_eq!(column.name, "current_sol_volume_128");
+    assert_eq!(column.clickhouse_type, "UInt128");
+}
+
+#[test]
+fn test_matching_columns_pass() {
+    let mut live = HashMap::new();
+    let mut columns = matching_columns();
+    // 有默认值的额外列不影响写入
+    columns.push(LiveColumn {
+        name: "inserted_at".to_string(),
+        column_type: "DateTime".to_string(),
+        default_kind: "DEFAULT".to_string(),
+    });
+    live.insert("pumpfun_migrate_event_v2".to_string(), columns);
+
+    assert!(verify_against(&migrate_registry(), &live).is_ok());
+}
+
+#[test]
+fn test_mismatches_are_reported() {
+    let mut columns = matching_columns();
+    columns[6].column_type = "UInt32".to_string(); // mint_amount 类型被改
+    columns[9].name = "curve".to_string(); // bonding_curve 被重命名
+    columns.swap(4, 5); // user / mint 顺序颠倒
+
+    let mut live = HashMap::new();
+    live.insert("pumpfun_migrate_event_v2".to_string(), columns);
+
+    let report = verify_against(&migrate_registry(), &live).unwrap_err();
+    let table = "pumpfun_migrate_event_v2".to_string();
+
+    assert!(report.mismatches.contains(&SchemaMismatch::TypeMismatch {
+        table: table.clone(),
+        column: "mint_amount".to_string(),
+        expected: "UInt64".to_string(),
+        found: "UInt32".to_string(),
+    }));
+    assert!(report.mismatches.contains(&SchemaMismatch::MissingColumn {
+        table: table.clone(),
+        column: "bonding_curve".to_string(),
+    }));
+    assert!(report.mismatches.contains(&SchemaMismatch::UnexpectedColumn {
+        table: table.clone(),
+        column: "curve".to_string(),
+        column_type: "String".to_string(),
+    }));
+    assert!(report.mismatches.contains(&SchemaMismatch::OrderMismatch {
+        table: table.clone(),
+        column: "user".to_string(),
+        expected_position: 4,
+        found_position: 5,
+    }));
+    assert!(report.to_string().contains("mint_amount"));
+}
+
+#[test]
+fn test_missing_table_is_reported() {
+    let report = verify_against(&migrate_registry(), &HashMap::new()).unwrap_err();
+    assert_eq!(
+        report.mismatches,
+        vec![SchemaMismatch::MissingTable {
+            table: "pumpfun_migrate_event_v2".to_string()
+        }]
+    );
+}
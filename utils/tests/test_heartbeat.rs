@@ -0,0 +1,44 @@
+use utils::heartbeat::Heartbeat;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+fn counting_emitter(counter: Arc<AtomicUsize>) -> impl Fn() -> std::future::Ready<Result<(), String>> {
+    move || {
+        counter.fetch_add(1, Ordering::SeqCst);
+        std::future::ready(Ok(()))
+    }
+}
+
+#[tokio::test]
+async fn test_heartbeat_emitted_after_idle_interval() {
+    let emitted = Arc::new(AtomicUsize::new(0));
+    let heartbeat = Heartbeat::new(Duration::from_millis(100));
+    let handle = heartbeat.spawn(counting_emitter(Arc::clone(&emitted)));
+
+    // 间隔未到，不应发送
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert_eq!(emitted.load(Ordering::SeqCst), 0);
+
+    // 无流量超过间隔后应发送心跳
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert!(emitted.load(Ordering::SeqCst) >= 1, "heartbeat should be emitted when idle");
+
+    handle.abort();
+}
+
+#[tokio::test]
+async fn test_no_heartbeat_while_signals_flow() {
+    let emitted = Arc::new(AtomicUsize::new(0));
+    let heartbeat = Heartbeat::new(Duration::from_millis(100));
+    let handle = heartbeat.spawn(counting_emitter(Arc::clone(&emitted)));
+
+    // 持续有真实信号，心跳不应触发
+    for _ in 0..10 {
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        heartbeat.mark_activity();
+    }
+    assert_eq!(emitted.load(Ordering::SeqCst), 0);
+
+    handle.abort();
+}
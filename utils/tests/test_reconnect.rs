@@ -0,0 +1,68 @@
+use utils::reconnect::ReconnectBackoff;
+use std::time::Duration;
+
+#[test]
+fn test_delay_doubles_up_to_max() {
+    let backoff =
+        ReconnectBackoff::with_delays(0, Duration::from_millis(100), Duration::from_millis(500));
+
+    assert_eq!(backoff.delay(1), Some(Duration::from_millis(100)));
+    assert_eq!(backoff.delay(2), Some(Duration::from_millis(200)));
+    assert_eq!(backoff.delay(3), Some(Duration::from_millis(400)));
+    assert_eq!(backoff.delay(4), Some(Duration::from_millis(500)));
+    // 0 表示无限重试
+    assert_eq!(backoff.delay(1000), Some(Duration::from_millis(500)));
+}
+
+#[tokio::test]
+async fn test_attempts_exhausted() {
+    let backoff = ReconnectBackoff::with_delays(2, Duration::from_millis(1), Duration::from_millis(1));
+
+    assert!(backoff.delay(2).is_some());
+    assert!(backoff.delay(3).is_none());
+
+    let error = backoff.wait(3).await.unwrap_err();
+    assert!(error.contains("after 2 reconnect attempts"));
+}
+
+#[tokio::test]
+async fn test_resubscribe_retries_until_success() {
+    let backoff = ReconnectBackoff::with_delays(0, Duration::from_millis(1), Duration::from_millis(1));
+    let mut attempts = 0;
+    let mut calls = 0;
+
+    let subscriber = backoff
+        .resubscribe(&mut attempts, || {
+            calls += 1;
+            let result = if calls < 3 { Err("connection refused") } else { Ok("subscriber") };
+            async move { result }
+        })
+        .await
+        .unwrap();
+
+    assert_eq!(subscriber, "subscriber");
+    // 失败的订阅也计入连续重连次数
+    assert_eq!(attempts, 3);
+}
+
+#[tokio::test]
+async fn test_resubscribe_gives_up_after_max_attempts() {
+    let backoff = ReconnectBackoff::with_delays(2, Duration::from_millis(1), Duration::from_millis(1));
+    let mut attempts = 0;
+
+    let error = backoff
+        .resubscribe(&mut attempts, || async { Err::<(), _>("connection refused") })
+        .await
+        .unwrap_err();
+
+    assert!(error.contains("after 2 reconnect attempts"));
+}
+
+#[test]
+fn test_client_delay_starts_at_base_and_never_gives_up() {
+    let backoff = ReconnectBackoff::with_delays(1, Duration::from_millis(100), Duration::from_secs(10));
+
+    // async-nats 的重连次数从 0 开始
+    assert_eq!(backoff.client_delay(0), Duration::from_millis(100));
+    assert_eq!(backoff.client_delay(usize::MAX), Duration::from_secs(10));
+}
@@ -0,0 +1,117 @@
+use utils::clickhouse_events::{PumpfunAmmSetParamsEventV2, PumpfunMigrateEventV2, PumpfunTradeEventV2};
+use utils::event_registry::EventRegistry;
+use utils::schema::{create_table_sql, create_table_sql_for, split_table};
+
+#[test]
+fn test_create_table_sql_follows_struct_fields() {
+    let sql = create_table_sql::<PumpfunMigrateEventV2>("pumpfun_migrate_event_v2").unwrap();
+
+    assert!(sql.starts_with("CREATE TABLE IF NOT EXISTS `pumpfun_migrate_event_v2` ("), "{}", sql);
+    assert!(sql.contains("    `signature` String,\n    `slot` UInt64,"), "{}", sql);
+    assert!(sql.contains("`timestamp` UInt32"), "{}", sql);
+    assert!(sql.ends_with("ENGINE = MergeTree\nORDER BY (slot, transaction_index, instruction_index)"), "{}", sql);
+}
+
+#[test]
+fn test_create_table_sql_quotes_database() {
+    let sql = create_table_sql::<PumpfunTradeEventV2>("staging.pumpfun_trade_event_v2").unwrap();
+
+    assert!(sql.contains("`staging`.`pumpfun_trade_event_v2`"), "{}", sql);
+    assert!(sql.contains("`current_sol_volume` UInt64"), "{}", sql);
+    assert!(sql.contains("`current_sol_volume_128` UInt128"), "{}", sql);
+}
+
+#[test]
+fn test_create_table_sql_for_set_params_event() {
+    let sql = create_table_sql::<PumpfunAmmSetParamsEventV2>("pumpfun_amm_set_params_event_v2").unwrap();
+
+    assert!(sql.starts_with("CREATE TABLE IF NOT EXISTS `pumpfun_amm_set_params_event_v2` ("), "{}", sql);
+    assert!(sql.contains("`admin` String,\n    `lp_fee_basis_points` UInt64,"), "{}", sql);
+    assert!(sql.ends_with("ORDER BY (slot, transaction_index, instruction_index)"), "{}", sql);
+}
+
+#[test]
+fn test_split_qualified_table_name() {
+    assert_eq!(
+        split_table("staging.pumpfun_trade_event_v2"),
+        (Some("staging"), "pumpfun_trade_event_v2")
+    );
+    assert_eq!(split_table("pumpfun_trade_event_v2"), (None, "pumpfun_trade_event_v2"));
+}
+
+#[test]
+fn test_every_v2_event_has_ddl() {
+    let registry = EventRegistry::v2().unwrap();
+
+    for schema in registry.schemas() {
+        let sql = create_table_sql_for(schema);
+        // 每一列一行，列数与推导结果一致
+        assert_eq!(sql.matches(" `").count(), schema.columns.len() + 1, "{}", sql);
+    }
+}
+
+#[tokio::test]
+#[ignore = "integration test, requires ClickHouse (CLICKHOUSE_* env)"]
+async fn test_ensure_table_creates_verifiable_table() {
+    use utils::clickhouse_client::ClickHouseClient;
+    use utils::event_registry::verify_schema;
+    use utils::schema::ensure_table;
+
+    let client = ClickHouseClient::instance().client();
+    let table = "schema_test_pumpfun_migrate_event_v2";
+    client
+        .query(&format!("DROP TABLE IF EXISTS {}", table))
+        .execute()
+        .await
+        .unwrap();
+
+    let mut registry = EventRegistry::new();
+    registry
+        .register::<PumpfunMigrateEventV2>("PumpfunMigrateEventV2", table)
+        .unwrap();
+    let schema = &registry.schemas()[0];
+
+    assert!(ensure_table(client, schema).await.unwrap());
+    assert!(!ensure_table(client, schema).await.unwrap());
+    verify_schema(client, &registry).await.unwrap();
+
+    client
+        .query(&format!("DROP TABLE {}", table))
+        .execute()
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+#[ignore = "integration test, requires ClickHouse (CLICKHOUSE_* env)"]
+async fn test_ensure_table_with_database_qualified_name() {
+    use utils::clickhouse_client::{ClickHouseClient, ClickHouseConfig};
+    use utils::event_registry::verify_schema;
+    use utils::schema::ensure_table;
+
+    let client = ClickHouseClient::instance().client();
+    let database = ClickHouseConfig::from_env().database;
+    let table = format!("{}.schema_test_qualified_pumpfun_migrate_event_v2", database);
+    client
+        .query(&format!("DROP TABLE IF EXISTS {}", table))
+        .execute()
+        .await
+        .unwrap();
+
+    let mut registry = EventRegistry::new();
+    registry
+        .register::<PumpfunMigrateEventV2>("PumpfunMigrateEventV2", &table)
+        .unwrap();
+    let schema = &registry.schemas()[0];
+
+    // 第二次调用必须找到已建的表，而不是拿 "db.table" 去匹配 system.tables.name
+    assert!(ensure_table(client, schema).await.unwrap());
+    assert!(!ensure_table(client, schema).await.unwrap());
+    verify_schema(client, &registry).await.unwrap();
+
+    client
+        .query(&format!("DROP TABLE {}", table))
+        .execute()
+        .await
+        .unwrap();
+}
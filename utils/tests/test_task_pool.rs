@@ -0,0 +1,130 @@
+use utils::task_pool::{TaskPanic, TaskPool};
+
+#[tokio::test]
+async fn test_collects_errors_from_failing_tasks() {
+    let pool: TaskPool<String> = TaskPool::new(3);
+
+    for i in 0..10u32 {
+        pool.submit_fallible(move || async move {
+            if i % 3 == 0 {
+                Err(format!("task {} failed", i))
+            } else {
+                Ok(())
+            }
+        });
+    }
+
+    let mut errors = pool.wait_all_tasks().await;
+    errors.sort();
+    assert_eq!(
+        errors,
+        vec!["task 0 failed", "task 3 failed", "task 6 failed", "task 9 failed"]
+    );
+
+    // wait_all_tasks 已取走错误
+    assert!(!pool.has_errors());
+    assert!(pool.take_errors().is_empty());
+    pool.join();
+}
+
+#[tokio::test]
+async fn test_no_errors_when_all_tasks_succeed() {
+    let pool: TaskPool<String> = TaskPool::new(2);
+
+    for _ in 0..5 {
+        pool.submit_fallible(|| async { Ok(()) });
+    }
+
+    assert!(pool.wait_all_tasks().await.is_empty());
+    pool.join();
+}
+
+#[tokio::test]
+async fn test_submit_blocking_applies_backpressure() {
+    use std::time::Duration;
+    use tokio::sync::Notify;
+
+    let pool: TaskPool<String> = TaskPool::new(2);
+    let release = std::sync::Arc::new(Notify::new());
+
+    // 占满两个名额，任务一直挂起直到 release
+    for _ in 0..2 {
+        let release = release.clone();
+        pool.submit_blocking(move || async move {
+            release.notified().await;
+            Ok(())
+        })
+        .await;
+    }
+    assert_eq!(pool.pending_len(), 2);
+
+    // 池满时第三个提交会等待
+    let blocked = tokio::time::timeout(
+        Duration::from_millis(100),
+        pool.submit_blocking(|| async { Ok(()) }),
+    )
+    .await;
+    assert!(blocked.is_err(), "submit_blocking should wait while the pool is full");
+
+    // 释放一个任务后可以继续提交
+    release.notify_one();
+    tokio::time::timeout(
+        Duration::from_secs(5),
+        pool.submit_blocking(|| async { Ok(()) }),
+    )
+    .await
+    .expect("submit_blocking should proceed once a task finishes");
+
+    release.notify_one();
+    assert!(pool.wait_all_tasks().await.is_empty());
+    assert_eq!(pool.pending_len(), 0);
+    pool.join();
+}
+
+#[tokio::test]
+async fn test_scoped_pools_track_errors_separately() {
+    let pool: TaskPool<String> = TaskPool::new(2);
+    let first = pool.scoped();
+    let second = pool.scoped();
+
+    first.submit_fallible(|| async { Err("first failed".to_string()) });
+    second.submit_fallible(|| async { Ok(()) });
+
+    assert_eq!(first.wait_all_tasks().await, vec!["first failed"]);
+    assert!(second.wait_all_tasks().await.is_empty());
+    assert!(!pool.has_errors());
+
+    first.join();
+    second.join();
+    pool.join();
+}
+
+async fn explode() -> Result<(), String> {
+    panic!("insert task exploded")
+}
+
+#[tokio::test]
+async fn test_panicking_task_is_reported_and_does_not_hang() {
+    use std::time::Duration;
+
+    let pool: TaskPool<String> = TaskPool::new(1);
+    pool.submit_blocking(explode).await;
+    pool.submit_fallible(|| async { Err("second failed".to_string()) });
+
+    // panic 的任务同样结束计数并释放名额
+    let mut errors = tokio::time::timeout(Duration::from_secs(5), pool.wait_all_tasks())
+        .await
+        .expect("wait_all_tasks should return after a task panics");
+    errors.sort();
+    let panic = String::from(TaskPanic {
+        message: "insert task exploded".to_string(),
+    });
+    assert_eq!(errors, vec!["second failed".to_string(), panic]);
+    assert_eq!(pool.pending_len(), 0);
+
+    tokio::time::timeout(Duration::from_secs(5), pool.submit_blocking(|| async { Ok(()) }))
+        .await
+        .expect("the panicked task should release its permit");
+    assert!(pool.wait_all_tasks().await.is_empty());
+    pool.join();
+}
@@ -0,0 +1,20 @@
+use utils::timestamp::{clamp_unix_u32, to_unix_u32};
+
+#[test]
+fn test_to_unix_u32_boundaries() {
+    assert_eq!(to_unix_u32(0i64), None);
+    assert_eq!(to_unix_u32(-1i64), None);
+    assert_eq!(to_unix_u32(1_700_000_000i64), Some(1_700_000_000));
+    assert_eq!(to_unix_u32(u32::MAX as i64), Some(u32::MAX));
+    assert_eq!(to_unix_u32(1i64 << 32), None);
+    assert_eq!(to_unix_u32(u64::MAX), None);
+}
+
+#[test]
+fn test_clamp_unix_u32() {
+    assert_eq!(clamp_unix_u32(-5i64), 0);
+    assert_eq!(clamp_unix_u32(0i64), 0);
+    assert_eq!(clamp_unix_u32(1_700_000_000u64), 1_700_000_000);
+    assert_eq!(clamp_unix_u32(1i64 << 32), u32::MAX);
+    assert_eq!(clamp_unix_u32(i64::MAX), u32::MAX);
+}
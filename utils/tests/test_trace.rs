@@ -0,0 +1,57 @@
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+use utils::trace::{message_span, resolve_trace_id};
+
+/// 把日志写进内存，方便断言
+#[derive(Clone, Default)]
+struct CapturedLogs(Arc<Mutex<Vec<u8>>>);
+
+impl Write for CapturedLogs {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl CapturedLogs {
+    fn contents(&self) -> String {
+        String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+    }
+}
+
+#[test]
+fn test_header_trace_id_appears_in_log_event() {
+    let logs = CapturedLogs::default();
+    let writer = logs.clone();
+    let subscriber = tracing_subscriber::fmt()
+        .with_ansi(false)
+        .with_writer(move || writer.clone())
+        .finish();
+
+    tracing::subscriber::with_default(subscriber, || {
+        let trace_id = resolve_trace_id(Some("publisher-trace-42"));
+        let span = message_span(&trace_id);
+        span.in_scope(|| {
+            tracing::error!("Failed to decode transaction");
+        });
+    });
+
+    let output = logs.contents();
+    assert!(output.contains("Failed to decode transaction"), "{}", output);
+    assert!(output.contains("trace_id=publisher-trace-42"), "{}", output);
+}
+
+#[test]
+fn test_missing_trace_id_is_generated() {
+    let generated = resolve_trace_id(None);
+    assert!(!generated.is_empty());
+    assert_ne!(generated, resolve_trace_id(None));
+
+    // 空 header 等同于缺失
+    assert_ne!(resolve_trace_id(Some("  ")), "  ");
+    assert_eq!(resolve_trace_id(Some("abc")), "abc");
+}
@@ -0,0 +1,50 @@
+use utils::url::{NATS_SCHEMES, normalize_url, normalize_url_list};
+
+#[test]
+fn test_adds_default_scheme_and_port() {
+    assert_eq!(normalize_url("nats_url", "localhost", &NATS_SCHEMES).unwrap(), "nats://localhost:4222");
+    assert_eq!(normalize_url("nats_url", "wss://edge", &NATS_SCHEMES).unwrap(), "wss://edge:443");
+    assert_eq!(normalize_url("url", "HTTPS://host", &["http", "https"]).unwrap(), "https://host:443");
+}
+
+#[test]
+fn test_keeps_userinfo_ipv6_and_path() {
+    assert_eq!(
+        normalize_url("nats_url", "nats://user:pass@[::1]:4443", &NATS_SCHEMES).unwrap(),
+        "nats://user:pass@[::1]:4443"
+    );
+    assert_eq!(
+        normalize_url("url", "http://host:8080/api", &["http", "https"]).unwrap(),
+        "http://host:8080/api"
+    );
+    // 只有结尾斜杠时去掉
+    assert_eq!(normalize_url("nats_url", "nats://host/", &NATS_SCHEMES).unwrap(), "nats://host:4222");
+}
+
+#[test]
+fn test_rejects_invalid_urls() {
+    let error = normalize_url("nats_url", "http://localhost", &NATS_SCHEMES).unwrap_err();
+    assert!(error.contains("nats_url") && error.contains("unsupported scheme"), "{}", error);
+
+    let error = normalize_url("nats_url", "nats:/localhost", &NATS_SCHEMES).unwrap_err();
+    assert!(error.contains("malformed scheme"), "{}", error);
+
+    let error = normalize_url("nats_url", "localhost:port", &NATS_SCHEMES).unwrap_err();
+    assert!(error.contains("invalid port"), "{}", error);
+
+    let error = normalize_url("nats_url", "nats://:4222", &NATS_SCHEMES).unwrap_err();
+    assert!(error.contains("host"), "{}", error);
+
+    assert!(normalize_url("nats_url", "  ", &NATS_SCHEMES).is_err());
+}
+
+#[test]
+fn test_normalizes_each_url_in_list() {
+    assert_eq!(
+        normalize_url_list("nats_url", "nats://a:4222, b", &NATS_SCHEMES).unwrap(),
+        "nats://a:4222,nats://b:4222"
+    );
+
+    let error = normalize_url_list("nats_url", "a,http://b", &NATS_SCHEMES).unwrap_err();
+    assert!(error.contains("http://b"), "{}", error);
+}